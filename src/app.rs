@@ -18,13 +18,28 @@ fn server_help(controller_type: &str) -> String {
          <offset>        The port offset. If not specified, 100 is used from the second server onwards.\n\
          start           Whether to start the server.",
         msg.name(),
-        msg.abbreviation(),
+        msg.abbreviation().unwrap_or_default(),
         osg.name(),
-        osg.abbreviation(),
+        osg.abbreviation().unwrap_or_default(),
         msg.name(),
     )
 }
 
+fn wait_timeout_arg() -> Arg {
+    Arg::new("wait-timeout")
+        .long("wait-timeout")
+        .value_name("seconds")
+        .value_parser(value_parser!(u64).range(1..))
+        .help("The number of seconds to wait until the servers are ready [default: 120]")
+}
+
+fn set_arg() -> Arg {
+    Arg::new("set").long("set").action(ArgAction::Append).help(
+        "A variable <key>=<value> used in ${key} references of the topology setup.
+Can be provided multiple times. Environment variables are used for variables which are not set.",
+    )
+}
+
 pub fn build_app() -> Command {
     Command::new(crate_name!())
         .version(crate_version!())
@@ -108,7 +123,7 @@ pub fn build_app() -> Command {
             .about("Start a standalone server")
             .arg(Arg::new("wildfly-version")
                 .index(1)
-                .required(true)
+                .required_unless_present_any(["resume", "from-snapshot"])
                 .help("A single WildFly version or version range"))
             .arg(Arg::new("wildfly-parameters")
                 .index(2)
@@ -137,16 +152,84 @@ Not allowed when multiple versions are specified."))
                 .short('o')
                 .long("offset")
                 .value_parser(value_parser!(u16).range(1..))
-                .help("The offset added to the published HTTP and management ports.
+                .help("The offset added to the published HTTP, management and additional ports.
 Not allowed when multiple versions are specified."))
+            .arg(Arg::new("publish")
+                .long("publish")
+                .action(ArgAction::Append)
+                .value_delimiter(',')
+                .value_parser(["https", "mgmt-https", "ajp", "remoting"])
+                .help("A comma seperated list of additional ports to publish: https (18<major><minor>),
+mgmt-https (19<major><minor>), ajp (7<major><minor>) or remoting (4<major><minor>).
+Can be provided multiple times."))
+            .arg(Arg::new("config")
+                .long("config")
+                .help("A built-in configuration (e.g. standalone-full-ha.xml) or a local XML file to start the standalone server with"))
             .arg(Arg::new("operations")
                 .long("operations")
                 .action(ArgAction::Append)
-                .help("A comma seperated list of operations to bootstrap the standalone server.
+                .help("A comma seperated list of operations (incl. batch / run-batch) to bootstrap the standalone server.
 Can be provided multiple times."))
             .arg(Arg::new("cli")
                 .long("cli")
-                .help("A file with operations to bootstrap the standalone server")))
+                .help("A file with operations to bootstrap the standalone server"))
+            .arg(Arg::new("deployment")
+                .long("deployment")
+                .action(ArgAction::Append)
+                .help("An archive or exploded directory to mount into the deployment scanner directory.
+Can be provided multiple times."))
+            .arg(Arg::new("volume")
+                .short('v')
+                .long("volume")
+                .action(ArgAction::Append)
+                .help("A bind mount or named volume <host>:<container>[:<options>] to mount into the standalone server.
+Can be provided multiple times."))
+            .arg(Arg::new("env")
+                .short('e')
+                .long("env")
+                .action(ArgAction::Append)
+                .help("An environment variable <key>=<value> to set in the standalone server.
+Can be provided multiple times."))
+            .arg(Arg::new("env-file")
+                .long("env-file")
+                .action(ArgAction::Append)
+                .help("A file with environment variables <key>=<value> (one per line) to set in the standalone server.
+Can be provided multiple times."))
+            .arg(Arg::new("java-opts")
+                .long("java-opts")
+                .allow_hyphen_values(true)
+                .help("The JVM options of the standalone server (JAVA_OPTS). Replaces the default JVM options of WildFly."))
+            .arg(Arg::new("system-property")
+                .short('D')
+                .long("system-property")
+                .action(ArgAction::Append)
+                .help("A system property <key>=<value> passed to the standalone server.
+Can be provided multiple times."))
+            .arg(Arg::new("debug")
+                .long("debug")
+                .num_args(0..=1)
+                .value_name("suspend")
+                .value_parser(["suspend"])
+                .help("Enable remote debugging (JDWP) of the standalone server and publish the debug port 5<major><minor>.
+Use 'suspend' to wait for a debugger before the standalone server starts."))
+            .arg(Arg::new("keep")
+                .long("keep")
+                .visible_alias("persistent")
+                .action(ArgAction::SetTrue)
+                .help("Keep the container after it has been stopped (no --rm).
+Use 'wado restart', 'wado start --resume' and 'wado rm' to manage kept containers."))
+            .arg(Arg::new("resume")
+                .long("resume")
+                .value_name("name")
+                .conflicts_with("wildfly-version")
+                .help("Start a stopped container that has been started with --keep before.
+All other options are ignored."))
+            .arg(Arg::new("from-snapshot")
+                .long("from-snapshot")
+                .value_name("label")
+                .conflicts_with_all(["wildfly-version", "config", "resume"])
+                .help("Start the version of a snapshot with the saved configuration (see 'wado snapshot')."))
+            .arg(wait_timeout_arg()))
 
         // standalone stop
         .subcommand(Command::new("stop")
@@ -174,7 +257,7 @@ stop all running standalone servers of that version.")))
                 .about("Start a domain controller")
                 .arg(Arg::new("wildfly-version")
                     .index(1)
-                    .required(true)
+                    .required_unless_present_any(["resume", "from-snapshot"])
                     .help("A single WildFly version or version range"))
                 .arg(Arg::new("wildfly-parameters")
                     .index(2)
@@ -203,21 +286,95 @@ Not allowed when multiple versions are specified."))
                     .short('o')
                     .long("offset")
                     .value_parser(value_parser!(u16).range(1..))
-                    .help("The offset added to the published HTTP and management ports.
+                    .help("The offset added to the published HTTP, management and additional ports.
 Not allowed when multiple versions are specified."))
+                .arg(Arg::new("publish")
+                    .long("publish")
+                    .action(ArgAction::Append)
+                    .value_delimiter(',')
+                    .value_parser(["https", "mgmt-https", "ajp", "remoting"])
+                    .help("A comma seperated list of additional ports to publish: https (18<major><minor>),
+mgmt-https (19<major><minor>), ajp (7<major><minor>) or remoting (4<major><minor>).
+Can be provided multiple times."))
                 .arg(Arg::new("server")
                     .short('s')
                     .long("server")
                     .action(ArgAction::Append)
                     .help(server_help("domain controller")))
+                .arg(Arg::new("config")
+                    .long("config")
+                    .help("The built-in domain.xml or a local XML file to start the domain controller with"))
                 .arg(Arg::new("operations")
                     .long("operations")
                     .action(ArgAction::Append)
-                    .help("A comma seperated list of operations to bootstrap the domain controller.
+                    .help("A comma seperated list of operations (incl. batch / run-batch) to bootstrap the domain controller.
 Can be provided multiple times."))
                 .arg(Arg::new("cli")
                     .long("cli")
-                    .help("A file with operations to bootstrap the domain controller")))
+                    .help("A file with operations to bootstrap the domain controller"))
+                .arg(Arg::new("deployment")
+                    .long("deployment")
+                    .action(ArgAction::Append)
+                    .help("An archive to deploy to the server groups of the domain.
+Can be provided multiple times."))
+                .arg(Arg::new("server-groups")
+                    .long("server-groups")
+                    .value_delimiter(',')
+                    .requires("deployment")
+                    .help("A comma seperated list of server groups to assign the deployments to
+[default: the server groups of --server or main-server-group]"))
+                .arg(Arg::new("volume")
+                    .short('v')
+                    .long("volume")
+                    .action(ArgAction::Append)
+                    .help("A bind mount or named volume <host>:<container>[:<options>] to mount into the domain controller.
+Can be provided multiple times."))
+                .arg(Arg::new("env")
+                    .short('e')
+                    .long("env")
+                    .action(ArgAction::Append)
+                    .help("An environment variable <key>=<value> to set in the domain controller.
+Can be provided multiple times."))
+                .arg(Arg::new("env-file")
+                    .long("env-file")
+                    .action(ArgAction::Append)
+                    .help("A file with environment variables <key>=<value> (one per line) to set in the domain controller.
+Can be provided multiple times."))
+                .arg(Arg::new("java-opts")
+                    .long("java-opts")
+                    .allow_hyphen_values(true)
+                    .help("The JVM options of the domain controller (JAVA_OPTS). Replaces the default JVM options of WildFly."))
+                .arg(Arg::new("system-property")
+                    .short('D')
+                    .long("system-property")
+                    .action(ArgAction::Append)
+                    .help("A system property <key>=<value> passed to the domain controller.
+Can be provided multiple times."))
+                .arg(Arg::new("debug")
+                    .long("debug")
+                    .num_args(0..=1)
+                    .value_name("suspend")
+                    .value_parser(["suspend"])
+                    .help("Enable remote debugging (JDWP) of the domain controller process and publish the debug port 5<major><minor>.
+Use 'suspend' to wait for a debugger before the domain controller starts."))
+                .arg(Arg::new("keep")
+                    .long("keep")
+                    .visible_alias("persistent")
+                    .action(ArgAction::SetTrue)
+                    .help("Keep the container after it has been stopped (no --rm).
+Use 'wado restart', 'wado dc start --resume' and 'wado rm' to manage kept containers."))
+                .arg(Arg::new("resume")
+                    .long("resume")
+                    .value_name("name")
+                    .conflicts_with("wildfly-version")
+                    .help("Start a stopped domain controller that has been started with --keep before.
+All other options are ignored."))
+                .arg(Arg::new("from-snapshot")
+                    .long("from-snapshot")
+                    .value_name("label")
+                    .conflicts_with_all(["wildfly-version", "config", "resume"])
+                    .help("Start the version of a snapshot with the saved configuration (see 'wado snapshot')."))
+                .arg(wait_timeout_arg()))
 
             // stop
             .subcommand(Command::new("stop")
@@ -245,7 +402,7 @@ stop all running domain controllers of that version."))))
                 .about("Start a host controller")
                 .arg(Arg::new("wildfly-version")
                     .index(1)
-                    .required(true)
+                    .required_unless_present("resume")
                     .help("A single WildFly version or version range"))
                 .arg(Arg::new("wildfly-parameters")
                     .index(2)
@@ -278,14 +435,75 @@ Required if different versions are specified."))
                     .long("server")
                     .action(ArgAction::Append)
                     .help(server_help("host controller")))
+                .arg(Arg::new("host-config")
+                    .long("host-config")
+                    .help("A built-in host configuration (e.g. host-secondary.xml) or a local XML file to start the host controller with"))
                 .arg(Arg::new("operations")
                     .long("operations")
                     .action(ArgAction::Append)
-                    .help("A comma seperated list of operations to bootstrap the host controller.
+                    .help("A comma seperated list of operations (incl. batch / run-batch) to bootstrap the host controller.
 Can be provided multiple times."))
                 .arg(Arg::new("cli")
                     .long("cli")
-                    .help("A file with operations to bootstrap the host controller")))
+                    .help("A file with operations to bootstrap the host controller"))
+                .arg(Arg::new("deployment")
+                    .long("deployment")
+                    .action(ArgAction::Append)
+                    .help("An archive to deploy to the server groups of the host controller's servers.
+The archive is uploaded to the running domain controller.
+Can be provided multiple times."))
+                .arg(Arg::new("server-groups")
+                    .long("server-groups")
+                    .value_delimiter(',')
+                    .requires("deployment")
+                    .help("A comma seperated list of server groups to assign the deployments to
+[default: the server groups of --server or main-server-group]"))
+                .arg(Arg::new("volume")
+                    .short('v')
+                    .long("volume")
+                    .action(ArgAction::Append)
+                    .help("A bind mount or named volume <host>:<container>[:<options>] to mount into the host controller.
+Can be provided multiple times."))
+                .arg(Arg::new("env")
+                    .short('e')
+                    .long("env")
+                    .action(ArgAction::Append)
+                    .help("An environment variable <key>=<value> to set in the host controller.
+Can be provided multiple times."))
+                .arg(Arg::new("env-file")
+                    .long("env-file")
+                    .action(ArgAction::Append)
+                    .help("A file with environment variables <key>=<value> (one per line) to set in the host controller.
+Can be provided multiple times."))
+                .arg(Arg::new("java-opts")
+                    .long("java-opts")
+                    .allow_hyphen_values(true)
+                    .help("The JVM options of the host controller (JAVA_OPTS). Replaces the default JVM options of WildFly."))
+                .arg(Arg::new("system-property")
+                    .short('D')
+                    .long("system-property")
+                    .action(ArgAction::Append)
+                    .help("A system property <key>=<value> passed to the host controller.
+Can be provided multiple times."))
+                .arg(Arg::new("debug")
+                    .long("debug")
+                    .num_args(0..=1)
+                    .value_name("suspend")
+                    .value_parser(["suspend"])
+                    .help("Enable remote debugging (JDWP) of the host controller process and publish the debug port 5<major><minor>.
+Use 'suspend' to wait for a debugger before the host controller starts."))
+                .arg(Arg::new("keep")
+                    .long("keep")
+                    .visible_alias("persistent")
+                    .action(ArgAction::SetTrue)
+                    .help("Keep the container after it has been stopped (no --rm).
+Use 'wado restart', 'wado hc start --resume' and 'wado rm' to manage kept containers."))
+                .arg(Arg::new("resume")
+                    .long("resume")
+                    .value_name("name")
+                    .conflicts_with("wildfly-version")
+                    .help("Start a stopped host controller that has been started with --keep before.
+All other options are ignored.")))
 
             // stop
             .subcommand(Command::new("stop")
@@ -306,21 +524,180 @@ stop all running host controllers of that version."))))
 
         // topology
         .subcommand(Command::new("topology")
-            .about("Start and stop a topology defined in YAML")
+            .about("Start, stop and inspect a topology defined in YAML")
 
             // start
             .subcommand(Command::new("start")
                 .about("Start a topology")
                 .arg(Arg::new("setup")
                     .required(true)
-                    .help("The topology setup")))
+                    .help("The topology setup"))
+                .arg(set_arg())
+                .arg(wait_timeout_arg()))
+
+            // apply
+            .subcommand(Command::new("apply")
+                .about("Reconcile a running topology with its topology setup")
+                .arg(Arg::new("setup")
+                    .required(true)
+                    .help("The topology setup"))
+                .arg(set_arg())
+                .arg(Arg::new("dry-run")
+                    .long("dry-run")
+                    .action(ArgAction::SetTrue)
+                    .help("Only show the changes without applying them"))
+                .arg(Arg::new("username")
+                    .short('u')
+                    .long("username")
+                    .default_value("admin")
+                    .help("The username to connect to the management interface of the domain controller"))
+                .arg(Arg::new("password")
+                    .short('p')
+                    .long("password")
+                    .default_value("admin")
+                    .help("The password to connect to the management interface of the domain controller")))
 
             // stop
             .subcommand(Command::new("stop")
                 .about("Stop a topology")
                 .arg(Arg::new("setup")
                     .required(true)
-                    .help("The topology name or the topology setup file"))))
+                    .help("The topology name or the topology setup file"))
+                .arg(set_arg()))
+
+            // status
+            .subcommand(Command::new("status")
+                .about("Show the hosts and servers of a running topology")
+                .arg(Arg::new("setup")
+                    .required(true)
+                    .help("The topology name or the topology setup file.
+If a setup file is given, hosts and servers which are defined, but not running are shown as missing."))
+                .arg(set_arg())
+                .arg(Arg::new("username")
+                    .short('u')
+                    .long("username")
+                    .default_value("admin")
+                    .help("The username to connect to the management interface of the domain controller"))
+                .arg(Arg::new("password")
+                    .short('p')
+                    .long("password")
+                    .default_value("admin")
+                    .help("The password to connect to the management interface of the domain controller")))
+
+            // render
+            .subcommand(Command::new("render")
+                .about("Print a topology setup with variables, includes and replicas expanded")
+                .arg(Arg::new("setup")
+                    .required(true)
+                    .help("The topology setup"))
+                .arg(set_arg()))
+
+            // validate
+            .subcommand(Command::new("validate")
+                .about("Validate a topology setup without starting any containers")
+                .arg(Arg::new("setup")
+                    .required(true)
+                    .help("The topology setup"))
+                .arg(set_arg()))
+
+            // schema
+            .subcommand(Command::new("schema")
+                .about("Print the JSON schema of topology setups")))
+
+        // restart
+        .subcommand(Command::new("restart")
+            .about("Restart containers that have been started with --keep")
+            .arg(Arg::new("wildfly-version")
+                .required_unless_present_any(["name", "all"])
+                .help("A single WildFly version or version range"))
+            .arg(Arg::new("name")
+                .short('n')
+                .long("name")
+                .help("The name of the container"))
+            .arg(Arg::new("all")
+                .short('a')
+                .long("all")
+                .action(ArgAction::SetTrue)
+                .help("Restart all kept containers. If specified with a version,
+restart all kept containers of that version."))
+            .arg(wait_timeout_arg()))
+
+        // rm
+        .subcommand(Command::new("rm")
+            .about("Remove stopped containers that have been started with --keep")
+            .arg(Arg::new("wildfly-version")
+                .required_unless_present_any(["name", "all"])
+                .help("A single WildFly version or version range"))
+            .arg(Arg::new("name")
+                .short('n')
+                .long("name")
+                .help("The name of the container"))
+            .arg(Arg::new("all")
+                .short('a')
+                .long("all")
+                .action(ArgAction::SetTrue)
+                .help("Remove all kept containers. If specified with a version,
+remove all kept containers of that version."))
+            .arg(Arg::new("force")
+                .short('f')
+                .long("force")
+                .action(ArgAction::SetTrue)
+                .help("Stop and remove running containers, too")))
+
+        // snapshot
+        .subcommand(Command::new("snapshot")
+            .about("Save, list and restore the configuration of standalone servers, domain and host controllers")
+            .subcommand_required(true)
+
+            // save
+            .subcommand(Command::new("save")
+                .about("Save the configuration of a running standalone server, domain or host controller")
+                .arg(Arg::new("name")
+                    .required(true)
+                    .help("The name of the standalone server, domain or host controller"))
+                .arg(Arg::new("label")
+                    .required(true)
+                    .help("The label of the snapshot"))
+                .arg(Arg::new("force")
+                    .short('f')
+                    .long("force")
+                    .action(ArgAction::SetTrue)
+                    .help("Replace an existing snapshot with the same label")))
+
+            // list
+            .subcommand(Command::new("list")
+                .about("List all snapshots"))
+
+            // restore
+            .subcommand(Command::new("restore")
+                .about("Start a new container with the configuration of a snapshot")
+                .arg(Arg::new("label")
+                    .required(true)
+                    .help("The label of the snapshot"))
+                .arg(Arg::new("name")
+                    .short('n')
+                    .long("name")
+                    .help("The name of the container [default: wado-<type>-<major><minor>]"))
+                .arg(Arg::new("keep")
+                    .long("keep")
+                    .visible_alias("persistent")
+                    .action(ArgAction::SetTrue)
+                    .help("Keep the container after it has been stopped (no --rm)."))
+                .arg(Arg::new("domain-controller")
+                    .short('d')
+                    .long("domain-controller")
+                    .help("The name of the domain controller host controllers connect to [default: wado-dc-<major><minor>]"))
+                .arg(Arg::new("username")
+                    .short('u')
+                    .long("username")
+                    .default_value("admin")
+                    .help("The username host controllers use to connect to the domain controller"))
+                .arg(Arg::new("password")
+                    .short('p')
+                    .long("password")
+                    .default_value("admin")
+                    .help("The password host controllers use to connect to the domain controller"))
+                .arg(wait_timeout_arg())))
 
         // images
         .subcommand(Command::new("images")
@@ -336,7 +713,188 @@ stop all running host controllers of that version."))))
             .arg(Arg::new("domain")
                 .long("domain")
                 .action(ArgAction::SetTrue)
-                .help("List domain controller and host controller containers only")))
+                .help("List domain controller and host controller containers only"))
+            .arg(Arg::new("all")
+                .short('a')
+                .long("all")
+                .action(ArgAction::SetTrue)
+                .help("List stopped containers that have been started with --keep, too"))
+            .arg(Arg::new("wide")
+                .short('w')
+                .long("wide")
+                .action(ArgAction::SetTrue)
+                .help("Show environment variables and system properties")))
+
+        // logs
+        .subcommand(Command::new("logs")
+            .about("Show the logs of standalone, domain and host controller containers")
+            .arg(Arg::new("wildfly-version")
+                .help("A single WildFly version or version range.
+If omitted the logs of all running containers are shown."))
+            .arg(Arg::new("name")
+                .short('n')
+                .long("name")
+                .help("The name of the container.
+Not allowed when multiple versions are specified."))
+            .arg(Arg::new("topology")
+                .short('t')
+                .long("topology")
+                .help("Show the logs of the containers of this topology only"))
+            .arg(Arg::new("follow")
+                .short('f')
+                .long("follow")
+                .action(ArgAction::SetTrue)
+                .help("Follow the log output"))
+            .arg(Arg::new("since")
+                .long("since")
+                .help("Show logs since a timestamp (e.g. 2026-06-25T10:00:00) or relative duration (e.g. 10m)"))
+            .arg(Arg::new("tail")
+                .long("tail")
+                .help("Number of lines to show from the end of the logs of each container"))
+            .arg(Arg::new("timestamps")
+                .long("timestamps")
+                .action(ArgAction::SetTrue)
+                .help("Show the timestamps of the container runtime")))
+
+        // wait
+        .subcommand(Command::new("wait")
+            .about("Wait until standalone, domain and host controller containers are ready")
+            .arg(Arg::new("wildfly-version")
+                .help("A single WildFly version or version range.
+If omitted all running containers are selected."))
+            .arg(Arg::new("name")
+                .short('n')
+                .long("name")
+                .help("The name of the container.
+Not allowed when multiple versions are specified."))
+            .arg(Arg::new("topology")
+                .short('t')
+                .long("topology")
+                .help("Wait for the containers of this topology only"))
+            .arg(wait_timeout_arg()
+                .visible_alias("timeout"))
+            .arg(Arg::new("server-state")
+                .long("server-state")
+                .value_parser(["running", "reload-required", "restart-required"])
+                .help("Wait until the server-state of standalone servers or of all servers with auto-start
+of domain and host controllers has this value"))
+            .arg(Arg::new("deployment")
+                .long("deployment")
+                .action(ArgAction::Append)
+                .help("Wait until this deployment is enabled (standalone servers) or assigned to a server group (domain controllers).
+Can be provided multiple times."))
+            .arg(Arg::new("log-match")
+                .long("log-match")
+                .value_name("regex")
+                .help("Wait until the logs of the container match this regular expression (e.g. WFLYSRV0025)"))
+            .arg(Arg::new("username")
+                .short('u')
+                .long("username")
+                .default_value("admin")
+                .help("The username to connect to the management interface"))
+            .arg(Arg::new("password")
+                .short('p')
+                .long("password")
+                .default_value("admin")
+                .help("The password to connect to the management interface")))
+
+        // health
+        .subcommand(Command::new("health")
+            .about("Show the health of standalone, domain and host controller containers")
+            .arg(Arg::new("wildfly-version")
+                .help("A single WildFly version or version range.
+If omitted the health of all running containers is shown."))
+            .arg(Arg::new("name")
+                .short('n')
+                .long("name")
+                .help("The name of the container.
+Not allowed when multiple versions are specified."))
+            .arg(Arg::new("topology")
+                .short('t')
+                .long("topology")
+                .help("Show the health of the containers of this topology only")))
+
+        // metrics
+        .subcommand(Command::new("metrics")
+            .about("Show the metrics of standalone servers")
+            .arg(Arg::new("wildfly-version")
+                .help("A single WildFly version or version range.
+If omitted the metrics of all running standalone servers are shown."))
+            .arg(Arg::new("name")
+                .short('n')
+                .long("name")
+                .help("The name of the standalone server.
+Not allowed when multiple versions are specified."))
+            .arg(Arg::new("raw")
+                .long("raw")
+                .action(ArgAction::SetTrue)
+                .help("Print the metrics in the Prometheus text format"))
+            .arg(Arg::new("filter")
+                .long("filter")
+                .value_name("regex")
+                .help("Show the metrics whose name matches this regular expression"))
+            .arg(Arg::new("watch")
+                .short('w')
+                .long("watch")
+                .value_name("seconds")
+                .value_parser(value_parser!(u64).range(1..))
+                .help("Refresh the metrics every <seconds> until interrupted"))
+            .arg(Arg::new("username")
+                .short('u')
+                .long("username")
+                .default_value("admin")
+                .help("The username to connect to the management interface"))
+            .arg(Arg::new("password")
+                .short('p')
+                .long("password")
+                .default_value("admin")
+                .help("The password to connect to the management interface")))
+
+        // top
+        .subcommand(Command::new("top")
+            .visible_alias("dashboard")
+            .about("Show an interactive dashboard of running standalone, domain and host controller containers")
+            .arg(Arg::new("interval")
+                .short('i')
+                .long("interval")
+                .value_name("seconds")
+                .value_parser(value_parser!(u64).range(1..))
+                .help("Refresh the containers every <seconds> [default: 2]"))
+            .arg(Arg::new("username")
+                .short('u')
+                .long("username")
+                .default_value("admin")
+                .help("The username to connect to the management interface"))
+            .arg(Arg::new("password")
+                .short('p')
+                .long("password")
+                .default_value("admin")
+                .help("The password to connect to the management interface")))
+
+        // exec
+        .subcommand(Command::new("exec")
+            .visible_alias("shell")
+            .about("Run a command in standalone, domain and host controller containers")
+            .arg(Arg::new("wildfly-version")
+                .index(1)
+                .help("A single WildFly version or version range.
+Can be omitted if only one container is running."))
+            .arg(Arg::new("command")
+                .index(2)
+                .last(true)
+                .num_args(0..)
+                .required(false)
+                .help("The command to run [default: an interactive bash].
+A command is required if multiple containers are selected."))
+            .arg(Arg::new("name")
+                .short('n')
+                .long("name")
+                .help("The name of the container.
+Not allowed when multiple versions are specified."))
+            .arg(Arg::new("topology")
+                .short('t')
+                .long("topology")
+                .help("Run the command in the containers of this topology only")))
 
         // console
         .subcommand(Command::new("console")
@@ -391,6 +949,204 @@ Not allowed when multiple versions are specified."))
                 .default_value("admin")
                 .help("The password to connect to the CLI")))
 
+        // op
+        .subcommand(Command::new("op")
+            .about("Execute management operations using the HTTP management API")
+            .arg(Arg::new("wildfly-version")
+                .index(1)
+                .help("A single WildFly version.
+Can be omitted if only one standalone or domain controller is running."))
+            .arg(Arg::new("operation")
+                .index(2)
+                .last(true)
+                .num_args(1..)
+                .required(true)
+                .help("One or more operations in CLI syntax
+(e.g. '/subsystem=logging:read-resource(recursive=true)')"))
+            .arg(Arg::new("name")
+                .short('n')
+                .long("name")
+                .help("The name of the standalone server or domain controller [default: wado-sa|dc-<major><minor>]"))
+            .arg(Arg::new("management")
+                .short('m')
+                .long("management")
+                .value_parser(value_parser!(u16))
+                .conflicts_with("name")
+                .help("The published management port"))
+            .arg(Arg::new("username")
+                .short('u')
+                .long("username")
+                .default_value("admin")
+                .help("The username to connect to the management interface"))
+            .arg(Arg::new("password")
+                .short('p')
+                .long("password")
+                .default_value("admin")
+                .help("The password to connect to the management interface")))
+
+        // diff
+        .subcommand(Command::new("diff")
+            .about("Compare the management model of two containers or WildFly versions")
+            .arg(Arg::new("left")
+                .required(true)
+                .help("The name of a running standalone server or domain controller or a single WildFly version.
+For versions without a running container, a temporary container is started."))
+            .arg(Arg::new("right")
+                .required(true)
+                .help("The name of a running standalone server or domain controller or a single WildFly version.
+For versions without a running container, a temporary container is started."))
+            .arg(Arg::new("domain")
+                .long("domain")
+                .action(ArgAction::SetTrue)
+                .help("Compare domain controllers instead of standalone servers for versions"))
+            .arg(Arg::new("address")
+                .short('a')
+                .long("address")
+                .help("Compare the resource at this address only (e.g. '/subsystem=undertow')"))
+            .arg(Arg::new("username")
+                .short('u')
+                .long("username")
+                .default_value("admin")
+                .help("The username to connect to the management interface"))
+            .arg(Arg::new("password")
+                .short('p')
+                .long("password")
+                .default_value("admin")
+                .help("The password to connect to the management interface")))
+
+        // model
+        .subcommand(Command::new("model")
+            .about("Export the management model metadata of WildFly versions")
+            .subcommand_required(true)
+
+            // dump
+            .subcommand(Command::new("dump")
+                .about("Write the resource description tree of standalone servers and domain controllers as JSON files")
+                .arg(Arg::new("wildfly-version")
+                    .required(true)
+                    .help("A single WildFly version or version range.
+Running containers are reused, temporary containers are started for all other versions."))
+                .arg(Arg::new("output")
+                    .short('o')
+                    .long("output")
+                    .default_value(".")
+                    .help("The output directory for the JSON files <sa|dc>-<major>[.<minor>].json"))
+                .arg(Arg::new("standalone")
+                    .long("standalone")
+                    .action(ArgAction::SetTrue)
+                    .conflicts_with("domain")
+                    .help("Export standalone servers only"))
+                .arg(Arg::new("domain")
+                    .long("domain")
+                    .action(ArgAction::SetTrue)
+                    .help("Export domain controllers only"))
+                .arg(Arg::new("operations")
+                    .long("operations")
+                    .action(ArgAction::SetTrue)
+                    .help("Include the descriptions of the operations"))
+                .arg(Arg::new("username")
+                    .short('u')
+                    .long("username")
+                    .default_value("admin")
+                    .help("The username to connect to the management interface"))
+                .arg(Arg::new("password")
+                    .short('p')
+                    .long("password")
+                    .default_value("admin")
+                    .help("The password to connect to the management interface"))))
+
+        // deploy
+        .subcommand(Command::new("deploy")
+            .about("Deploy applications to running standalone servers or domain controllers")
+            .arg(Arg::new("wildfly-version")
+                .index(1)
+                .required(true)
+                .help("A single WildFly version or version range"))
+            .arg(Arg::new("deployment")
+                .index(2)
+                .num_args(1..)
+                .required(true)
+                .help("One or more archives or exploded directories to deploy"))
+            .arg(Arg::new("name")
+                .short('n')
+                .long("name")
+                .help("The name of the standalone server or domain controller [default: wado-sa|dc-<major><minor>]"))
+            .arg(Arg::new("server-groups")
+                .short('g')
+                .long("server-groups")
+                .value_delimiter(',')
+                .help("A comma seperated list of server groups to deploy to [default: all server groups]
+Only applies to domain controllers."))
+            .arg(Arg::new("force")
+                .short('f')
+                .long("force")
+                .action(ArgAction::SetTrue)
+                .help("Redeploy existing deployments"))
+            .arg(Arg::new("username")
+                .short('u')
+                .long("username")
+                .default_value("admin")
+                .help("The username to connect to the management interface"))
+            .arg(Arg::new("password")
+                .short('p')
+                .long("password")
+                .default_value("admin")
+                .help("The password to connect to the management interface")))
+
+        // undeploy
+        .subcommand(Command::new("undeploy")
+            .about("Undeploy applications from running standalone servers or domain controllers")
+            .arg(Arg::new("wildfly-version")
+                .index(1)
+                .required(true)
+                .help("A single WildFly version or version range"))
+            .arg(Arg::new("deployment")
+                .index(2)
+                .num_args(1..)
+                .required_unless_present("all")
+                .help("One or more deployment names"))
+            .arg(Arg::new("all")
+                .short('a')
+                .long("all")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("deployment")
+                .help("Undeploy all deployments"))
+            .arg(Arg::new("name")
+                .short('n')
+                .long("name")
+                .help("The name of the standalone server or domain controller [default: wado-sa|dc-<major><minor>]"))
+            .arg(Arg::new("username")
+                .short('u')
+                .long("username")
+                .default_value("admin")
+                .help("The username to connect to the management interface"))
+            .arg(Arg::new("password")
+                .short('p')
+                .long("password")
+                .default_value("admin")
+                .help("The password to connect to the management interface")))
+
+        // deployments
+        .subcommand(Command::new("deployments")
+            .about("List the deployments of running standalone servers and domain controllers")
+            .arg(Arg::new("wildfly-version")
+                .index(1)
+                .help("A single WildFly version or version range"))
+            .arg(Arg::new("name")
+                .short('n')
+                .long("name")
+                .help("The name of the standalone server or domain controller [default: wado-sa|dc-<major><minor>]"))
+            .arg(Arg::new("username")
+                .short('u')
+                .long("username")
+                .default_value("admin")
+                .help("The username to connect to the management interface"))
+            .arg(Arg::new("password")
+                .short('p')
+                .long("password")
+                .default_value("admin")
+                .help("The password to connect to the management interface")))
+
         // completions
         .subcommand(Command::new("completions")
             .about("Generate and install shell completions")
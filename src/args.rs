@@ -1,12 +1,24 @@
+use crate::container::{
+    DebugMode, Mount, RunOptions, add_java_opts, container_ps, deployment_operations,
+    deployment_server_groups, get_instance, kept_containers, parse_key_value, read_env_file,
+    select_containers, verify_container_command,
+};
+use crate::error::WadoError;
+use crate::healthcheck::DEFAULT_WAIT_TIMEOUT;
+use crate::wildfly::ServerType::{DomainController, Standalone};
 use crate::wildfly::{
-    AdminImage, DEFAULT_SERVER_OFFSET, Server, ServerType, StartSpec, apply_offsets,
+    AdminImage, ContainerInstance, DEFAULT_SERVER_OFFSET, ExtraPort, ManagementClient, Server,
+    ServerConfig, ServerType, Snapshot, StartSpec, apply_offsets, parse_commands, validate_batches,
 };
-use anyhow::bail;
+use anyhow::{Context, bail};
 use clap::ArgMatches;
 use fs::read_to_string;
+use futures::executor::block_on;
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
-use wildfly_meta::WildFlyImage;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use wildfly_meta::{WildFlyImage, WildFlyImageRegistry};
 
 // ------------------------------------------------------ sorted a-z
 
@@ -27,6 +39,144 @@ pub fn admin_images_argument(matches: &ArgMatches) -> Vec<AdminImage> {
         .collect::<Vec<_>>()
 }
 
+/// Parses `--config` (`--host-config` for host controllers) and validates built-in
+/// configurations against all `<wildfly-version>`s. Fails if the configuration is also
+/// given as WildFly parameter.
+pub fn config_argument(
+    matches: &ArgMatches,
+    server_type: ServerType,
+    base_dir: &Path,
+) -> anyhow::Result<Option<ServerConfig>> {
+    if server_type == ServerType::HostController {
+        let Some(value) = matches.get_one::<String>("host-config") else {
+            return Ok(None);
+        };
+        if extract_host_config(&parameters_argument(matches)).is_some() {
+            bail!(
+                "Option <host-config> is not allowed together with the WildFly parameter --host-config!"
+            );
+        }
+        return validated_config(matches, value, server_type, base_dir).map(Some);
+    }
+    let Some(value) = matches.get_one::<String>("config") else {
+        return Ok(None);
+    };
+    if config_parameter(&parameters_argument(matches)).is_some() {
+        bail!(
+            "Option <config> is not allowed together with the WildFly parameter -c / --server-config!"
+        );
+    }
+    validated_config(matches, value, server_type, base_dir).map(Some)
+}
+
+/// Returns the JDWP mode given by `--debug [suspend]`.
+pub fn debug_argument(matches: &ArgMatches) -> Option<DebugMode> {
+    if matches.contains_id("debug") {
+        match matches.get_one::<String>("debug") {
+            Some(_) => Some(DebugMode::Suspend),
+            None => Some(DebugMode::Run),
+        }
+    } else {
+        None
+    }
+}
+
+/// Returns the server groups given by `--server-groups` or the server groups of the
+/// servers given by `--server` to assign deployments to.
+pub fn deployment_groups_argument(matches: &ArgMatches) -> Vec<String> {
+    match matches.get_many::<String>("server-groups") {
+        Some(groups) => groups.cloned().collect(),
+        None => deployment_server_groups(&server_argument(matches)),
+    }
+}
+
+/// Collects the environment variables given by `--env-file`, `--env` and `--java-opts`
+/// (in this order).
+pub fn env_argument(matches: &ArgMatches) -> anyhow::Result<Vec<(String, String)>> {
+    let mut env = vec![];
+    for env_file in matches.get_many::<PathBuf>("env-file").unwrap_or_default() {
+        env.extend(read_env_file(env_file)?);
+    }
+    for value in matches.get_many::<String>("env").unwrap_or_default() {
+        env.push(parse_key_value(value)?);
+    }
+    if let Some(java_opts) = matches.get_one::<String>("java-opts") {
+        add_java_opts(&mut env, java_opts)?;
+    }
+    Ok(env)
+}
+
+/// Returns the containers started with `--keep` (running or stopped) selected by
+/// `<wildfly-version>` and `--name`. Without both, all kept containers are returned.
+pub fn kept_containers_argument(
+    matches: &ArgMatches,
+    registry: &WildFlyImageRegistry,
+) -> anyhow::Result<Vec<ContainerInstance>> {
+    let wildfly_images = matches.get_one::<Vec<WildFlyImage>>("wildfly-version");
+    let name = matches.get_one::<String>("name").map(|s| s.as_str());
+    block_on(kept_containers(
+        wildfly_images.map(|v| v.as_slice()),
+        name,
+        registry,
+    ))
+}
+
+/// Resolves the management client of a single standalone server or domain controller
+/// from the `<wildfly-version>`, `--name` and `--management` arguments.
+///
+/// If neither is given, exactly one standalone server or domain controller must be running.
+pub fn management_client_argument(
+    matches: &ArgMatches,
+    registry: &WildFlyImageRegistry,
+) -> anyhow::Result<ManagementClient> {
+    if let Some(name) = matches.get_one::<String>("name") {
+        let mut v = vec![];
+        let wildfly_images =
+            if let Some(wildfly_image) = matches.get_one::<WildFlyImage>("wildfly-version") {
+                v.push(wildfly_image.clone());
+                Some(&v)
+            } else {
+                None
+            };
+        let instance = block_on(get_instance(
+            vec![Standalone, DomainController],
+            wildfly_images.map(|v| v.as_slice()),
+            Some(name),
+            registry,
+        ))?;
+        Ok(ManagementClient::from_container_instance(
+            &instance, registry,
+        ))
+    } else if let Some(wildfly_image) = matches.get_one::<WildFlyImage>("wildfly-version") {
+        Ok(ManagementClient::custom_port(
+            wildfly_image,
+            *matches
+                .get_one::<u16>("management")
+                .unwrap_or(&wildfly_image.management_port()),
+            registry,
+        ))
+    } else {
+        let containers = block_on(container_ps(
+            vec![Standalone, DomainController],
+            None,
+            None,
+            true,
+            false,
+            registry,
+        ))?;
+        if containers.is_empty() {
+            bail!("No running containers found.")
+        } else if containers.len() > 1 {
+            bail!("Multiple running containers found. Please specify a version or a name.")
+        } else {
+            Ok(ManagementClient::from_container_instance(
+                &containers[0],
+                registry,
+            ))
+        }
+    }
+}
+
 pub fn name_argument<F>(name: &str, matches: &ArgMatches, f: F) -> String
 where
     F: FnOnce() -> String,
@@ -37,54 +187,39 @@ where
         .unwrap_or_else(f)
 }
 
-pub fn operations_argument(matches: &ArgMatches) -> Vec<String> {
-    let mut operations = matches
-        .get_many::<String>("operations")
-        .unwrap_or_default()
-        .flat_map(|operation| {
-            operation
-                .split(',')
-                .map(|s| s.to_string())
-                .collect::<Vec<String>>()
-        })
-        .filter(|op| {
-            if is_valid_cli_operation(op) {
-                true
-            } else {
-                eprintln!(
-                    "Skipping invalid CLI operation (must start with '/' or ':'): {}",
-                    op
-                );
-                false
-            }
-        })
-        .collect::<Vec<_>>();
+/// Parses, validates and normalizes the bootstrap operations given by `--operations`
+/// and `--cli`. Syntax errors are reported with their exact position before any
+/// container is started.
+pub fn operations_argument(matches: &ArgMatches) -> anyhow::Result<Vec<String>> {
+    let mut commands = vec![];
+    for operations in matches.get_many::<String>("operations").unwrap_or_default() {
+        commands.extend(
+            parse_commands(operations)
+                .map_err(|e| WadoError::invalid_operation(&e.display_with(operations)))?,
+        );
+    }
     if matches.contains_id("cli")
         && let Some(cli_path) = matches.get_one::<String>("cli")
     {
-        let path = Path::new(cli_path);
-        match path.canonicalize().and_then(|_| read_to_string(path)) {
-            Ok(content) => {
-                for line in content.lines().map(|s| s.trim().to_string()) {
-                    if line.is_empty() || line.starts_with('#') {
-                        continue;
-                    }
-                    if is_valid_cli_operation(&line) {
-                        operations.push(line);
-                    } else {
-                        eprintln!(
-                            "Skipping invalid CLI operation (must start with '/' or ':'): {}",
-                            line
-                        );
-                    }
-                }
-            }
-            Err(err) => {
-                eprintln!("Failed to read file {}: {}", cli_path, err);
+        let content = read_to_string(Path::new(cli_path))
+            .with_context(|| format!("Failed to read file {}", cli_path))?;
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
             }
+            commands.extend(parse_commands(line).map_err(|e| {
+                WadoError::invalid_operation(&format!(
+                    "{}:{}: {}",
+                    cli_path,
+                    index + 1,
+                    e.display_with(line)
+                ))
+            })?);
         }
     }
-    operations
+    validate_batches(&commands).map_err(|e| WadoError::invalid_operation(&e))?;
+    Ok(commands.iter().map(ToString::to_string).collect())
 }
 
 pub fn parameters_argument(matches: &ArgMatches) -> Vec<String> {
@@ -95,6 +230,113 @@ pub fn parameters_argument(matches: &ArgMatches) -> Vec<String> {
         .collect::<Vec<_>>()
 }
 
+/// Returns the additional ports given by `--publish`.
+pub fn publish_argument(matches: &ArgMatches) -> Vec<ExtraPort> {
+    matches
+        .get_many::<String>("publish")
+        .unwrap_or_default()
+        .filter_map(|name| ExtraPort::from_name(name))
+        .collect()
+}
+
+/// Collects the bootstrap operations, deployments, volumes, configuration, environment
+/// variables, system properties, debug mode and `--keep` of the start commands.
+pub fn run_options_argument(
+    matches: &ArgMatches,
+    server_type: ServerType,
+) -> anyhow::Result<RunOptions> {
+    let base_dir = std::env::current_dir()?;
+    let mut operations = operations_argument(matches)?;
+    let mut mounts = vec![];
+    // deployments of host controllers are uploaded to the domain controller (see hc start)
+    if server_type != ServerType::HostController && matches.contains_id("deployment") {
+        for path in matches
+            .get_many::<PathBuf>("deployment")
+            .unwrap_or_default()
+        {
+            mounts.push(Mount::deployment(path, &base_dir, server_type)?);
+        }
+        let server_groups = if server_type == ServerType::DomainController {
+            deployment_groups_argument(matches)
+        } else {
+            vec![]
+        };
+        operations.extend(deployment_operations(&mounts, server_type, &server_groups));
+    }
+    for volume in matches.get_many::<String>("volume").unwrap_or_default() {
+        mounts.push(Mount::parse_volume(volume, &base_dir)?);
+    }
+    let config = config_argument(matches, server_type, &base_dir)?;
+    mounts.extend(config.as_ref().and_then(ServerConfig::mount));
+    Ok(RunOptions {
+        operations,
+        mounts,
+        config,
+        host_config: None,
+        env: env_argument(matches)?,
+        system_properties: system_properties_argument(matches)?,
+        debug: debug_argument(matches),
+        server_debug: vec![],
+        keep: matches.get_flag("keep"),
+    })
+}
+
+/// Returns the running containers of the given server types selected by the
+/// `wildfly-version`, `name`, and (if the command defines it) `topology` arguments,
+/// sorted by version and name. Fails if no container is found.
+pub fn selected_containers_argument(
+    matches: &ArgMatches,
+    server_types: Vec<ServerType>,
+    registry: &WildFlyImageRegistry,
+) -> anyhow::Result<Vec<ContainerInstance>> {
+    verify_container_command()?;
+    let wildfly_images = matches.get_one::<Vec<WildFlyImage>>("wildfly-version");
+    let name = matches.get_one::<String>("name").map(|s| s.as_str());
+    let topology = matches
+        .try_get_one::<String>("topology")
+        .ok()
+        .flatten()
+        .map(|s| s.as_str());
+    if let Some(wildfly_images) = wildfly_images
+        && wildfly_images.len() > 1
+        && name.is_some()
+    {
+        bail!("Option <name> is not allowed when multiple <wildfly-version> are specified!");
+    }
+    let message = match server_types.as_slice() {
+        [Standalone] => "No running standalone server found.",
+        [Standalone, DomainController] => {
+            "No running standalone server or domain controller found."
+        }
+        _ => "No running containers found.",
+    };
+    let mut instances = block_on(select_containers(
+        server_types,
+        wildfly_images.map(|v| v.as_slice()),
+        name,
+        topology,
+        registry,
+    ))?;
+    if instances.is_empty() {
+        bail!(message);
+    }
+    instances.sort();
+    Ok(instances)
+}
+
+pub fn server_argument(matches: &ArgMatches) -> Vec<Server> {
+    let servers = matches
+        .get_many::<Vec<Server>>("server")
+        .unwrap_or_default()
+        .cloned()
+        .collect::<Vec<_>>();
+    let servers = servers
+        .iter()
+        .flat_map(|server| server.clone())
+        .collect::<Vec<_>>();
+    apply_offsets(servers, DEFAULT_SERVER_OFFSET)
+}
+
 pub fn start_spec(
     matches: &ArgMatches,
     wildfly_image: &WildFlyImage,
@@ -128,20 +370,48 @@ pub fn start_spec(
         custom_name: matches.get_one::<String>("name").cloned(),
         custom_http,
         custom_management,
+        custom_offset: has_offset.then_some(offset),
+        publish: publish_argument(matches),
+        debug: matches.contains_id("debug"),
     }
 }
 
-pub fn server_argument(matches: &ArgMatches) -> Vec<Server> {
-    let servers = matches
-        .get_many::<Vec<Server>>("server")
+/// Returns the versions to start: the version of the snapshot given by `--from-snapshot`
+/// or the `<wildfly-version>`s. A snapshot also replaces the configuration of `run_options`.
+pub fn start_versions_argument(
+    matches: &ArgMatches,
+    server_type: ServerType,
+    run_options: &mut RunOptions,
+    registry: &WildFlyImageRegistry,
+) -> anyhow::Result<Vec<WildFlyImage>> {
+    match matches.get_one::<String>("from-snapshot") {
+        Some(label) => {
+            let snapshot = Snapshot::load(label)?;
+            let admin_image = snapshot.admin_image(registry)?;
+            if admin_image.server_type != server_type {
+                bail!(
+                    "Snapshot '{}' has been taken from a {} container. Use 'wado snapshot restore {}' instead.",
+                    label,
+                    admin_image.server_type.short_name(),
+                    label
+                );
+            }
+            run_options.set_config(snapshot.server_config()?);
+            if let Some(host_config) = snapshot.host_server_config()? {
+                run_options.set_host_config(host_config);
+            }
+            Ok(vec![admin_image.wildfly_image])
+        }
+        None => Ok(versions_argument(matches)),
+    }
+}
+
+pub fn system_properties_argument(matches: &ArgMatches) -> anyhow::Result<Vec<(String, String)>> {
+    matches
+        .get_many::<String>("system-property")
         .unwrap_or_default()
-        .cloned()
-        .collect::<Vec<_>>();
-    let servers = servers
-        .iter()
-        .flat_map(|server| server.clone())
-        .collect::<Vec<_>>();
-    apply_offsets(servers, DEFAULT_SERVER_OFFSET)
+        .map(|value| parse_key_value(value))
+        .collect()
 }
 
 pub fn username_password_argument(matches: &ArgMatches) -> (&str, &str) {
@@ -156,6 +426,28 @@ pub fn username_password_argument(matches: &ArgMatches) -> (&str, &str) {
     (username, password)
 }
 
+fn validated_config(
+    matches: &ArgMatches,
+    value: &str,
+    server_type: ServerType,
+    base_dir: &Path,
+) -> anyhow::Result<ServerConfig> {
+    let config = ServerConfig::parse(value, base_dir)?;
+    for wildfly_image in versions_argument(matches) {
+        config.validate(&wildfly_image, server_type)?;
+    }
+    Ok(config)
+}
+
+/// Collects the topology variables given by `--set <key>=<value>`.
+pub fn variables_argument(matches: &ArgMatches) -> anyhow::Result<BTreeMap<String, String>> {
+    matches
+        .get_many::<String>("set")
+        .unwrap_or_default()
+        .map(|value| parse_key_value(value))
+        .collect()
+}
+
 pub fn versions_argument(matches: &ArgMatches) -> Vec<WildFlyImage> {
     matches
         .get_one::<Vec<WildFlyImage>>("wildfly-version")
@@ -163,6 +455,16 @@ pub fn versions_argument(matches: &ArgMatches) -> Vec<WildFlyImage> {
         .clone()
 }
 
+/// Returns the timeout given by `--wait-timeout` in seconds.
+pub fn wait_timeout_argument(matches: &ArgMatches) -> Duration {
+    Duration::from_secs(
+        matches
+            .get_one::<u64>("wait-timeout")
+            .copied()
+            .unwrap_or(DEFAULT_WAIT_TIMEOUT),
+    )
+}
+
 // ------------------------------------------------------ validation
 
 pub fn validate_multiple_versions(matches: &ArgMatches, options: &[&str]) -> anyhow::Result<()> {
@@ -178,22 +480,34 @@ pub fn validate_multiple_versions(matches: &ArgMatches, options: &[&str]) -> any
 }
 
 pub fn extract_config(parameters: &[String], default: &str) -> String {
+    config_parameter(parameters).unwrap_or_else(|| default.to_string())
+}
+
+/// Returns the host configuration given as WildFly parameter `--host-config`.
+pub fn extract_host_config(parameters: &[String]) -> Option<String> {
     let mut iter = parameters.iter();
     while let Some(param) = iter.next() {
-        if param == "-c" {
+        if param == "--host-config" {
             if let Some(value) = iter.next() {
-                return value.clone();
+                return Some(value.clone());
             }
-        } else if let Some(value) = param.strip_prefix("--server-config=") {
-            return value.to_string();
+        } else if let Some(value) = param.strip_prefix("--host-config=") {
+            return Some(value.to_string());
         }
     }
-    default.to_string()
+    None
 }
 
-// ------------------------------------------------------ validation
-
-fn is_valid_cli_operation(operation: &str) -> bool {
-    let trimmed = operation.trim();
-    trimmed.starts_with('/') || trimmed.starts_with(':')
+fn config_parameter(parameters: &[String]) -> Option<String> {
+    let mut iter = parameters.iter();
+    while let Some(param) = iter.next() {
+        if param == "-c" {
+            if let Some(value) = iter.next() {
+                return Some(value.clone());
+            }
+        } else if let Some(value) = param.strip_prefix("--server-config=") {
+            return Some(value.to_string());
+        }
+    }
+    None
 }
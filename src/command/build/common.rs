@@ -1,11 +1,13 @@
-use crate::constants::{ADD_USER, ALLOWED_ORIGINS, ENTRYPOINT, NO_AUTH, WILDFLY_ADMIN_CONTAINER};
+use crate::constants::{
+    ADD_USER, ALLOWED_ORIGINS, ENTRYPOINT, ENTRYPOINT_REVISION, NO_AUTH, WILDFLY_ADMIN_CONTAINER,
+};
 use crate::container::container_command;
 use crate::label::Label;
 use crate::progress::CommandStatus;
 use crate::resources::{
     DOMAIN_CONTROLLER_ENTRYPOINT_SH, HOST_CONTROLLER_ENTRYPOINT_SH, STANDALONE_ENTRYPOINT_SH,
 };
-use crate::wildfly::{AdminImage, ServerType};
+use crate::wildfly::{AdminImage, ServerType, default_host_config};
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::Write;
@@ -30,6 +32,8 @@ pub(super) fn base_template_data(admin_image: &AdminImage) -> HashMap<&'static s
     let mut data = HashMap::new();
     data.insert("label-name", Label::Id.key().to_string());
     data.insert("label-value", admin_image.identifier());
+    data.insert("entrypoint-label-name", Label::Entrypoint.key().to_string());
+    data.insert("entrypoint-revision", ENTRYPOINT_REVISION.to_string());
     data.insert("entrypoint", ENTRYPOINT.to_string());
     data.insert("add-user", ADD_USER.to_string());
     data.insert("allowed-origins", ALLOWED_ORIGINS.to_string());
@@ -49,28 +53,13 @@ pub(super) fn dockerfile_data(
         data.insert("base-image", admin_image.wildfly_image.image_ref());
     }
 
-    let use_legacy_names =
-        !admin_image.wildfly_image.is_dev() && admin_image.wildfly_image.version.major < 27;
-    match admin_image.server_type {
-        ServerType::Standalone => {
-            data.insert("is-standalone", "true".to_string());
-        }
-        ServerType::DomainController => {
-            let name = if use_legacy_names {
-                "master"
-            } else {
-                "primary"
-            };
-            data.insert("host-config", format!("host-{}.xml", name));
-        }
-        ServerType::HostController => {
-            let name = if use_legacy_names {
-                "slave"
-            } else {
-                "secondary"
-            };
-            data.insert("host-config", format!("host-{}.xml", name));
-        }
+    if admin_image.server_type == ServerType::Standalone {
+        data.insert("is-standalone", "true".to_string());
+    }
+    if let Some(host_config) =
+        default_host_config(&admin_image.wildfly_image, admin_image.server_type)
+    {
+        data.insert("host-config", host_config);
     }
 
     data
@@ -1,10 +1,8 @@
-use crate::args::username_password_argument;
+use crate::args::{management_client_argument, username_password_argument};
 use crate::constants::WILDFLY_ADMIN_CONTAINER;
-use crate::container::{container_ps, get_instance};
 use crate::progress::Progress;
 use crate::wildfly::ManagementClient;
-use crate::wildfly::ServerType::{DomainController, Standalone};
-use anyhow::{Context, anyhow, bail};
+use anyhow::{Context, anyhow};
 use clap::ArgMatches;
 use fs::{File, create_dir_all};
 use futures::executor::block_on;
@@ -14,56 +12,29 @@ use std::io::Write;
 use std::path::{Path, PathBuf};
 use tokio::process::Command;
 use which::which;
-use wildfly_meta::{WildFlyImage, WildFlyImageRegistry};
+use wildfly_meta::WildFlyImageRegistry;
 
 pub fn cli(matches: &ArgMatches, registry: &WildFlyImageRegistry) -> anyhow::Result<()> {
-    which("java").with_context(|| "java not found. Install JDK 11 or later to run JBoss CLI")?;
-
-    let management_client = if let Some(name) = matches.get_one::<String>("name") {
-        let mut v = vec![];
-        let wildfly_images =
-            if let Some(wildfly_image) = matches.get_one::<WildFlyImage>("wildfly-version") {
-                v.push(wildfly_image.clone());
-                Some(&v)
-            } else {
-                None
-            };
-        let instance = block_on(get_instance(
-            wildfly_images.map(|v| v.as_slice()),
-            Some(name),
-            registry,
-        ))?;
-        ManagementClient::from_container_instance(&instance, registry)
-    } else if let Some(wildfly_image) = matches.get_one::<WildFlyImage>("wildfly-version") {
-        ManagementClient::custom_port(
-            wildfly_image,
-            *matches
-                .get_one::<u16>("management")
-                .unwrap_or(&wildfly_image.management_port()),
-            registry,
-        )
-    } else {
-        let containers = block_on(container_ps(
-            vec![Standalone, DomainController],
-            None,
-            None,
-            true,
-            registry,
-        ))?;
-        if containers.is_empty() {
-            bail!("No running containers found.")
-        } else if containers.len() > 1 {
-            bail!("Multiple running containers found. Please specify a version or a name.")
-        } else {
-            ManagementClient::from_container_instance(&containers[0], registry)
-        }
-    };
+    let management_client = management_client_argument(matches, registry)?;
     let (username, password) = username_password_argument(matches);
     let parameters = matches
         .get_many::<String>("cli-parameters")
         .unwrap_or_default()
         .cloned()
         .collect::<Vec<_>>();
+    run_cli(&management_client, username, password, parameters)
+}
+
+/// Downloads the CLI jar and config (if necessary) and connects to the management
+/// interface of the given client.
+pub(crate) fn run_cli(
+    management_client: &ManagementClient,
+    username: &str,
+    password: &str,
+    parameters: Vec<String>,
+) -> anyhow::Result<()> {
+    which("java").with_context(|| "java not found. Install JDK 11 or later to run JBoss CLI")?;
+
     let dir_suffix = if management_client.wildfly_image.is_dev() {
         "dev".to_string()
     } else {
@@ -73,7 +44,7 @@ pub fn cli(matches: &ArgMatches, registry: &WildFlyImageRegistry) -> anyhow::Res
 
     create_dir_all(&temp_dir)?;
     block_on(connect_to_cli(
-        &management_client,
+        management_client,
         &temp_dir,
         username,
         password,
@@ -7,6 +7,8 @@ use std::process::Command;
 use anyhow::{Context, Result, bail};
 use clap::ArgMatches;
 
+use crate::paths::home_dir;
+
 const SUPPORTED_SHELLS: &[&str] = &["bash", "zsh", "fish", "elvish", "powershell"];
 
 pub fn completions(matches: &ArgMatches) -> Result<()> {
@@ -100,12 +102,6 @@ fn completion_path(shell: &str) -> Result<PathBuf> {
     }
 }
 
-fn home_dir() -> Option<PathBuf> {
-    env::var_os("HOME")
-        .or_else(|| env::var_os("USERPROFILE"))
-        .map(PathBuf::from)
-}
-
 fn print_post_install_instructions(shell: &str, path: &Path) {
     match shell {
         "fish" => {
@@ -9,12 +9,18 @@ use wildfly_meta::{WildFlyImage, WildFlyImageRegistry};
 pub fn console(matches: &ArgMatches, registry: &WildFlyImageRegistry) -> anyhow::Result<()> {
     let management_clients = get_management_clients(matches, registry)?;
     for client in management_clients {
-        let url = format!("http://localhost:{}/console", client.management_port);
-        webbrowser::open(&url)?;
+        open_console(&client)?;
     }
     Ok(())
 }
 
+/// Opens the management console of the given client in the default browser.
+pub(crate) fn open_console(client: &ManagementClient) -> anyhow::Result<()> {
+    let url = format!("http://localhost:{}/console", client.management_port);
+    webbrowser::open(&url)?;
+    Ok(())
+}
+
 fn get_management_clients(
     matches: &ArgMatches,
     registry: &WildFlyImageRegistry,
@@ -27,6 +33,7 @@ fn get_management_clients(
             bail!("Option <name> is not allowed when multiple <wildfly-version> are specified!");
         }
         let instance = block_on(get_instance(
+            vec![Standalone, DomainController],
             wildfly_images.map(|v| v.as_slice()),
             Some(name),
             registry,
@@ -65,6 +72,7 @@ fn get_management_clients(
             None,
             None,
             true,
+            false,
             registry,
         ))?;
         Ok(containers
@@ -2,12 +2,18 @@ use super::lifecycle::{
     apply_ports, prepare_instances, print_json_results, run_instances,
     stop_containers_by_server_type, wait_for_instances,
 };
-use crate::args::{extract_config, operations_argument, parameters_argument, server_argument};
+use super::restart::resume;
+use crate::args::{
+    extract_config, parameters_argument, run_options_argument, server_argument,
+    start_versions_argument, wait_timeout_argument,
+};
 use crate::constants::{HOSTNAME_VARIABLE, WILDFLY_ADMIN_CONTAINER};
-use crate::container::{add_servers, container_network_cmd, container_run_cmd};
+use crate::container::{RunOptions, add_servers, container_network_cmd, container_run_cmd};
+use crate::progress::{CommandStatus, Progress};
 use crate::wildfly::{DomainController, Server, ServerType};
 use clap::ArgMatches;
 use futures::executor::block_on;
+use std::time::Duration;
 use wildfly_meta::WildFlyImageRegistry;
 
 // ------------------------------------------------------ start
@@ -17,29 +23,66 @@ pub fn dc_start(
     registry: &WildFlyImageRegistry,
     json: bool,
 ) -> anyhow::Result<()> {
+    if let Some(name) = matches.get_one::<String>("resume") {
+        return resume(
+            name,
+            ServerType::DomainController,
+            wait_timeout_argument(matches),
+            registry,
+            json,
+        );
+    }
+    let mut run_options = run_options_argument(matches, ServerType::DomainController)?;
+    let wildfly_images = start_versions_argument(
+        matches,
+        ServerType::DomainController,
+        &mut run_options,
+        registry,
+    )?;
     let instances: Vec<DomainController> = prepare_instances(
         matches,
+        wildfly_images,
         ServerType::DomainController,
         &["name", "http", "management", "offset"],
-        |r| DomainController::new(r.admin_image, r.name, r.ports.unwrap()),
+        |r| DomainController::new(r.admin_image, r.name, r.ports.unwrap(), r.debug_port),
         registry,
     )?;
+    let mut parameters = parameters_argument(matches);
+    parameters.extend(run_options.parameters(ServerType::DomainController));
     block_on(start_instances(
         instances,
         server_argument(matches),
-        operations_argument(matches),
-        parameters_argument(matches),
+        run_options,
+        parameters,
+        wait_timeout_argument(matches),
         json,
     ))
 }
 
-async fn start_instances(
+pub async fn start_instances(
     instances: Vec<DomainController>,
     servers: Vec<Server>,
-    operations: Vec<String>,
+    run_options: RunOptions,
     parameters: Vec<String>,
+    timeout: Duration,
     json: bool,
 ) -> anyhow::Result<()> {
+    let status = start_and_wait(instances, servers, run_options, parameters, timeout, json).await?;
+    if json {
+        print_json_results(&status);
+    }
+    Ok(())
+}
+
+/// Starts the instances and waits until their management interfaces are available.
+pub async fn start_and_wait(
+    instances: Vec<DomainController>,
+    servers: Vec<Server>,
+    run_options: RunOptions,
+    parameters: Vec<String>,
+    timeout: Duration,
+    json: bool,
+) -> anyhow::Result<Vec<(CommandStatus, Progress)>> {
     let config = extract_config(&parameters, "domain.xml");
     container_network_cmd().await?;
 
@@ -54,7 +97,8 @@ async fn start_instances(
             let mut command = container_run_cmd(
                 &instance.name,
                 Some(&instance.ports),
-                operations.clone(),
+                instance.debug_port,
+                &run_options,
                 instance.admin_image.wildfly_image.is_dev(),
                 None,
                 Some(&config),
@@ -75,12 +119,8 @@ async fn start_instances(
     .await?;
 
     let mut status = apply_ports(results, &port_map);
-    wait_for_instances(&mut status, json).await;
-
-    if json {
-        print_json_results(&status);
-    }
-    Ok(())
+    wait_for_instances(&mut status, timeout, json).await;
+    Ok(status)
 }
 
 // ------------------------------------------------------ stop
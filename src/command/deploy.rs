@@ -0,0 +1,258 @@
+//! Deploys applications to running standalone servers and domain controllers.
+//!
+//! Deployments are uploaded using the HTTP management API. A version range
+//! deploys the same archives to all matching containers in parallel.
+
+use super::lifecycle::{create_progress, print_json_results};
+use crate::args::{selected_containers_argument, username_password_argument};
+use crate::json::DeploymentInfo;
+use crate::progress::{CommandStatus, Progress, summary};
+use crate::wildfly::ServerType::{DomainController, Standalone};
+use crate::wildfly::{ContainerInstance, Deployment, ManagementApi};
+use anyhow::Context;
+use clap::ArgMatches;
+use comfy_table::presets::UTF8_BORDERS_ONLY;
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+use futures::executor::block_on;
+use futures::future::join_all;
+use indicatif::MultiProgress;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+use wildfly_meta::WildFlyImageRegistry;
+
+// ------------------------------------------------------ deploy
+
+pub fn deploy(
+    matches: &ArgMatches,
+    registry: &WildFlyImageRegistry,
+    json: bool,
+) -> anyhow::Result<()> {
+    let deployments = matches
+        .get_many::<PathBuf>("deployment")
+        .unwrap_or_default()
+        .map(|path| Deployment::load(path))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let server_groups = matches
+        .get_many::<String>("server-groups")
+        .map(|groups| groups.cloned().collect::<Vec<_>>());
+    let force = matches.get_flag("force");
+    let instances = select_instances(matches, registry)?;
+
+    let deployments = Arc::new(deployments);
+    for_each_instance(
+        matches,
+        instances,
+        "Deployed to",
+        json,
+        move |api, instance, progress| {
+            let deployments = deployments.clone();
+            let server_groups = server_groups.clone();
+            async move {
+                let server_groups = if instance.admin_image.server_type == DomainController {
+                    match server_groups {
+                        Some(server_groups) => Some(server_groups),
+                        None => Some(api.server_group_names().await?),
+                    }
+                } else {
+                    None
+                };
+                for deployment in deployments.iter() {
+                    progress.show_progress(&format!("Deploying {}", deployment.name));
+                    api.deploy(deployment, server_groups.as_deref(), force)
+                        .await
+                        .with_context(|| format!("Failed to deploy {}", deployment.name))?;
+                }
+                Ok(())
+            }
+        },
+    )
+}
+
+// ------------------------------------------------------ undeploy
+
+pub fn undeploy(
+    matches: &ArgMatches,
+    registry: &WildFlyImageRegistry,
+    json: bool,
+) -> anyhow::Result<()> {
+    let names = matches
+        .get_many::<String>("deployment")
+        .map(|names| names.cloned().collect::<Vec<_>>());
+    let instances = select_instances(matches, registry)?;
+
+    for_each_instance(
+        matches,
+        instances,
+        "Undeployed from",
+        json,
+        move |api, instance, progress| {
+            let names = names.clone();
+            async move {
+                let names = match names {
+                    Some(names) => names,
+                    None => api.deployment_names().await?,
+                };
+                let domain = instance.admin_image.server_type == DomainController;
+                for name in names {
+                    progress.show_progress(&format!("Undeploying {}", name));
+                    api.undeploy(&name, domain)
+                        .await
+                        .with_context(|| format!("Failed to undeploy {}", name))?;
+                }
+                Ok(())
+            }
+        },
+    )
+}
+
+// ------------------------------------------------------ deployments
+
+pub fn deployments(
+    matches: &ArgMatches,
+    registry: &WildFlyImageRegistry,
+    json: bool,
+) -> anyhow::Result<()> {
+    let instances = select_instances(matches, registry)?;
+    let (username, password) = username_password_argument(matches);
+
+    let states = block_on(join_all(instances.iter().map(|instance| async move {
+        let api = management_api(instance, username, password)?;
+        let domain = instance.admin_image.server_type == DomainController;
+        api.deployment_states(domain)
+            .await
+            .with_context(|| format!("Failed to read deployments of {}", instance.name))
+    })));
+    let mut infos = vec![];
+    for (instance, states) in instances.iter().zip(states) {
+        for state in states? {
+            infos.push((
+                instance,
+                DeploymentInfo {
+                    container: instance.name.clone(),
+                    name: state.name,
+                    enabled: state.enabled,
+                    status: state.status,
+                    server_groups: state.server_groups,
+                },
+            ));
+        }
+    }
+
+    if json {
+        let infos = infos.into_iter().map(|(_, info)| info).collect::<Vec<_>>();
+        println!("{}", serde_json::to_string(&infos)?);
+        return Ok(());
+    }
+    if infos.is_empty() {
+        println!("\nNo deployments found.");
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_BORDERS_ONLY)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            "Version",
+            "Container",
+            "Deployment",
+            "Enabled",
+            "Status",
+            "Server Groups",
+        ]);
+    for (instance, info) in infos {
+        table.add_row(vec![
+            Cell::new(instance.admin_image.wildfly_image.short_name()).fg(Color::DarkMagenta),
+            Cell::new(info.container).fg(Color::DarkYellow),
+            Cell::new(info.name).fg(Color::DarkCyan),
+            if info.enabled {
+                Cell::new("yes").fg(Color::Green)
+            } else {
+                Cell::new("no").fg(Color::Red)
+            },
+            Cell::new(info.status.unwrap_or_default()),
+            Cell::new(info.server_groups.join(", ")).fg(Color::DarkBlue),
+        ]);
+    }
+    println!("\n{table}");
+    Ok(())
+}
+
+// ------------------------------------------------------ helpers
+
+/// Selects the running standalone servers and domain controllers matching
+/// the version and name arguments.
+fn select_instances(
+    matches: &ArgMatches,
+    registry: &WildFlyImageRegistry,
+) -> anyhow::Result<Vec<ContainerInstance>> {
+    selected_containers_argument(matches, vec![Standalone, DomainController], registry)
+}
+
+fn management_api(
+    instance: &ContainerInstance,
+    username: &str,
+    password: &str,
+) -> anyhow::Result<ManagementApi> {
+    let port = instance
+        .ports
+        .as_ref()
+        .map(|ports| ports.management)
+        .with_context(|| format!("No management port published for {}", instance.name))?;
+    ManagementApi::new(port, username, password)
+}
+
+/// Runs a management task for all instances in parallel, showing one progress
+/// spinner per container followed by a summary (or the JSON results).
+fn for_each_instance<F, Fut>(
+    matches: &ArgMatches,
+    instances: Vec<ContainerInstance>,
+    verb: &str,
+    json: bool,
+    task: F,
+) -> anyhow::Result<()>
+where
+    F: Fn(ManagementApi, ContainerInstance, Progress) -> Fut,
+    Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+{
+    let (username, password) = username_password_argument(matches);
+    let count = instances.len();
+    let instant = Instant::now();
+    let multi_progress = if json {
+        None
+    } else {
+        Some(MultiProgress::new())
+    };
+    let mut tasks = JoinSet::new();
+    for instance in instances {
+        let progress = create_progress(
+            &multi_progress,
+            &instance.admin_image.wildfly_image.short_name(),
+            &instance.admin_image.image_name(),
+        );
+        let name = instance.name.clone();
+        let future = management_api(&instance, username, password)
+            .map(|api| task(api, instance, progress.clone()));
+        tasks.spawn(async move {
+            let result = match future {
+                Ok(future) => future.await,
+                Err(e) => Err(e),
+            };
+            let status = match result {
+                Ok(()) => progress.finish_no_output(Some(&name)),
+                Err(e) => progress.finish_error(&name, &format!("{:#}", e)),
+            };
+            (status, progress)
+        });
+    }
+    let results = block_on(tasks.join_all());
+    if json {
+        print_json_results(&results);
+    } else {
+        let statuses: Vec<CommandStatus> = results.iter().map(|(s, _)| s.clone()).collect();
+        summary(verb, "container", count, instant, statuses);
+    }
+    Ok(())
+}
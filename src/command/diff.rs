@@ -0,0 +1,175 @@
+//! Compares the management models of two running containers or WildFly versions.
+//!
+//! Each side is either the name of a running standalone server or domain controller,
+//! or a WildFly version. For versions, a running container of that version is used
+//! if there is one. Otherwise, a temporary container is started for the comparison
+//! and stopped afterward.
+
+use super::temporary::{resolve_target, start_targets, stop_temporary};
+use crate::args::username_password_argument;
+use crate::container::verify_container_command;
+use crate::error::WadoError;
+use crate::wildfly::{
+    ChangeKind, ManagementClient, Model, ModelChange, Operation, ServerType, diff_models,
+};
+use anyhow::anyhow;
+use clap::ArgMatches;
+use console::style;
+use futures::executor::block_on;
+use serde_json::Value;
+use wildfly_meta::WildFlyImageRegistry;
+
+pub fn diff(
+    matches: &ArgMatches,
+    registry: &WildFlyImageRegistry,
+    json: bool,
+) -> anyhow::Result<()> {
+    verify_container_command()?;
+    let server_type = if matches.get_flag("domain") {
+        ServerType::DomainController
+    } else {
+        ServerType::Standalone
+    };
+    let address = matches
+        .get_one::<String>("address")
+        .map(|a| a.trim_end_matches('/'))
+        .unwrap_or_default();
+    let input = format!("{}:read-resource(recursive=true)", address);
+    let operation = Operation::parse(&input)
+        .map_err(|e| anyhow!(WadoError::invalid_operation(&e.display_with(&input))))?;
+    let description = Operation {
+        name: "read-resource-description".to_string(),
+        ..operation.clone()
+    };
+    let address = format!(
+        "/{}",
+        operation
+            .address
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>()
+            .join("/")
+    );
+    let (username, password) = username_password_argument(matches);
+
+    block_on(async {
+        let left = resolve_target(
+            matches.get_one::<String>("left").unwrap(),
+            server_type,
+            registry,
+        )
+        .await?;
+        let right = resolve_target(
+            matches.get_one::<String>("right").unwrap(),
+            server_type,
+            registry,
+        )
+        .await?;
+        let (clients, temporary) =
+            start_targets(vec![left, right], server_type, registry, json).await?;
+
+        let mut models = vec![];
+        let mut result = Ok(());
+        for target in &clients {
+            let model = match &target.client {
+                Ok(client) => read_model(client, username, password, &operation, &description)
+                    .await
+                    .map_err(|e| e.to_string()),
+                Err(e) => Err(e.clone()),
+            };
+            match model {
+                Ok(model) => models.push(model),
+                Err(e) => {
+                    result = Err(anyhow!(
+                        "Unable to read the model of '{}': {}",
+                        target.name,
+                        e
+                    ));
+                    break;
+                }
+            }
+        }
+        stop_temporary(&temporary).await;
+        result?;
+
+        let [left, right] = [&models[0], &models[1]].map(|(resource, description)| Model {
+            resource,
+            description,
+        });
+        let changes = diff_models(&address, left, right);
+        if json {
+            println!("{}", serde_json::to_string(&changes)?);
+        } else {
+            print_changes(&clients[0].name, &clients[1].name, &changes);
+        }
+        Ok(())
+    })
+}
+
+/// Reads the model and the resource description of a target.
+async fn read_model(
+    client: &ManagementClient,
+    username: &str,
+    password: &str,
+    operation: &Operation,
+    description: &Operation,
+) -> anyhow::Result<(Value, Value)> {
+    let api = client.http_api(username, password)?;
+    let model = api.execute(&operation.to_dmr()).await?;
+    let description = api.execute(&description.to_dmr()).await?;
+    Ok((model, description))
+}
+
+fn print_changes(left: &str, right: &str, changes: &[ModelChange]) {
+    println!(
+        "\n{} {}\n{} {}\n",
+        style("---").red(),
+        style(left).red(),
+        style("+++").green(),
+        style(right).green()
+    );
+    for change in changes {
+        let line = match (&change.attribute, change.kind) {
+            (None, _) => change.address.clone(),
+            (Some(attribute), ChangeKind::Changed) => format!(
+                "{} {}: {} → {}",
+                change.address,
+                attribute,
+                value_string(&change.left),
+                value_string(&change.right)
+            ),
+            (Some(attribute), ChangeKind::Added) => format!(
+                "{} {} = {}",
+                change.address,
+                attribute,
+                value_string(&change.right)
+            ),
+            (Some(attribute), ChangeKind::Removed) => format!(
+                "{} {} = {}",
+                change.address,
+                attribute,
+                value_string(&change.left)
+            ),
+        };
+        match change.kind {
+            ChangeKind::Added => println!("{} {}", style("+").green(), style(line).green()),
+            ChangeKind::Removed => println!("{} {}", style("-").red(), style(line).red()),
+            ChangeKind::Changed => println!("{} {}", style("~").yellow(), line),
+        }
+    }
+
+    let count = |kind: ChangeKind| changes.iter().filter(|c| c.kind == kind).count();
+    println!(
+        "\n{} added, {} removed, {} changed.",
+        style(count(ChangeKind::Added)).green(),
+        style(count(ChangeKind::Removed)).red(),
+        style(count(ChangeKind::Changed)).yellow()
+    );
+}
+
+fn value_string(value: &Option<Value>) -> String {
+    match value {
+        None | Some(Value::Null) => "undefined".to_string(),
+        Some(value) => value.to_string(),
+    }
+}
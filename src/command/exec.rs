@@ -0,0 +1,219 @@
+//! Runs commands inside running containers.
+//!
+//! A single container can be entered interactively (defaults to `bash`). A
+//! non-interactive command can be fanned out to all containers matched by a
+//! version range or topology, printing one output section per container.
+
+use crate::args::selected_containers_argument;
+use crate::container::container_exec_cmd;
+use crate::json::ExecResult;
+use crate::wildfly::ContainerInstance;
+use crate::wildfly::ServerType::{DomainController, HostController, Standalone};
+use anyhow::{anyhow, bail};
+use clap::ArgMatches;
+use console::style;
+use futures::executor::block_on;
+use std::io::IsTerminal;
+use std::process::Stdio;
+use tokio::task::JoinSet;
+use wildfly_meta::WildFlyImageRegistry;
+
+const DEFAULT_SHELL: &str = "bash";
+
+pub fn exec(
+    matches: &ArgMatches,
+    registry: &WildFlyImageRegistry,
+    json: bool,
+) -> anyhow::Result<()> {
+    let command = command_argument(matches);
+
+    let instances = selected_containers_argument(
+        matches,
+        vec![Standalone, DomainController, HostController],
+        registry,
+    )?;
+
+    match exec_mode(instances.len(), command.is_empty(), json)? {
+        ExecMode::Attached => block_on(exec_attached(&instances[0], command)),
+        ExecMode::All => block_on(exec_all(&instances, command, json)),
+    }
+}
+
+/// Returns the command passed after `--` (empty for an interactive shell).
+fn command_argument(matches: &ArgMatches) -> Vec<String> {
+    matches
+        .get_many::<String>("command")
+        .unwrap_or_default()
+        .cloned()
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+enum ExecMode {
+    /// Run the command attached to the terminal of the only selected container.
+    Attached,
+    /// Run the command in all selected containers and collect the output.
+    All,
+}
+
+/// Decides how to run the command based on the number of selected containers.
+fn exec_mode(containers: usize, shell: bool, json: bool) -> anyhow::Result<ExecMode> {
+    if containers == 0 {
+        bail!("No running containers found.")
+    } else if containers == 1 && !json {
+        Ok(ExecMode::Attached)
+    } else if shell && json {
+        bail!("An interactive shell can't be used with --json. Please pass a command after '--'.")
+    } else if shell {
+        bail!(
+            "An interactive shell needs exactly one container, but {} containers were found. \
+             Please specify a version or a name, or pass a command after '--'.",
+            containers
+        )
+    } else {
+        Ok(ExecMode::All)
+    }
+}
+
+/// Runs the command attached to the terminal of the current process.
+async fn exec_attached(instance: &ContainerInstance, command: Vec<String>) -> anyhow::Result<()> {
+    let (interactive, command) = if command.is_empty() {
+        (true, vec![DEFAULT_SHELL.to_string()])
+    } else {
+        (std::io::stdin().is_terminal(), command)
+    };
+    let status = container_exec_cmd(&instance.name, interactive, &command)
+        .spawn()?
+        .wait()
+        .await?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Command failed in {} with exit code: {}",
+            instance.name,
+            status.code().unwrap_or(-1)
+        ))
+    }
+}
+
+/// Runs the command in all containers in parallel and prints one section per container.
+async fn exec_all(
+    instances: &[ContainerInstance],
+    command: Vec<String>,
+    json: bool,
+) -> anyhow::Result<()> {
+    let mut commands = JoinSet::new();
+    for instance in instances {
+        let child = container_exec_cmd(&instance.name, false, &command)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let name = instance.name.clone();
+        commands.spawn(async move {
+            match child.wait_with_output().await {
+                Ok(output) => ExecResult {
+                    container: name,
+                    success: output.status.success(),
+                    exit_code: output.status.code(),
+                    stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+                    stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+                },
+                Err(e) => ExecResult {
+                    container: name,
+                    success: false,
+                    exit_code: None,
+                    stdout: String::new(),
+                    stderr: e.to_string(),
+                },
+            }
+        });
+    }
+
+    let mut results = commands.join_all().await;
+    results.sort_by_key(|r| {
+        instances
+            .iter()
+            .position(|i| i.name == r.container)
+            .unwrap_or(usize::MAX)
+    });
+
+    if json {
+        println!("{}", serde_json::to_string(&results)?);
+        return Ok(());
+    }
+
+    for result in &results {
+        let status = match result.exit_code {
+            Some(0) => style("exit code 0".to_string()).green(),
+            Some(code) => style(format!("exit code {}", code)).red(),
+            None => style("failed".to_string()).red(),
+        };
+        println!("\n{} ({})", style(&result.container).cyan().bold(), status);
+        print!("{}", result.stdout);
+        if !result.stderr.is_empty() {
+            eprint!("{}", result.stderr);
+        }
+    }
+    let failed = results.iter().filter(|r| !r.success).count();
+    if failed > 0 {
+        bail!(
+            "Command failed in {} of {} containers",
+            failed,
+            results.len()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::build_app;
+
+    fn exec_matches(args: &[&str]) -> ArgMatches {
+        let matches = build_app()
+            .try_get_matches_from([&["wado", "exec"], args].concat())
+            .unwrap();
+        matches.subcommand_matches("exec").unwrap().clone()
+    }
+
+    #[test]
+    fn command_after_separator() {
+        let matches = exec_matches(&["34", "--name", "foo", "--", "ls", "-l"]);
+        assert_eq!(command_argument(&matches), vec!["ls", "-l"]);
+        assert_eq!(
+            matches.get_one::<String>("name").map(String::as_str),
+            Some("foo")
+        );
+    }
+
+    #[test]
+    fn shell_without_command() {
+        let matches = exec_matches(&["--topology", "demo"]);
+        assert!(command_argument(&matches).is_empty());
+        assert_eq!(
+            matches.get_one::<String>("topology").map(String::as_str),
+            Some("demo")
+        );
+    }
+
+    #[test]
+    fn single_container() {
+        assert_eq!(exec_mode(1, true, false).unwrap(), ExecMode::Attached);
+        assert_eq!(exec_mode(1, false, false).unwrap(), ExecMode::Attached);
+        assert_eq!(exec_mode(1, false, true).unwrap(), ExecMode::All);
+        let error = exec_mode(1, true, true).unwrap_err().to_string();
+        assert!(error.contains("--json"), "{}", error);
+    }
+
+    #[test]
+    fn multiple_containers() {
+        assert_eq!(exec_mode(3, false, false).unwrap(), ExecMode::All);
+        assert_eq!(exec_mode(3, false, true).unwrap(), ExecMode::All);
+        let error = exec_mode(3, true, false).unwrap_err().to_string();
+        assert!(error.contains("exactly one container"), "{}", error);
+        assert!(exec_mode(0, false, false).is_err());
+    }
+}
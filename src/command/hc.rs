@@ -1,20 +1,29 @@
 use super::lifecycle::{print_json_results, run_instances, stop_containers_by_server_type};
+use super::restart::resume;
 use crate::args::{
-    extract_config, name_argument, operations_argument, parameters_argument, server_argument,
-    username_password_argument, versions_argument,
+    deployment_groups_argument, extract_host_config, name_argument, parameters_argument,
+    run_options_argument, server_argument, username_password_argument, versions_argument,
 };
 use crate::constants::{
     DOMAIN_CONTROLLER_VARIABLE, HOSTNAME_VARIABLE, PASSWORD_VARIABLE, USERNAME_VARIABLE,
     WILDFLY_ADMIN_CONTAINER,
 };
 use crate::container::{
-    add_servers, container_network_cmd, container_run_cmd, create_secret, resolve_start_specs,
-    verify_container_command,
+    RunOptions, add_servers, container_network_cmd, container_ps, container_run_cmd, create_secret,
+    resolve_start_specs, verify_container_command,
 };
-use crate::wildfly::{AdminImage, HostController, Server, ServerType, StartSpec};
-use anyhow::bail;
+use crate::error::WadoError;
+use crate::healthcheck::DEFAULT_WAIT_TIMEOUT;
+use crate::wildfly::{
+    AdminImage, Deployment, HostController, ManagementApi, MixedDomain, Server, ServerType,
+    StartSpec, default_host_config,
+};
+use anyhow::{Context, bail};
 use clap::ArgMatches;
+use console::style;
 use futures::executor::block_on;
+use std::path::PathBuf;
+use std::time::Duration;
 use tokio::try_join;
 use wildfly_meta::{WildFlyImage, WildFlyImageRegistry};
 
@@ -25,7 +34,18 @@ pub fn hc_start(
     registry: &WildFlyImageRegistry,
     json: bool,
 ) -> anyhow::Result<()> {
+    if let Some(name) = matches.get_one::<String>("resume") {
+        // host controllers don't have a --wait-timeout, since they're not waited for
+        return resume(
+            name,
+            ServerType::HostController,
+            Duration::from_secs(DEFAULT_WAIT_TIMEOUT),
+            registry,
+            json,
+        );
+    }
     verify_container_command()?;
+    let run_options = run_options_argument(matches, ServerType::HostController)?;
 
     let wildfly_images = versions_argument(matches);
     let wildfly_image = wildfly_images[0].clone();
@@ -52,20 +72,41 @@ pub fn hc_start(
             custom_name: matches.get_one::<String>("name").cloned(),
             custom_http: None,
             custom_management: None,
+            custom_offset: None,
+            publish: vec![],
+            debug: run_options.debug.is_some(),
         })
         .collect();
     let resolved = block_on(resolve_start_specs(
         ServerType::HostController,
         specs,
+        &[],
         registry,
     ))?;
     let instances: Vec<HostController> = resolved
         .into_iter()
-        .map(|r| HostController::new(r.admin_image, r.name, dc_name.clone()))
+        .map(|r| HostController::new(r.admin_image, r.name, dc_name.clone(), r.debug_port))
         .collect();
+    check_mixed_domain(&dc_name, &instances, registry, json)?;
 
     let (username, password) = username_password_argument(matches);
+    let deployments = matches
+        .get_many::<PathBuf>("deployment")
+        .unwrap_or_default()
+        .map(|path| Deployment::load(path))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    if !deployments.is_empty() {
+        block_on(deploy_to_domain_controller(
+            &dc_name,
+            &deployments,
+            &deployment_groups_argument(matches),
+            username,
+            password,
+            registry,
+        ))?;
+    }
     let mut parameters = parameters_argument(matches);
+    parameters.extend(run_options.parameters(ServerType::HostController));
     let primary_address = format!("--primary-address={}", dc_name);
     parameters.push(primary_address);
     block_on(start_instances(
@@ -73,12 +114,91 @@ pub fn hc_start(
         username,
         password,
         server_argument(matches),
-        operations_argument(matches),
+        run_options,
         parameters,
         json,
     ))
 }
 
+/// Rejects host controllers which are newer than the running domain controller `dc_name`
+/// and warns about host controllers which are considerably older. Nothing is checked
+/// if the domain controller is not running.
+fn check_mixed_domain(
+    dc_name: &str,
+    instances: &[HostController],
+    registry: &WildFlyImageRegistry,
+    json: bool,
+) -> anyhow::Result<()> {
+    let running = block_on(container_ps(
+        vec![ServerType::DomainController],
+        None,
+        Some(dc_name),
+        false,
+        false,
+        registry,
+    ))?;
+    let Some(dc) = running.iter().find(|instance| instance.name == dc_name) else {
+        return Ok(());
+    };
+    let dc_image = &dc.admin_image.wildfly_image;
+    for instance in instances {
+        let hc_image = &instance.admin_image.wildfly_image;
+        let check = MixedDomain::check(dc_image, hc_image);
+        if let Some(message) = check.message(&instance.name, dc_image, hc_image) {
+            if check == MixedDomain::Unsupported {
+                return Err(WadoError::topology_error(&message)
+                    .with_details(check.details(&instance.name, dc_image, hc_image))
+                    .into());
+            } else if !json {
+                println!("{}", style(message).yellow());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Deploys the archives of host controllers to the running domain controller `dc_name`
+/// and assigns them to the given server groups. Existing deployments are only assigned.
+async fn deploy_to_domain_controller(
+    dc_name: &str,
+    deployments: &[Deployment],
+    server_groups: &[String],
+    username: &str,
+    password: &str,
+    registry: &WildFlyImageRegistry,
+) -> anyhow::Result<()> {
+    let running = container_ps(
+        vec![ServerType::DomainController],
+        None,
+        Some(dc_name),
+        true,
+        false,
+        registry,
+    )
+    .await?;
+    let Some(ports) = running
+        .iter()
+        .find(|instance| instance.name == dc_name)
+        .and_then(|instance| instance.ports.as_ref())
+    else {
+        bail!(
+            "Domain controller {} is not running. Deployments of host controllers are deployed to the domain controller.",
+            dc_name
+        );
+    };
+    let api = ManagementApi::new(ports.management, username, password)?;
+    let existing = api.deployment_names().await?;
+    for deployment in deployments {
+        let result = if existing.contains(&deployment.name) {
+            api.assign(&deployment.name, server_groups).await
+        } else {
+            api.deploy(deployment, Some(server_groups), false).await
+        };
+        result.with_context(|| format!("Failed to deploy {} to {}", deployment.name, dc_name))?;
+    }
+    Ok(())
+}
+
 fn same_versions(instances: &[WildFlyImage]) -> bool {
     instances
         .iter()
@@ -86,12 +206,12 @@ fn same_versions(instances: &[WildFlyImage]) -> bool {
         .all(|identifier| identifier == instances[0].identifier)
 }
 
-async fn start_instances(
+pub async fn start_instances(
     instances: Vec<HostController>,
     username: &str,
     password: &str,
     servers: Vec<Server>,
-    operations: Vec<String>,
+    run_options: RunOptions,
     parameters: Vec<String>,
     json: bool,
 ) -> anyhow::Result<()> {
@@ -100,14 +220,22 @@ async fn start_instances(
         create_secret("username", username),
         create_secret("password", password)
     )?;
-    let config = extract_config(&parameters, "domain.xml");
+    let host_config = extract_host_config(&parameters);
     let (results, _instant) = run_instances(
         &instances,
         |instance| {
+            let config = host_config.clone().unwrap_or_else(|| {
+                default_host_config(
+                    &instance.admin_image.wildfly_image,
+                    ServerType::HostController,
+                )
+                .unwrap_or_default()
+            });
             let mut command = container_run_cmd(
                 &instance.name,
                 None,
-                operations.clone(),
+                instance.debug_port,
+                &run_options,
                 instance.admin_image.wildfly_image.is_dev(),
                 None,
                 Some(&config),
@@ -141,7 +269,7 @@ async fn start_instances(
     .await?;
 
     for (s, p) in &results {
-        p.finish_if_alive(Some(&s.identifier));
+        p.finish_if_alive(Some(&s.display_name()));
     }
 
     if json {
@@ -0,0 +1,234 @@
+//! Shows the health of running containers.
+//!
+//! Reads the MicroProfile Health endpoints of the management interface (standalone
+//! servers and domain controllers) and the health reported by the `HEALTHCHECK` of
+//! the container runtime (all containers).
+
+use crate::args::selected_containers_argument;
+use crate::container::{RuntimeHealth, container_health};
+use crate::healthcheck::{HEALTH_ENDPOINTS, HealthEndpoint, read_health};
+use crate::json::{HealthCheckInfo, HealthEndpointInfo, HealthInfo, RuntimeHealthInfo};
+use crate::wildfly::ContainerInstance;
+use crate::wildfly::ServerType::{DomainController, HostController, Standalone};
+use clap::ArgMatches;
+use comfy_table::presets::UTF8_BORDERS_ONLY;
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+use futures::executor::block_on;
+use futures::future::join_all;
+use wildfly_meta::WildFlyImageRegistry;
+
+pub fn health(
+    matches: &ArgMatches,
+    registry: &WildFlyImageRegistry,
+    json: bool,
+) -> anyhow::Result<()> {
+    let instances = selected_containers_argument(
+        matches,
+        vec![Standalone, DomainController, HostController],
+        registry,
+    )?;
+
+    let infos = block_on(join_all(instances.iter().map(health_info)));
+    if json {
+        println!("{}", serde_json::to_string(&infos)?);
+    } else {
+        print_table(&infos);
+    }
+    Ok(())
+}
+
+async fn health_info(instance: &ContainerInstance) -> HealthInfo {
+    let runtime = container_health(&instance.name).await.ok().flatten();
+    let endpoints = match instance.ports.as_ref() {
+        Some(ports) if instance.admin_image.server_type != HostController => {
+            let results = join_all(
+                HEALTH_ENDPOINTS
+                    .iter()
+                    .map(|endpoint| read_health(ports.management, endpoint)),
+            )
+            .await;
+            HEALTH_ENDPOINTS
+                .iter()
+                .zip(results)
+                .map(|(endpoint, result)| endpoint_info(endpoint, result))
+                .collect()
+        }
+        _ => vec![],
+    };
+    HealthInfo {
+        container: instance.name.clone(),
+        version: instance.admin_image.wildfly_image.short_name(),
+        server_type: instance.admin_image.server_type.short_name().to_string(),
+        runtime: runtime.map(runtime_info),
+        endpoints,
+    }
+}
+
+fn runtime_info(health: RuntimeHealth) -> RuntimeHealthInfo {
+    RuntimeHealthInfo {
+        status: health.status,
+        failing_streak: health.failing_streak,
+        last_output: health.last_output,
+    }
+}
+
+fn endpoint_info(endpoint: &str, result: HealthEndpoint) -> HealthEndpointInfo {
+    let (status, checks, error) = match result {
+        HealthEndpoint::Available(health) => (
+            Some(health.status),
+            health
+                .checks
+                .into_iter()
+                .map(|check| HealthCheckInfo {
+                    name: check.name,
+                    status: check.status,
+                    data: check.data,
+                })
+                .collect(),
+            None,
+        ),
+        HealthEndpoint::NotFound => (
+            None,
+            vec![],
+            Some("Not supported by this version".to_string()),
+        ),
+        HealthEndpoint::Unavailable(error) => (None, vec![], Some(error)),
+    };
+    HealthEndpointInfo {
+        endpoint: endpoint.to_string(),
+        status,
+        checks,
+        error,
+    }
+}
+
+fn print_table(infos: &[HealthInfo]) {
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_BORDERS_ONLY)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            "Version",
+            "Container",
+            "Runtime",
+            "Endpoint",
+            "Status",
+            "Checks",
+        ]);
+    for info in infos {
+        let runtime = match &info.runtime {
+            Some(runtime) => {
+                let color = match runtime.status.as_str() {
+                    "healthy" => Color::Green,
+                    "unhealthy" => Color::Red,
+                    _ => Color::Yellow,
+                };
+                let text = if runtime.failing_streak > 0 {
+                    format!("{} ({} failing)", runtime.status, runtime.failing_streak)
+                } else {
+                    runtime.status.clone()
+                };
+                Cell::new(text).fg(color)
+            }
+            None => Cell::new("-"),
+        };
+        let mut first = vec![
+            Cell::new(&info.version).fg(Color::DarkMagenta),
+            Cell::new(&info.container).fg(Color::DarkYellow),
+            runtime,
+        ];
+        if info.endpoints.is_empty() {
+            first.extend([
+                Cell::new("-"),
+                Cell::new("-"),
+                Cell::new("No management interface").fg(Color::DarkGrey),
+            ]);
+            table.add_row(first);
+            continue;
+        }
+        for (index, endpoint) in info.endpoints.iter().enumerate() {
+            let mut row = if index == 0 {
+                std::mem::take(&mut first)
+            } else {
+                vec![Cell::new(""), Cell::new(""), Cell::new("")]
+            };
+            row.push(Cell::new(&endpoint.endpoint).fg(Color::DarkCyan));
+            match &endpoint.status {
+                Some(status) => {
+                    row.push(Cell::new(status).fg(status_color(status)));
+                    row.push(Cell::new(checks_text(&endpoint.checks)));
+                }
+                None => {
+                    row.push(Cell::new("-"));
+                    row.push(
+                        Cell::new(endpoint.error.clone().unwrap_or_default()).fg(Color::DarkGrey),
+                    );
+                }
+            }
+            table.add_row(row);
+        }
+    }
+    println!("\n{table}");
+}
+
+fn status_color(status: &str) -> Color {
+    if status == "UP" {
+        Color::Green
+    } else {
+        Color::Red
+    }
+}
+
+/// Returns one line per check: `<name> <status>` followed by the data of the check (if any).
+fn checks_text(checks: &[HealthCheckInfo]) -> String {
+    checks
+        .iter()
+        .map(|check| {
+            let data = check
+                .data
+                .as_ref()
+                .filter(|data| !data.is_empty())
+                .map(|data| {
+                    let pairs = data
+                        .iter()
+                        .map(|(key, value)| match value.as_str() {
+                            Some(value) => format!("{}={}", key, value),
+                            None => format!("{}={}", key, value),
+                        })
+                        .collect::<Vec<_>>();
+                    format!(" ({})", pairs.join(", "))
+                })
+                .unwrap_or_default();
+            format!("{} {}{}", check.name, check.status, data)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn checks() {
+        let checks = vec![
+            HealthCheckInfo {
+                name: "ready-deployments".to_string(),
+                status: "UP".to_string(),
+                data: None,
+            },
+            HealthCheckInfo {
+                name: "data-sources".to_string(),
+                status: "DOWN".to_string(),
+                data: json!({"ExampleDS": "DOWN", "connections": 0})
+                    .as_object()
+                    .cloned(),
+            },
+        ];
+        assert_eq!(
+            checks_text(&checks),
+            "ready-deployments UP\ndata-sources DOWN (ExampleDS=DOWN, connections=0)"
+        );
+    }
+}
@@ -53,7 +53,7 @@ pub fn images(registry: &WildFlyImageRegistry) -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn local_image_names() -> anyhow::Result<HashSet<String>> {
+pub(crate) async fn local_image_names() -> anyhow::Result<HashSet<String>> {
     let mut command = container_images_cmd();
     let child = command
         .stdout(Stdio::piped())
@@ -74,6 +74,7 @@ async fn image_names_in_use(registry: &WildFlyImageRegistry) -> anyhow::Result<H
         None,
         None,
         false,
+        true,
         registry,
     )
     .await?;
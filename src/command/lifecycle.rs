@@ -4,39 +4,43 @@
 //! Uses [`tokio::task::JoinSet`] for concurrent operations and
 //! [`indicatif::MultiProgress`] for visual feedback.
 
-use crate::args::{start_spec, validate_multiple_versions, versions_argument};
+use crate::args::{start_spec, validate_multiple_versions};
+use crate::constants::ENTRYPOINT_REVISION;
 use crate::healthcheck::wait_for_healthy;
-use crate::json::CommandResult;
+use crate::json::{CommandResult, ServerResult};
+use crate::label::Label;
 use crate::progress::{CommandStatus, Progress, stderr_reader, summary};
 use crate::wildfly::{ContainerConfig, ResolvedStart, ServerType};
 use anyhow::bail;
 use clap::ArgMatches;
+use console::style;
 use futures::executor::block_on;
 use indicatif::MultiProgress;
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 use std::process::Stdio;
+use std::time::Duration;
 use tokio::process::Command;
 use tokio::task::JoinSet;
 use tokio::time::Instant;
 use wildfly_meta::{WildFlyImage, WildFlyImageRegistry};
 
 use crate::container::{
-    container_command, container_ps, container_stop_cmd, resolve_start_specs,
+    container_command, container_ps, container_stop_cmd, image_label, resolve_start_specs,
     verify_container_command,
 };
 
-/// Verifies the container runtime, extracts versions from CLI args, validates
-/// options for multi-version runs, resolves unique names/ports, and converts
-/// each [`ResolvedStart`] into the caller's instance type.
+/// Verifies the container runtime, validates options for multi-version runs,
+/// resolves unique names/ports for the given versions, and converts each
+/// [`ResolvedStart`] into the caller's instance type.
 pub fn prepare_instances<T>(
     matches: &ArgMatches,
+    wildfly_images: Vec<WildFlyImage>,
     server_type: ServerType,
     restricted_options: &[&str],
     convert: impl Fn(ResolvedStart) -> T,
     registry: &WildFlyImageRegistry,
 ) -> anyhow::Result<Vec<T>> {
     verify_container_command()?;
-    let wildfly_images = versions_argument(matches);
     if wildfly_images.len() > 1 {
         validate_multiple_versions(matches, restricted_options)?;
     }
@@ -44,7 +48,7 @@ pub fn prepare_instances<T>(
         .iter()
         .map(|wc| start_spec(matches, wc, server_type))
         .collect();
-    let resolved = block_on(resolve_start_specs(server_type, specs, registry))?;
+    let resolved = block_on(resolve_start_specs(server_type, specs, &[], registry))?;
     Ok(resolved.into_iter().map(convert).collect())
 }
 
@@ -65,6 +69,17 @@ where
 {
     let names: Vec<&str> = instances.iter().map(|i| i.name()).collect();
     check_name_conflicts(&names).await?;
+    if !json {
+        let images: BTreeSet<String> = instances
+            .iter()
+            .map(|i| i.admin_image().image_name())
+            .collect();
+        for image in images {
+            if let Some(warning) = entrypoint_warning(&image).await {
+                println!("{}", style(warning).yellow());
+            }
+        }
+    }
 
     let instant = Instant::now();
     let multi_progress = if json {
@@ -88,10 +103,13 @@ where
 
         let stderr = stderr_reader(&mut child);
         let name = instance.name().to_string();
+        let debug_port = instance.debug_port();
         let progress_clone = progress.clone();
         commands.spawn(async move {
             let output = child.wait_with_output().await;
-            let status = progress.finish_keep_alive(output, Some(&name));
+            let status = progress
+                .finish_keep_alive(output, Some(&name))
+                .with_debug_port(debug_port);
             (status, progress)
         });
         if !json {
@@ -105,13 +123,36 @@ where
     Ok((results, instant))
 }
 
+/// Returns a warning if the local image was built with an older entrypoint, which
+/// ignores newer options like `--config <file>`, `--debug`, or `--keep`. Images which
+/// aren't available locally are pulled by the container runtime and not checked.
+async fn entrypoint_warning(image: &str) -> Option<String> {
+    let label = image_label(image, Label::Entrypoint).await.ok()?;
+    let revision = entrypoint_revision(label.as_deref());
+    (revision < ENTRYPOINT_REVISION).then(|| {
+        format!(
+            "Image {} was built by an older version of wado and doesn't support all options. Rebuild it with 'wado build'.",
+            image
+        )
+    })
+}
+
+/// Parses the entrypoint revision of an image. Images without a revision have revision 1.
+fn entrypoint_revision(label: Option<&str>) -> u32 {
+    label.and_then(|value| value.parse().ok()).unwrap_or(1)
+}
+
 /// Polls management interfaces in parallel for all successfully started containers.
 ///
 /// Reuses the progress bars from [`run_instances`] so the health check status
 /// appears on the same terminal line as the container start. Updates each
 /// [`CommandStatus`] based on whether the health check succeeded or timed out.
 /// Containers that failed to start are skipped.
-pub async fn wait_for_instances(status: &mut [(CommandStatus, Progress)], _json: bool) {
+pub async fn wait_for_instances(
+    status: &mut [(CommandStatus, Progress)],
+    timeout: Duration,
+    _json: bool,
+) {
     let mut health_checks = JoinSet::new();
 
     for (s, progress) in status.iter() {
@@ -123,11 +164,12 @@ pub async fn wait_for_instances(status: &mut [(CommandStatus, Progress)], _json:
             None => continue,
         };
         let identifier = s.identifier.clone();
+        let display_name = s.display_name();
         let progress = progress.clone();
         health_checks.spawn(async move {
-            let healthy = wait_for_healthy(mgmt_port, &progress).await;
+            let healthy = wait_for_healthy(mgmt_port, timeout, &progress).await;
             if healthy {
-                progress.finish_healthy(&identifier);
+                progress.finish_healthy(&display_name);
             } else {
                 progress.finish_unhealthy();
             }
@@ -178,6 +220,7 @@ pub fn stop_containers_by_server_type(
             wildfly_images.map(|v| v.as_slice()),
             name,
             false,
+            false,
             registry,
         )
         .await?;
@@ -263,10 +306,22 @@ fn status_to_json(status: &[(CommandStatus, Progress)]) -> Vec<CommandResult> {
     status
         .iter()
         .map(|(s, _)| {
-            if s.success {
-                CommandResult::success(&s.identifier, s.http, s.management)
+            let result = if s.success {
+                CommandResult::success(&s.identifier, s.http, s.management, s.debug)
             } else {
                 CommandResult::error(&s.identifier, &s.error_message)
+            };
+            CommandResult {
+                servers: s
+                    .servers
+                    .iter()
+                    .map(|server| ServerResult {
+                        name: server.name.clone(),
+                        ready: server.ready,
+                        state: server.state.clone(),
+                    })
+                    .collect(),
+                ..result
             }
         })
         .collect()
@@ -281,7 +336,7 @@ pub fn print_json_results(status: &[(CommandStatus, Progress)]) {
 
 /// Creates a progress bar, joining a [`MultiProgress`] group if present,
 /// or returning a hidden no-op progress bar for JSON mode.
-fn create_progress(
+pub fn create_progress(
     multi_progress: &Option<MultiProgress>,
     prefix: &str,
     image_name: &str,
@@ -292,10 +347,11 @@ fn create_progress(
     }
 }
 
-/// Checks for name collisions against all running containers, not just wado-managed ones.
+/// Checks for name collisions against all containers (incl. stopped ones), not just
+/// wado-managed ones.
 async fn check_name_conflicts(names: &[&str]) -> anyhow::Result<()> {
     let mut cmd = container_command()?;
-    cmd.arg("ps").arg("--format").arg("{{.Names}}");
+    cmd.arg("ps").arg("--all").arg("--format").arg("{{.Names}}");
     let output = cmd
         .stdout(Stdio::piped())
         .stderr(Stdio::null())
@@ -0,0 +1,209 @@
+//! Shows the logs of one or more containers.
+//!
+//! Lines of several containers are interleaved and prefixed with the colored
+//! container name (like `docker compose logs`). Without `--follow`, the lines
+//! are merged in chronological order using the timestamps of the container runtime.
+
+use crate::args::selected_containers_argument;
+use crate::container::container_logs_cmd;
+use crate::json::LogLine;
+use crate::progress::{stderr_reader, stdout_reader};
+use crate::wildfly::ContainerInstance;
+use crate::wildfly::ServerType::{DomainController, HostController, Standalone};
+use clap::ArgMatches;
+use console::{Color, style};
+use futures::executor::block_on;
+use std::process::Stdio;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+use tokio::io::{AsyncRead, BufReader, Lines};
+use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
+use wildfly_meta::WildFlyImageRegistry;
+
+const PREFIX_COLORS: [Color; 6] = [
+    Color::Cyan,
+    Color::Yellow,
+    Color::Green,
+    Color::Magenta,
+    Color::Blue,
+    Color::Red,
+];
+
+pub fn logs(
+    matches: &ArgMatches,
+    registry: &WildFlyImageRegistry,
+    json: bool,
+) -> anyhow::Result<()> {
+    let instances = selected_containers_argument(
+        matches,
+        vec![Standalone, DomainController, HostController],
+        registry,
+    )?;
+
+    let options = LogOptions {
+        follow: matches.get_flag("follow"),
+        since: matches.get_one::<String>("since").cloned(),
+        tail: matches.get_one::<String>("tail").cloned(),
+        timestamps: matches.get_flag("timestamps"),
+        json,
+    };
+    block_on(show_logs(&instances, &options))
+}
+
+struct LogOptions {
+    follow: bool,
+    since: Option<String>,
+    tail: Option<String>,
+    timestamps: bool,
+    json: bool,
+}
+
+/// A single log line of the container at `index`.
+struct Entry {
+    index: usize,
+    timestamp: String,
+    /// The parsed timestamp used to merge the lines of several containers.
+    instant: Option<OffsetDateTime>,
+    message: String,
+}
+
+async fn show_logs(instances: &[ContainerInstance], options: &LogOptions) -> anyhow::Result<()> {
+    let (tx, mut rx) = unbounded_channel::<Entry>();
+    let mut children = Vec::with_capacity(instances.len());
+    for (index, instance) in instances.iter().enumerate() {
+        let mut child = container_logs_cmd(
+            &instance.name,
+            options.follow,
+            options.since.as_deref(),
+            options.tail.as_deref(),
+        )
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+        tokio::spawn(forward(index, stdout_reader(&mut child), tx.clone()));
+        tokio::spawn(forward(index, stderr_reader(&mut child), tx.clone()));
+        children.push(child);
+    }
+    drop(tx);
+
+    let names: Vec<&str> = instances.iter().map(|i| i.name.as_str()).collect();
+    let width = names.iter().map(|n| n.len()).max().unwrap_or(0);
+    if options.follow {
+        while let Some(entry) = rx.recv().await {
+            print_entry(&entry, &names, width, options);
+        }
+    } else {
+        let mut entries = Vec::new();
+        while let Some(entry) = rx.recv().await {
+            entries.push(entry);
+        }
+        entries.sort_by_key(|entry| entry.instant);
+        for entry in &entries {
+            print_entry(entry, &names, width, options);
+        }
+    }
+
+    for mut child in children {
+        child.wait().await?;
+    }
+    Ok(())
+}
+
+async fn forward<R>(index: usize, mut reader: Lines<BufReader<R>>, tx: UnboundedSender<Entry>)
+where
+    R: AsyncRead + Unpin,
+{
+    while let Ok(Some(line)) = reader.next_line().await {
+        let (timestamp, message) = split_timestamp(&line);
+        let entry = Entry {
+            index,
+            timestamp: timestamp.to_string(),
+            instant: parse_timestamp(timestamp),
+            message: message.to_string(),
+        };
+        if tx.send(entry).is_err() {
+            break;
+        }
+    }
+}
+
+fn print_entry(entry: &Entry, names: &[&str], width: usize, options: &LogOptions) {
+    let name = names[entry.index];
+    if options.json {
+        let line = LogLine {
+            container: name.to_string(),
+            timestamp: entry.timestamp.clone(),
+            message: entry.message.clone(),
+        };
+        println!("{}", serde_json::to_string(&line).unwrap_or_default());
+        return;
+    }
+
+    let mut output = String::new();
+    if names.len() > 1 {
+        let color = PREFIX_COLORS[entry.index % PREFIX_COLORS.len()];
+        output.push_str(&format!(
+            "{} ",
+            style(format!("{:<width$} |", name, width = width)).fg(color)
+        ));
+    }
+    if options.timestamps && !entry.timestamp.is_empty() {
+        output.push_str(&format!("{} ", style(&entry.timestamp).dim()));
+    }
+    output.push_str(&entry.message);
+    println!("{}", output);
+}
+
+/// Splits a line produced by `logs --timestamps` into timestamp and message.
+fn split_timestamp(line: &str) -> (&str, &str) {
+    match line.split_once(' ') {
+        Some((timestamp, message)) if timestamp.starts_with(|c: char| c.is_ascii_digit()) => {
+            (timestamp, message)
+        }
+        _ => ("", line),
+    }
+}
+
+/// Parses an RFC 3339 timestamp of the container runtime. Comparing the parsed values
+/// orders timestamps with different UTC offsets or precision correctly.
+fn parse_timestamp(timestamp: &str) -> Option<OffsetDateTime> {
+    OffsetDateTime::parse(timestamp, &Rfc3339).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_timestamp_podman() {
+        let (timestamp, message) = split_timestamp(
+            "2026-06-25T10:15:30.123456789+02:00 12:15:30,123 INFO  [org.jboss.as] WFLYSRV0025",
+        );
+        assert_eq!(timestamp, "2026-06-25T10:15:30.123456789+02:00");
+        assert_eq!(message, "12:15:30,123 INFO  [org.jboss.as] WFLYSRV0025");
+    }
+
+    #[test]
+    fn split_timestamp_docker() {
+        let (timestamp, message) = split_timestamp("2026-06-25T08:15:30.123456789Z hello");
+        assert_eq!(timestamp, "2026-06-25T08:15:30.123456789Z");
+        assert_eq!(message, "hello");
+    }
+
+    #[test]
+    fn split_timestamp_without_timestamp() {
+        let (timestamp, message) = split_timestamp("no timestamp here");
+        assert_eq!(timestamp, "");
+        assert_eq!(message, "no timestamp here");
+    }
+
+    #[test]
+    fn timestamps_with_different_offsets() {
+        let podman = parse_timestamp("2026-06-25T10:15:30.5+02:00").unwrap();
+        let docker = parse_timestamp("2026-06-25T08:15:30.123456789Z").unwrap();
+        assert!(docker < podman);
+        assert!(parse_timestamp("").is_none());
+        assert!(parse_timestamp("no timestamp").is_none());
+    }
+}
@@ -0,0 +1,379 @@
+//! Shows the metrics of running standalone servers.
+//!
+//! By default, a summary of heap, threads, datasources, and Undertow requests is
+//! shown. `--filter` shows the matching samples, `--raw` the Prometheus text format,
+//! and `--watch` refreshes the output periodically.
+
+use crate::args::{selected_containers_argument, username_password_argument};
+use crate::json::{DatasourceMetricsInfo, MetricSampleInfo, MetricsInfo};
+use crate::wildfly::ServerType::Standalone;
+use crate::wildfly::{
+    ContainerInstance, ManagementApi, MetricsSummary, Sample, has_metrics_endpoint,
+    parse_prometheus, scrape_metrics,
+};
+use anyhow::Context;
+use clap::ArgMatches;
+use comfy_table::presets::UTF8_BORDERS_ONLY;
+use comfy_table::{Cell, CellAlignment, Color, ContentArrangement, Table};
+use console::{Term, style};
+use futures::executor::block_on;
+use futures::future::join_all;
+use regex::Regex;
+use std::time::Duration;
+use wildfly_meta::WildFlyImageRegistry;
+
+pub fn metrics(
+    matches: &ArgMatches,
+    registry: &WildFlyImageRegistry,
+    json: bool,
+) -> anyhow::Result<()> {
+    let (username, password) = username_password_argument(matches);
+    let options = MetricsOptions {
+        raw: matches.get_flag("raw"),
+        filter: matches
+            .get_one::<String>("filter")
+            .map(|pattern| Regex::new(pattern))
+            .transpose()
+            .context("Invalid regular expression for --filter")?,
+        json,
+    };
+    let watch = matches.get_one::<u64>("watch").copied();
+
+    let instances = selected_containers_argument(matches, vec![Standalone], registry)?;
+
+    loop {
+        let metrics = block_on(join_all(
+            instances
+                .iter()
+                .map(|instance| read_metrics(instance, username, password)),
+        ));
+        if watch.is_some() && !json {
+            Term::stdout().clear_screen()?;
+        }
+        print_metrics(&metrics, &options)?;
+        match watch {
+            Some(interval) => std::thread::sleep(Duration::from_secs(interval)),
+            None => return Ok(()),
+        }
+    }
+}
+
+struct MetricsOptions {
+    raw: bool,
+    filter: Option<Regex>,
+    json: bool,
+}
+
+impl MetricsOptions {
+    fn matches(&self, name: &str) -> bool {
+        self.filter
+            .as_ref()
+            .is_none_or(|filter| filter.is_match(name))
+    }
+}
+
+/// The metrics of one container.
+struct ContainerMetrics<'a> {
+    instance: &'a ContainerInstance,
+    /// The Prometheus text format (only if read from `/metrics`).
+    text: Option<String>,
+    samples: Vec<Sample>,
+    result: anyhow::Result<MetricsSummary>,
+}
+
+impl ContainerMetrics<'_> {
+    fn source(&self) -> &'static str {
+        if self.text.is_some() {
+            "metrics"
+        } else {
+            "management"
+        }
+    }
+}
+
+/// Reads the `/metrics` endpoint and falls back to the management API for versions
+/// without the endpoint.
+async fn read_metrics<'a>(
+    instance: &'a ContainerInstance,
+    username: &str,
+    password: &str,
+) -> ContainerMetrics<'a> {
+    let Some(port) = instance.ports.as_ref().map(|ports| ports.management) else {
+        return ContainerMetrics {
+            instance,
+            text: None,
+            samples: vec![],
+            result: Err(anyhow::anyhow!(
+                "No management port published for {}",
+                instance.name
+            )),
+        };
+    };
+    if has_metrics_endpoint(&instance.admin_image.wildfly_image)
+        && let Ok(Some(text)) = scrape_metrics(port).await
+    {
+        let samples = parse_prometheus(&text);
+        return ContainerMetrics {
+            instance,
+            result: Ok(MetricsSummary::from_samples(&samples)),
+            text: Some(text),
+            samples,
+        };
+    }
+    let result = match ManagementApi::new(port, username, password) {
+        Ok(api) => api.metrics_summary().await,
+        Err(e) => Err(e),
+    };
+    ContainerMetrics {
+        instance,
+        text: None,
+        samples: vec![],
+        result,
+    }
+}
+
+fn print_metrics(metrics: &[ContainerMetrics], options: &MetricsOptions) -> anyhow::Result<()> {
+    if options.json {
+        let infos = metrics
+            .iter()
+            .map(|m| metrics_info(m, options))
+            .collect::<Vec<_>>();
+        println!("{}", serde_json::to_string(&infos)?);
+    } else if options.raw {
+        print_raw(metrics, options);
+    } else if options.filter.is_some() {
+        print_samples(metrics, options);
+    } else {
+        print_summary(metrics);
+    }
+    Ok(())
+}
+
+fn metrics_info(metrics: &ContainerMetrics, options: &MetricsOptions) -> MetricsInfo {
+    let samples = if options.raw || options.filter.is_some() {
+        metrics
+            .samples
+            .iter()
+            .filter(|sample| options.matches(&sample.name))
+            .map(|sample| MetricSampleInfo {
+                name: sample.name.clone(),
+                labels: sample.labels.clone(),
+                value: sample.value,
+            })
+            .collect()
+    } else {
+        vec![]
+    };
+    let instance = metrics.instance;
+    let container = instance.name.clone();
+    let version = instance.admin_image.wildfly_image.short_name();
+    match &metrics.result {
+        Ok(summary) => MetricsInfo {
+            container,
+            version,
+            source: Some(metrics.source().to_string()),
+            heap_used: summary.heap_used,
+            heap_max: summary.heap_max,
+            threads: summary.threads,
+            datasources: summary
+                .datasources
+                .iter()
+                .map(|ds| DatasourceMetricsInfo {
+                    name: ds.name.clone(),
+                    active: ds.active,
+                    in_use: ds.in_use,
+                })
+                .collect(),
+            requests: summary.requests,
+            samples,
+            error: None,
+        },
+        Err(e) => MetricsInfo {
+            container,
+            version,
+            source: None,
+            heap_used: None,
+            heap_max: None,
+            threads: None,
+            datasources: vec![],
+            requests: None,
+            samples,
+            error: Some(format!("{:#}", e)),
+        },
+    }
+}
+
+fn print_summary(metrics: &[ContainerMetrics]) {
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_BORDERS_ONLY)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            "Version",
+            "Container",
+            "Source",
+            "Heap",
+            "Threads",
+            "Datasources",
+            "Requests",
+        ]);
+    for m in metrics {
+        let mut row = vec![
+            Cell::new(m.instance.admin_image.wildfly_image.short_name()).fg(Color::DarkMagenta),
+            Cell::new(&m.instance.name).fg(Color::DarkYellow),
+        ];
+        match &m.result {
+            Ok(summary) => {
+                let heap = match (summary.heap_used, summary.heap_max) {
+                    (Some(used), Some(max)) => {
+                        format!("{} / {}", megabytes(used), megabytes(max))
+                    }
+                    (Some(used), None) => megabytes(used),
+                    _ => "-".to_string(),
+                };
+                let datasources = if summary.datasources.is_empty() {
+                    "-".to_string()
+                } else {
+                    summary
+                        .datasources
+                        .iter()
+                        .map(|ds| {
+                            format!(
+                                "{}: {} active, {} in use",
+                                ds.name,
+                                optional(ds.active),
+                                optional(ds.in_use)
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                };
+                row.extend([
+                    Cell::new(m.source()).fg(Color::DarkGrey),
+                    Cell::new(heap).set_alignment(CellAlignment::Right),
+                    Cell::new(optional(summary.threads)).set_alignment(CellAlignment::Right),
+                    Cell::new(datasources).fg(Color::DarkCyan),
+                    Cell::new(optional(summary.requests)).set_alignment(CellAlignment::Right),
+                ]);
+            }
+            Err(e) => row.push(Cell::new(format!("{:#}", e)).fg(Color::Red)),
+        }
+        table.add_row(row);
+    }
+    println!("\n{table}");
+}
+
+fn print_samples(metrics: &[ContainerMetrics], options: &MetricsOptions) {
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_BORDERS_ONLY)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec!["Version", "Container", "Metric", "Labels", "Value"]);
+    for m in metrics {
+        if m.text.is_none() {
+            table.add_row(vec![
+                Cell::new(m.instance.admin_image.wildfly_image.short_name()).fg(Color::DarkMagenta),
+                Cell::new(&m.instance.name).fg(Color::DarkYellow),
+                Cell::new(not_available(m)).fg(Color::Red),
+            ]);
+            continue;
+        }
+        for sample in m.samples.iter().filter(|s| options.matches(&s.name)) {
+            table.add_row(vec![
+                Cell::new(m.instance.admin_image.wildfly_image.short_name()).fg(Color::DarkMagenta),
+                Cell::new(&m.instance.name).fg(Color::DarkYellow),
+                Cell::new(&sample.name).fg(Color::DarkCyan),
+                Cell::new(
+                    sample
+                        .labels
+                        .iter()
+                        .map(|(key, value)| format!("{}={}", key, value))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ),
+                Cell::new(sample.value).set_alignment(CellAlignment::Right),
+            ]);
+        }
+    }
+    println!("\n{table}");
+}
+
+fn print_raw(metrics: &[ContainerMetrics], options: &MetricsOptions) {
+    for m in metrics {
+        if metrics.len() > 1 {
+            println!("{}", style(format!("# {}", m.instance.name)).yellow());
+        }
+        match &m.text {
+            Some(text) => {
+                for line in raw_lines(text, options) {
+                    println!("{}", line);
+                }
+            }
+            None => println!("{}", style(not_available(m)).red()),
+        }
+    }
+}
+
+/// Returns the lines of the Prometheus text format whose metric name matches the filter.
+/// `# HELP` and `# TYPE` comments are kept for matching metrics.
+fn raw_lines<'a>(text: &'a str, options: &MetricsOptions) -> Vec<&'a str> {
+    text.lines()
+        .filter(|line| {
+            let name = match line.strip_prefix('#') {
+                Some(comment) => comment.split_whitespace().nth(1).unwrap_or_default(),
+                None => line
+                    .split(|c: char| c == '{' || c.is_whitespace())
+                    .next()
+                    .unwrap_or_default(),
+            };
+            !line.trim().is_empty() && options.matches(name)
+        })
+        .collect()
+}
+
+fn not_available(metrics: &ContainerMetrics) -> String {
+    match &metrics.result {
+        Ok(_) => format!(
+            "The /metrics endpoint is not available for {}",
+            metrics.instance.name
+        ),
+        Err(e) => format!("{:#}", e),
+    }
+}
+
+fn megabytes(bytes: u64) -> String {
+    format!("{} MB", bytes / 1024 / 1024)
+}
+
+fn optional(value: Option<u64>) -> String {
+    value.map_or_else(|| "-".to_string(), |value| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_lines_filtered() {
+        let text = "# HELP base_thread_count Number of threads\n\
+                    # TYPE base_thread_count gauge\n\
+                    base_thread_count 42.0\n\
+                    # TYPE base_memory_usedHeap_bytes gauge\n\
+                    base_memory_usedHeap_bytes 1024\n\
+                    wildfly_undertow_request_count_total{server=\"default-server\"} 3\n";
+        let options = MetricsOptions {
+            raw: true,
+            filter: Some(Regex::new("thread|undertow").unwrap()),
+            json: false,
+        };
+        assert_eq!(
+            raw_lines(text, &options),
+            vec![
+                "# HELP base_thread_count Number of threads",
+                "# TYPE base_thread_count gauge",
+                "base_thread_count 42.0",
+                "wildfly_undertow_request_count_total{server=\"default-server\"} 3",
+            ]
+        );
+    }
+}
@@ -8,12 +8,26 @@ pub mod cli;
 pub mod completions;
 pub mod console;
 pub mod dc;
+pub mod deploy;
+pub mod diff;
+pub mod exec;
 pub mod hc;
+pub mod health;
 pub mod images;
 pub(crate) mod lifecycle;
+pub mod logs;
+pub mod metrics;
+pub mod model;
+pub mod op;
 pub mod ps;
 pub mod push;
+pub mod restart;
+pub mod rm;
+pub mod snapshot;
 pub mod standalone;
+pub(crate) mod temporary;
+pub mod top;
 pub mod topology;
 pub mod update;
 pub mod versions;
+pub mod wait;
@@ -0,0 +1,155 @@
+//! Exports the management model metadata of WildFly versions.
+//!
+//! Reads the resource description tree (`read-resource-description(recursive=true)`)
+//! of standalone servers and domain controllers and writes one JSON file per version
+//! and server type. Running containers are reused, temporary containers are started
+//! for all other versions and stopped afterward.
+
+use super::lifecycle::create_progress;
+use super::temporary::{TargetClient, start_targets, stop_temporary, version_target};
+use crate::args::{username_password_argument, versions_argument};
+use crate::container::verify_container_command;
+use crate::json::ModelDumpResult;
+use crate::progress::{CommandStatus, summary};
+use crate::wildfly::ServerType::{DomainController, Standalone};
+use crate::wildfly::{AdminImage, ServerType};
+use anyhow::Context;
+use clap::ArgMatches;
+use futures::executor::block_on;
+use indicatif::MultiProgress;
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+use wildfly_meta::WildFlyImageRegistry;
+
+// ------------------------------------------------------ dump
+
+pub fn model_dump(
+    matches: &ArgMatches,
+    registry: &WildFlyImageRegistry,
+    json: bool,
+) -> anyhow::Result<()> {
+    verify_container_command()?;
+    let wildfly_images = versions_argument(matches);
+    let mut server_types = vec![];
+    if matches.get_flag("standalone") || !matches.get_flag("domain") {
+        server_types.push(Standalone);
+    }
+    if matches.get_flag("domain") || !matches.get_flag("standalone") {
+        server_types.push(DomainController);
+    }
+    let output = matches
+        .get_one::<PathBuf>("output")
+        .cloned()
+        .unwrap_or_else(|| PathBuf::from("."));
+    fs::create_dir_all(&output)
+        .with_context(|| format!("Unable to create output directory {}", output.display()))?;
+    let operation = json!({
+        "operation": "read-resource-description",
+        "address": [],
+        "recursive": true,
+        "operations": matches.get_flag("operations"),
+    });
+    let (username, password) = username_password_argument(matches);
+
+    block_on(async {
+        let instant = Instant::now();
+        let mut results = vec![];
+        for server_type in server_types {
+            let mut targets = vec![];
+            for wildfly_image in &wildfly_images {
+                targets.push(version_target(wildfly_image, server_type, registry).await?);
+            }
+            let (clients, temporary) = start_targets(targets, server_type, registry, json).await?;
+            results.extend(
+                dump_descriptions(
+                    clients,
+                    server_type,
+                    &operation,
+                    &output,
+                    username,
+                    password,
+                    json,
+                )
+                .await,
+            );
+            stop_temporary(&temporary).await;
+        }
+
+        if json {
+            let results: Vec<ModelDumpResult> = results
+                .into_iter()
+                .map(|(server_type, target, status)| ModelDumpResult {
+                    version: target,
+                    server_type: server_type.short_name().to_string(),
+                    success: status.success,
+                    file: status.success.then_some(status.identifier),
+                    error: (!status.success).then_some(status.error_message),
+                })
+                .collect();
+            println!("{}", serde_json::to_string(&results)?);
+        } else {
+            let count = results.len();
+            let statuses = results.into_iter().map(|(_, _, s)| s).collect();
+            summary("Exported", "models", count, instant, statuses);
+        }
+        Ok(())
+    })
+}
+
+/// Reads the resource descriptions of all targets in parallel and writes them to
+/// `<output>/<sa|dc>-<version>.json`.
+async fn dump_descriptions(
+    clients: Vec<TargetClient>,
+    server_type: ServerType,
+    operation: &serde_json::Value,
+    output: &Path,
+    username: &str,
+    password: &str,
+    json: bool,
+) -> Vec<(ServerType, String, CommandStatus)> {
+    let multi_progress = if json {
+        None
+    } else {
+        Some(MultiProgress::new())
+    };
+    let mut commands = JoinSet::new();
+
+    for target in clients {
+        let admin_image = AdminImage::new(target.wildfly_image.clone(), server_type);
+        let version = target.wildfly_image.short_name();
+        let progress = create_progress(&multi_progress, &version, &admin_image.image_name());
+        let file = output.join(format!(
+            "{}-{}.json",
+            server_type.short_name(),
+            target.wildfly_image.short_name()
+        ));
+        let api = target.client.and_then(|client| {
+            client
+                .http_api(username, password)
+                .map_err(|e| e.to_string())
+        });
+        let operation = operation.clone();
+        commands.spawn(async move {
+            let status = match api {
+                Ok(api) => {
+                    progress
+                        .show_progress(&format!("Reading resource description of {}", target.name));
+                    let description = api.execute(&operation).await.and_then(|description| {
+                        fs::write(&file, serde_json::to_string_pretty(&description)?)
+                            .with_context(|| format!("Unable to write {}", file.display()))
+                    });
+                    match description {
+                        Ok(()) => progress.finish_no_output(Some(&file.display().to_string())),
+                        Err(e) => progress.finish_error(&target.name, &e.to_string()),
+                    }
+                }
+                Err(e) => progress.finish_error(&target.name, &e),
+            };
+            (server_type, version, status)
+        });
+    }
+    commands.join_all().await
+}
@@ -0,0 +1,52 @@
+//! Executes management operations using the native HTTP management API.
+//!
+//! Operations are given in JBoss CLI syntax and don't require a local JVM.
+
+use crate::args::{management_client_argument, username_password_argument};
+use crate::error::WadoError;
+use crate::wildfly::{Operation, dmr_string, failure_description, is_success};
+use anyhow::{anyhow, bail};
+use clap::ArgMatches;
+use futures::executor::block_on;
+use serde_json::json;
+use wildfly_meta::WildFlyImageRegistry;
+
+pub fn op(matches: &ArgMatches, registry: &WildFlyImageRegistry, json: bool) -> anyhow::Result<()> {
+    let operations = matches
+        .get_many::<String>("operation")
+        .unwrap_or_default()
+        .map(|input| {
+            Operation::parse(input)
+                .map_err(|e| anyhow!(WadoError::invalid_operation(&e.display_with(input))))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let management_client = management_client_argument(matches, registry)?;
+    let (username, password) = username_password_argument(matches);
+    let api = management_client.http_api(username, password)?;
+
+    block_on(async {
+        let mut responses = vec![];
+        let mut failures = vec![];
+        for operation in &operations {
+            let response = api.execute_raw(&operation.to_dmr()).await?;
+            if !json {
+                println!("{}", dmr_string(&response));
+            }
+            if !is_success(&response) {
+                failures.push(failure_description(&response));
+            }
+            responses.push(response);
+        }
+        // with --json, the responses are part of the error envelope printed by main
+        if !failures.is_empty() {
+            bail!(
+                WadoError::management_operation_failed(&failures.join(", "))
+                    .with_details(json!({ "responses": responses }))
+            );
+        }
+        if json {
+            println!("{}", serde_json::to_string(&responses)?);
+        }
+        Ok(())
+    })
+}
@@ -1,6 +1,7 @@
 use crate::container::container_ps;
 use crate::json::ContainerInfo;
 use crate::wildfly::ServerType::{DomainController, HostController, Standalone};
+use crate::wildfly::{ContainerInstance, ExtraPort};
 use clap::ArgMatches;
 use comfy_table::presets::UTF8_BORDERS_ONLY;
 use comfy_table::{Cell, Color, ContentArrangement, Table};
@@ -21,7 +22,14 @@ pub fn ps(matches: &ArgMatches, registry: &WildFlyImageRegistry, json: bool) ->
         server_types.push(DomainController);
         server_types.push(HostController);
     }
-    let mut instances = block_on(container_ps(server_types, None, None, true, registry))?;
+    let mut instances = block_on(container_ps(
+        server_types,
+        None,
+        None,
+        true,
+        matches.get_flag("all"),
+        registry,
+    ))?;
 
     if json {
         instances.sort();
@@ -34,9 +42,28 @@ pub fn ps(matches: &ArgMatches, registry: &WildFlyImageRegistry, json: bool) ->
                 version: i.admin_image.wildfly_image.short_name(),
                 http: i.ports.as_ref().map(|p| p.http),
                 management: i.ports.as_ref().map(|p| p.management),
+                https: i
+                    .ports
+                    .as_ref()
+                    .and_then(|p| p.extra_port(ExtraPort::Https)),
+                management_https: i
+                    .ports
+                    .as_ref()
+                    .and_then(|p| p.extra_port(ExtraPort::ManagementHttps)),
+                ajp: i.ports.as_ref().and_then(|p| p.extra_port(ExtraPort::Ajp)),
+                remoting: i
+                    .ports
+                    .as_ref()
+                    .and_then(|p| p.extra_port(ExtraPort::Remoting)),
+                debug: i.debug_port,
+                server_debug: i.server_debug_ports.iter().cloned().collect(),
                 config: i.config.clone(),
                 topology: i.topology.clone(),
+                mounts: i.mounts.clone(),
+                environment: i.environment.clone(),
                 status: i.status.clone(),
+                running: i.running,
+                keep: i.keep,
                 container_id: i.container_id.clone(),
             })
             .collect();
@@ -45,34 +72,82 @@ pub fn ps(matches: &ArgMatches, registry: &WildFlyImageRegistry, json: bool) ->
     }
 
     if instances.is_empty() {
-        println!("\nNo running WildFly containers found.");
+        if matches.get_flag("all") {
+            println!("\nNo WildFly containers found.");
+        } else {
+            println!("\nNo running WildFly containers found.");
+        }
         return Ok(());
     }
 
     instances.sort();
+    let wide = matches.get_flag("wide");
+    let mut header = vec![
+        "Version", "Type", "Name", "Config", "Ports", "Debug", "Topology", "Mounts",
+    ];
+    if wide {
+        header.push("Environment");
+    }
+    header.extend(["Status", "ID"]);
     let mut table = Table::new();
     table
         .load_preset(UTF8_BORDERS_ONLY)
         .set_content_arrangement(ContentArrangement::Dynamic)
-        .set_header(vec![
-            "Version", "Type", "Name", "Config", "Ports", "Topology", "Status", "ID",
-        ]);
+        .set_header(header);
     for instance in instances {
-        table.add_row(vec![
+        let debug = debug_ports(&instance);
+        let mut row = vec![
             Cell::new(instance.admin_image.wildfly_image.short_name()).fg(Color::DarkMagenta),
             Cell::new(instance.admin_image.server_type.short_name()).fg(Color::DarkCyan),
             Cell::new(instance.name).fg(Color::DarkYellow),
             Cell::new(instance.config.as_deref().unwrap_or("")).fg(Color::DarkCyan),
             if let Some(ports) = instance.ports {
-                Cell::new(format!("{}/{}", ports.http, ports.management)).fg(Color::Green)
+                let mut text = format!("{}/{}", ports.http, ports.management);
+                for (port, host_port) in &ports.extra {
+                    text.push_str(&format!("\n{} {}", port.name(), host_port));
+                }
+                Cell::new(text).fg(Color::Green)
             } else {
                 Cell::new("")
             },
+            Cell::new(debug).fg(Color::Green),
             Cell::new(instance.topology.as_deref().unwrap_or("")).fg(Color::DarkBlue),
-            Cell::new(instance.status),
+            Cell::new(instance.mounts.join("\n")).fg(Color::Grey),
+        ];
+        if wide {
+            row.push(Cell::new(instance.environment.join("\n")).fg(Color::Grey));
+        }
+        let status = if instance.keep {
+            format!("{} (kept)", instance.status)
+        } else {
+            instance.status
+        };
+        row.extend([
+            if instance.running {
+                Cell::new(status)
+            } else {
+                Cell::new(status).fg(Color::Grey)
+            },
             Cell::new(instance.container_id).fg(Color::Grey),
         ]);
+        table.add_row(row);
     }
     println!("\n{table}");
     Ok(())
 }
+
+/// Returns the debug port of the container and the debug ports of its servers.
+fn debug_ports(instance: &ContainerInstance) -> String {
+    let mut lines = instance
+        .debug_port
+        .map(|port| port.to_string())
+        .into_iter()
+        .collect::<Vec<_>>();
+    lines.extend(
+        instance
+            .server_debug_ports
+            .iter()
+            .map(|(server, port)| format!("{} {}", server, port)),
+    );
+    lines.join("\n")
+}
@@ -0,0 +1,221 @@
+use super::lifecycle::{create_progress, print_json_results, wait_for_instances};
+use crate::args::{kept_containers_argument, wait_timeout_argument};
+use crate::container::{
+    container_ports, container_restart_cmd, container_start_cmd, kept_containers,
+    verify_container_command,
+};
+use crate::progress::{CommandStatus, Progress, summary};
+use crate::wildfly::{ContainerInstance, ServerType};
+use anyhow::bail;
+use clap::ArgMatches;
+use futures::executor::block_on;
+use indicatif::MultiProgress;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+use wildfly_meta::WildFlyImageRegistry;
+
+// ------------------------------------------------------ restart
+
+pub fn restart(
+    matches: &ArgMatches,
+    registry: &WildFlyImageRegistry,
+    json: bool,
+) -> anyhow::Result<()> {
+    verify_container_command()?;
+    let instances = kept_containers_argument(matches, registry)?;
+    if instances.is_empty() {
+        bail!("No containers found which have been started with --keep");
+    }
+    block_on(restart_instances(
+        instances,
+        false,
+        wait_timeout_argument(matches),
+        json,
+    ))
+}
+
+// ------------------------------------------------------ resume
+
+/// Starts the stopped container `name` of the given server type which has been started
+/// with `--keep` before.
+pub fn resume(
+    name: &str,
+    server_type: ServerType,
+    timeout: Duration,
+    registry: &WildFlyImageRegistry,
+    json: bool,
+) -> anyhow::Result<()> {
+    verify_container_command()?;
+    let instances = block_on(kept_containers(None, Some(name), registry))?;
+    let instance = resume_target(&instances, name, server_type)?;
+    block_on(restart_instances(vec![instance], true, timeout, json))
+}
+
+/// Returns the kept container `name` if it has the given server type and is stopped.
+fn resume_target(
+    instances: &[ContainerInstance],
+    name: &str,
+    server_type: ServerType,
+) -> anyhow::Result<ContainerInstance> {
+    match instances.iter().find(|instance| instance.name == name) {
+        None => bail!(
+            "No container '{}' found which has been started with --keep",
+            name
+        ),
+        Some(instance) if instance.admin_image.server_type != server_type => bail!(
+            "Container '{}' is not a {}. Use 'wado {} --resume {}' instead.",
+            name,
+            description(server_type),
+            start_command(instance.admin_image.server_type),
+            name
+        ),
+        Some(instance) if instance.running => bail!("Container '{}' is already running", name),
+        Some(instance) => Ok(instance.clone()),
+    }
+}
+
+fn description(server_type: ServerType) -> &'static str {
+    match server_type {
+        ServerType::Standalone => "standalone server",
+        ServerType::DomainController => "domain controller",
+        ServerType::HostController => "host controller",
+    }
+}
+
+fn start_command(server_type: ServerType) -> &'static str {
+    match server_type {
+        ServerType::Standalone => "start",
+        ServerType::DomainController => "dc start",
+        ServerType::HostController => "hc start",
+    }
+}
+
+// ------------------------------------------------------ internal
+
+/// Restarts (or starts if `resume` is true) the given containers in parallel and
+/// waits until their management interfaces are available again. The published
+/// ports are read back from the containers, since they have been fixed when the
+/// containers were created.
+async fn restart_instances(
+    instances: Vec<ContainerInstance>,
+    resume: bool,
+    timeout: Duration,
+    json: bool,
+) -> anyhow::Result<()> {
+    let count = instances.len();
+    let instant = Instant::now();
+    let multi_progress = if json {
+        None
+    } else {
+        Some(MultiProgress::new())
+    };
+    let mut commands = JoinSet::new();
+
+    for instance in instances {
+        let progress = create_progress(
+            &multi_progress,
+            &instance.admin_image.wildfly_image.short_name(),
+            &instance.admin_image.image_name(),
+        );
+        let mut command = if resume {
+            container_start_cmd(&instance.name)
+        } else {
+            container_restart_cmd(&instance.name)
+        };
+        let child = command
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("Unable to run podman-restart.");
+
+        commands.spawn(async move {
+            let output = child.wait_with_output().await;
+            let status = progress
+                .finish_keep_alive(output, Some(&instance.name))
+                .with_debug_port(instance.debug_port);
+            let status = if status.success {
+                match container_ports(&instance).await {
+                    Ok(ContainerInstance {
+                        ports: Some(ports), ..
+                    }) => status.with_ports(ports.http, ports.management),
+                    _ => status,
+                }
+            } else {
+                status
+            };
+            (status, progress)
+        });
+    }
+
+    let mut status: Vec<(CommandStatus, Progress)> = commands.join_all().await;
+    wait_for_instances(&mut status, timeout, json).await;
+    for (s, progress) in &status {
+        progress.finish_if_alive(Some(&s.display_name()));
+    }
+
+    if json {
+        print_json_results(&status);
+    } else {
+        let verb = if resume { "Started" } else { "Restarted" };
+        let statuses: Vec<CommandStatus> = status.iter().map(|(s, _)| s.clone()).collect();
+        summary(verb, "container", count, instant, statuses);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_registry() -> WildFlyImageRegistry {
+        WildFlyImageRegistry::from_toml(include_str!("../../testdata/wildfly-images.toml"))
+            .expect("failed to parse test registry")
+    }
+
+    fn instance(identifier: &str, name: &str, running: bool) -> ContainerInstance {
+        let mut instance =
+            ContainerInstance::new(identifier, "id", name, "Exited", "", "", &test_registry())
+                .unwrap();
+        instance.running = running;
+        instance
+    }
+
+    #[test]
+    fn resume_stopped_container() {
+        let instances = vec![instance("dc-340", "dc", false)];
+        let instance = resume_target(&instances, "dc", ServerType::DomainController).unwrap();
+        assert_eq!(instance.name, "dc");
+    }
+
+    #[test]
+    fn resume_unknown_container() {
+        let instances = vec![instance("sa-340", "sa", false)];
+        let error = resume_target(&instances, "foo", ServerType::Standalone)
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("No container 'foo'"));
+    }
+
+    #[test]
+    fn resume_running_container() {
+        let instances = vec![instance("hc-340", "hc", true)];
+        let error = resume_target(&instances, "hc", ServerType::HostController)
+            .err()
+            .unwrap();
+        assert_eq!(error.to_string(), "Container 'hc' is already running");
+    }
+
+    #[test]
+    fn resume_other_server_type() {
+        let instances = vec![instance("hc-340", "hc", false)];
+        let error = resume_target(&instances, "hc", ServerType::Standalone)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Container 'hc' is not a standalone server. Use 'wado hc start --resume hc' instead."
+        );
+    }
+}
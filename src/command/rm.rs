@@ -0,0 +1,125 @@
+use super::lifecycle::{create_progress, print_json_results};
+use crate::args::kept_containers_argument;
+use crate::container::{container_rm_cmd, verify_container_command};
+use crate::progress::{CommandStatus, summary};
+use crate::wildfly::ContainerInstance;
+use anyhow::bail;
+use clap::ArgMatches;
+use futures::executor::block_on;
+use indicatif::MultiProgress;
+use std::process::Stdio;
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+use wildfly_meta::WildFlyImageRegistry;
+
+/// Removes containers which have been started with `--keep`. Running containers
+/// are only removed if `--force` is given.
+pub fn rm(matches: &ArgMatches, registry: &WildFlyImageRegistry, json: bool) -> anyhow::Result<()> {
+    verify_container_command()?;
+    let force = matches.get_flag("force");
+    let instances = kept_containers_argument(matches, registry)?;
+    if instances.is_empty() {
+        bail!("No containers found which have been started with --keep");
+    }
+    check_running(&instances, force)?;
+
+    block_on(async {
+        let count = instances.len();
+        let instant = Instant::now();
+        let multi_progress = if json {
+            None
+        } else {
+            Some(MultiProgress::new())
+        };
+        let mut commands = JoinSet::new();
+
+        for instance in instances {
+            let progress = create_progress(
+                &multi_progress,
+                &instance.admin_image.wildfly_image.short_name(),
+                &instance.admin_image.image_name(),
+            );
+            let mut command = container_rm_cmd(&instance.name, force);
+            let child = command
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .expect("Unable to run podman-rm.");
+
+            commands.spawn(async move {
+                let output = child.wait_with_output().await;
+                let status = progress.finish(output, Some(&instance.name));
+                (status, progress)
+            });
+        }
+
+        let results = commands.join_all().await;
+        if json {
+            print_json_results(&results);
+        } else {
+            let statuses: Vec<CommandStatus> = results.iter().map(|(s, _)| s.clone()).collect();
+            summary("Removed", "container", count, instant, statuses);
+        }
+        Ok(())
+    })
+}
+
+/// Fails if some of the containers are still running, unless `force` is set.
+fn check_running(instances: &[ContainerInstance], force: bool) -> anyhow::Result<()> {
+    if !force {
+        let running: Vec<String> = instances
+            .iter()
+            .filter(|i| i.running)
+            .map(|i| format!("'{}'", i.name))
+            .collect();
+        if !running.is_empty() {
+            bail!(
+                "Container(s) {} still running. Stop them first or use --force.",
+                running.join(", ")
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_registry() -> WildFlyImageRegistry {
+        WildFlyImageRegistry::from_toml(include_str!("../../testdata/wildfly-images.toml"))
+            .expect("failed to parse test registry")
+    }
+
+    fn instance(identifier: &str, name: &str, running: bool) -> ContainerInstance {
+        let mut instance =
+            ContainerInstance::new(identifier, "id", name, "Exited", "", "", &test_registry())
+                .unwrap();
+        instance.running = running;
+        instance
+    }
+
+    #[test]
+    fn stopped_containers() {
+        let instances = vec![
+            instance("sa-340", "a", false),
+            instance("sa-340", "b", false),
+        ];
+        assert!(check_running(&instances, false).is_ok());
+    }
+
+    #[test]
+    fn running_containers() {
+        let instances = vec![
+            instance("sa-340", "a", true),
+            instance("sa-340", "b", false),
+            instance("sa-340", "c", true),
+        ];
+        let error = check_running(&instances, false).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Container(s) 'a', 'c' still running. Stop them first or use --force."
+        );
+        assert!(check_running(&instances, true).is_ok());
+    }
+}
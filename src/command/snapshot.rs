@@ -0,0 +1,343 @@
+//! Saves, lists and restores configuration snapshots of running containers.
+
+use super::{dc, hc, standalone};
+use crate::args::{name_argument, username_password_argument, wait_timeout_argument};
+use crate::container::{
+    RunOptions, container_cp_cmd, get_instance, resolve_start_specs, verify_container_command,
+};
+use crate::wildfly::{
+    AdminImage, ContainerInstance, DomainController, HostController, ServerType, Snapshot,
+    StandaloneInstance, StartSpec, configuration_path, default_host_config, reset_host_name,
+    strip_deployments, validate_label,
+};
+use anyhow::{Context, bail};
+use clap::ArgMatches;
+use comfy_table::presets::UTF8_BORDERS_ONLY;
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+use console::style;
+use futures::executor::block_on;
+use indicatif::HumanDuration;
+use std::fs;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use wildfly_meta::WildFlyImageRegistry;
+
+// ------------------------------------------------------ save
+
+pub fn snapshot_save(
+    matches: &ArgMatches,
+    registry: &WildFlyImageRegistry,
+    json: bool,
+) -> anyhow::Result<()> {
+    verify_container_command()?;
+    let name = matches.get_one::<String>("name").unwrap();
+    let label = matches.get_one::<String>("label").unwrap();
+    let force = matches.get_flag("force");
+    validate_label(label)?;
+
+    let instance = block_on(get_instance(
+        vec![
+            ServerType::Standalone,
+            ServerType::DomainController,
+            ServerType::HostController,
+        ],
+        None,
+        Some(name),
+        registry,
+    ))?;
+    let server_type = instance.admin_image.server_type;
+    let (config, host_config) = snapshot_configs(&instance);
+
+    let directory = tempfile::tempdir()?;
+    let (xml, deployments) = snapshot_xml(
+        &copy_config(name, server_type, &config, directory.path())?,
+        server_type,
+    );
+    let host_xml = host_config
+        .as_ref()
+        .map(|host_config| copy_config(name, server_type, host_config, directory.path()))
+        .transpose()?
+        .map(|host_xml| reset_host_name(&host_xml, server_type));
+
+    let snapshot = Snapshot::new(
+        label,
+        &instance.admin_image,
+        &config,
+        host_config.as_deref(),
+        name,
+    );
+    snapshot.save(&xml, host_xml.as_deref(), force)?;
+    if json {
+        println!("{}", serde_json::to_string(&snapshot)?);
+    } else {
+        if !deployments.is_empty() {
+            println!(
+                "{}",
+                style(format!(
+                    "Deployments are not part of a snapshot. Removed {} from {}.",
+                    deployments.join(", "),
+                    config
+                ))
+                .yellow()
+            );
+        }
+        let files = match &host_config {
+            Some(host_config) => format!(
+                "{} and {}",
+                style(&config).cyan(),
+                style(host_config).cyan()
+            ),
+            None => style(&config).cyan().to_string(),
+        };
+        println!(
+            "Saved {} of {} as snapshot {}",
+            files,
+            style(name).yellow(),
+            style(label).cyan()
+        );
+    }
+    Ok(())
+}
+
+/// Returns the configuration and, for domain controllers, the host configuration
+/// the container was started with.
+fn snapshot_configs(instance: &ContainerInstance) -> (String, Option<String>) {
+    let server_type = instance.admin_image.server_type;
+    let wildfly_image = &instance.admin_image.wildfly_image;
+    let config = instance
+        .config
+        .clone()
+        .unwrap_or_else(|| match server_type {
+            ServerType::Standalone => "standalone.xml".to_string(),
+            ServerType::DomainController => "domain.xml".to_string(),
+            ServerType::HostController => {
+                default_host_config(wildfly_image, server_type).unwrap_or_default()
+            }
+        });
+    // domain controllers are started with the domain and the host configuration
+    let host_config = if server_type == ServerType::DomainController {
+        default_host_config(wildfly_image, server_type)
+    } else {
+        None
+    };
+    (config, host_config)
+}
+
+/// Removes the deployments from the configuration `xml` and resets the host name of
+/// host controller configurations. Returns the XML and the removed deployments.
+fn snapshot_xml(xml: &str, server_type: ServerType) -> (String, Vec<String>) {
+    let (xml, deployments) = strip_deployments(xml);
+    if server_type == ServerType::HostController {
+        (reset_host_name(&xml, server_type), deployments)
+    } else {
+        (xml, deployments)
+    }
+}
+
+/// Copies the configuration file `config` of the container `name` to `directory` and
+/// returns its content.
+fn copy_config(
+    name: &str,
+    server_type: ServerType,
+    config: &str,
+    directory: &Path,
+) -> anyhow::Result<String> {
+    let target = directory.join(config);
+    let output = block_on(
+        container_cp_cmd(name, &configuration_path(server_type, config), &target)
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output(),
+    )?;
+    if !output.status.success() {
+        bail!(
+            "Unable to copy {} from container '{}': {}",
+            config,
+            name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    fs::read_to_string(&target)
+        .with_context(|| format!("Unable to read {} of container '{}'", config, name))
+}
+
+// ------------------------------------------------------ list
+
+pub fn snapshot_list(json: bool) -> anyhow::Result<()> {
+    let snapshots = Snapshot::list()?;
+    if json {
+        println!("{}", serde_json::to_string(&snapshots)?);
+        return Ok(());
+    }
+    if snapshots.is_empty() {
+        println!("\nNo snapshots found.");
+        return Ok(());
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_BORDERS_ONLY)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            "Label",
+            "Version",
+            "Type",
+            "Config",
+            "Container",
+            "Created",
+        ]);
+    for snapshot in snapshots {
+        let age = Duration::from_secs(now.saturating_sub(snapshot.created));
+        table.add_row(vec![
+            Cell::new(snapshot.label).fg(Color::DarkYellow),
+            Cell::new(snapshot.version).fg(Color::DarkMagenta),
+            Cell::new(snapshot.server_type).fg(Color::DarkCyan),
+            Cell::new(match snapshot.host_config {
+                Some(host_config) => format!("{}, {}", snapshot.config, host_config),
+                None => snapshot.config,
+            })
+            .fg(Color::DarkCyan),
+            Cell::new(snapshot.container),
+            Cell::new(format!("{} ago", HumanDuration(age))).fg(Color::Grey),
+        ]);
+    }
+    println!("\n{table}");
+    Ok(())
+}
+
+// ------------------------------------------------------ restore
+
+/// Starts a new container of the snapshot's admin image with the saved configuration.
+/// Host controllers connect to the domain controller given by `--domain-controller`.
+/// Use `wado start --from-snapshot` or `wado dc start --from-snapshot` for all other
+/// start options.
+pub fn snapshot_restore(
+    matches: &ArgMatches,
+    registry: &WildFlyImageRegistry,
+    json: bool,
+) -> anyhow::Result<()> {
+    verify_container_command()?;
+    let label = matches.get_one::<String>("label").unwrap();
+    let snapshot = Snapshot::load(label)?;
+    let admin_image = snapshot.admin_image(registry)?;
+    let server_type = admin_image.server_type;
+    let admin_image_dc = AdminImage::new(
+        admin_image.wildfly_image.clone(),
+        ServerType::DomainController,
+    );
+
+    let mut run_options = RunOptions {
+        keep: matches.get_flag("keep"),
+        ..Default::default()
+    };
+    run_options.set_config(snapshot.server_config()?);
+    if let Some(host_config) = snapshot.host_server_config()? {
+        run_options.set_host_config(host_config);
+    }
+    let mut parameters = run_options.parameters(server_type);
+    let spec = StartSpec {
+        admin_image,
+        custom_name: matches.get_one::<String>("name").cloned(),
+        custom_http: None,
+        custom_management: None,
+        custom_offset: None,
+        publish: vec![],
+        debug: false,
+    };
+    let resolved = block_on(resolve_start_specs(server_type, vec![spec], &[], registry))?;
+
+    match server_type {
+        ServerType::Standalone => {
+            let instances = resolved
+                .into_iter()
+                .map(|r| {
+                    StandaloneInstance::new(r.admin_image, r.name, r.ports.unwrap(), r.debug_port)
+                })
+                .collect();
+            block_on(standalone::start_instances(
+                instances,
+                parameters,
+                run_options,
+                wait_timeout_argument(matches),
+                json,
+            ))
+        }
+        ServerType::DomainController => {
+            let instances = resolved
+                .into_iter()
+                .map(|r| {
+                    DomainController::new(r.admin_image, r.name, r.ports.unwrap(), r.debug_port)
+                })
+                .collect();
+            block_on(dc::start_instances(
+                instances,
+                vec![],
+                run_options,
+                parameters,
+                wait_timeout_argument(matches),
+                json,
+            ))
+        }
+        ServerType::HostController => {
+            let dc_name = name_argument("domain-controller", matches, || {
+                admin_image_dc.container_name()
+            });
+            let instances = resolved
+                .into_iter()
+                .map(|r| HostController::new(r.admin_image, r.name, dc_name.clone(), r.debug_port))
+                .collect();
+            let (username, password) = username_password_argument(matches);
+            parameters.push(format!("--primary-address={}", dc_name));
+            block_on(hc::start_instances(
+                instances,
+                username,
+                password,
+                vec![],
+                run_options,
+                parameters,
+                json,
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_registry() -> WildFlyImageRegistry {
+        WildFlyImageRegistry::from_toml(include_str!("../../testdata/wildfly-images.toml"))
+            .expect("failed to parse test registry")
+    }
+
+    fn instance(identifier: &str, name: &str) -> ContainerInstance {
+        ContainerInstance::new(identifier, "id", name, "Up", "", "", &test_registry()).unwrap()
+    }
+
+    #[test]
+    fn host_controller_configs() {
+        let (config, host_config) = snapshot_configs(&instance("hc-340", "hc"));
+        assert_eq!(config, "host-secondary.xml");
+        assert!(host_config.is_none());
+
+        let xml = r#"<host xmlns="urn:jboss:domain:20.0" name="hc"><profile/></host>"#;
+        let (xml, deployments) = snapshot_xml(xml, ServerType::HostController);
+        assert_eq!(
+            xml,
+            r#"<host xmlns="urn:jboss:domain:20.0"><profile/></host>"#
+        );
+        assert!(deployments.is_empty());
+    }
+
+    #[test]
+    fn domain_controller_configs() {
+        let (config, host_config) = snapshot_configs(&instance("dc-340", "dc"));
+        assert_eq!(config, "domain.xml");
+        assert_eq!(host_config.as_deref(), Some("host-primary.xml"));
+    }
+}
@@ -2,11 +2,17 @@ use super::lifecycle::{
     apply_ports, prepare_instances, print_json_results, run_instances,
     stop_containers_by_server_type, wait_for_instances,
 };
-use crate::args::{extract_config, operations_argument, parameters_argument};
-use crate::container::{container_network_cmd, container_run_cmd};
+use super::restart::resume;
+use crate::args::{
+    extract_config, parameters_argument, run_options_argument, start_versions_argument,
+    wait_timeout_argument,
+};
+use crate::container::{RunOptions, container_network_cmd, container_run_cmd};
+use crate::progress::{CommandStatus, Progress};
 use crate::wildfly::{ServerType, StandaloneInstance};
 use clap::ArgMatches;
 use futures::executor::block_on;
+use std::time::Duration;
 use wildfly_meta::WildFlyImageRegistry;
 
 // ------------------------------------------------------ start
@@ -16,27 +22,59 @@ pub fn standalone_start(
     registry: &WildFlyImageRegistry,
     json: bool,
 ) -> anyhow::Result<()> {
+    if let Some(name) = matches.get_one::<String>("resume") {
+        return resume(
+            name,
+            ServerType::Standalone,
+            wait_timeout_argument(matches),
+            registry,
+            json,
+        );
+    }
+    let mut run_options = run_options_argument(matches, ServerType::Standalone)?;
+    let wildfly_images =
+        start_versions_argument(matches, ServerType::Standalone, &mut run_options, registry)?;
     let instances: Vec<StandaloneInstance> = prepare_instances(
         matches,
+        wildfly_images,
         ServerType::Standalone,
         &["name", "http", "management", "offset"],
-        |r| StandaloneInstance::new(r.admin_image, r.name, r.ports.unwrap()),
+        |r| StandaloneInstance::new(r.admin_image, r.name, r.ports.unwrap(), r.debug_port),
         registry,
     )?;
+    let mut parameters = parameters_argument(matches);
+    parameters.extend(run_options.parameters(ServerType::Standalone));
     block_on(start_instances(
         instances,
-        parameters_argument(matches),
-        operations_argument(matches),
+        parameters,
+        run_options,
+        wait_timeout_argument(matches),
         json,
     ))
 }
 
-async fn start_instances(
+pub async fn start_instances(
     instances: Vec<StandaloneInstance>,
     parameters: Vec<String>,
-    operations: Vec<String>,
+    run_options: RunOptions,
+    timeout: Duration,
     json: bool,
 ) -> anyhow::Result<()> {
+    let status = start_and_wait(instances, parameters, run_options, timeout, json).await?;
+    if json {
+        print_json_results(&status);
+    }
+    Ok(())
+}
+
+/// Starts the instances and waits until their management interfaces are available.
+pub async fn start_and_wait(
+    instances: Vec<StandaloneInstance>,
+    parameters: Vec<String>,
+    run_options: RunOptions,
+    timeout: Duration,
+    json: bool,
+) -> anyhow::Result<Vec<(CommandStatus, Progress)>> {
     let config = extract_config(&parameters, "standalone.xml");
     container_network_cmd().await?;
 
@@ -51,7 +89,8 @@ async fn start_instances(
             let mut command = container_run_cmd(
                 &instance.name,
                 Some(&instance.ports),
-                operations.clone(),
+                instance.debug_port,
+                &run_options,
                 instance.admin_image.wildfly_image.is_dev(),
                 None,
                 Some(&config),
@@ -66,12 +105,8 @@ async fn start_instances(
     .await?;
 
     let mut status = apply_ports(results, &port_map);
-    wait_for_instances(&mut status, json).await;
-
-    if json {
-        print_json_results(&status);
-    }
-    Ok(())
+    wait_for_instances(&mut status, timeout, json).await;
+    Ok(status)
 }
 
 // ------------------------------------------------------ stop
@@ -0,0 +1,205 @@
+//! Temporary containers for commands that need the management interface of a version.
+//!
+//! A [`Target`] is either a running standalone server or domain controller or a
+//! WildFly version without a running container. For the latter, a temporary
+//! container is started by [`start_targets`] and removed by [`stop_temporary`]
+//! once the command is done.
+
+use super::{dc, standalone};
+use crate::container::{RunOptions, container_ps, container_stop_cmd, resolve_start_specs};
+use crate::healthcheck::DEFAULT_WAIT_TIMEOUT;
+use crate::progress::{CommandStatus, Progress};
+use crate::wildfly::{
+    AdminImage, ContainerInstance, DomainController, ManagementClient, ServerType,
+    StandaloneInstance, StartSpec,
+};
+use anyhow::anyhow;
+use std::process::Stdio;
+use std::time::Duration;
+use wildfly_meta::{WildFlyImage, WildFlyImageRegistry, parse_wildfly_image};
+
+/// A running container or a version to start a temporary container for.
+pub enum Target {
+    Running(String, ManagementClient),
+    Temporary(WildFlyImage),
+}
+
+/// The management client of a [`Target`] or the reason why its temporary
+/// container couldn't be started.
+pub struct TargetClient {
+    pub name: String,
+    pub wildfly_image: WildFlyImage,
+    pub client: Result<ManagementClient, String>,
+}
+
+/// Resolves a running standalone server or domain controller by name or, if there's
+/// no such container, `value` as version (see [`version_target`]).
+pub async fn resolve_target(
+    value: &str,
+    server_type: ServerType,
+    registry: &WildFlyImageRegistry,
+) -> anyhow::Result<Target> {
+    let instances = container_ps(
+        vec![ServerType::Standalone, ServerType::DomainController],
+        None,
+        Some(value),
+        true,
+        false,
+        registry,
+    )
+    .await?;
+    if let Some(instance) = instances.first() {
+        return Ok(running(instance, registry));
+    }
+
+    let wildfly_image = parse_wildfly_image(value, registry).map_err(|_| {
+        anyhow!(
+            "'{}' is neither a running standalone server or domain controller nor a WildFly version",
+            value
+        )
+    })?;
+    version_target(&wildfly_image, server_type, registry).await
+}
+
+/// Returns a running container of the version and server type (preferring the
+/// container with the default name) or a temporary target if there's none.
+pub async fn version_target(
+    wildfly_image: &WildFlyImage,
+    server_type: ServerType,
+    registry: &WildFlyImageRegistry,
+) -> anyhow::Result<Target> {
+    let admin_image = AdminImage::new(wildfly_image.clone(), server_type);
+    let mut instances = container_ps(
+        vec![server_type],
+        Some(std::slice::from_ref(wildfly_image)),
+        None,
+        true,
+        false,
+        registry,
+    )
+    .await?;
+    instances.sort_by_key(|i| i.name != admin_image.container_name());
+    match instances.first() {
+        Some(instance) => Ok(running(instance, registry)),
+        None => Ok(Target::Temporary(wildfly_image.clone())),
+    }
+}
+
+fn running(instance: &ContainerInstance, registry: &WildFlyImageRegistry) -> Target {
+    Target::Running(
+        instance.name.clone(),
+        ManagementClient::from_container_instance(instance, registry),
+    )
+}
+
+/// Starts temporary containers for all [`Target::Temporary`] targets in parallel and
+/// waits until they're available. Returns the clients of all targets (in the order
+/// of `targets`) together with the names of the temporary containers.
+pub async fn start_targets(
+    targets: Vec<Target>,
+    server_type: ServerType,
+    registry: &WildFlyImageRegistry,
+    json: bool,
+) -> anyhow::Result<(Vec<TargetClient>, Vec<String>)> {
+    let specs: Vec<StartSpec> = targets
+        .iter()
+        .filter_map(|target| match target {
+            Target::Temporary(wildfly_image) => Some(StartSpec {
+                admin_image: AdminImage::new(wildfly_image.clone(), server_type),
+                custom_name: None,
+                custom_http: None,
+                custom_management: None,
+                custom_offset: None,
+                publish: vec![],
+                debug: false,
+            }),
+            Target::Running(..) => None,
+        })
+        .collect();
+    let mut resolved = resolve_start_specs(server_type, specs, &[], registry)
+        .await?
+        .into_iter();
+
+    let mut clients = vec![];
+    let mut temporary = vec![];
+    for target in targets {
+        match target {
+            Target::Running(name, client) => clients.push(TargetClient {
+                name,
+                wildfly_image: client.wildfly_image.clone(),
+                client: Ok(client),
+            }),
+            Target::Temporary(wildfly_image) => {
+                let r = resolved.next().expect("resolved start spec");
+                let ports = r.ports.expect("resolved ports");
+                clients.push(TargetClient {
+                    name: r.name.clone(),
+                    client: Ok(ManagementClient::custom_port(
+                        &wildfly_image,
+                        ports.management,
+                        registry,
+                    )),
+                    wildfly_image,
+                });
+                temporary.push((r.admin_image, r.name, ports));
+            }
+        }
+    }
+    if temporary.is_empty() {
+        return Ok((clients, vec![]));
+    }
+
+    let names: Vec<String> = temporary.iter().map(|(_, name, _)| name.clone()).collect();
+    let status: Vec<(CommandStatus, Progress)> = match server_type {
+        ServerType::DomainController => {
+            let instances = temporary
+                .into_iter()
+                .map(|(admin_image, name, ports)| {
+                    DomainController::new(admin_image, name, ports, None)
+                })
+                .collect();
+            dc::start_and_wait(
+                instances,
+                vec![],
+                RunOptions::default(),
+                vec![],
+                Duration::from_secs(DEFAULT_WAIT_TIMEOUT),
+                json,
+            )
+            .await?
+        }
+        _ => {
+            let instances = temporary
+                .into_iter()
+                .map(|(admin_image, name, ports)| {
+                    StandaloneInstance::new(admin_image, name, ports, None)
+                })
+                .collect();
+            standalone::start_and_wait(
+                instances,
+                vec![],
+                RunOptions::default(),
+                Duration::from_secs(DEFAULT_WAIT_TIMEOUT),
+                json,
+            )
+            .await?
+        }
+    };
+    for (s, _) in status.iter().filter(|(s, _)| !s.success) {
+        if let Some(target) = clients.iter_mut().find(|c| c.name == s.identifier) {
+            target.client = Err(s.error_message.clone());
+        }
+    }
+    Ok((clients, names))
+}
+
+/// Stops (and thereby removes) the temporary containers.
+pub async fn stop_temporary(names: &[String]) {
+    for name in names {
+        let _ = container_stop_cmd(name)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await;
+    }
+}
@@ -0,0 +1,653 @@
+//! Interactive dashboard of running containers.
+//!
+//! Refreshes the running containers periodically in a background thread and shows their
+//! CPU and memory usage, health, ports, and topology. Keybindings stop, restart, open the
+//! console or the CLI, or tail the logs of the selected container.
+
+use crate::args::username_password_argument;
+use crate::command::cli::run_cli;
+use crate::command::console::open_console;
+use crate::container::{
+    ContainerStats, container_health, container_logs_cmd, container_ps, container_restart_cmd,
+    container_stats, container_stop_cmd, verify_container_command,
+};
+use crate::wildfly::ServerType::{DomainController, HostController, Standalone};
+use crate::wildfly::{ContainerInstance, ManagementClient};
+use clap::ArgMatches;
+use console::style;
+use futures::executor::block_on;
+use futures::future::join_all;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Cell, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::runtime::Handle;
+use wildfly_meta::WildFlyImageRegistry;
+
+pub fn top(matches: &ArgMatches, registry: &WildFlyImageRegistry) -> anyhow::Result<()> {
+    verify_container_command()?;
+    let interval = Duration::from_secs(matches.get_one::<u64>("interval").copied().unwrap_or(2));
+    let (username, password) = username_password_argument(matches);
+
+    // Ctrl-C must not terminate wado while the logs or the CLI of a container are shown.
+    let handle = Handle::current();
+    let signals = handle.spawn(async { while tokio::signal::ctrl_c().await.is_ok() {} });
+    let running = AtomicBool::new(true);
+    let refresh = AtomicBool::new(false);
+    let (sender, receiver) = channel();
+
+    let result = thread::scope(|scope| {
+        let refresh_sender = sender.clone();
+        scope.spawn(|| {
+            refresh_containers(
+                &handle,
+                registry,
+                interval,
+                &running,
+                &refresh,
+                refresh_sender,
+            )
+        });
+        let mut dashboard = Dashboard {
+            registry,
+            username,
+            password,
+            interval,
+            handle: handle.clone(),
+            sender,
+            refresh: &refresh,
+            entries: vec![],
+            loaded: false,
+            selected: None,
+            table_state: TableState::default(),
+            error: None,
+            message: None,
+            confirm_stop: None,
+        };
+        let result = dashboard.run(&receiver);
+        running.store(false, Ordering::Relaxed);
+        result
+    });
+    signals.abort();
+    result
+}
+
+// ------------------------------------------------------ refresh
+
+/// A running container with its resource usage and runtime health.
+struct Entry {
+    instance: ContainerInstance,
+    stats: Option<ContainerStats>,
+    health: Option<String>,
+}
+
+enum Update {
+    Containers(anyhow::Result<Vec<Entry>>),
+    Action(Message),
+}
+
+#[derive(Clone, Copy)]
+enum Action {
+    Stop,
+    Restart,
+}
+
+impl Action {
+    fn name(self) -> &'static str {
+        match self {
+            Action::Stop => "stop",
+            Action::Restart => "restart",
+        }
+    }
+
+    fn running(self) -> &'static str {
+        match self {
+            Action::Stop => "Stopping",
+            Action::Restart => "Restarting",
+        }
+    }
+
+    fn done(self) -> &'static str {
+        match self {
+            Action::Stop => "Stopped",
+            Action::Restart => "Restarted",
+        }
+    }
+}
+
+enum Message {
+    Info(String),
+    Error(String),
+}
+
+/// Reads the running containers every `interval` (or when `refresh` is set) until
+/// `running` is cleared or the dashboard is gone.
+fn refresh_containers(
+    handle: &Handle,
+    registry: &WildFlyImageRegistry,
+    interval: Duration,
+    running: &AtomicBool,
+    refresh: &AtomicBool,
+    sender: Sender<Update>,
+) {
+    while running.load(Ordering::Relaxed) {
+        let entries = handle.block_on(read_entries(registry));
+        if sender.send(Update::Containers(entries)).is_err() {
+            return;
+        }
+        let instant = Instant::now();
+        while running.load(Ordering::Relaxed)
+            && instant.elapsed() < interval
+            && !refresh.swap(false, Ordering::Relaxed)
+        {
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+}
+
+async fn read_entries(registry: &WildFlyImageRegistry) -> anyhow::Result<Vec<Entry>> {
+    let mut instances = container_ps(
+        vec![Standalone, DomainController, HostController],
+        None,
+        None,
+        true,
+        false,
+        registry,
+    )
+    .await?;
+    instances.sort();
+    let names = instances
+        .iter()
+        .map(|instance| instance.name.clone())
+        .collect::<Vec<_>>();
+    let (stats, health) = futures::join!(
+        container_stats(&names),
+        join_all(names.iter().map(|name| container_health(name)))
+    );
+    let mut stats = stats.unwrap_or_default();
+    Ok(instances
+        .into_iter()
+        .zip(health)
+        .map(|(instance, health)| Entry {
+            stats: stats.remove(&instance.name),
+            health: health.ok().flatten().map(|health| health.status),
+            instance,
+        })
+        .collect())
+}
+
+// ------------------------------------------------------ dashboard
+
+struct Dashboard<'a> {
+    registry: &'a WildFlyImageRegistry,
+    username: &'a str,
+    password: &'a str,
+    interval: Duration,
+    handle: Handle,
+    sender: Sender<Update>,
+    refresh: &'a AtomicBool,
+    entries: Vec<Entry>,
+    loaded: bool,
+    /// The name of the selected container.
+    selected: Option<String>,
+    table_state: TableState,
+    error: Option<String>,
+    message: Option<Message>,
+    /// The name of the container to stop once the user confirms it.
+    confirm_stop: Option<String>,
+}
+
+impl Dashboard<'_> {
+    fn run(&mut self, receiver: &Receiver<Update>) -> anyhow::Result<()> {
+        let mut terminal = ratatui::init();
+        let result = self.event_loop(&mut terminal, receiver);
+        ratatui::restore();
+        result
+    }
+
+    fn event_loop(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        receiver: &Receiver<Update>,
+    ) -> anyhow::Result<()> {
+        loop {
+            while let Ok(update) = receiver.try_recv() {
+                self.update(update);
+            }
+            terminal.draw(|frame| self.render(frame))?;
+            if !event::poll(Duration::from_millis(200))? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if let Some(name) = self.confirm_stop.take() {
+                if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                    self.container_action(name, Action::Stop);
+                } else {
+                    self.message = Some(Message::Info(format!("Did not stop {}", name)));
+                }
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(());
+                }
+                KeyCode::Up | KeyCode::Char('k') => self.select(-1),
+                KeyCode::Down | KeyCode::Char('j') => self.select(1),
+                KeyCode::Char('R') | KeyCode::F(5) => self.refresh.store(true, Ordering::Relaxed),
+                KeyCode::Char('s') => self.stop(),
+                KeyCode::Char('r') => self.restart(),
+                KeyCode::Char('c') => self.console(),
+                KeyCode::Char('i') => {
+                    self.suspend(terminal, |dashboard| dashboard.cli());
+                }
+                KeyCode::Char('l') => {
+                    self.suspend(terminal, |dashboard| dashboard.logs());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn update(&mut self, update: Update) {
+        match update {
+            Update::Containers(Ok(entries)) => {
+                self.entries = entries;
+                self.loaded = true;
+                self.error = None;
+                let selected = self
+                    .selected
+                    .as_ref()
+                    .is_some_and(|name| self.entries.iter().any(|e| &e.instance.name == name));
+                if !selected {
+                    self.selected = self.entries.first().map(|e| e.instance.name.clone());
+                }
+            }
+            Update::Containers(Err(e)) => self.error = Some(format!("{:#}", e)),
+            Update::Action(message) => {
+                self.message = Some(message);
+                self.refresh.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn selected_entry(&self) -> Option<&Entry> {
+        self.selected
+            .as_ref()
+            .and_then(|name| self.entries.iter().find(|e| &e.instance.name == name))
+    }
+
+    fn select(&mut self, delta: isize) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let index = self
+            .selected
+            .as_ref()
+            .and_then(|name| self.entries.iter().position(|e| &e.instance.name == name))
+            .unwrap_or_default();
+        let index = index
+            .saturating_add_signed(delta)
+            .min(self.entries.len() - 1);
+        self.selected = Some(self.entries[index].instance.name.clone());
+    }
+
+    // ------------------------------------------------------ actions
+
+    /// Asks for confirmation before the selected container is stopped. The next key
+    /// press confirms (`y`) or cancels the stop.
+    fn stop(&mut self) {
+        if let Some(entry) = self.selected_entry() {
+            self.confirm_stop = Some(entry.instance.name.clone());
+        }
+    }
+
+    fn restart(&mut self) {
+        if let Some(entry) = self.selected_entry() {
+            let name = entry.instance.name.clone();
+            if entry.instance.keep {
+                self.container_action(name, Action::Restart);
+            } else {
+                self.message = Some(Message::Error(format!(
+                    "Container {} has not been started with --keep and can't be restarted",
+                    name
+                )));
+            }
+        }
+    }
+
+    /// Runs the action in the background and reports the result as an [`Update::Action`].
+    fn container_action(&mut self, name: String, action: Action) {
+        self.message = Some(Message::Info(format!("{} {}...", action.running(), name)));
+        let mut command = match action {
+            Action::Stop => container_stop_cmd(&name),
+            Action::Restart => container_restart_cmd(&name),
+        };
+        let sender = self.sender.clone();
+        self.handle.spawn(async move {
+            let message = match command.output().await {
+                Ok(output) if output.status.success() => {
+                    Message::Info(format!("{} {}", action.done(), name))
+                }
+                Ok(output) => Message::Error(format!(
+                    "Unable to {} {}: {}",
+                    action.name(),
+                    name,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )),
+                Err(e) => Message::Error(format!("Unable to {} {}: {}", action.name(), name, e)),
+            };
+            let _ = sender.send(Update::Action(message));
+        });
+    }
+
+    fn management_client(&self) -> Result<ManagementClient, Message> {
+        match self.selected_entry() {
+            Some(entry) if entry.instance.admin_image.server_type == HostController => {
+                Err(Message::Error(format!(
+                    "Host controller {} has no management interface",
+                    entry.instance.name
+                )))
+            }
+            Some(entry) => Ok(ManagementClient::from_container_instance(
+                &entry.instance,
+                self.registry,
+            )),
+            None => Err(Message::Error("No container selected".to_string())),
+        }
+    }
+
+    fn console(&mut self) {
+        self.message = Some(match self.management_client() {
+            Ok(client) => match open_console(&client) {
+                Ok(()) => Message::Info(format!(
+                    "Opened the console at http://localhost:{}/console",
+                    client.management_port
+                )),
+                Err(e) => Message::Error(format!("Unable to open the console: {:#}", e)),
+            },
+            Err(message) => message,
+        });
+    }
+
+    fn cli(&mut self) -> Option<Message> {
+        match self.management_client() {
+            Ok(client) => run_cli(&client, self.username, self.password, vec![])
+                .err()
+                .map(|e| Message::Error(format!("{:#}", e))),
+            Err(message) => Some(message),
+        }
+    }
+
+    fn logs(&mut self) -> Option<Message> {
+        let name = self.selected_entry()?.instance.name.clone();
+        println!(
+            "{}",
+            style(format!("Logs of {} (press Ctrl-C to return)", name)).yellow()
+        );
+        let result = block_on(async {
+            let mut child = container_logs_cmd(&name, true, None, Some("100"))
+                .kill_on_drop(true)
+                .spawn()?;
+            tokio::select! {
+                _ = child.wait() => {},
+                _ = tokio::signal::ctrl_c() => {},
+            }
+            anyhow::Ok(())
+        });
+        result
+            .err()
+            .map(|e| Message::Error(format!("Unable to show the logs of {}: {:#}", name, e)))
+    }
+
+    /// Leaves the dashboard while `f` runs in the normal terminal.
+    fn suspend(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        f: impl FnOnce(&mut Self) -> Option<Message>,
+    ) {
+        if self.selected_entry().is_none() {
+            return;
+        }
+        ratatui::restore();
+        self.message = f(self);
+        *terminal = ratatui::init();
+        let _ = terminal.clear();
+    }
+
+    // ------------------------------------------------------ render
+
+    fn render(&mut self, frame: &mut Frame) {
+        let [title_area, table_area, message_area, help_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        frame.render_widget(
+            Line::from(vec![
+                Span::styled(" wado top ", Style::new().bold().reversed()),
+                Span::raw(format!(
+                    " {} containers, refreshed every {}s",
+                    self.entries.len(),
+                    self.interval.as_secs()
+                )),
+            ]),
+            title_area,
+        );
+
+        if self.entries.is_empty() {
+            let text = if self.loaded {
+                "No running WildFly containers found."
+            } else {
+                "Reading containers..."
+            };
+            frame.render_widget(
+                Paragraph::new(text).block(Block::bordered()).dark_gray(),
+                table_area,
+            );
+        } else {
+            self.render_table(frame, table_area);
+        }
+
+        let message = match (&self.error, &self.message) {
+            _ if self.confirm_stop.is_some() => Line::from(self.confirm_stop_text()).yellow(),
+            (Some(error), _) => Line::from(error.as_str()).red(),
+            (None, Some(Message::Error(error))) => Line::from(error.as_str()).red(),
+            (None, Some(Message::Info(info))) => Line::from(info.as_str()).green(),
+            (None, None) => Line::default(),
+        };
+        frame.render_widget(message, message_area);
+        frame.render_widget(
+            Line::from(
+                "↑/↓ select  s stop  r restart  c console  i cli  l logs  R refresh  q quit",
+            )
+            .dark_gray(),
+            help_area,
+        );
+    }
+
+    fn confirm_stop_text(&self) -> String {
+        let Some(name) = &self.confirm_stop else {
+            return String::new();
+        };
+        let kept = self
+            .entries
+            .iter()
+            .any(|e| &e.instance.name == name && e.instance.keep);
+        if kept {
+            format!("Stop {}? [y/N]", name)
+        } else {
+            format!(
+                "Stop {}? The container has not been started with --keep and will be removed. [y/N]",
+                name
+            )
+        }
+    }
+
+    fn render_table(&mut self, frame: &mut Frame, area: ratatui::layout::Rect) {
+        let topologies = self
+            .entries
+            .iter()
+            .map(|e| e.instance.topology.as_deref())
+            .collect::<Vec<_>>();
+        let display_rows = display_rows(&topologies);
+        let mut selected_row = None;
+        let rows = display_rows
+            .iter()
+            .enumerate()
+            .map(|(row_index, row)| match row {
+                DisplayRow::Group(Some(topology)) => {
+                    Row::new([Cell::from(format!("topology {}", topology)).blue().bold()])
+                }
+                DisplayRow::Group(None) => Row::new([Cell::from("no topology").blue().bold()]),
+                DisplayRow::Container(index) => {
+                    let entry = &self.entries[*index];
+                    if self.selected.as_ref() == Some(&entry.instance.name) {
+                        selected_row = Some(row_index);
+                    }
+                    entry_row(entry)
+                }
+            })
+            .collect::<Vec<_>>();
+        let widths = [
+            Constraint::Fill(2),
+            Constraint::Length(4),
+            Constraint::Length(10),
+            Constraint::Fill(2),
+            Constraint::Length(10),
+            Constraint::Length(8),
+            Constraint::Fill(2),
+            Constraint::Length(8),
+            Constraint::Fill(3),
+        ];
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new([
+                    "Name", "Type", "Version", "Status", "Health", "CPU", "Memory", "Mem %",
+                    "Ports",
+                ])
+                .bold(),
+            )
+            .row_highlight_style(Style::new().reversed())
+            .highlight_symbol("▶ ")
+            .block(Block::bordered());
+        self.table_state.select(selected_row);
+        frame.render_stateful_widget(table, area, &mut self.table_state);
+    }
+}
+
+fn entry_row(entry: &Entry) -> Row<'static> {
+    let instance = &entry.instance;
+    let health = match entry.health.as_deref() {
+        Some(health) => {
+            let color = match health {
+                "healthy" => Color::Green,
+                "unhealthy" => Color::Red,
+                _ => Color::Yellow,
+            };
+            Cell::from(health.to_string()).fg(color)
+        }
+        None => Cell::from("-"),
+    };
+    let (cpu, memory, memory_percent) = match &entry.stats {
+        Some(stats) => (
+            stats.cpu.clone(),
+            stats.memory.clone(),
+            stats.memory_percent.clone(),
+        ),
+        None => ("-".to_string(), "-".to_string(), "-".to_string()),
+    };
+    let status = if instance.keep {
+        format!("{} (kept)", instance.status)
+    } else {
+        instance.status.clone()
+    };
+    Row::new([
+        Cell::from(instance.name.clone()).yellow(),
+        Cell::from(instance.admin_image.server_type.short_name()).cyan(),
+        Cell::from(instance.admin_image.wildfly_image.short_name()).magenta(),
+        Cell::from(status),
+        health,
+        Cell::from(cpu),
+        Cell::from(memory),
+        Cell::from(memory_percent),
+        Cell::from(ports_text(instance)).green(),
+    ])
+}
+
+/// Returns the published ports as `<http>/<management>` followed by the additional
+/// and the debug port.
+fn ports_text(instance: &ContainerInstance) -> String {
+    let mut ports = vec![];
+    if let Some(published) = &instance.ports {
+        ports.push(format!("{}/{}", published.http, published.management));
+        for (port, host_port) in &published.extra {
+            ports.push(format!("{} {}", port.name(), host_port));
+        }
+    }
+    if let Some(debug) = instance.debug_port {
+        ports.push(format!("debug {}", debug));
+    }
+    ports.join(", ")
+}
+
+#[derive(Debug, PartialEq)]
+enum DisplayRow<'a> {
+    /// The header of the containers of a topology (or of the containers without one).
+    Group(Option<&'a str>),
+    /// The index of a container.
+    Container(usize),
+}
+
+/// Groups the sorted containers by their topology. Group headers are only added if
+/// at least one container belongs to a topology.
+fn display_rows<'a>(topologies: &[Option<&'a str>]) -> Vec<DisplayRow<'a>> {
+    let grouped = topologies.iter().any(Option::is_some);
+    let mut rows = vec![];
+    for (index, topology) in topologies.iter().enumerate() {
+        if grouped && (index == 0 || topologies[index - 1] != *topology) {
+            rows.push(DisplayRow::Group(*topology));
+        }
+        rows.push(DisplayRow::Container(index));
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_rows_grouped() {
+        assert_eq!(
+            display_rows(&[Some("demo"), Some("demo"), Some("test"), None]),
+            vec![
+                DisplayRow::Group(Some("demo")),
+                DisplayRow::Container(0),
+                DisplayRow::Container(1),
+                DisplayRow::Group(Some("test")),
+                DisplayRow::Container(2),
+                DisplayRow::Group(None),
+                DisplayRow::Container(3),
+            ]
+        );
+        assert_eq!(
+            display_rows(&[None, None]),
+            vec![DisplayRow::Container(0), DisplayRow::Container(1)]
+        );
+    }
+}
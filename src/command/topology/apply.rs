@@ -0,0 +1,1245 @@
+use crate::args::{username_password_argument, variables_argument};
+use crate::command::lifecycle::{create_progress, stop_containers_by_name};
+use crate::container::{
+    container_label, containers_by_topology, resolve_start_specs, verify_container_command,
+};
+use crate::error::WadoError;
+use crate::json::PlanChangeInfo;
+use crate::label::Label;
+use crate::progress::{CommandStatus, Progress, summary};
+use crate::wildfly::{
+    DomainHost, DomainServer, DomainServerGroup, HostController, ManagementApi, ManagementClient,
+    Operation, Server, ServerGroup, ServerType,
+};
+use anyhow::{anyhow, bail};
+use clap::ArgMatches;
+use console::style;
+use futures::executor::block_on;
+use indicatif::MultiProgress;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tokio::time::Instant;
+use wildfly_meta::WildFlyImageRegistry;
+
+use super::model::{HostSetup, ServerGroupSetup, TopologySetup, quote};
+use super::start::{
+    allocate_server_debug, bootstraps_of, build_bootstrap_map, build_hc_specs, run_host_controllers,
+};
+use super::status::{expected_hosts, match_instances};
+
+pub fn topology_apply(
+    matches: &ArgMatches,
+    registry: &WildFlyImageRegistry,
+    json: bool,
+) -> anyhow::Result<()> {
+    let path = matches.get_one::<PathBuf>("setup").unwrap();
+    let setup = TopologySetup::load(path, &variables_argument(matches)?, registry)?;
+    let dry_run = matches.get_flag("dry-run");
+    verify_container_command()?;
+    let (username, password) = username_password_argument(matches);
+
+    let mut instances = block_on(containers_by_topology(&setup.name, registry))?;
+    instances.sort();
+    let expected = expected_hosts(&setup, registry)?;
+    let (matched, remaining) = match_instances(&expected, &instances);
+
+    let dc_index = setup
+        .hosts
+        .iter()
+        .position(|h| h.domain_controller)
+        .expect("No domain controller found (should have been validated)");
+    let Some(dc) = matched[dc_index] else {
+        bail!(
+            "The domain controller of topology '{}' is not running. Use 'wado topology start' to start the topology.",
+            setup.name
+        );
+    };
+    let api =
+        ManagementClient::from_container_instance(dc, registry).http_api(username, password)?;
+    let (hosts, groups, system_properties) = block_on(async {
+        Ok::<_, anyhow::Error>((
+            api.domain_hosts().await?,
+            api.domain_server_groups().await?,
+            api.domain_system_properties().await?,
+        ))
+    })
+    .map_err(|e| anyhow!("Unable to read the domain model of '{}': {}", dc.name, e))?;
+    // domain controllers started by older versions don't record their operations
+    let operations = block_on(container_label(&dc.name, Label::Operations))?
+        .and_then(|operations| serde_json::from_str(&operations).ok());
+    let domain = Domain {
+        groups,
+        system_properties,
+        hosts,
+        operations,
+    };
+    let desired_domain = DesiredDomain {
+        groups: &setup.server_groups,
+        system_properties: &setup.system_properties,
+        operations: setup.normalized_operations()?,
+        host_operations: setup.dc_host().bootstrap_operations()?,
+    };
+
+    let declared = setup.declared_groups();
+    let desired: Vec<DesiredHost> = setup
+        .hosts
+        .iter()
+        .zip(&expected)
+        .zip(&matched)
+        .enumerate()
+        .map(|(index, ((host, expected), instance))| DesiredHost {
+            index,
+            name: expected.host_name(*instance),
+            version: expected.admin_image.wildfly_image.short_name(),
+            running: instance.is_some(),
+            servers: host.to_servers(&declared),
+            debug_servers: host
+                .servers
+                .iter()
+                .filter(|server| server.debug.is_some())
+                .map(|server| server.name.clone())
+                .collect(),
+        })
+        .collect();
+    let obsolete: Vec<String> = remaining
+        .iter()
+        .filter(|i| i.admin_image.server_type == ServerType::HostController)
+        .map(|i| i.name.clone())
+        .collect();
+    let (changes, warnings) = plan(&desired_domain, &domain, &desired, &obsolete);
+
+    if !json {
+        print_plan(&setup.name, &changes, &warnings);
+    }
+    if dry_run || changes.is_empty() {
+        if json {
+            let infos: Vec<PlanChangeInfo> = changes.iter().map(|c| c.info(None)).collect();
+            println!("{}", serde_json::to_string(&infos)?);
+        }
+        return Ok(());
+    }
+
+    block_on(apply_changes(
+        &setup,
+        dc.name.clone(),
+        &api,
+        changes,
+        registry,
+        json,
+    ))
+}
+
+// ------------------------------------------------------ plan
+
+/// The server groups, system properties and operations of the topology file.
+struct DesiredDomain<'a> {
+    groups: &'a [ServerGroupSetup],
+    system_properties: &'a BTreeMap<String, String>,
+    /// The domain-wide operations in normalized CLI syntax.
+    operations: Vec<String>,
+    /// The bootstrap operations of the domain controller in normalized CLI syntax.
+    host_operations: Vec<String>,
+}
+
+impl DesiredDomain<'_> {
+    /// Returns whether one of the operations adds the resource `address` (e.g.
+    /// `/server-group=foo`). Such resources are not removed, although they're not declared.
+    fn adds(&self, address: &str) -> bool {
+        let add = format!("{}:add", address);
+        self.operations
+            .iter()
+            .chain(&self.host_operations)
+            .any(|operation| operation.starts_with(&add))
+    }
+}
+
+/// The server groups, system properties and hosts of the domain controller.
+struct Domain {
+    groups: Vec<DomainServerGroup>,
+    system_properties: BTreeMap<String, String>,
+    hosts: Vec<DomainHost>,
+    /// The domain-wide operations the topology has been started with (if known).
+    operations: Option<Vec<String>>,
+}
+
+/// A host of the topology file together with its servers (incl. port offsets).
+struct DesiredHost {
+    /// The index of the host in the topology file.
+    index: usize,
+    name: String,
+    version: String,
+    running: bool,
+    servers: Vec<Server>,
+    /// The names of the servers with `debug`.
+    debug_servers: Vec<String>,
+}
+
+/// A change to reconcile a running topology with its topology file.
+#[derive(Debug, PartialEq)]
+enum Change {
+    AddServerGroup {
+        name: String,
+        operations: Vec<String>,
+    },
+    /// Changes to the attributes and JVMs of a server group as readable descriptions.
+    UpdateServerGroup {
+        name: String,
+        details: Vec<String>,
+        operations: Vec<String>,
+    },
+    RemoveServerGroup {
+        name: String,
+    },
+    AddSystemProperty {
+        name: String,
+        value: String,
+    },
+    UpdateSystemProperty {
+        name: String,
+        old: String,
+        new: String,
+    },
+    RemoveSystemProperty {
+        name: String,
+    },
+    RemoveServer {
+        host: String,
+        server: String,
+        running: bool,
+    },
+    AddServer {
+        host: String,
+        server: Server,
+    },
+    /// Attributes as tuples of name, old value and new value. Running servers are
+    /// restarted if their group or port offset changes.
+    UpdateServer {
+        host: String,
+        server: String,
+        attributes: Vec<(&'static str, String, String)>,
+        running: bool,
+    },
+    StartServer {
+        host: String,
+        server: String,
+    },
+    RemoveHost {
+        name: String,
+    },
+    AddHost {
+        index: usize,
+        name: String,
+        version: String,
+    },
+}
+
+/// Compares the desired state with the server groups, system properties and hosts of
+/// the domain controller and the running host controllers that are no longer part of
+/// the topology (`obsolete`). Returns the changes in the order in which they're applied
+/// and warnings about changes which can't be reconciled.
+fn plan(
+    desired_domain: &DesiredDomain,
+    domain: &Domain,
+    desired: &[DesiredHost],
+    obsolete: &[String],
+) -> (Vec<Change>, Vec<String>) {
+    let mut changes = vec![];
+    let mut warnings = vec![];
+    for group in desired_domain.groups {
+        match domain.groups.iter().find(|g| g.name == group.name) {
+            None => changes.push(Change::AddServerGroup {
+                name: group.name.clone(),
+                operations: group.operations(),
+            }),
+            Some(existing) => changes.extend(update_server_group(group, existing)),
+        }
+    }
+    for existing in &domain.groups {
+        if !ServerGroup::is_builtin(&existing.name)
+            && !desired_domain
+                .groups
+                .iter()
+                .any(|g| g.name == existing.name)
+            && !desired_domain.adds(&format!("/server-group={}", existing.name))
+        {
+            changes.push(Change::RemoveServerGroup {
+                name: existing.name.clone(),
+            });
+        }
+    }
+    for (name, value) in desired_domain.system_properties {
+        match domain.system_properties.get(name) {
+            None => changes.push(Change::AddSystemProperty {
+                name: name.clone(),
+                value: value.clone(),
+            }),
+            Some(old) if old != value => changes.push(Change::UpdateSystemProperty {
+                name: name.clone(),
+                old: old.clone(),
+                new: value.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for name in domain.system_properties.keys() {
+        if !desired_domain.system_properties.contains_key(name)
+            && !desired_domain.adds(&format!("/system-property={}", name))
+        {
+            changes.push(Change::RemoveSystemProperty { name: name.clone() });
+        }
+    }
+    if domain
+        .operations
+        .as_ref()
+        .is_some_and(|operations| *operations != desired_domain.operations)
+    {
+        warnings.push(
+            "The operations of the topology have changed. Operations are only executed when the topology starts. Restart the topology to apply them.".to_string(),
+        );
+    }
+    for host in desired {
+        if !host.running {
+            changes.push(Change::AddHost {
+                index: host.index,
+                name: host.name.clone(),
+                version: host.version.clone(),
+            });
+            continue;
+        }
+        let Some(domain_host) = domain.hosts.iter().find(|h| h.name == host.name) else {
+            warnings.push(format!(
+                "Host '{}' is not registered at the domain controller. Its servers are not reconciled.",
+                host.name
+            ));
+            continue;
+        };
+        for server in &host.servers {
+            match domain_host.servers.iter().find(|s| s.name == server.name) {
+                None => {
+                    if host.debug_servers.contains(&server.name) {
+                        warnings.push(format!(
+                            "Server '{}' is added to the running host '{}' without debugging. Restart the topology to debug it.",
+                            server.name, host.name
+                        ));
+                    }
+                    changes.push(Change::AddServer {
+                        host: host.name.clone(),
+                        server: server.clone(),
+                    })
+                }
+                Some(existing) => {
+                    let mut attributes = vec![];
+                    let group = existing.group.clone().unwrap_or_default();
+                    if group != server.server_group.name() {
+                        attributes.push(("group", group, server.server_group.to_string()));
+                    }
+                    if existing.offset != server.offset {
+                        attributes.push((
+                            "socket-binding-port-offset",
+                            existing.offset.to_string(),
+                            server.offset.to_string(),
+                        ));
+                    }
+                    if existing.auto_start != server.autostart {
+                        attributes.push((
+                            "auto-start",
+                            existing.auto_start.to_string(),
+                            server.autostart.to_string(),
+                        ));
+                    }
+                    if !attributes.is_empty() {
+                        changes.push(Change::UpdateServer {
+                            host: host.name.clone(),
+                            server: server.name.clone(),
+                            attributes,
+                            running: running(existing),
+                        });
+                    }
+                    if server.autostart && existing.status.as_deref() == Some("STOPPED") {
+                        changes.push(Change::StartServer {
+                            host: host.name.clone(),
+                            server: server.name.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        for existing in &domain_host.servers {
+            if !host.servers.iter().any(|s| s.name == existing.name) {
+                changes.push(Change::RemoveServer {
+                    host: host.name.clone(),
+                    server: existing.name.clone(),
+                    running: running(existing),
+                });
+            }
+        }
+    }
+    for name in obsolete {
+        changes.push(Change::RemoveHost { name: name.clone() });
+    }
+    changes.sort_by_key(Change::rank);
+    (changes, warnings)
+}
+
+fn running(server: &DomainServer) -> bool {
+    !matches!(server.status.as_deref(), Some("STOPPED" | "DISABLED"))
+}
+
+/// Compares the profile, the socket binding group and the JVM of a server group and
+/// returns the operations to update it. The running servers of the group are restarted.
+fn update_server_group(group: &ServerGroupSetup, existing: &DomainServerGroup) -> Option<Change> {
+    let address = format!("/server-group={}", group.name);
+    let mut details = vec![];
+    let mut operations = vec![];
+    let mut write = |address: &str, name: &str, old: Option<String>, new: Option<String>| {
+        if old != new {
+            details.push(format!(
+                "{}: {} → {}",
+                name,
+                old.as_deref().unwrap_or("undefined"),
+                new.as_deref().unwrap_or("undefined")
+            ));
+            operations.push(match new {
+                Some(value) => {
+                    format!("{}:write-attribute(name={},value={})", address, name, value)
+                }
+                None => format!("{}:undefine-attribute(name={})", address, name),
+            });
+        }
+    };
+    write(
+        &address,
+        "profile",
+        existing.profile.clone(),
+        Some(group.profile.clone()),
+    );
+    write(
+        &address,
+        "socket-binding-group",
+        existing.socket_binding_group.clone(),
+        Some(group.socket_binding_group.clone()),
+    );
+    let jvm_name = group.jvm.as_ref().map(|jvm| jvm.name());
+    if let Some(jvm) = &group.jvm {
+        let jvm_address = format!("{}/jvm={}", address, jvm.name());
+        match existing.jvms.get(jvm.name()) {
+            Some(existing_jvm) => {
+                let string = |name: &str| existing_jvm[name].as_str().map(str::to_string);
+                write(
+                    &jvm_address,
+                    "heap-size",
+                    string("heap-size"),
+                    jvm.heap_size.clone(),
+                );
+                write(
+                    &jvm_address,
+                    "max-heap-size",
+                    string("max-heap-size"),
+                    jvm.max_heap_size.clone(),
+                );
+                let options: Vec<String> = existing_jvm["jvm-options"]
+                    .as_array()
+                    .map(|options| {
+                        options
+                            .iter()
+                            .filter_map(Value::as_str)
+                            .map(quote)
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let old = (!options.is_empty()).then(|| format!("[{}]", options.join(",")));
+                let new = (!jvm.jvm_options.is_empty()).then(|| jvm.options_value());
+                write(&jvm_address, "jvm-options", old, new);
+            }
+            None => {
+                details.push(format!("add jvm={}", jvm.name()));
+                operations.extend(group.jvm_operation());
+            }
+        }
+    }
+    for name in existing.jvms.keys() {
+        if jvm_name != Some(name.as_str()) {
+            details.push(format!("remove jvm={}", name));
+            operations.push(format!("{}/jvm={}:remove", address, name));
+        }
+    }
+    if operations.is_empty() {
+        return None;
+    }
+    details.push("restart servers".to_string());
+    operations.push(format!("{}:restart-servers(blocking=true)", address));
+    Some(Change::UpdateServerGroup {
+        name: group.name.clone(),
+        details,
+        operations,
+    })
+}
+
+impl Change {
+    /// Server groups and system properties are changed first and servers are removed
+    /// before new servers are added. Server groups are removed once their servers and
+    /// hosts are gone. Host controllers are added last, so they can use the new server
+    /// groups.
+    fn rank(&self) -> u8 {
+        match self {
+            Change::AddServerGroup { .. } => 0,
+            Change::UpdateServerGroup { .. } => 1,
+            Change::AddSystemProperty { .. }
+            | Change::UpdateSystemProperty { .. }
+            | Change::RemoveSystemProperty { .. } => 2,
+            Change::RemoveServer { .. } => 3,
+            Change::AddServer { .. } => 4,
+            Change::UpdateServer { .. } => 5,
+            Change::StartServer { .. } => 6,
+            Change::RemoveHost { .. } => 7,
+            Change::RemoveServerGroup { .. } => 8,
+            Change::AddHost { .. } => 9,
+        }
+    }
+
+    fn action(&self) -> &'static str {
+        match self {
+            Change::AddServerGroup { .. }
+            | Change::AddSystemProperty { .. }
+            | Change::AddServer { .. }
+            | Change::AddHost { .. } => "add",
+            Change::UpdateServerGroup { .. }
+            | Change::UpdateSystemProperty { .. }
+            | Change::UpdateServer { .. } => "update",
+            Change::StartServer { .. } => "start",
+            Change::RemoveServerGroup { .. }
+            | Change::RemoveSystemProperty { .. }
+            | Change::RemoveServer { .. }
+            | Change::RemoveHost { .. } => "remove",
+        }
+    }
+
+    fn resource(&self) -> &'static str {
+        match self {
+            Change::AddServerGroup { .. }
+            | Change::UpdateServerGroup { .. }
+            | Change::RemoveServerGroup { .. } => "server-group",
+            Change::AddSystemProperty { .. }
+            | Change::UpdateSystemProperty { .. }
+            | Change::RemoveSystemProperty { .. } => "system-property",
+            Change::AddHost { .. } | Change::RemoveHost { .. } => "host",
+            _ => "server",
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Change::AddServerGroup { name, .. }
+            | Change::UpdateServerGroup { name, .. }
+            | Change::RemoveServerGroup { name }
+            | Change::AddSystemProperty { name, .. }
+            | Change::UpdateSystemProperty { name, .. }
+            | Change::RemoveSystemProperty { name }
+            | Change::AddHost { name, .. }
+            | Change::RemoveHost { name } => name.clone(),
+            Change::AddServer { host, server } => format!("{}/{}", host, server.name),
+            Change::RemoveServer { host, server, .. }
+            | Change::UpdateServer { host, server, .. }
+            | Change::StartServer { host, server } => format!("{}/{}", host, server),
+        }
+    }
+
+    fn detail(&self) -> Option<String> {
+        match self {
+            Change::AddServer { server, .. } => {
+                let mut detail = format!("{}, offset {}", server.server_group, server.offset);
+                if server.autostart {
+                    detail.push_str(", auto-start");
+                }
+                Some(detail)
+            }
+            Change::UpdateServer {
+                attributes,
+                running,
+                ..
+            } => {
+                let mut details: Vec<String> = attributes
+                    .iter()
+                    .map(|(name, old, new)| format!("{}: {} → {}", name, old, new))
+                    .collect();
+                if restart(attributes, *running) {
+                    details.push("restart".to_string());
+                }
+                Some(details.join(", "))
+            }
+            Change::UpdateServerGroup { details, .. } => Some(details.join(", ")),
+            Change::AddSystemProperty { value, .. } => Some(value.clone()),
+            Change::UpdateSystemProperty { old, new, .. } => {
+                Some(format!("value: {} → {}", old, new))
+            }
+            Change::RemoveServer { running: true, .. } => Some("stop".to_string()),
+            Change::AddHost { version, .. } => Some(format!("host controller {}", version)),
+            Change::RemoveHost { .. } => Some("stop container".to_string()),
+            _ => None,
+        }
+    }
+
+    /// Returns the management operations of this change in CLI syntax.
+    /// Hosts are added and removed by starting and stopping containers.
+    fn operations(&self) -> Vec<String> {
+        match self {
+            Change::AddServerGroup { operations, .. }
+            | Change::UpdateServerGroup { operations, .. } => operations.clone(),
+            Change::RemoveServerGroup { name } => vec![format!("/server-group={}:remove", name)],
+            Change::AddSystemProperty { name, value } => vec![format!(
+                "/system-property={}:add(value={})",
+                name,
+                quote(value)
+            )],
+            Change::UpdateSystemProperty { name, new, .. } => vec![format!(
+                "/system-property={}:write-attribute(name=value,value={})",
+                name,
+                quote(new)
+            )],
+            Change::RemoveSystemProperty { name } => {
+                vec![format!("/system-property={}:remove", name)]
+            }
+            Change::AddServer { host, server } => {
+                let mut operations = vec![server.add_server_op(host)];
+                if server.autostart {
+                    operations.push(server_operation(host, &server.name, "start(blocking=true)"));
+                }
+                operations
+            }
+            Change::UpdateServer {
+                host,
+                server,
+                attributes,
+                running,
+            } => {
+                let mut operations: Vec<String> = attributes
+                    .iter()
+                    .map(|(name, _, value)| {
+                        server_operation(
+                            host,
+                            server,
+                            &format!("write-attribute(name={},value={})", name, value),
+                        )
+                    })
+                    .collect();
+                if restart(attributes, *running) {
+                    operations.push(server_operation(host, server, "restart(blocking=true)"));
+                }
+                operations
+            }
+            Change::StartServer { host, server } => {
+                vec![server_operation(host, server, "start(blocking=true)")]
+            }
+            Change::RemoveServer {
+                host,
+                server,
+                running,
+            } => {
+                let mut operations = vec![];
+                if *running {
+                    operations.push(server_operation(host, server, "stop(blocking=true)"));
+                }
+                operations.push(server_operation(host, server, "remove"));
+                operations
+            }
+            Change::AddHost { .. } | Change::RemoveHost { .. } => vec![],
+        }
+    }
+
+    fn info(&self, status: Option<&CommandStatus>) -> PlanChangeInfo {
+        PlanChangeInfo {
+            action: self.action().to_string(),
+            resource: self.resource().to_string(),
+            name: self.name(),
+            detail: self.detail(),
+            success: status.map(|s| s.success),
+            error: status
+                .filter(|s| !s.success)
+                .map(|s| s.error_message.clone()),
+        }
+    }
+}
+
+/// Running servers have to be restarted if their group or port offset changes.
+fn restart(attributes: &[(&'static str, String, String)], running: bool) -> bool {
+    running
+        && attributes
+            .iter()
+            .any(|(name, _, _)| matches!(*name, "group" | "socket-binding-port-offset"))
+}
+
+fn server_operation(host: &str, server: &str, operation: &str) -> String {
+    format!("/host={}/server-config={}:{}", host, server, operation)
+}
+
+fn print_plan(topology: &str, changes: &[Change], warnings: &[String]) {
+    for warning in warnings {
+        println!("{}", style(warning).yellow());
+    }
+    if changes.is_empty() {
+        println!(
+            "\nNo changes. Topology {} is up to date.",
+            style(topology).cyan()
+        );
+        return;
+    }
+    println!("\nTopology {}\n", style(topology).cyan());
+    for change in changes {
+        let line = match change.detail() {
+            Some(detail) => format!("{} {} ({})", change.resource(), change.name(), detail),
+            None => format!("{} {}", change.resource(), change.name()),
+        };
+        match change.action() {
+            "add" => println!("  {} {}", style("+").green(), style(line).green()),
+            "remove" => println!("  {} {}", style("-").red(), style(line).red()),
+            _ => println!("  {} {}", style("~").yellow(), style(line).yellow()),
+        }
+    }
+    let count = |actions: &[&str]| {
+        changes
+            .iter()
+            .filter(|c| actions.contains(&c.action()))
+            .count()
+    };
+    println!(
+        "\nPlan: {} to add, {} to change, {} to remove.",
+        style(count(&["add"])).green(),
+        style(count(&["update", "start"])).yellow(),
+        style(count(&["remove"])).red()
+    );
+}
+
+// ------------------------------------------------------ apply
+
+async fn apply_changes(
+    setup: &TopologySetup,
+    dc_name: String,
+    api: &ManagementApi,
+    changes: Vec<Change>,
+    registry: &WildFlyImageRegistry,
+    json: bool,
+) -> anyhow::Result<()> {
+    let instant = Instant::now();
+    let count = changes.len();
+    let multi_progress = if json {
+        None
+    } else {
+        println!();
+        Some(MultiProgress::new())
+    };
+
+    let mut results = vec![];
+    let mut statuses = vec![];
+    let mut obsolete = vec![];
+    let mut removed_groups = vec![];
+    let mut added = vec![];
+    for change in changes {
+        match change {
+            Change::RemoveHost { .. } => obsolete.push(change),
+            Change::RemoveServerGroup { .. } => removed_groups.push(change),
+            Change::AddHost { .. } => added.push(change),
+            change => {
+                let progress = create_progress(&multi_progress, &change.name(), change.resource());
+                let status = execute_change(api, &change, progress).await;
+                results.push(change.info(Some(&status)));
+                statuses.push(status);
+            }
+        }
+    }
+
+    if !obsolete.is_empty() {
+        let names: Vec<String> = obsolete.iter().map(Change::name).collect();
+        for (status, _) in stop_containers_by_name(&names, json).await? {
+            if let Some(change) = obsolete.iter().find(|c| c.name() == status.identifier) {
+                results.push(change.info(Some(&status)));
+            }
+            statuses.push(status);
+        }
+    }
+    // server groups can only be removed once the obsolete hosts are gone
+    for change in removed_groups {
+        let progress = create_progress(&multi_progress, &change.name(), change.resource());
+        let status = execute_change(api, &change, progress).await;
+        results.push(change.info(Some(&status)));
+        statuses.push(status);
+    }
+    if !added.is_empty() {
+        let hosts: Vec<&HostSetup> = added
+            .iter()
+            .filter_map(|change| match change {
+                Change::AddHost { index, .. } => Some(&setup.hosts[*index]),
+                _ => None,
+            })
+            .collect();
+        let specs = build_hc_specs(&hosts, &setup.version, registry)?;
+        let resolved =
+            resolve_start_specs(ServerType::HostController, specs, &[], registry).await?;
+        let hcs: Vec<HostController> = resolved
+            .into_iter()
+            .map(|r| HostController::new(r.admin_image, r.name, dc_name.clone(), r.debug_port))
+            .collect();
+        let mut bootstrap_map = build_bootstrap_map(&hosts, &hcs, setup)?;
+        let reserved = hcs
+            .iter()
+            .filter_map(|hc| hc.debug_port)
+            .collect::<Vec<_>>();
+        allocate_server_debug(bootstraps_of(&hcs, &mut bootstrap_map), &reserved, registry).await?;
+        for (status, progress) in
+            run_host_controllers(&setup.name, &hcs, &bootstrap_map, json).await?
+        {
+            progress.finish_if_alive(Some(&status.display_name()));
+            // The container names of unnamed hosts are resolved when they're started.
+            if let Some(position) = hcs.iter().position(|hc| hc.name == status.identifier) {
+                let mut info = added[position].info(Some(&status));
+                info.name = status.identifier.clone();
+                results.push(info);
+            }
+            statuses.push(status);
+        }
+    }
+
+    if json {
+        println!("{}", serde_json::to_string(&results)?);
+    } else {
+        summary("Applied", "changes", count, instant, statuses);
+    }
+    Ok(())
+}
+
+async fn execute_change(api: &ManagementApi, change: &Change, progress: Progress) -> CommandStatus {
+    let name = change.name();
+    let mut result = Ok(());
+    for operation in change.operations() {
+        progress.show_progress(&operation);
+        result = execute_cli(api, &operation).await;
+        if result.is_err() {
+            break;
+        }
+    }
+    match result {
+        Ok(()) => progress.finish_no_output(Some(&name)),
+        Err(e) => progress.finish_error(&name, &e.to_string()),
+    }
+}
+
+async fn execute_cli(api: &ManagementApi, input: &str) -> anyhow::Result<()> {
+    let operation = Operation::parse(input)
+        .map_err(|e| anyhow!(WadoError::invalid_operation(&e.display_with(input))))?;
+    api.execute(&operation.to_dmr()).await.map(|_| ())
+}
+
+// ------------------------------------------------------ tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::topology::model::JvmSetup;
+    use crate::wildfly::{DomainServer, ServerGroup};
+
+    fn domain_server(name: &str, group: &str, offset: u16, status: &str) -> DomainServer {
+        DomainServer {
+            name: name.to_string(),
+            group: Some(group.to_string()),
+            offset,
+            auto_start: false,
+            status: Some(status.to_string()),
+            server_state: None,
+        }
+    }
+
+    fn domain_host(name: &str, servers: Vec<DomainServer>) -> DomainHost {
+        DomainHost {
+            name: name.to_string(),
+            primary: false,
+            host_state: Some("running".to_string()),
+            release_version: None,
+            servers,
+        }
+    }
+
+    fn domain(hosts: Vec<DomainHost>) -> Domain {
+        let group = |name: &str| DomainServerGroup {
+            name: name.to_string(),
+            profile: Some("default".to_string()),
+            socket_binding_group: Some("standard-sockets".to_string()),
+            jvms: BTreeMap::new(),
+        };
+        Domain {
+            groups: vec![group("main-server-group"), group("other-server-group")],
+            system_properties: BTreeMap::new(),
+            hosts,
+            operations: Some(vec![]),
+        }
+    }
+
+    fn desired_domain<'a>(
+        groups: &'a [ServerGroupSetup],
+        system_properties: &'a BTreeMap<String, String>,
+    ) -> DesiredDomain<'a> {
+        DesiredDomain {
+            groups,
+            system_properties,
+            operations: vec![],
+            host_operations: vec![],
+        }
+    }
+
+    fn server_group(name: &str, profile: &str, jvm: Option<JvmSetup>) -> ServerGroupSetup {
+        ServerGroupSetup {
+            name: name.to_string(),
+            profile: profile.to_string(),
+            socket_binding_group: "standard-sockets".to_string(),
+            jvm,
+            origin: None,
+        }
+    }
+
+    fn desired(index: usize, name: &str, running: bool, servers: &str) -> DesiredHost {
+        DesiredHost {
+            index,
+            name: name.to_string(),
+            version: "34".to_string(),
+            running,
+            servers: if servers.is_empty() {
+                vec![]
+            } else {
+                Server::parse_servers(servers).unwrap()
+            },
+            debug_servers: vec![],
+        }
+    }
+
+    #[test]
+    fn no_changes() {
+        let desired = vec![desired(0, "host1", true, "server-one,server-two:osg:100")];
+        let domain = domain(vec![domain_host(
+            "host1",
+            vec![
+                domain_server("server-one", "main-server-group", 0, "STOPPED"),
+                domain_server("server-two", "other-server-group", 100, "STOPPED"),
+            ],
+        )]);
+        let properties = BTreeMap::new();
+        let (changes, warnings) = plan(&desired_domain(&[], &properties), &domain, &desired, &[]);
+        assert!(changes.is_empty());
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn add_debugged_server() {
+        let mut host = desired(0, "host1", true, "server-one,server-two:osg:100");
+        host.debug_servers = vec!["server-two".to_string()];
+        let domain = domain(vec![domain_host(
+            "host1",
+            vec![domain_server(
+                "server-one",
+                "main-server-group",
+                0,
+                "STARTED",
+            )],
+        )]);
+        let properties = BTreeMap::new();
+        let (changes, warnings) = plan(&desired_domain(&[], &properties), &domain, &[host], &[]);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("Server 'server-two'"));
+    }
+
+    #[test]
+    fn changes() {
+        let groups = vec![server_group("app-group", "full", None)];
+        let desired = vec![
+            desired(
+                0,
+                "host1",
+                true,
+                "server-one:start,server-two:osg:200,server-three:100",
+            ),
+            desired(1, "host2", false, "server-one"),
+            desired(2, "host3", true, "server-one"),
+        ];
+        let domain = domain(vec![domain_host(
+            "host1",
+            vec![
+                domain_server("server-one", "main-server-group", 0, "STOPPED"),
+                domain_server("server-two", "main-server-group", 100, "STOPPED"),
+                domain_server("server-four", "main-server-group", 300, "STARTED"),
+            ],
+        )]);
+        let obsolete = vec!["host4".to_string()];
+        let properties = BTreeMap::new();
+        let (changes, warnings) = plan(
+            &desired_domain(&groups, &properties),
+            &domain,
+            &desired,
+            &obsolete,
+        );
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("host3"));
+        assert_eq!(
+            changes,
+            vec![
+                Change::AddServerGroup {
+                    name: "app-group".to_string(),
+                    operations: vec![
+                        "/server-group=app-group:add(profile=full,socket-binding-group=standard-sockets)"
+                            .to_string()
+                    ],
+                },
+                Change::RemoveServer {
+                    host: "host1".to_string(),
+                    server: "server-four".to_string(),
+                    running: true,
+                },
+                Change::AddServer {
+                    host: "host1".to_string(),
+                    server: Server {
+                        name: "server-three".to_string(),
+                        server_group: ServerGroup::MainServerGroup,
+                        offset: 100,
+                        autostart: false,
+                    },
+                },
+                Change::UpdateServer {
+                    host: "host1".to_string(),
+                    server: "server-one".to_string(),
+                    attributes: vec![("auto-start", "false".to_string(), "true".to_string())],
+                    running: false,
+                },
+                Change::UpdateServer {
+                    host: "host1".to_string(),
+                    server: "server-two".to_string(),
+                    attributes: vec![
+                        (
+                            "group",
+                            "main-server-group".to_string(),
+                            "other-server-group".to_string()
+                        ),
+                        (
+                            "socket-binding-port-offset",
+                            "100".to_string(),
+                            "200".to_string()
+                        ),
+                    ],
+                    running: false,
+                },
+                Change::StartServer {
+                    host: "host1".to_string(),
+                    server: "server-one".to_string(),
+                },
+                Change::RemoveHost {
+                    name: "host4".to_string(),
+                },
+                Change::AddHost {
+                    index: 1,
+                    name: "host2".to_string(),
+                    version: "34".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn operations() {
+        let change = Change::RemoveServer {
+            host: "host1".to_string(),
+            server: "server-four".to_string(),
+            running: true,
+        };
+        assert_eq!(
+            change.operations(),
+            vec![
+                "/host=host1/server-config=server-four:stop(blocking=true)",
+                "/host=host1/server-config=server-four:remove",
+            ]
+        );
+        let change = Change::AddServer {
+            host: "host1".to_string(),
+            server: Server::parse_server("server-one:osg:100:start").unwrap(),
+        };
+        assert_eq!(
+            change.operations(),
+            vec![
+                "/host=host1/server-config=server-one:add(group=other-server-group,socket-binding-port-offset=100,auto-start=true)",
+                "/host=host1/server-config=server-one:start(blocking=true)",
+            ]
+        );
+        let change = Change::UpdateServer {
+            host: "host1".to_string(),
+            server: "server-two".to_string(),
+            attributes: vec![("group", "a".to_string(), "b".to_string())],
+            running: false,
+        };
+        assert_eq!(
+            change.operations(),
+            vec!["/host=host1/server-config=server-two:write-attribute(name=group,value=b)"]
+        );
+        assert_eq!(change.detail().unwrap(), "group: a → b");
+    }
+
+    #[test]
+    fn move_running_server() {
+        let change = Change::UpdateServer {
+            host: "host1".to_string(),
+            server: "server-two".to_string(),
+            attributes: vec![("group", "a".to_string(), "b".to_string())],
+            running: true,
+        };
+        assert_eq!(
+            change.operations(),
+            vec![
+                "/host=host1/server-config=server-two:write-attribute(name=group,value=b)",
+                "/host=host1/server-config=server-two:restart(blocking=true)",
+            ]
+        );
+        assert_eq!(change.detail().unwrap(), "group: a → b, restart");
+
+        let change = Change::UpdateServer {
+            host: "host1".to_string(),
+            server: "server-two".to_string(),
+            attributes: vec![("auto-start", "false".to_string(), "true".to_string())],
+            running: true,
+        };
+        assert_eq!(change.operations().len(), 1);
+    }
+
+    #[test]
+    fn server_groups() {
+        let jvm = JvmSetup {
+            name: None,
+            heap_size: Some("128m".to_string()),
+            max_heap_size: None,
+            jvm_options: vec!["-Dfoo=bar".to_string()],
+        };
+        let groups = vec![
+            server_group("app-group", "full", Some(jvm)),
+            server_group("web-group", "default", None),
+        ];
+        let mut domain = domain(vec![]);
+        domain.groups.push(DomainServerGroup {
+            name: "app-group".to_string(),
+            profile: Some("default".to_string()),
+            socket_binding_group: Some("standard-sockets".to_string()),
+            jvms: BTreeMap::from([(
+                "default".to_string(),
+                serde_json::json!({"heap-size": "64m", "max-heap-size": "512m", "jvm-options": null}),
+            )]),
+        });
+        for name in ["web-group", "old-group", "ops-group"] {
+            domain.groups.push(DomainServerGroup {
+                name: name.to_string(),
+                profile: Some("default".to_string()),
+                socket_binding_group: Some("standard-sockets".to_string()),
+                jvms: BTreeMap::from([("old".to_string(), Value::Null)]),
+            });
+        }
+        let properties = BTreeMap::new();
+        let mut desired = desired_domain(&groups, &properties);
+        desired.host_operations = vec![
+            "/server-group=ops-group:add(profile=default,socket-binding-group=standard-sockets)"
+                .to_string(),
+        ];
+        let (changes, _) = plan(&desired, &domain, &[], &[]);
+        assert_eq!(
+            changes,
+            vec![
+                Change::UpdateServerGroup {
+                    name: "app-group".to_string(),
+                    details: vec![
+                        "profile: default → full".to_string(),
+                        "heap-size: 64m → 128m".to_string(),
+                        "max-heap-size: 512m → undefined".to_string(),
+                        "jvm-options: undefined → [\"-Dfoo=bar\"]".to_string(),
+                        "restart servers".to_string(),
+                    ],
+                    operations: vec![
+                        "/server-group=app-group:write-attribute(name=profile,value=full)"
+                            .to_string(),
+                        "/server-group=app-group/jvm=default:write-attribute(name=heap-size,value=128m)"
+                            .to_string(),
+                        "/server-group=app-group/jvm=default:undefine-attribute(name=max-heap-size)"
+                            .to_string(),
+                        "/server-group=app-group/jvm=default:write-attribute(name=jvm-options,value=[\"-Dfoo=bar\"])"
+                            .to_string(),
+                        "/server-group=app-group:restart-servers(blocking=true)".to_string(),
+                    ],
+                },
+                Change::UpdateServerGroup {
+                    name: "web-group".to_string(),
+                    details: vec![
+                        "remove jvm=old".to_string(),
+                        "restart servers".to_string()
+                    ],
+                    operations: vec![
+                        "/server-group=web-group/jvm=old:remove".to_string(),
+                        "/server-group=web-group:restart-servers(blocking=true)".to_string(),
+                    ],
+                },
+                Change::RemoveServerGroup {
+                    name: "old-group".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn system_properties() {
+        let properties = BTreeMap::from([
+            ("added".to_string(), "a".to_string()),
+            ("changed".to_string(), "new".to_string()),
+            ("unchanged".to_string(), "u".to_string()),
+        ]);
+        let mut domain = domain(vec![]);
+        domain.system_properties = BTreeMap::from([
+            ("changed".to_string(), "old".to_string()),
+            ("unchanged".to_string(), "u".to_string()),
+            ("removed".to_string(), "r".to_string()),
+            ("operation".to_string(), "o".to_string()),
+        ]);
+        let mut desired = desired_domain(&[], &properties);
+        desired.operations = vec!["/system-property=operation:add(value=o)".to_string()];
+        domain.operations = Some(desired.operations.clone());
+        let (changes, warnings) = plan(&desired, &domain, &[], &[]);
+        assert!(warnings.is_empty());
+        assert_eq!(
+            changes,
+            vec![
+                Change::AddSystemProperty {
+                    name: "added".to_string(),
+                    value: "a".to_string(),
+                },
+                Change::UpdateSystemProperty {
+                    name: "changed".to_string(),
+                    old: "old".to_string(),
+                    new: "new".to_string(),
+                },
+                Change::RemoveSystemProperty {
+                    name: "removed".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            changes[1].operations(),
+            vec!["/system-property=changed:write-attribute(name=value,value=\"new\")"]
+        );
+    }
+
+    #[test]
+    fn changed_operations() {
+        let properties = BTreeMap::new();
+        let mut desired = desired_domain(&[], &properties);
+        desired.operations =
+            vec!["/subsystem=logging:write-attribute(name=foo,value=bar)".to_string()];
+        let (changes, warnings) = plan(&desired, &domain(vec![]), &[], &[]);
+        assert!(changes.is_empty());
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("operations of the topology have changed"));
+
+        // domain controllers of older versions don't record their operations
+        let mut domain = domain(vec![]);
+        domain.operations = None;
+        let (_, warnings) = plan(&desired, &domain, &[], &[]);
+        assert!(warnings.is_empty());
+    }
+}
@@ -1,6 +1,17 @@
+mod apply;
 pub(crate) mod model;
+mod render;
+mod schema;
 mod start;
+mod status;
 mod stop;
+mod template;
+mod validate;
 
+pub use apply::topology_apply;
+pub use render::topology_render;
+pub use schema::topology_schema;
 pub use start::topology_start;
+pub use status::topology_status;
 pub use stop::topology_stop;
+pub use validate::topology_validate;
@@ -1,52 +1,282 @@
-use crate::wildfly::{Server, ServerGroup};
+use super::template::interpolate;
+use crate::container::{
+    DebugMode, Mount, RunOptions, ServerDebug, add_java_opts, deployment_operations,
+    deployment_server_groups, read_env_file,
+};
+use crate::error::WadoError;
+use crate::wildfly::{
+    DEFAULT_SERVER_OFFSET, MixedDomain, Server, ServerConfig, ServerGroup, ServerType,
+    apply_offsets, parse_commands, validate_batches,
+};
 use anyhow::{Context, bail};
-use serde::Deserialize;
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
 use serde::de;
-use std::collections::HashSet;
-use std::path::Path;
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize, Serializer};
+use serde_saphyr::Spanned;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
 use wildfly_meta::{WildFlyImage, WildFlyImageRegistry, parse_wildfly_image};
 
-#[derive(Deserialize)]
+/// A domain topology with a domain controller, host controllers and their servers.
+#[derive(Deserialize, JsonSchema, Serialize)]
+#[serde(deny_unknown_fields)]
+#[schemars(title = "wado topology")]
 pub struct TopologySetup {
+    /// Name of the topology.
     pub name: String,
+    /// WildFly version used for all hosts unless overridden per host (e.g. `34`, `26.1` or `dev`).
     #[serde(deserialize_with = "deserialize_version")]
+    #[schemars(schema_with = "version_schema")]
     pub version: String,
+    /// Fragments with shared server groups, system properties, operations and hosts.
+    /// Paths are relative to the including file.
+    #[serde(default, skip_serializing)]
+    pub include: Vec<PathBuf>,
+    /// Server groups added to the domain.
+    #[serde(
+        rename = "server-groups",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub server_groups: Vec<ServerGroupSetup>,
+    /// Domain-wide system properties (`/system-property=<key>:add(value=<value>)`).
+    #[serde(
+        rename = "system-properties",
+        default,
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub system_properties: BTreeMap<String, String>,
+    /// Domain-wide operations executed on the domain controller.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub operations: Vec<String>,
+    /// The domain controller and host controllers of the topology.
+    #[serde(default)]
     pub hosts: Vec<HostSetup>,
+    /// The directory of the topology file. Relative paths are resolved against it.
+    #[serde(skip)]
+    pub base_dir: PathBuf,
 }
 
-#[derive(Deserialize)]
+/// A file included by a topology. Fragments can't define the name or version of a topology.
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TopologyFragment {
+    #[serde(default)]
+    include: Vec<PathBuf>,
+    #[serde(rename = "server-groups", default)]
+    server_groups: Vec<ServerGroupSetup>,
+    #[serde(rename = "system-properties", default)]
+    system_properties: BTreeMap<String, String>,
+    #[serde(default)]
+    operations: Vec<String>,
+    #[serde(default)]
+    hosts: Vec<HostSetup>,
+}
+
+/// A domain controller or host controller.
+#[derive(Clone, Deserialize, JsonSchema, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct HostSetup {
+    /// Name of the host. Defaults to the container name based on the server type and version.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
-    #[serde(rename = "domain-controller", default)]
+    /// Expands this host into `replicas` hosts named `<name>-1` to `<name>-<replicas>`.
+    #[serde(skip_serializing)]
+    pub replicas: Option<u16>,
+    /// Whether this host is the domain controller.
+    #[serde(
+        rename = "domain-controller",
+        default,
+        skip_serializing_if = "std::ops::Not::not"
+    )]
     pub domain_controller: bool,
-    #[serde(default, deserialize_with = "deserialize_optional_version")]
+    /// WildFly version of this host (e.g. `34`, `26.1` or `dev`).
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_version",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(schema_with = "optional_version_schema")]
     pub version: Option<String>,
-    #[serde(default)]
+    /// Servers of this host.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub servers: Vec<ServerSetup>,
+    /// Management operations in CLI syntax to bootstrap this host.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub operations: Vec<String>,
+    /// Archives deployed to the server groups of the topology's servers or to the
+    /// `main-server-group` if there are no servers (domain controller only).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deployments: Vec<PathBuf>,
+    /// Bind mounts or named volumes `<host>:<container>[:<options>]`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub volumes: Vec<String>,
+    /// Built-in domain configuration or local XML file to start this host with (domain
+    /// controller only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<String>,
+    /// Built-in host configuration (e.g. `host-secondary.xml`) or local XML file to start
+    /// this host with (host controllers only).
+    #[serde(rename = "host-config", skip_serializing_if = "Option::is_none")]
+    pub host_config: Option<String>,
+    /// Environment variables of this host.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// File with environment variables `<key>=<value>`.
+    #[serde(rename = "env-file", skip_serializing_if = "Option::is_none")]
+    pub env_file: Option<PathBuf>,
+    /// JVM options (`JAVA_OPTS`) of the host controller process.
+    #[serde(rename = "java-opts", skip_serializing_if = "Option::is_none")]
+    pub java_opts: Option<String>,
+    /// System properties passed to the host controller process.
+    #[serde(
+        rename = "system-properties",
+        default,
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub system_properties: BTreeMap<String, String>,
+    /// Enables remote debugging of the host controller process (`true` or `suspend`).
+    #[serde(
+        default,
+        deserialize_with = "deserialize_debug",
+        serialize_with = "serialize_debug",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(schema_with = "debug_schema")]
+    pub debug: Option<DebugMode>,
+    /// The file and line this host is defined at.
+    #[serde(skip)]
+    pub origin: Option<Origin>,
+}
+
+/// A server group added to the domain.
+#[derive(Deserialize, JsonSchema, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ServerGroupSetup {
+    /// Name of the server group. Must not be a built-in server group.
+    pub name: String,
+    /// Profile of the server group (e.g. `full`).
+    pub profile: String,
+    /// Socket binding group of the server group (e.g. `full-sockets`).
+    #[serde(rename = "socket-binding-group")]
+    pub socket_binding_group: String,
+    /// JVM settings of the server group.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jvm: Option<JvmSetup>,
+    /// The file and line this server group is defined at.
+    #[serde(skip)]
+    pub origin: Option<Origin>,
 }
 
+/// The file and line a host or server group is defined at. Hosts resolve their
+/// relative paths against the directory of this file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Origin {
+    pub path: PathBuf,
+    pub line: u64,
+}
+
+/// Locations of the server groups and hosts of a topology file or fragment.
 #[derive(Deserialize)]
+struct Locations {
+    #[serde(rename = "server-groups", default)]
+    server_groups: Vec<Spanned<IgnoredAny>>,
+    #[serde(default)]
+    hosts: Vec<Spanned<IgnoredAny>>,
+}
+
+/// JVM settings of a server group.
+#[derive(Deserialize, JsonSchema, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct JvmSetup {
+    /// Name of the JVM. Defaults to `default`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(rename = "heap-size", skip_serializing_if = "Option::is_none")]
+    pub heap_size: Option<String>,
+    #[serde(rename = "max-heap-size", skip_serializing_if = "Option::is_none")]
+    pub max_heap_size: Option<String>,
+    #[serde(rename = "jvm-options", default, skip_serializing_if = "Vec::is_empty")]
+    pub jvm_options: Vec<String>,
+}
+
+/// A server of a host.
+#[derive(Clone, Deserialize, JsonSchema, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct ServerSetup {
+    /// Name of the server. Must be unique per host.
     pub name: String,
+    /// Server group: `main-server-group` (`msg`), `other-server-group` (`osg`) or a declared group.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub group: Option<String>,
+    /// Socket binding port offset. Auto-incremented by 100 from the second server onward if 0.
     #[serde(default)]
     pub offset: u16,
+    /// Whether to start the server when the host starts.
     #[serde(rename = "auto-start", default)]
     pub auto_start: bool,
+    /// Enables remote debugging of the server process (`true` or `suspend`).
+    #[serde(
+        default,
+        deserialize_with = "deserialize_debug",
+        serialize_with = "serialize_debug",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(schema_with = "debug_schema")]
+    pub debug: Option<DebugMode>,
 }
 
 impl TopologySetup {
-    pub fn load(path: &Path, registry: &WildFlyImageRegistry) -> anyhow::Result<TopologySetup> {
-        let content = std::fs::read_to_string(path)
-            .with_context(|| format!("Failed to read topology file: {}", path.display()))?;
-        let setup: TopologySetup = serde_saphyr::from_str(&content)
-            .with_context(|| format!("Failed to parse topology file: {}", path.display()))?;
+    /// Reads, expands and validates the topology file at `path`.
+    pub fn load(
+        path: &Path,
+        variables: &BTreeMap<String, String>,
+        registry: &WildFlyImageRegistry,
+    ) -> anyhow::Result<TopologySetup> {
+        let setup = TopologySetup::read(path, variables)?;
         setup.validate(registry)?;
         resolve_version(&setup.version, registry)
             .with_context(|| format!("Unknown WildFly version: {}", setup.version))?;
         Ok(setup)
     }
 
+    /// Reads the topology file at `path` without validating it. Variables are
+    /// interpolated, includes are merged and replicated hosts are expanded.
+    pub fn read(
+        path: &Path,
+        variables: &BTreeMap<String, String>,
+    ) -> anyhow::Result<TopologySetup> {
+        let content = read_template(path, variables)?;
+        let mut setup: TopologySetup = serde_saphyr::from_str(&content)
+            .with_context(|| format!("Failed to parse topology file: {}", path.display()))?;
+        setup.base_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        set_origins(&content, path, &mut setup.server_groups, &mut setup.hosts)?;
+
+        let mut stack = vec![canonical(path)?];
+        let mut fragment = TopologyFragment::default();
+        for include in std::mem::take(&mut setup.include) {
+            fragment.merge(read_fragment(
+                &setup.base_dir.join(include),
+                variables,
+                &mut stack,
+            )?);
+        }
+        fragment.merge(TopologyFragment {
+            include: vec![],
+            server_groups: std::mem::take(&mut setup.server_groups),
+            system_properties: std::mem::take(&mut setup.system_properties),
+            operations: std::mem::take(&mut setup.operations),
+            hosts: std::mem::take(&mut setup.hosts),
+        });
+        setup.server_groups = fragment.server_groups;
+        setup.system_properties = fragment.system_properties;
+        setup.operations = fragment.operations;
+        setup.hosts = expand_replicas(fragment.hosts)?;
+        Ok(setup)
+    }
+
     pub fn validate(&self, registry: &WildFlyImageRegistry) -> anyhow::Result<()> {
         let dc_count = self.hosts.iter().filter(|h| h.domain_controller).count();
         if dc_count == 0 {
@@ -62,38 +292,219 @@ impl TopologySetup {
             bail!("Multiple domain controllers defined: {}", names.join(", "));
         }
 
+        let mut seen = HashSet::new();
+        for group in &self.server_groups {
+            let result = if ServerGroup::is_builtin(&group.name) {
+                Err(anyhow::anyhow!(
+                    "Server group '{}' is already defined in the default configuration",
+                    group.name
+                ))
+            } else if !seen.insert(&group.name) {
+                Err(anyhow::anyhow!("Duplicate server group: '{}'", group.name))
+            } else {
+                Ok(())
+            };
+            located(result, &group.origin)?;
+        }
+        self.domain_operations()
+            .context("Invalid domain operations")?;
+
         let mut seen = HashSet::new();
         for host in &self.hosts {
             if let Some(name) = &host.name
                 && !seen.insert(name)
             {
-                bail!("Duplicate host name: '{}'", name);
+                located(
+                    Err(anyhow::anyhow!("Duplicate host name: '{}'", name)),
+                    &host.origin,
+                )?;
             }
         }
 
         for host in &self.hosts {
-            let host_label = host.name.as_deref().unwrap_or("<unnamed>");
-            if let Some(v) = &host.version {
-                resolve_version(v, registry).with_context(|| {
-                    format!("Unknown WildFly version '{}' for host '{}'", v, host_label)
-                })?;
+            located(self.validate_host(host, registry), &host.origin)?;
+        }
+
+        for (check, message, details) in self.mixed_domain(registry) {
+            if check == MixedDomain::Unsupported {
+                return Err(WadoError::topology_error(&message)
+                    .with_details(details)
+                    .into());
             }
-            for server in &host.servers {
-                if let Some(group) = &server.group
-                    && ServerGroup::parse_group(group).is_none()
-                {
-                    bail!(
-                        "Invalid server group '{}' for server '{}' on host '{}'",
-                        group,
-                        server.name,
-                        host_label
-                    );
-                }
+        }
+        Ok(())
+    }
+
+    /// Validates a host and its servers.
+    fn validate_host(
+        &self,
+        host: &HostSetup,
+        registry: &WildFlyImageRegistry,
+    ) -> anyhow::Result<()> {
+        let host_label = host.name.as_deref().unwrap_or("<unnamed>");
+        if let Some(v) = &host.version {
+            resolve_version(v, registry).with_context(|| {
+                format!("Unknown WildFly version '{}' for host '{}'", v, host_label)
+            })?;
+        }
+        host.bootstrap_operations()
+            .with_context(|| format!("Invalid operations for host '{}'", host_label))?;
+        if !host.deployments.is_empty() && !host.domain_controller {
+            bail!(
+                "Deployments are only supported for the domain controller, not for host '{}'",
+                host_label
+            );
+        }
+        if host.config.is_some() && !host.domain_controller {
+            bail!(
+                "Configurations are only supported for the domain controller, not for host '{}'. Use 'host-config' instead.",
+                host_label
+            );
+        }
+        if host.host_config.is_some() && host.domain_controller {
+            bail!(
+                "Host configurations are only supported for host controllers, not for host '{}'",
+                host_label
+            );
+        }
+        if let Some(config) = host.server_config(self.host_dir(host)).transpose() {
+            let config = config
+                .with_context(|| format!("Invalid configuration for host '{}'", host_label))?;
+            if let Ok(wildfly_image) =
+                resolve_version(host.effective_version(&self.version), registry)
+            {
+                config
+                    .validate(&wildfly_image, host.server_type())
+                    .with_context(|| format!("Invalid configuration for host '{}'", host_label))?;
+            }
+        }
+        host.run_options(self.host_dir(host), &self.deployment_groups())
+            .with_context(|| format!("Invalid options for host '{}'", host_label))?;
+        let declared = self.declared_groups();
+        let mut seen = HashSet::new();
+        for server in &host.servers {
+            if !seen.insert(&server.name) {
+                bail!(
+                    "Duplicate server name '{}' on host '{}'",
+                    server.name,
+                    host_label
+                );
+            }
+            if let Some(group) = &server.group
+                && ServerGroup::parse_declared_group(group, &declared).is_none()
+            {
+                bail!(
+                    "Invalid server group '{}' for server '{}' on host '{}': not declared in 'server-groups'",
+                    group,
+                    server.name,
+                    host_label
+                );
+            }
+        }
+        let servers = host.to_servers(&declared);
+        for (index, server) in servers.iter().enumerate() {
+            if let Some(other) = servers[..index].iter().find(|s| s.offset == server.offset) {
+                bail!(
+                    "Port offset collision on host '{}': servers '{}' and '{}' both use offset {}",
+                    host_label,
+                    other.name,
+                    server.name,
+                    server.offset
+                );
             }
         }
         Ok(())
     }
 
+    /// Returns warnings about host controllers which are considerably older than
+    /// the domain controller.
+    pub fn warnings(&self, registry: &WildFlyImageRegistry) -> Vec<String> {
+        self.mixed_domain(registry)
+            .into_iter()
+            .filter(|(check, _, _)| *check == MixedDomain::Untested)
+            .map(|(_, message, _)| message)
+            .collect()
+    }
+
+    /// Compares the versions of the host controllers with the version of the domain
+    /// controller. Returns the unsupported and untested combinations with their message
+    /// and details.
+    fn mixed_domain(
+        &self,
+        registry: &WildFlyImageRegistry,
+    ) -> Vec<(MixedDomain, String, serde_json::Value)> {
+        let Some(dc) = self.hosts.iter().find(|h| h.domain_controller) else {
+            return vec![];
+        };
+        let Ok(dc_image) = resolve_version(dc.effective_version(&self.version), registry) else {
+            return vec![];
+        };
+        self.hc_hosts()
+            .into_iter()
+            .filter_map(|host| {
+                let hc_image =
+                    resolve_version(host.effective_version(&self.version), registry).ok()?;
+                let check = MixedDomain::check(&dc_image, &hc_image);
+                let host_label = host.name.as_deref().unwrap_or("<unnamed>");
+                check
+                    .message(host_label, &dc_image, &hc_image)
+                    .map(|message| {
+                        let details = check.details(host_label, &dc_image, &hc_image);
+                        (check, message, details)
+                    })
+            })
+            .collect()
+    }
+
+    /// Returns the names of the server groups declared in `server-groups`.
+    pub fn declared_groups(&self) -> Vec<&str> {
+        self.server_groups.iter().map(|g| g.name.as_str()).collect()
+    }
+
+    /// Returns the server groups deployments are assigned to: the groups of all servers
+    /// in the topology or `main-server-group` if there are no servers.
+    pub fn deployment_groups(&self) -> Vec<String> {
+        let declared = self.declared_groups();
+        let servers = self
+            .hosts
+            .iter()
+            .flat_map(|host| host.to_servers(&declared))
+            .collect::<Vec<_>>();
+        deployment_server_groups(&servers)
+    }
+
+    /// Returns the operations for the declared server groups, the domain-wide system
+    /// properties and the domain-wide operations in normalized CLI syntax.
+    pub fn domain_operations(&self) -> anyhow::Result<Vec<String>> {
+        let mut operations = vec![];
+        for group in &self.server_groups {
+            operations.extend(group.operations());
+        }
+        for (key, value) in &self.system_properties {
+            operations.push(format!(
+                "/system-property={}:add(value={})",
+                key,
+                quote(value)
+            ));
+        }
+        operations.extend(self.operations.iter().cloned());
+        normalize_operations(&operations)
+    }
+
+    /// Returns the domain-wide `operations` in normalized CLI syntax.
+    pub fn normalized_operations(&self) -> anyhow::Result<Vec<String>> {
+        normalize_operations(&self.operations)
+    }
+
+    /// Returns the directory relative paths of `host` are resolved against: the
+    /// directory of the file which defines the host.
+    pub fn host_dir<'a>(&'a self, host: &'a HostSetup) -> &'a Path {
+        host.origin
+            .as_ref()
+            .and_then(|origin| origin.path.parent())
+            .unwrap_or(&self.base_dir)
+    }
+
     pub fn dc_host(&self) -> &HostSetup {
         self.hosts
             .iter()
@@ -110,6 +521,301 @@ impl HostSetup {
     pub fn effective_version<'a>(&'a self, default: &'a str) -> &'a str {
         self.version.as_deref().unwrap_or(default)
     }
+
+    /// Returns the bootstrap operations in normalized CLI syntax.
+    pub fn bootstrap_operations(&self) -> anyhow::Result<Vec<String>> {
+        normalize_operations(&self.operations)
+    }
+
+    /// Returns the servers of this host with the port offsets applied.
+    pub fn to_servers(&self, declared: &[&str]) -> Vec<Server> {
+        apply_offsets(
+            self.servers
+                .iter()
+                .map(|server| server.to_server(declared))
+                .collect(),
+            DEFAULT_SERVER_OFFSET,
+        )
+    }
+
+    pub fn server_type(&self) -> ServerType {
+        if self.domain_controller {
+            ServerType::DomainController
+        } else {
+            ServerType::HostController
+        }
+    }
+
+    /// Returns the configuration of this host: `config` for the domain controller and
+    /// `host-config` for host controllers.
+    pub fn server_config(&self, base_dir: &Path) -> anyhow::Result<Option<ServerConfig>> {
+        let config = if self.domain_controller {
+            &self.config
+        } else {
+            &self.host_config
+        };
+        config
+            .as_deref()
+            .map(|config| ServerConfig::parse(config, base_dir))
+            .transpose()
+    }
+
+    /// Returns the bootstrap operations, deployments, volumes, configuration, environment
+    /// variables, system properties and debug mode of this host and its servers. The debug
+    /// ports of the servers are allocated when the host is started. Relative paths are resolved
+    /// against `base_dir`, deployments are assigned to `deployment_groups`.
+    pub fn run_options(
+        &self,
+        base_dir: &Path,
+        deployment_groups: &[String],
+    ) -> anyhow::Result<RunOptions> {
+        let server_type = self.server_type();
+        let mut operations = self.bootstrap_operations()?;
+        let mut mounts = self
+            .deployments
+            .iter()
+            .map(|path| Mount::deployment(path, base_dir, server_type))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        operations.extend(deployment_operations(
+            &mounts,
+            server_type,
+            deployment_groups,
+        ));
+        for volume in &self.volumes {
+            mounts.push(Mount::parse_volume(volume, base_dir)?);
+        }
+        let config = self.server_config(base_dir)?;
+        mounts.extend(config.as_ref().and_then(ServerConfig::mount));
+        let mut env = match &self.env_file {
+            Some(env_file) => read_env_file(&base_dir.join(env_file))?,
+            None => vec![],
+        };
+        env.extend(self.env.clone());
+        if let Some(java_opts) = &self.java_opts {
+            add_java_opts(&mut env, java_opts)?;
+        }
+        Ok(RunOptions {
+            operations,
+            mounts,
+            config,
+            host_config: None,
+            env,
+            system_properties: self.system_properties.clone().into_iter().collect(),
+            debug: self.debug,
+            server_debug: self
+                .servers
+                .iter()
+                .filter_map(|server| {
+                    server.debug.map(|mode| ServerDebug {
+                        server: server.name.clone(),
+                        mode,
+                        port: 0,
+                    })
+                })
+                .collect(),
+            keep: false,
+        })
+    }
+}
+
+impl ServerGroupSetup {
+    /// Returns the operations to add this server group and its JVM settings.
+    pub fn operations(&self) -> Vec<String> {
+        let mut operations = vec![format!(
+            "/server-group={}:add(profile={},socket-binding-group={})",
+            self.name, self.profile, self.socket_binding_group
+        )];
+        operations.extend(self.jvm_operation());
+        operations
+    }
+
+    /// Returns the operation to add the JVM of this server group (if any).
+    pub fn jvm_operation(&self) -> Option<String> {
+        let jvm = self.jvm.as_ref()?;
+        let mut attributes = vec![];
+        if let Some(heap_size) = &jvm.heap_size {
+            attributes.push(format!("heap-size={}", heap_size));
+        }
+        if let Some(max_heap_size) = &jvm.max_heap_size {
+            attributes.push(format!("max-heap-size={}", max_heap_size));
+        }
+        if !jvm.jvm_options.is_empty() {
+            attributes.push(format!("jvm-options={}", jvm.options_value()));
+        }
+        Some(format!(
+            "/server-group={}/jvm={}:add({})",
+            self.name,
+            jvm.name(),
+            attributes.join(",")
+        ))
+    }
+}
+
+impl JvmSetup {
+    /// Returns the name of the JVM: `default` if no name was given.
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or("default")
+    }
+
+    /// Returns the JVM options as list value in CLI syntax.
+    pub fn options_value(&self) -> String {
+        let options: Vec<String> = self.jvm_options.iter().map(|o| quote(o)).collect();
+        format!("[{}]", options.join(","))
+    }
+}
+
+/// Parses the operations and returns them in normalized CLI syntax.
+fn normalize_operations(operations: &[String]) -> anyhow::Result<Vec<String>> {
+    let mut commands = vec![];
+    for operation in operations {
+        commands.extend(
+            parse_commands(operation)
+                .map_err(|e| anyhow::anyhow!("{}", e.display_with(operation)))?,
+        );
+    }
+    validate_batches(&commands).map_err(|e| anyhow::anyhow!("{}", e))?;
+    Ok(commands.iter().map(ToString::to_string).collect())
+}
+
+pub(super) fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl Display for Origin {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.path.display(), self.line)
+    }
+}
+
+impl TopologyFragment {
+    /// Appends the lists of `other` and overrides system properties with the ones of `other`.
+    fn merge(&mut self, other: TopologyFragment) {
+        self.server_groups.extend(other.server_groups);
+        self.system_properties.extend(other.system_properties);
+        self.operations.extend(other.operations);
+        self.hosts.extend(other.hosts);
+    }
+}
+
+fn read_template(path: &Path, variables: &BTreeMap<String, String>) -> anyhow::Result<String> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read topology file: {}", path.display()))?;
+    interpolate(&content, variables, path)
+}
+
+/// Records the file and line of the server groups and hosts parsed from `content`.
+fn set_origins(
+    content: &str,
+    path: &Path,
+    server_groups: &mut [ServerGroupSetup],
+    hosts: &mut [HostSetup],
+) -> anyhow::Result<()> {
+    let locations: Locations = serde_saphyr::from_str(content)
+        .with_context(|| format!("Failed to parse topology file: {}", path.display()))?;
+    let origin = |location: &Spanned<IgnoredAny>| {
+        Some(Origin {
+            path: path.to_path_buf(),
+            line: location.referenced.line(),
+        })
+    };
+    for (group, location) in server_groups.iter_mut().zip(&locations.server_groups) {
+        group.origin = origin(location);
+    }
+    for (host, location) in hosts.iter_mut().zip(&locations.hosts) {
+        host.origin = origin(location);
+    }
+    Ok(())
+}
+
+/// Adds the origin of a host or server group to the errors of `result`.
+fn located<T>(result: anyhow::Result<T>, origin: &Option<Origin>) -> anyhow::Result<T> {
+    match origin {
+        Some(origin) => result.with_context(|| origin.to_string()),
+        None => result,
+    }
+}
+
+fn canonical(path: &Path) -> anyhow::Result<PathBuf> {
+    path.canonicalize()
+        .with_context(|| format!("Failed to read topology file: {}", path.display()))
+}
+
+/// Reads the fragment at `path` merged with its own includes. `stack` holds the
+/// files currently being read to detect circular includes.
+fn read_fragment(
+    path: &Path,
+    variables: &BTreeMap<String, String>,
+    stack: &mut Vec<PathBuf>,
+) -> anyhow::Result<TopologyFragment> {
+    let canonical = canonical(path)?;
+    if stack.contains(&canonical) {
+        bail!("Circular include of topology file: {}", path.display());
+    }
+    let content = read_template(path, variables)?;
+    let mut fragment: TopologyFragment = serde_saphyr::from_str(&content)
+        .with_context(|| format!("Failed to parse included file: {}", path.display()))?;
+    set_origins(
+        &content,
+        path,
+        &mut fragment.server_groups,
+        &mut fragment.hosts,
+    )?;
+    let base_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    stack.push(canonical);
+    let mut merged = TopologyFragment::default();
+    for include in std::mem::take(&mut fragment.include) {
+        merged.merge(read_fragment(&base_dir.join(include), variables, stack)?);
+    }
+    stack.pop();
+    merged.merge(fragment);
+    Ok(merged)
+}
+
+/// Replaces hosts with `replicas` by the given number of copies. Named hosts
+/// get the suffixes `-1` to `-<replicas>`.
+fn expand_replicas(hosts: Vec<HostSetup>) -> anyhow::Result<Vec<HostSetup>> {
+    let mut expanded = vec![];
+    for mut host in hosts {
+        let Some(replicas) = host.replicas.take() else {
+            expanded.push(host);
+            continue;
+        };
+        let host_label = host.name.as_deref().unwrap_or("<unnamed>");
+        if replicas == 0 {
+            bail!(
+                "Invalid replicas for host '{}': must be at least 1",
+                host_label
+            );
+        }
+        if host.domain_controller && replicas > 1 {
+            bail!(
+                "Invalid replicas for host '{}': the domain controller can't be replicated",
+                host_label
+            );
+        }
+        for index in 1..=replicas {
+            let mut replica = host.clone();
+            replica.name = host.name.as_ref().map(|name| format!("{}-{}", name, index));
+            expanded.push(replica);
+        }
+    }
+    Ok(expanded)
+}
+
+/// Returns the topology name and setup if `setup_arg` is a topology file or
+/// `setup_arg` as topology name otherwise.
+pub fn resolve_topology(
+    setup_arg: &str,
+    variables: &BTreeMap<String, String>,
+    registry: &WildFlyImageRegistry,
+) -> anyhow::Result<(String, Option<TopologySetup>)> {
+    let path = Path::new(setup_arg);
+    if path.exists() {
+        let setup = TopologySetup::load(path, variables, registry)?;
+        Ok((setup.name.clone(), Some(setup)))
+    } else {
+        Ok((setup_arg.to_string(), None))
+    }
 }
 
 fn resolve_version(version: &str, registry: &WildFlyImageRegistry) -> anyhow::Result<WildFlyImage> {
@@ -156,14 +862,67 @@ where
     deserialize_version(deserializer).map(Some)
 }
 
+fn version_schema(_: &mut SchemaGenerator) -> Schema {
+    json_schema!({ "type": ["string", "number"] })
+}
+
+fn optional_version_schema(_: &mut SchemaGenerator) -> Schema {
+    json_schema!({ "type": ["string", "number", "null"] })
+}
+
+fn debug_schema(_: &mut SchemaGenerator) -> Schema {
+    json_schema!({ "anyOf": [{ "type": "boolean" }, { "const": "suspend" }, { "type": "null" }] })
+}
+
+struct DebugVisitor;
+
+impl<'de> de::Visitor<'de> for DebugVisitor {
+    type Value = Option<DebugMode>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("true, false or 'suspend'")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Option<DebugMode>, E> {
+        Ok(v.then_some(DebugMode::Run))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Option<DebugMode>, E> {
+        match v {
+            "suspend" => Ok(Some(DebugMode::Suspend)),
+            _ => Err(E::invalid_value(de::Unexpected::Str(v), &self)),
+        }
+    }
+}
+
+fn deserialize_debug<'de, D>(deserializer: D) -> Result<Option<DebugMode>, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    deserializer.deserialize_any(DebugVisitor)
+}
+
+fn serialize_debug<S: Serializer>(
+    debug: &Option<DebugMode>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match debug {
+        Some(DebugMode::Suspend) => serializer.serialize_str("suspend"),
+        Some(DebugMode::Run) => serializer.serialize_bool(true),
+        None => serializer.serialize_bool(false),
+    }
+}
+
 impl ServerSetup {
-    pub fn to_server(&self) -> Server {
+    /// Converts this setup to a [`Server`]. Groups which are neither built-in nor
+    /// `declared` fall back to `main-server-group` (see [`TopologySetup::validate`]).
+    pub fn to_server(&self, declared: &[&str]) -> Server {
         Server {
             name: self.name.clone(),
             server_group: self
                 .group
                 .as_deref()
-                .and_then(ServerGroup::parse_group)
+                .and_then(|group| ServerGroup::parse_declared_group(group, declared))
                 .unwrap_or(ServerGroup::MainServerGroup),
             offset: self.offset,
             autostart: self.auto_start,
@@ -316,11 +1075,11 @@ hosts:
         let servers = &setup.hosts[1].servers;
         assert!(servers[0].group.is_none());
         assert_eq!(
-            servers[0].to_server().server_group,
+            servers[0].to_server(&[]).server_group,
             ServerGroup::MainServerGroup
         );
         assert_eq!(
-            servers[1].to_server().server_group,
+            servers[1].to_server(&[]).server_group,
             ServerGroup::OtherServerGroup
         );
     }
@@ -412,6 +1171,147 @@ hosts:
         );
     }
 
+    #[test]
+    fn server_groups() {
+        let yaml = r#"
+name: test-topology
+version: 34
+server-groups:
+  - name: app-group
+    profile: full
+    socket-binding-group: full-sockets
+    jvm:
+      heap-size: 64m
+      max-heap-size: 512m
+      jvm-options:
+        - -Dfoo=bar
+  - name: batch-group
+    profile: default
+    socket-binding-group: standard-sockets
+system-properties:
+  env: "test run"
+operations:
+  - /profile=full/subsystem=logging/root-logger=ROOT:write-attribute(name=level,value=DEBUG)
+hosts:
+  - name: dc
+    domain-controller: true
+  - name: host1
+    servers:
+      - name: server-one
+        group: app-group
+      - name: server-two
+        group: osg
+      - name: server-three
+"#;
+        let setup: TopologySetup = serde_saphyr::from_str(yaml).unwrap();
+        assert!(setup.validate(&test_registry()).is_ok());
+        assert_eq!(
+            setup.domain_operations().unwrap(),
+            vec![
+                "/server-group=app-group:add(profile=full,socket-binding-group=full-sockets)",
+                "/server-group=app-group/jvm=default:add(heap-size=64m,max-heap-size=512m,jvm-options=[\"-Dfoo=bar\"])",
+                "/server-group=batch-group:add(profile=default,socket-binding-group=standard-sockets)",
+                "/system-property=env:add(value=\"test run\")",
+                "/profile=full/subsystem=logging/root-logger=ROOT:write-attribute(name=level,value=DEBUG)",
+            ]
+        );
+        let declared = setup.declared_groups();
+        let groups: Vec<ServerGroup> = setup.hosts[1]
+            .servers
+            .iter()
+            .map(|s| s.to_server(&declared).server_group)
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                ServerGroup::Custom("app-group".to_string()),
+                ServerGroup::OtherServerGroup,
+                ServerGroup::MainServerGroup
+            ]
+        );
+        assert_eq!(
+            setup.deployment_groups(),
+            vec!["app-group", "other-server-group", "main-server-group"]
+        );
+    }
+
+    #[test]
+    fn validate_undeclared_server_group() {
+        let yaml = r#"
+name: test-topology
+version: 34
+server-groups:
+  - name: app-group
+    profile: full
+    socket-binding-group: full-sockets
+hosts:
+  - name: dc
+    domain-controller: true
+    servers:
+      - name: server-one
+        group: batch-group
+"#;
+        let setup: TopologySetup = serde_saphyr::from_str(yaml).unwrap();
+        let error = setup.validate(&test_registry()).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("not declared in 'server-groups'")
+        );
+    }
+
+    #[test]
+    fn validate_server_group_names() {
+        let yaml = r#"
+name: test-topology
+version: 34
+server-groups:
+  - name: main-server-group
+    profile: full
+    socket-binding-group: full-sockets
+hosts:
+  - name: dc
+    domain-controller: true
+"#;
+        let setup: TopologySetup = serde_saphyr::from_str(yaml).unwrap();
+        let error = setup.validate(&test_registry()).unwrap_err();
+        assert!(error.to_string().contains("already defined"));
+
+        let yaml = r#"
+name: test-topology
+version: 34
+server-groups:
+  - name: app-group
+    profile: full
+    socket-binding-group: full-sockets
+  - name: app-group
+    profile: default
+    socket-binding-group: standard-sockets
+hosts:
+  - name: dc
+    domain-controller: true
+"#;
+        let setup: TopologySetup = serde_saphyr::from_str(yaml).unwrap();
+        let error = setup.validate(&test_registry()).unwrap_err();
+        assert!(error.to_string().contains("Duplicate server group"));
+    }
+
+    #[test]
+    fn validate_invalid_domain_operation() {
+        let yaml = r#"
+name: test-topology
+version: 34
+operations:
+  - batch
+hosts:
+  - name: dc
+    domain-controller: true
+"#;
+        let setup: TopologySetup = serde_saphyr::from_str(yaml).unwrap();
+        let error = setup.validate(&test_registry()).unwrap_err();
+        assert!(format!("{:#}", error).contains("Invalid domain operations"));
+    }
+
     #[test]
     fn validate_unnamed_no_duplicate_error() {
         let yaml = r#"
@@ -430,6 +1330,266 @@ hosts:
         assert!(setup.validate(&test_registry()).is_ok());
     }
 
+    #[test]
+    fn validate_operations() {
+        let yaml = r#"
+name: test-topology
+version: 34
+hosts:
+  - name: dc
+    domain-controller: true
+    operations:
+      - batch
+      - /system-property=foo:add(value=bar)
+      - /profile=full/subsystem=logging/root-logger=ROOT:write-attribute(name=level, value=DEBUG)
+      - run-batch
+"#;
+        let setup: TopologySetup = serde_saphyr::from_str(yaml).unwrap();
+        assert!(setup.validate(&test_registry()).is_ok());
+        assert_eq!(
+            setup.dc_host().bootstrap_operations().unwrap(),
+            vec![
+                "batch",
+                "/system-property=foo:add(value=bar)",
+                "/profile=full/subsystem=logging/root-logger=ROOT:write-attribute(name=level,value=DEBUG)",
+                "run-batch",
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_invalid_operation() {
+        let yaml = r#"
+name: test-topology
+version: 34
+hosts:
+  - name: dc
+    domain-controller: true
+  - name: host1
+    operations:
+      - /system-property=foo:add(value=bar
+"#;
+        let setup: TopologySetup = serde_saphyr::from_str(yaml).unwrap();
+        let error = setup.validate(&test_registry()).unwrap_err();
+        assert!(format!("{:#}", error).contains("Invalid operations for host 'host1'"));
+        assert!(format!("{:#}", error).contains("at position 34"));
+    }
+
+    #[test]
+    fn validate_mounts() {
+        let yaml = r#"
+name: test-topology
+version: 34
+hosts:
+  - name: dc
+    domain-controller: true
+    volumes:
+      - data:/opt/data
+  - name: host1
+    volumes:
+      - invalid
+"#;
+        let setup: TopologySetup = serde_saphyr::from_str(yaml).unwrap();
+        let error = setup.validate(&test_registry()).unwrap_err();
+        assert!(format!("{:#}", error).contains("Invalid options for host 'host1'"));
+        let options = setup
+            .dc_host()
+            .run_options(&setup.base_dir, &setup.deployment_groups())
+            .unwrap();
+        assert_eq!(options.mounts[0].run_arg(), "data:/opt/data");
+    }
+
+    #[test]
+    fn validate_config() {
+        let yaml = r#"
+name: test-topology
+version: 34
+hosts:
+  - name: dc
+    domain-controller: true
+    config: domain.xml
+  - name: host1
+    host-config: domain.xml
+"#;
+        let setup: TopologySetup = serde_saphyr::from_str(yaml).unwrap();
+        let error = setup.validate(&test_registry()).unwrap_err();
+        assert!(format!("{:#}", error).contains("Invalid configuration for host 'host1'"));
+        assert!(format!("{:#}", error).contains("Unknown host configuration 'domain.xml'"));
+        let options = setup
+            .dc_host()
+            .run_options(&setup.base_dir, &setup.deployment_groups())
+            .unwrap();
+        assert_eq!(
+            options.parameters(ServerType::DomainController),
+            vec!["-c", "domain.xml"]
+        );
+        assert!(options.mounts.is_empty());
+    }
+
+    #[test]
+    fn validate_host_config() {
+        let yaml = r#"
+name: test-topology
+version: 34
+hosts:
+  - name: dc
+    domain-controller: true
+  - name: host1
+    host-config: host-secondary-no-auth.xml
+"#;
+        let setup: TopologySetup = serde_saphyr::from_str(yaml).unwrap();
+        assert!(setup.validate(&test_registry()).is_ok());
+        let options = setup.hosts[1]
+            .run_options(&setup.base_dir, &setup.deployment_groups())
+            .unwrap();
+        assert_eq!(
+            options.parameters(ServerType::HostController),
+            vec!["--host-config", "host-secondary-no-auth.xml"]
+        );
+
+        let yaml = r#"
+name: test-topology
+version: 34
+hosts:
+  - name: dc
+    domain-controller: true
+  - name: host1
+    config: domain.xml
+"#;
+        let setup: TopologySetup = serde_saphyr::from_str(yaml).unwrap();
+        let error = setup.validate(&test_registry()).unwrap_err();
+        assert!(format!("{:#}", error).contains("Use 'host-config' instead"));
+    }
+
+    #[test]
+    fn environment() {
+        let yaml = r#"
+name: test-topology
+version: 34
+hosts:
+  - name: dc
+    domain-controller: true
+    env:
+      FOO: bar
+    java-opts: -Xmx1g
+    system-properties:
+      foo: bar
+  - name: host1
+    env:
+      JAVA_OPTS: -Xmx512m
+    java-opts: -Xmx1g
+"#;
+        let setup: TopologySetup = serde_saphyr::from_str(yaml).unwrap();
+        let options = setup
+            .dc_host()
+            .run_options(&setup.base_dir, &setup.deployment_groups())
+            .unwrap();
+        assert_eq!(
+            options.env,
+            vec![
+                ("FOO".to_string(), "bar".to_string()),
+                ("JAVA_OPTS".to_string(), "-Xmx1g".to_string())
+            ]
+        );
+        assert_eq!(
+            options.parameters(ServerType::DomainController),
+            vec!["-Dfoo=bar"]
+        );
+        let error = setup.validate(&test_registry()).unwrap_err();
+        assert!(format!("{:#}", error).contains("Invalid options for host 'host1'"));
+    }
+
+    #[test]
+    fn debug() {
+        let yaml = r#"
+name: test-topology
+version: 34
+hosts:
+  - name: dc
+    domain-controller: true
+    debug: true
+  - name: host1
+    debug: suspend
+  - name: host2
+    debug: false
+  - name: host3
+"#;
+        let setup: TopologySetup = serde_saphyr::from_str(yaml).unwrap();
+        let debug = setup.hosts.iter().map(|h| h.debug).collect::<Vec<_>>();
+        assert_eq!(
+            debug,
+            vec![Some(DebugMode::Run), Some(DebugMode::Suspend), None, None]
+        );
+
+        let yaml = r#"
+name: test-topology
+version: 34
+hosts:
+  - name: dc
+    domain-controller: true
+    debug: always
+"#;
+        assert!(serde_saphyr::from_str::<TopologySetup>(yaml).is_err());
+    }
+
+    #[test]
+    fn server_debug() {
+        let yaml = r#"
+name: test-topology
+version: 34
+hosts:
+  - name: dc
+    domain-controller: true
+  - name: host1
+    servers:
+      - name: server-one
+        debug: true
+      - name: server-two
+      - name: server-three
+        debug: suspend
+"#;
+        let setup: TopologySetup = serde_saphyr::from_str(yaml).unwrap();
+        let options = setup.hosts[1]
+            .run_options(&setup.base_dir, &setup.deployment_groups())
+            .unwrap();
+        assert_eq!(
+            options.server_debug,
+            vec![
+                ServerDebug {
+                    server: "server-one".to_string(),
+                    mode: DebugMode::Run,
+                    port: 0,
+                },
+                ServerDebug {
+                    server: "server-three".to_string(),
+                    mode: DebugMode::Suspend,
+                    port: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_deployments_on_host_controller() {
+        let yaml = r#"
+name: test-topology
+version: 34
+hosts:
+  - name: dc
+    domain-controller: true
+  - name: host1
+    deployments:
+      - app.war
+"#;
+        let setup: TopologySetup = serde_saphyr::from_str(yaml).unwrap();
+        let error = setup.validate(&test_registry()).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("only supported for the domain controller")
+        );
+    }
+
     #[test]
     fn deserialize_host_dev_version_override() {
         let yaml = r#"
@@ -438,6 +1598,7 @@ version: 34
 hosts:
   - name: dc
     domain-controller: true
+    version: dev
   - name: host1
     version: dev
 "#;
@@ -470,8 +1631,9 @@ hosts:
             group: Some("main-server-group".to_string()),
             offset: 100,
             auto_start: true,
+            debug: None,
         };
-        let server = setup.to_server();
+        let server = setup.to_server(&[]);
         assert_eq!(server.name, "server-one");
         assert_eq!(server.server_group, ServerGroup::MainServerGroup);
         assert_eq!(server.offset, 100);
@@ -485,8 +1647,9 @@ hosts:
             group: Some("other-server-group".to_string()),
             offset: 200,
             auto_start: false,
+            debug: None,
         };
-        let server = setup.to_server();
+        let server = setup.to_server(&[]);
         assert_eq!(server.server_group, ServerGroup::OtherServerGroup);
         assert_eq!(server.offset, 200);
         assert!(!server.autostart);
@@ -499,8 +1662,312 @@ hosts:
             group: None,
             offset: 0,
             auto_start: false,
+            debug: None,
         };
-        let server = setup.to_server();
+        let server = setup.to_server(&[]);
         assert_eq!(server.server_group, ServerGroup::MainServerGroup);
     }
+
+    #[test]
+    fn read_variables_includes_and_replicas() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("shared")).unwrap();
+        std::fs::write(
+            dir.path().join("shared/groups.yml"),
+            r#"
+server-groups:
+  - name: app-group
+    profile: full
+    socket-binding-group: full-sockets
+system-properties:
+  env: shared
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("shared/common.yml"),
+            r#"
+include:
+  - groups.yml
+hosts:
+  - name: dc
+    domain-controller: true
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("topology.yml"),
+            r#"
+name: ${name:-templated}
+version: ${version}
+include:
+  - shared/common.yml
+system-properties:
+  env: ${env:-dev}
+hosts:
+  - name: worker
+    replicas: 3
+    servers:
+      - name: server-one
+        group: app-group
+  - replicas: 2
+"#,
+        )
+        .unwrap();
+        let variables = BTreeMap::from([("version".to_string(), "38".to_string())]);
+        let setup = TopologySetup::read(&dir.path().join("topology.yml"), &variables).unwrap();
+        assert_eq!(setup.name, "templated");
+        assert_eq!(setup.version, "38");
+        assert_eq!(setup.declared_groups(), vec!["app-group"]);
+        assert_eq!(setup.system_properties["env"], "dev");
+        let names: Vec<Option<&str>> = setup.hosts.iter().map(|h| h.name.as_deref()).collect();
+        assert_eq!(
+            names,
+            vec![
+                Some("dc"),
+                Some("worker-1"),
+                Some("worker-2"),
+                Some("worker-3"),
+                None,
+                None
+            ]
+        );
+        assert!(setup.hosts.iter().all(|h| h.replicas.is_none()));
+        assert_eq!(setup.hosts[3].servers[0].name, "server-one");
+        setup.validate(&test_registry()).unwrap();
+
+        let rendered = serde_saphyr::to_string(&setup).unwrap();
+        assert!(!rendered.contains("include"));
+        assert!(!rendered.contains("replicas"));
+        let reparsed: TopologySetup = serde_saphyr::from_str(&rendered).unwrap();
+        assert_eq!(reparsed.hosts.len(), 6);
+        assert_eq!(reparsed.hosts[2].name.as_deref(), Some("worker-2"));
+        assert_eq!(reparsed.server_groups.len(), 1);
+    }
+
+    #[test]
+    fn read_fragment_paths_and_origins() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("shared/data")).unwrap();
+        std::fs::write(dir.path().join("shared/app.war"), "").unwrap();
+        std::fs::write(
+            dir.path().join("shared/dc.yml"),
+            r#"
+hosts:
+  - name: dc
+    domain-controller: true
+    deployments: [app.war]
+    volumes: ["./data:/opt/data"]
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("shared/hosts.yml"),
+            r#"
+hosts:
+  - name: host1
+  - name: host2
+    servers:
+      - name: server-one
+      - name: server-one
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("topology.yml"),
+            "name: test\nversion: 34\ninclude: [shared/dc.yml]\n",
+        )
+        .unwrap();
+        let setup =
+            TopologySetup::read(&dir.path().join("topology.yml"), &BTreeMap::new()).unwrap();
+        setup.validate(&test_registry()).unwrap();
+        assert_eq!(
+            setup.dc_host().origin,
+            Some(Origin {
+                path: dir.path().join("shared/dc.yml"),
+                line: 3
+            })
+        );
+        assert_eq!(setup.host_dir(setup.dc_host()), dir.path().join("shared"));
+        let options = setup
+            .dc_host()
+            .run_options(setup.host_dir(setup.dc_host()), &setup.deployment_groups())
+            .unwrap();
+        assert_eq!(options.mounts.len(), 2);
+
+        std::fs::write(
+            dir.path().join("topology.yml"),
+            "name: test\nversion: 34\ninclude: [shared/dc.yml, shared/hosts.yml]\n",
+        )
+        .unwrap();
+        let setup =
+            TopologySetup::read(&dir.path().join("topology.yml"), &BTreeMap::new()).unwrap();
+        let error = setup.validate(&test_registry()).err().unwrap();
+        assert!(
+            format!("{:#}", error).contains("hosts.yml:4: Duplicate server name 'server-one'"),
+            "{:#}",
+            error
+        );
+    }
+
+    #[test]
+    fn read_circular_include() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.yml"), "include: [b.yml]\n").unwrap();
+        std::fs::write(dir.path().join("b.yml"), "include: [a.yml]\n").unwrap();
+        std::fs::write(
+            dir.path().join("topology.yml"),
+            "name: test\nversion: 34\ninclude: [a.yml]\n",
+        )
+        .unwrap();
+        let error = TopologySetup::read(&dir.path().join("topology.yml"), &BTreeMap::new())
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("Circular include"));
+    }
+
+    #[test]
+    fn read_unterminated_variable_in_include() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("hosts.yml"),
+            "hosts:\n  - name: dc\n    domain-controller: true\n    version: ${wado_test_version\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("topology.yml"),
+            "name: test\nversion: 34\ninclude: [hosts.yml]\n",
+        )
+        .unwrap();
+        let error = TopologySetup::read(&dir.path().join("topology.yml"), &BTreeMap::new())
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("hosts.yml:4:14"));
+    }
+
+    #[test]
+    fn expand_replicated_domain_controller() {
+        let yaml = r#"
+name: test-topology
+version: 34
+hosts:
+  - name: dc
+    domain-controller: true
+    replicas: 2
+"#;
+        let setup: TopologySetup = serde_saphyr::from_str(yaml).unwrap();
+        assert!(expand_replicas(setup.hosts).is_err());
+    }
+
+    #[test]
+    fn validate_duplicate_server_names() {
+        let yaml = r#"
+name: test-topology
+version: 34
+hosts:
+  - name: dc
+    domain-controller: true
+  - name: host1
+    servers:
+      - name: server-one
+      - name: server-one
+        offset: 100
+"#;
+        let setup: TopologySetup = serde_saphyr::from_str(yaml).unwrap();
+        let error = setup.validate(&test_registry()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Duplicate server name 'server-one' on host 'host1'"
+        );
+    }
+
+    #[test]
+    fn validate_offset_collision() {
+        let yaml = r#"
+name: test-topology
+version: 34
+hosts:
+  - name: dc
+    domain-controller: true
+  - name: host1
+    servers:
+      - name: server-one
+      - name: server-two
+      - name: server-three
+        offset: 100
+"#;
+        let setup: TopologySetup = serde_saphyr::from_str(yaml).unwrap();
+        let error = setup.validate(&test_registry()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Port offset collision on host 'host1': servers 'server-two' and 'server-three' both use offset 100"
+        );
+    }
+
+    #[test]
+    fn deserialize_unknown_field() {
+        let yaml = r#"
+name: test-topology
+version: 34
+hosts:
+  - name: dc
+    domain-controler: true
+"#;
+        assert!(serde_saphyr::from_str::<TopologySetup>(yaml).is_err());
+    }
+
+    #[test]
+    fn schema() {
+        let schema = serde_json::to_value(schemars::schema_for!(TopologySetup)).unwrap();
+        assert_eq!(schema["additionalProperties"], false);
+        let host = &schema["$defs"]["HostSetup"]["properties"];
+        assert!(host.get("domain-controller").is_some());
+        assert!(host.get("replicas").is_some());
+        assert_eq!(
+            host["version"]["type"],
+            serde_json::json!(["string", "number", "null"])
+        );
+        let server = &schema["$defs"]["ServerSetup"]["properties"];
+        assert!(server.get("auto-start").is_some());
+    }
+
+    #[test]
+    fn validate_mixed_versions() {
+        let yaml = r#"
+name: test-topology
+version: 33
+hosts:
+  - name: dc
+    domain-controller: true
+  - name: host1
+    version: 26.1
+  - name: host2
+    version: 34
+"#;
+        let setup: TopologySetup = serde_saphyr::from_str(yaml).unwrap();
+        let registry = test_registry();
+        let error = setup.validate(&registry).unwrap_err();
+        assert_eq!(
+            WadoError::error_code(&error),
+            crate::error::WadoErrorCode::TopologyError
+        );
+        assert!(
+            error
+                .to_string()
+                .contains("Host controller 'host2' runs WildFly 34.0")
+        );
+        let details = error.downcast_ref::<WadoError>().unwrap().details.clone();
+        assert_eq!(
+            details,
+            Some(serde_json::json!({
+                "host": "host2",
+                "dc_version": "33.0",
+                "hc_version": "34.0",
+                "compatibility": "unsupported",
+            }))
+        );
+        let warnings = setup.warnings(&registry);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("'host1'"));
+    }
 }
@@ -0,0 +1,18 @@
+use crate::args::variables_argument;
+use clap::ArgMatches;
+use std::path::PathBuf;
+
+use super::model::TopologySetup;
+
+/// Prints the topology setup with variables interpolated, includes merged and
+/// replicated hosts expanded. The setup is not validated.
+pub fn topology_render(matches: &ArgMatches, json: bool) -> anyhow::Result<()> {
+    let path = matches.get_one::<PathBuf>("setup").unwrap();
+    let setup = TopologySetup::read(path, &variables_argument(matches)?)?;
+    if json {
+        println!("{}", serde_json::to_string(&setup)?);
+    } else {
+        print!("{}", serde_saphyr::to_string(&setup)?);
+    }
+    Ok(())
+}
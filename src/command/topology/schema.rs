@@ -0,0 +1,12 @@
+use schemars::schema_for;
+
+use super::model::TopologySetup;
+
+/// Prints the JSON schema of topology files.
+pub fn topology_schema() -> anyhow::Result<()> {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&schema_for!(TopologySetup))?
+    );
+    Ok(())
+}
@@ -1,3 +1,4 @@
+use crate::args::{variables_argument, wait_timeout_argument};
 use crate::command::lifecycle::{
     apply_ports, print_json_results, run_instances, wait_for_instances,
 };
@@ -6,19 +7,24 @@ use crate::constants::{
     WILDFLY_ADMIN_CONTAINER,
 };
 use crate::container::{
-    add_servers, container_network_cmd, container_run_cmd, create_secret, resolve_start_specs,
-    verify_container_command,
+    RunOptions, add_servers, allocate_server_debug_ports, container_network_cmd, container_run_cmd,
+    create_secret, resolve_start_specs, running_debug_ports, verify_container_command,
 };
+use crate::healthcheck::{ExpectedDomainHost, wait_for_domain};
+use crate::label::Label;
+use crate::progress::{CommandStatus, Progress};
 use crate::wildfly::{
-    AdminImage, DEFAULT_SERVER_OFFSET, DomainController, HostController, Server, ServerType,
-    StartSpec, apply_offsets,
+    AdminImage, DomainController, HostController, ManagementApi, Server, ServerConfig, ServerType,
+    StartSpec, default_host_config,
 };
 use clap::ArgMatches;
+use console::style;
 use futures::executor::block_on;
 use std::collections::BTreeMap;
 use std::path::PathBuf;
+use std::time::Duration;
 use tokio::try_join;
-use wildfly_meta::{WildFlyImageRegistry, parse_wildfly_image};
+use wildfly_meta::{WildFlyImage, WildFlyImageRegistry, parse_wildfly_image};
 
 use super::model::{HostSetup, TopologySetup};
 
@@ -28,8 +34,13 @@ pub fn topology_start(
     json: bool,
 ) -> anyhow::Result<()> {
     let path = matches.get_one::<PathBuf>("setup").unwrap();
-    let setup = TopologySetup::load(path, registry)?;
+    let setup = TopologySetup::load(path, &variables_argument(matches)?, registry)?;
     verify_container_command()?;
+    if !json {
+        for warning in setup.warnings(registry) {
+            println!("{}", style(warning).yellow());
+        }
+    }
 
     let topology_name = setup.name.clone();
 
@@ -41,10 +52,14 @@ pub fn topology_start(
         custom_name: dc_host.name.clone(),
         custom_http: None,
         custom_management: None,
+        custom_offset: None,
+        publish: vec![],
+        debug: dc_host.debug.is_some(),
     };
     let dc_resolved = block_on(resolve_start_specs(
         ServerType::DomainController,
         vec![dc_spec],
+        &[],
         registry,
     ))?;
     let dc_r = &dc_resolved[0];
@@ -52,35 +67,47 @@ pub fn topology_start(
         dc_r.admin_image.clone(),
         dc_r.name.clone(),
         dc_r.ports.clone().unwrap(),
+        dc_r.debug_port,
     );
-    let dc_servers: Vec<Server> = dc_host.servers.iter().map(|s| s.to_server()).collect();
-    let dc_servers = apply_offsets(dc_servers, DEFAULT_SERVER_OFFSET);
+    let mut dc_bootstrap = HostBootstrap::new(dc_host, &setup)?;
+    dc_bootstrap.prepend_domain_operations(&dc.name, setup.domain_operations()?);
+    dc_bootstrap.operations = Some(setup.normalized_operations()?);
 
     let hc_hosts = setup.hc_hosts();
     let hc_specs = build_hc_specs(&hc_hosts, &setup.version, registry)?;
     let hc_resolved = block_on(resolve_start_specs(
         ServerType::HostController,
         hc_specs,
+        dc.debug_port.as_slice(),
         registry,
     ))?;
     let hcs: Vec<HostController> = hc_resolved
         .into_iter()
-        .map(|r| HostController::new(r.admin_image, r.name, dc.name.clone()))
+        .map(|r| HostController::new(r.admin_image, r.name, dc.name.clone(), r.debug_port))
         .collect();
 
-    let hc_server_map = build_server_map(&hc_hosts, &hcs);
+    let mut hc_bootstrap_map = build_bootstrap_map(&hc_hosts, &hcs, &setup)?;
+    let reserved = dc
+        .debug_port
+        .into_iter()
+        .chain(hcs.iter().filter_map(|hc| hc.debug_port))
+        .collect::<Vec<_>>();
+    let mut bootstraps = vec![(&dc.admin_image.wildfly_image, &mut dc_bootstrap)];
+    bootstraps.extend(bootstraps_of(&hcs, &mut hc_bootstrap_map));
+    block_on(allocate_server_debug(bootstraps, &reserved, registry))?;
 
     block_on(start_topology(
         topology_name,
         dc,
-        dc_servers,
+        dc_bootstrap,
         hcs,
-        hc_server_map,
+        hc_bootstrap_map,
+        wait_timeout_argument(matches),
         json,
     ))
 }
 
-fn build_hc_specs(
+pub(super) fn build_hc_specs(
     hc_hosts: &[&HostSetup],
     default_version: &str,
     registry: &WildFlyImageRegistry,
@@ -96,32 +123,105 @@ fn build_hc_specs(
                 custom_name: host.name.clone(),
                 custom_http: None,
                 custom_management: None,
+                custom_offset: None,
+                publish: vec![],
+                debug: host.debug.is_some(),
             })
         })
         .collect()
 }
 
-fn build_server_map(
+/// Servers, operations and mounts used to bootstrap a host.
+#[derive(Clone, Default)]
+pub(super) struct HostBootstrap {
+    pub servers: Vec<Server>,
+    pub run_options: RunOptions,
+    /// The domain-wide operations of the topology (domain controller only), recorded
+    /// in a label so that `topology apply` can detect changes.
+    pub operations: Option<Vec<String>>,
+}
+
+impl HostBootstrap {
+    pub fn new(host: &HostSetup, setup: &TopologySetup) -> anyhow::Result<HostBootstrap> {
+        Ok(HostBootstrap {
+            servers: host.to_servers(&setup.declared_groups()),
+            run_options: host.run_options(setup.host_dir(host), &setup.deployment_groups())?,
+            operations: None,
+        })
+    }
+
+    /// Runs the domain-wide operations before the servers of the domain controller are
+    /// added, so that servers can reference the server groups declared in the topology.
+    fn prepend_domain_operations(&mut self, hostname: &str, domain_operations: Vec<String>) {
+        if domain_operations.is_empty() {
+            return;
+        }
+        let mut operations = domain_operations;
+        operations.extend(self.servers.drain(..).map(|s| s.add_server_op(hostname)));
+        operations.append(&mut self.run_options.operations);
+        self.run_options.operations = operations;
+    }
+}
+
+/// Returns the bootstraps of the given host controllers together with their WildFly images.
+pub(super) fn bootstraps_of<'a>(
+    hcs: &'a [HostController],
+    bootstrap_map: &'a mut BTreeMap<String, HostBootstrap>,
+) -> Vec<(&'a WildFlyImage, &'a mut HostBootstrap)> {
+    bootstrap_map
+        .iter_mut()
+        .filter_map(|(name, bootstrap)| {
+            hcs.iter()
+                .find(|hc| &hc.name == name)
+                .map(|hc| (&hc.admin_image.wildfly_image, bootstrap))
+        })
+        .collect()
+}
+
+/// Allocates the published debug ports of the servers with `debug`, skipping the debug
+/// ports of running containers and `reserved`.
+pub(super) async fn allocate_server_debug(
+    bootstraps: Vec<(&WildFlyImage, &mut HostBootstrap)>,
+    reserved: &[u16],
+    registry: &WildFlyImageRegistry,
+) -> anyhow::Result<()> {
+    if bootstraps
+        .iter()
+        .all(|(_, bootstrap)| bootstrap.run_options.server_debug.is_empty())
+    {
+        return Ok(());
+    }
+    let mut used = running_debug_ports(registry).await?;
+    used.extend(reserved);
+    for (wildfly_image, bootstrap) in bootstraps {
+        allocate_server_debug_ports(
+            &mut bootstrap.run_options.server_debug,
+            wildfly_image,
+            &mut used,
+        );
+    }
+    Ok(())
+}
+
+pub(super) fn build_bootstrap_map(
     hc_hosts: &[&HostSetup],
     hcs: &[HostController],
-) -> BTreeMap<String, Vec<Server>> {
+    setup: &TopologySetup,
+) -> anyhow::Result<BTreeMap<String, HostBootstrap>> {
     let mut map = BTreeMap::new();
     for (host, hc) in hc_hosts.iter().zip(hcs.iter()) {
-        let servers: Vec<Server> = host.servers.iter().map(|s| s.to_server()).collect();
-        let servers = apply_offsets(servers, DEFAULT_SERVER_OFFSET);
-        if !servers.is_empty() {
-            map.insert(hc.name.clone(), servers);
-        }
+        map.insert(hc.name.clone(), HostBootstrap::new(host, setup)?);
     }
-    map
+    Ok(map)
 }
 
 async fn start_topology(
     topology_name: String,
     dc: DomainController,
-    dc_servers: Vec<Server>,
+    dc_bootstrap: HostBootstrap,
     hcs: Vec<HostController>,
-    hc_server_map: BTreeMap<String, Vec<Server>>,
+    hc_bootstrap_map: BTreeMap<String, HostBootstrap>,
+    timeout: Duration,
     json: bool,
 ) -> anyhow::Result<()> {
     try_join!(
@@ -135,24 +235,34 @@ async fn start_topology(
     let dc_port_map: Vec<(String, u16, u16)> =
         vec![(dc.name.clone(), dc.ports.http, dc.ports.management)];
 
+    let dc_config = config_name(&dc_bootstrap.run_options, "domain.xml");
+    let dc_operations =
+        serde_json::to_string(dc_bootstrap.operations.as_deref().unwrap_or_default())?;
     let (dc_results, _instant) = run_instances(
         std::slice::from_ref(&dc),
         |instance| {
             let mut command = container_run_cmd(
                 &instance.name,
                 Some(&instance.ports),
-                vec![],
+                instance.debug_port,
+                &dc_bootstrap.run_options,
                 false,
                 Some(topology),
-                Some("domain.xml"),
+                Some(&dc_config),
             );
             command
                 .arg("--network")
                 .arg(WILDFLY_ADMIN_CONTAINER)
                 .arg("--env")
-                .arg(format!("{}={}", HOSTNAME_VARIABLE, instance.name));
-            let mut command = add_servers(command, &instance.name, dc_servers.clone());
-            command.arg(instance.admin_image.image_name());
+                .arg(format!("{}={}", HOSTNAME_VARIABLE, instance.name))
+                .arg("--label")
+                .arg(Label::Operations.run_arg(&dc_operations));
+            let mut command = add_servers(command, &instance.name, dc_bootstrap.servers.clone());
+            command.arg(instance.admin_image.image_name()).args(
+                dc_bootstrap
+                    .run_options
+                    .parameters(ServerType::DomainController),
+            );
             command
         },
         json,
@@ -160,62 +270,163 @@ async fn start_topology(
     .await?;
 
     let mut dc_status = apply_ports(dc_results, &dc_port_map);
-    wait_for_instances(&mut dc_status, json).await;
+    wait_for_instances(&mut dc_status, timeout, json).await;
+    let dc_healthy = dc_status.iter().all(|(s, _)| s.success);
 
     let mut all_status = dc_status;
 
     if !hcs.is_empty() {
-        let (hc_results, _instant) = run_instances(
-            &hcs,
-            |instance| {
-                let servers = hc_server_map
-                    .get(&instance.name)
-                    .cloned()
-                    .unwrap_or_default();
-                let mut command = container_run_cmd(
-                    &instance.name,
-                    None,
-                    vec![],
-                    false,
-                    Some(topology),
-                    Some("domain.xml"),
-                );
-                command
-                    .arg(format!(
-                        "--secret=username,type=env,target={}",
-                        USERNAME_VARIABLE
-                    ))
-                    .arg(format!(
-                        "--secret=password,type=env,target={}",
-                        PASSWORD_VARIABLE
-                    ))
-                    .arg("--network")
-                    .arg(WILDFLY_ADMIN_CONTAINER)
-                    .arg("--env")
-                    .arg(format!("{}={}", HOSTNAME_VARIABLE, instance.name))
-                    .arg("--env")
-                    .arg(format!(
-                        "{}={}",
-                        DOMAIN_CONTROLLER_VARIABLE, instance.domain_controller
-                    ));
-                let mut command = add_servers(command, &instance.name, servers);
-                command
-                    .arg(instance.admin_image.image_name())
-                    .arg(format!("--primary-address={}", instance.domain_controller));
-                command
-            },
-            json,
-        )
-        .await?;
+        all_status.extend(run_host_controllers(topology, &hcs, &hc_bootstrap_map, json).await?);
+    }
 
-        for (s, p) in &hc_results {
-            p.finish_if_alive(Some(&s.identifier));
-        }
-        all_status.extend(hc_results);
+    if dc_healthy {
+        let mut bootstraps = hc_bootstrap_map;
+        bootstraps.insert(dc.name.clone(), dc_bootstrap);
+        let api = ManagementApi::new(dc.ports.management, "admin", "admin")?;
+        all_status = wait_for_servers(&api, all_status, &bootstraps, timeout).await;
+    }
+    for (s, p) in &all_status {
+        p.finish_if_alive(Some(&s.display_name()));
     }
 
     if json {
         print_json_results(&all_status);
+    } else {
+        for (s, _) in all_status.iter().filter(|(s, _)| !s.success) {
+            println!(
+                "{}: {}",
+                style(&s.identifier).cyan(),
+                style(&s.error_message).red()
+            );
+        }
     }
     Ok(())
 }
+
+/// Waits until the started hosts are registered at the domain controller and their
+/// servers with `auto-start` are running. Updates the status of each host with the
+/// readiness of its servers.
+async fn wait_for_servers(
+    api: &ManagementApi,
+    status: Vec<(CommandStatus, Progress)>,
+    bootstraps: &BTreeMap<String, HostBootstrap>,
+    timeout: Duration,
+) -> Vec<(CommandStatus, Progress)> {
+    let expected: Vec<ExpectedDomainHost> = status
+        .iter()
+        .filter(|(s, _)| s.success)
+        .map(|(s, _)| ExpectedDomainHost {
+            name: s.identifier.clone(),
+            servers: bootstraps
+                .get(&s.identifier)
+                .map(|bootstrap| {
+                    bootstrap
+                        .servers
+                        .iter()
+                        .filter(|server| server.autostart)
+                        .map(|server| server.name.clone())
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+        .collect();
+    let progress: BTreeMap<String, Progress> = status
+        .iter()
+        .filter(|(_, p)| !p.bar.is_finished())
+        .map(|(s, p)| (s.identifier.clone(), p.clone()))
+        .collect();
+    let readiness = wait_for_domain(api, &expected, timeout, &progress).await;
+
+    status
+        .into_iter()
+        .map(
+            |(s, p)| match readiness.iter().find(|r| r.name == s.identifier) {
+                Some(host) if !host.registered => {
+                    let message = "Host not registered at the domain controller";
+                    p.finish_failure(message);
+                    (s.with_failure(message), p)
+                }
+                Some(host) => {
+                    let s = s.with_servers(host.servers.clone());
+                    if !s.success {
+                        p.finish_failure(&s.error_message);
+                    }
+                    (s, p)
+                }
+                None => (s, p),
+            },
+        )
+        .collect()
+}
+
+/// Starts the host controllers of a topology with their servers and bootstrap options.
+/// The progress bars of the started host controllers are kept alive.
+pub(super) async fn run_host_controllers(
+    topology: &str,
+    hcs: &[HostController],
+    hc_bootstrap_map: &BTreeMap<String, HostBootstrap>,
+    json: bool,
+) -> anyhow::Result<Vec<(CommandStatus, Progress)>> {
+    let (hc_results, _instant) = run_instances(
+        hcs,
+        |instance| {
+            let bootstrap = hc_bootstrap_map
+                .get(&instance.name)
+                .cloned()
+                .unwrap_or_default();
+            let mut command = container_run_cmd(
+                &instance.name,
+                None,
+                instance.debug_port,
+                &bootstrap.run_options,
+                false,
+                Some(topology),
+                Some(&config_name(
+                    &bootstrap.run_options,
+                    &default_host_config(
+                        &instance.admin_image.wildfly_image,
+                        ServerType::HostController,
+                    )
+                    .unwrap_or_default(),
+                )),
+            );
+            command
+                .arg(format!(
+                    "--secret=username,type=env,target={}",
+                    USERNAME_VARIABLE
+                ))
+                .arg(format!(
+                    "--secret=password,type=env,target={}",
+                    PASSWORD_VARIABLE
+                ))
+                .arg("--network")
+                .arg(WILDFLY_ADMIN_CONTAINER)
+                .arg("--env")
+                .arg(format!("{}={}", HOSTNAME_VARIABLE, instance.name))
+                .arg("--env")
+                .arg(format!(
+                    "{}={}",
+                    DOMAIN_CONTROLLER_VARIABLE, instance.domain_controller
+                ));
+            let mut command = add_servers(command, &instance.name, bootstrap.servers);
+            command
+                .arg(instance.admin_image.image_name())
+                .arg(format!("--primary-address={}", instance.domain_controller))
+                .args(bootstrap.run_options.parameters(ServerType::HostController));
+            command
+        },
+        json,
+    )
+    .await?;
+
+    Ok(hc_results)
+}
+
+/// Returns the name of the configuration of `run_options` or `default`.
+fn config_name(run_options: &RunOptions, default: &str) -> String {
+    run_options
+        .config
+        .as_ref()
+        .map(ServerConfig::name)
+        .unwrap_or_else(|| default.to_string())
+}
@@ -0,0 +1,452 @@
+use crate::args::{username_password_argument, variables_argument};
+use crate::container::{containers_by_topology, verify_container_command};
+use crate::json::{HostStatusInfo, ServerStatusInfo, TopologyStatusInfo};
+use crate::wildfly::{AdminImage, ContainerInstance, DomainHost, ManagementClient, ServerType};
+use clap::ArgMatches;
+use console::style;
+use futures::executor::block_on;
+use wildfly_meta::{WildFlyImageRegistry, parse_wildfly_image};
+
+use super::model::{TopologySetup, resolve_topology};
+
+pub fn topology_status(
+    matches: &ArgMatches,
+    registry: &WildFlyImageRegistry,
+    json: bool,
+) -> anyhow::Result<()> {
+    let setup_arg = matches.get_one::<String>("setup").unwrap();
+    let (topology_name, setup) =
+        resolve_topology(setup_arg, &variables_argument(matches)?, registry)?;
+    let expected = match &setup {
+        Some(setup) => expected_hosts(setup, registry)?,
+        None => vec![],
+    };
+    verify_container_command()?;
+    let (username, password) = username_password_argument(matches);
+
+    let mut instances = block_on(containers_by_topology(&topology_name, registry))?;
+    instances.sort();
+    if instances.is_empty() && setup.is_none() {
+        if json {
+            let status = TopologyStatusInfo {
+                name: topology_name,
+                hosts: vec![],
+                error: None,
+            };
+            println!("{}", serde_json::to_string(&status)?);
+        } else {
+            println!(
+                "No running containers found for topology '{}'",
+                topology_name
+            );
+        }
+        return Ok(());
+    }
+
+    let dc = instances
+        .iter()
+        .find(|i| i.admin_image.server_type == ServerType::DomainController);
+    let domain = match dc {
+        Some(dc) => block_on(async {
+            ManagementClient::from_container_instance(dc, registry)
+                .http_api(username, password)?
+                .domain_hosts()
+                .await
+        })
+        .map_err(|e| format!("Unable to read the domain model of '{}': {}", dc.name, e)),
+        None => Err(format!(
+            "No running domain controller found for topology '{}'",
+            topology_name
+        )),
+    };
+
+    let status = TopologyStatusInfo {
+        name: topology_name,
+        hosts: host_statuses(&expected, &instances, domain.as_deref().ok()),
+        error: domain.err(),
+    };
+    if json {
+        println!("{}", serde_json::to_string(&status)?);
+    } else {
+        print_status(&status);
+    }
+    Ok(())
+}
+
+// ------------------------------------------------------ status
+
+/// A host defined in the topology file.
+pub(super) struct ExpectedHost {
+    pub name: Option<String>,
+    pub admin_image: AdminImage,
+    pub servers: Vec<String>,
+}
+
+impl ExpectedHost {
+    /// Returns the name of the matched container, the configured name or the
+    /// default container name.
+    pub fn host_name(&self, instance: Option<&ContainerInstance>) -> String {
+        match (instance, &self.name) {
+            (Some(instance), _) => instance.name.clone(),
+            (None, Some(name)) => name.clone(),
+            (None, None) => self.admin_image.container_name(),
+        }
+    }
+}
+
+/// Returns the hosts of the topology file in the order of the file.
+pub(super) fn expected_hosts(
+    setup: &TopologySetup,
+    registry: &WildFlyImageRegistry,
+) -> anyhow::Result<Vec<ExpectedHost>> {
+    setup
+        .hosts
+        .iter()
+        .map(|host| {
+            let wildfly_image =
+                parse_wildfly_image(host.effective_version(&setup.version), registry)
+                    .map_err(|e| anyhow::anyhow!("{}", e))?;
+            Ok(ExpectedHost {
+                name: host.name.clone(),
+                admin_image: AdminImage::new(wildfly_image, host.server_type()),
+                servers: host.servers.iter().map(|s| s.name.clone()).collect(),
+            })
+        })
+        .collect()
+}
+
+/// Matches the hosts of the topology file against the running containers of the
+/// topology. Named hosts are matched by name, unnamed hosts against containers with
+/// the default name of their version. Returns the container of each host and the
+/// containers which don't belong to any host.
+pub(super) fn match_instances<'a>(
+    expected: &[ExpectedHost],
+    instances: &'a [ContainerInstance],
+) -> (
+    Vec<Option<&'a ContainerInstance>>,
+    Vec<&'a ContainerInstance>,
+) {
+    let mut remaining: Vec<&ContainerInstance> = instances.iter().collect();
+    let mut matched = vec![];
+    for host in expected {
+        let position = remaining.iter().position(|instance| match &host.name {
+            Some(name) => instance.name == *name,
+            None => {
+                let default_name = host.admin_image.container_name();
+                instance.admin_image.server_type == host.admin_image.server_type
+                    && instance.admin_image.wildfly_image.identifier
+                        == host.admin_image.wildfly_image.identifier
+                    && (instance.name == default_name
+                        || instance.name.starts_with(&format!("{}-", default_name)))
+            }
+        });
+        matched.push(position.map(|position| remaining.remove(position)));
+    }
+    (matched, remaining)
+}
+
+/// Combines the hosts defined in the topology file, the running containers of the
+/// topology and the hosts registered at the domain controller (`None` if the domain
+/// model couldn't be read). Unnamed hosts of the topology file are matched against
+/// containers with the default name of their version.
+fn host_statuses(
+    expected: &[ExpectedHost],
+    instances: &[ContainerInstance],
+    domain: Option<&[DomainHost]>,
+) -> Vec<HostStatusInfo> {
+    let (matched, remaining) = match_instances(expected, instances);
+    let mut hosts = vec![];
+    for (host, instance) in expected.iter().zip(matched) {
+        hosts.push(host_status(
+            host.host_name(instance),
+            host.admin_image.server_type,
+            Some(host.admin_image.wildfly_image.short_name()),
+            instance.is_some(),
+            &host.servers,
+            domain,
+        ));
+    }
+    for instance in remaining {
+        hosts.push(host_status(
+            instance.name.clone(),
+            instance.admin_image.server_type,
+            Some(instance.admin_image.wildfly_image.short_name()),
+            true,
+            &[],
+            domain,
+        ));
+    }
+    for domain_host in domain.unwrap_or_default() {
+        if !hosts.iter().any(|h| h.name == domain_host.name) {
+            let server_type = if domain_host.primary {
+                ServerType::DomainController
+            } else {
+                ServerType::HostController
+            };
+            hosts.push(host_status(
+                domain_host.name.clone(),
+                server_type,
+                None,
+                false,
+                &[],
+                domain,
+            ));
+        }
+    }
+    hosts.sort_by_key(|h| h.server_type != ServerType::DomainController.short_name());
+    hosts
+}
+
+fn host_status(
+    name: String,
+    server_type: ServerType,
+    version: Option<String>,
+    running: bool,
+    expected_servers: &[String],
+    domain: Option<&[DomainHost]>,
+) -> HostStatusInfo {
+    let domain_host = domain.and_then(|hosts| hosts.iter().find(|h| h.name == name));
+    let status = match (domain_host, domain, running) {
+        (Some(_), _, _) => "registered",
+        (None, Some(_), true) => "unregistered",
+        (None, None, true) => "unknown",
+        (None, _, false) => "missing",
+    };
+    let mut servers: Vec<ServerStatusInfo> = domain_host
+        .map(|host| {
+            host.servers
+                .iter()
+                .map(|server| ServerStatusInfo {
+                    name: server.name.clone(),
+                    group: server.group.clone(),
+                    status: server.status.clone().unwrap_or_default(),
+                    server_state: server.server_state.clone(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    for server in expected_servers {
+        if !servers.iter().any(|s| s.name == *server) {
+            servers.push(ServerStatusInfo {
+                name: server.clone(),
+                group: None,
+                status: if domain.is_some() {
+                    "missing"
+                } else {
+                    "unknown"
+                }
+                .to_string(),
+                server_state: None,
+            });
+        }
+    }
+    HostStatusInfo {
+        name,
+        server_type: server_type.short_name().to_string(),
+        version,
+        release_version: domain_host.and_then(|h| h.release_version.clone()),
+        status: status.to_string(),
+        host_state: domain_host.and_then(|h| h.host_state.clone()),
+        servers,
+    }
+}
+
+// ------------------------------------------------------ print
+
+fn print_status(status: &TopologyStatusInfo) {
+    println!("\nTopology {}\n", style(&status.name).cyan());
+    let dc_type = ServerType::DomainController.short_name();
+    let (dcs, hcs): (Vec<&HostStatusInfo>, Vec<&HostStatusInfo>) =
+        status.hosts.iter().partition(|h| h.server_type == dc_type);
+    if dcs.is_empty() {
+        for hc in hcs {
+            print_host(hc, "", "");
+        }
+    } else {
+        for dc in dcs {
+            print_host(dc, "", "");
+            let children = dc.servers.len() + hcs.len();
+            print_servers(&dc.servers, "", children);
+            for (index, hc) in hcs.iter().enumerate() {
+                let last = dc.servers.len() + index + 1 == children;
+                let (branch, indent) = branch(last);
+                print_host(hc, branch, "");
+                print_servers(&hc.servers, indent, hc.servers.len());
+            }
+        }
+    }
+    if let Some(error) = &status.error {
+        println!("\n{}", style(error).red());
+    }
+}
+
+fn print_host(host: &HostStatusInfo, branch: &str, prefix: &str) {
+    let server_type = if host.server_type == ServerType::DomainController.short_name() {
+        "domain controller"
+    } else {
+        "host controller"
+    };
+    let version = host
+        .release_version
+        .as_ref()
+        .or(host.version.as_ref())
+        .map(|v| format!(" {}", style(v).magenta()))
+        .unwrap_or_default();
+    let status = match host.status.as_str() {
+        "registered" => {
+            let state = host.host_state.as_deref().unwrap_or("registered");
+            if state == "running" {
+                style(state.to_string()).green()
+            } else {
+                style(state.to_string()).yellow()
+            }
+        }
+        "unregistered" => style("not registered".to_string()).red(),
+        "missing" => style("missing".to_string()).red(),
+        other => style(other.to_string()).dim(),
+    };
+    println!(
+        "{}{}{} ({}){} {}",
+        prefix,
+        branch,
+        style(&host.name).yellow(),
+        server_type,
+        version,
+        status
+    );
+}
+
+/// Prints the servers as the first children of a node with `children` children.
+fn print_servers(servers: &[ServerStatusInfo], prefix: &str, children: usize) {
+    for (index, server) in servers.iter().enumerate() {
+        let (branch, _) = branch(index + 1 == children);
+        let group = server
+            .group
+            .as_ref()
+            .map(|g| format!(" {}", style(g).blue()))
+            .unwrap_or_default();
+        let status = match server.status.as_str() {
+            "STARTED" => style(server.status.to_lowercase()).green(),
+            "STOPPED" | "DISABLED" => style(server.status.to_lowercase()).dim(),
+            "missing" => style(server.status.clone()).red(),
+            _ => style(server.status.to_lowercase()).yellow(),
+        };
+        let server_state = match server.server_state.as_deref() {
+            Some(state) if state != "running" => format!(" ({})", style(state).yellow()),
+            _ => String::new(),
+        };
+        println!(
+            "{}{}{}{} {}{}",
+            prefix,
+            branch,
+            style(&server.name).cyan(),
+            group,
+            status,
+            server_state
+        );
+    }
+}
+
+/// Returns the branch and the indentation of its children.
+fn branch(last: bool) -> (&'static str, &'static str) {
+    if last {
+        ("└── ", "    ")
+    } else {
+        ("├── ", "│   ")
+    }
+}
+
+// ------------------------------------------------------ tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wildfly::DomainServer;
+
+    fn test_registry() -> WildFlyImageRegistry {
+        WildFlyImageRegistry::from_toml(include_str!("../../../testdata/wildfly-images.toml"))
+            .expect("failed to parse test registry")
+    }
+
+    fn instance(identifier: &str, name: &str) -> ContainerInstance {
+        ContainerInstance::new(identifier, "id", name, "Up", "", "", &test_registry()).unwrap()
+    }
+
+    fn expected(identifier: &str, name: Option<&str>, servers: &[&str]) -> ExpectedHost {
+        ExpectedHost {
+            name: name.map(str::to_string),
+            admin_image: AdminImage::from_identifier(identifier.to_string(), &test_registry())
+                .unwrap(),
+            servers: servers.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn domain_host(name: &str, primary: bool, servers: &[&str]) -> DomainHost {
+        DomainHost {
+            name: name.to_string(),
+            primary,
+            host_state: Some("running".to_string()),
+            release_version: None,
+            servers: servers
+                .iter()
+                .map(|s| DomainServer {
+                    name: s.to_string(),
+                    group: Some("main-server-group".to_string()),
+                    offset: 0,
+                    auto_start: false,
+                    status: Some("STARTED".to_string()),
+                    server_state: Some("running".to_string()),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn registered_missing_and_unregistered() {
+        let expected = vec![
+            expected("dc-340", Some("dc"), &[]),
+            expected("hc-340", Some("host1"), &["server-one", "server-two"]),
+            expected("hc-330", None, &["server-one"]),
+            expected("hc-340", Some("host3"), &[]),
+        ];
+        let instances = vec![
+            instance("dc-340", "dc"),
+            instance("hc-340", "host1"),
+            instance("hc-330", "wado-hc-330"),
+            instance("hc-340", "host4"),
+        ];
+        let domain = vec![
+            domain_host("dc", true, &[]),
+            domain_host("host1", false, &["server-one"]),
+            domain_host("wado-hc-330", false, &["server-one"]),
+        ];
+        let hosts = host_statuses(&expected, &instances, Some(&domain));
+        let status: Vec<(&str, &str)> = hosts
+            .iter()
+            .map(|h| (h.name.as_str(), h.status.as_str()))
+            .collect();
+        assert_eq!(
+            status,
+            vec![
+                ("dc", "registered"),
+                ("host1", "registered"),
+                ("wado-hc-330", "registered"),
+                ("host3", "missing"),
+                ("host4", "unregistered"),
+            ]
+        );
+        assert_eq!(hosts[1].servers.len(), 2);
+        assert_eq!(hosts[1].servers[0].status, "STARTED");
+        assert_eq!(hosts[1].servers[1].name, "server-two");
+        assert_eq!(hosts[1].servers[1].status, "missing");
+    }
+
+    #[test]
+    fn domain_unknown() {
+        let instances = vec![instance("hc-340", "host1")];
+        let hosts = host_statuses(&[], &instances, None);
+        assert_eq!(hosts.len(), 1);
+        assert_eq!(hosts[0].status, "unknown");
+        assert_eq!(hosts[0].server_type, "hc");
+    }
+}
@@ -1,3 +1,4 @@
+use crate::args::variables_argument;
 use crate::command::lifecycle::{print_json_results, stop_containers_by_name};
 use crate::container::{containers_by_topology, verify_container_command};
 use crate::wildfly::ServerType;
@@ -5,7 +6,7 @@ use clap::ArgMatches;
 use futures::executor::block_on;
 use wildfly_meta::WildFlyImageRegistry;
 
-use super::model::TopologySetup;
+use super::model::resolve_topology;
 
 pub fn topology_stop(
     matches: &ArgMatches,
@@ -13,24 +14,11 @@ pub fn topology_stop(
     json: bool,
 ) -> anyhow::Result<()> {
     let setup_arg = matches.get_one::<String>("setup").unwrap();
-    let topology_name = resolve_topology_name(setup_arg, registry)?;
+    let (topology_name, _) = resolve_topology(setup_arg, &variables_argument(matches)?, registry)?;
     verify_container_command()?;
     block_on(stop_topology(&topology_name, registry, json))
 }
 
-fn resolve_topology_name(
-    setup_arg: &str,
-    registry: &WildFlyImageRegistry,
-) -> anyhow::Result<String> {
-    let path = std::path::Path::new(setup_arg);
-    if path.exists() {
-        let setup = TopologySetup::load(path, registry)?;
-        Ok(setup.name)
-    } else {
-        Ok(setup_arg.to_string())
-    }
-}
-
 async fn stop_topology(
     topology_name: &str,
     registry: &WildFlyImageRegistry,
@@ -0,0 +1,159 @@
+//! Variable interpolation in topology files.
+//!
+//! `${name}` is replaced with the value of the variable `name` given by `--set`
+//! or, if there's no such variable, the environment variable `name`. Use
+//! `${name:-default}` to provide a default value and `$$` for a literal `$`.
+//! References which are not valid names or are not defined are kept verbatim,
+//! so WildFly expressions like `${jboss.home.dir}` or
+//! `${jboss.bind.address:127.0.0.1}` reach the management model unchanged.
+
+use anyhow::bail;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Replaces all defined variables in `content`. Unterminated references are
+/// reported with their position in `path`.
+pub fn interpolate(
+    content: &str,
+    variables: &BTreeMap<String, String>,
+    path: &Path,
+) -> anyhow::Result<String> {
+    let mut output = String::with_capacity(content.len());
+    for (index, line) in content.split_inclusive('\n').enumerate() {
+        let mut rest = line;
+        while let Some(start) = rest.find('$') {
+            output.push_str(&rest[..start]);
+            let reference = &rest[start..];
+            if let Some(escaped) = reference.strip_prefix("$$") {
+                output.push('$');
+                rest = escaped;
+            } else if let Some(expression) = reference.strip_prefix("${") {
+                let column = line.len() - reference.len() + 1;
+                let Some(end) = expression.find('}') else {
+                    bail!(
+                        "Unterminated variable reference at {}:{}:{}",
+                        path.display(),
+                        index + 1,
+                        column
+                    );
+                };
+                let (name, default) = match expression[..end].split_once(":-") {
+                    Some((name, default)) => (name.trim(), Some(default)),
+                    None => (expression[..end].trim(), None),
+                };
+                let value = if is_valid_name(name) {
+                    lookup(name, variables).or(default.map(str::to_string))
+                } else {
+                    None
+                };
+                match value {
+                    Some(value) => output.push_str(&value),
+                    None => output.push_str(&reference[..end + 3]),
+                }
+                rest = &expression[end + 1..];
+            } else {
+                output.push('$');
+                rest = &reference[1..];
+            }
+        }
+        output.push_str(rest);
+    }
+    Ok(output)
+}
+
+fn lookup(name: &str, variables: &BTreeMap<String, String>) -> Option<String> {
+    variables
+        .get(name)
+        .cloned()
+        .or_else(|| std::env::var(name).ok())
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn variables_and_defaults() {
+        let content = "version: ${version}\nname: ${name:-default}-topology\nprice: $$5 $x\n";
+        let result = interpolate(
+            content,
+            &variables(&[("version", "38")]),
+            Path::new("topology.yml"),
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            "version: 38\nname: default-topology\nprice: $5 $x\n"
+        );
+    }
+
+    #[test]
+    fn set_overrides_default() {
+        let result = interpolate(
+            "name: ${name:-default}",
+            &variables(&[("name", "custom")]),
+            Path::new("topology.yml"),
+        )
+        .unwrap();
+        assert_eq!(result, "name: custom");
+    }
+
+    #[test]
+    fn environment() {
+        let path = std::env::var("PATH").unwrap_or_default();
+        let result =
+            interpolate("path: ${PATH}", &BTreeMap::new(), Path::new("topology.yml")).unwrap();
+        assert_eq!(result, format!("path: {}", path));
+    }
+
+    #[test]
+    fn undefined_variable() {
+        let result = interpolate(
+            "name: test\nversion: ${wado_undefined_version}\n",
+            &BTreeMap::new(),
+            Path::new("topology.yml"),
+        )
+        .unwrap();
+        assert_eq!(result, "name: test\nversion: ${wado_undefined_version}\n");
+    }
+
+    #[test]
+    fn wildfly_expressions() {
+        let content = "operations:\n  - /interface=public:write-attribute(name=inet-address,value=\"${jboss.bind.address:127.0.0.1}\")\n  - /path=data:add(path=${jboss.home.dir}/data/${name})\n";
+        let result = interpolate(
+            content,
+            &variables(&[("name", "app")]),
+            Path::new("topology.yml"),
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            "operations:\n  - /interface=public:write-attribute(name=inet-address,value=\"${jboss.bind.address:127.0.0.1}\")\n  - /path=data:add(path=${jboss.home.dir}/data/app)\n"
+        );
+    }
+
+    #[test]
+    fn unterminated_reference() {
+        let error =
+            interpolate("version: ${version", &BTreeMap::new(), Path::new("t.yml")).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("Unterminated variable reference at t.yml:1:10")
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use crate::args::variables_argument;
+use crate::command::images::local_image_names;
+use crate::container::verify_container_command;
+use crate::json::TopologyValidationInfo;
+use clap::ArgMatches;
+use console::style;
+use futures::executor::block_on;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use wildfly_meta::WildFlyImageRegistry;
+
+use super::model::TopologySetup;
+use super::status::expected_hosts;
+
+/// Validates a topology file without starting or inspecting any containers. Besides
+/// [`TopologySetup::validate`], checks whether the images of all hosts are available
+/// locally. Missing images and untested mixed-domain versions are reported as warnings.
+pub fn topology_validate(
+    matches: &ArgMatches,
+    registry: &WildFlyImageRegistry,
+    json: bool,
+) -> anyhow::Result<()> {
+    let path = matches.get_one::<PathBuf>("setup").unwrap();
+    let setup = TopologySetup::load(path, &variables_argument(matches)?, registry)?;
+    let images: BTreeSet<String> = expected_hosts(&setup, registry)?
+        .iter()
+        .map(|host| host.admin_image.image_name())
+        .collect();
+
+    let mut warnings = setup.warnings(registry);
+    if verify_container_command().is_ok() {
+        let local = block_on(local_image_names())?;
+        for image in images.iter().filter(|image| !local.contains(*image)) {
+            warnings.push(format!(
+                "Image {} is not available locally and will be pulled on start",
+                image
+            ));
+        }
+    } else {
+        warnings.push("No container runtime found: local images have not been checked".into());
+    }
+
+    let servers = setup.hosts.iter().map(|h| h.servers.len()).sum();
+    if json {
+        let info = TopologyValidationInfo {
+            name: setup.name.clone(),
+            hosts: setup.hosts.len(),
+            servers,
+            warnings,
+        };
+        println!("{}", serde_json::to_string(&info)?);
+    } else {
+        for warning in &warnings {
+            println!("{}", style(warning).yellow());
+        }
+        println!(
+            "{} Topology {} is valid: {} host(s), {} server(s)",
+            style("✓").green(),
+            style(&setup.name).cyan(),
+            setup.hosts.len(),
+            servers
+        );
+    }
+    Ok(())
+}
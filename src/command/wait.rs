@@ -0,0 +1,516 @@
+//! Waits until containers are ready, e.g. in scripts and CI pipelines.
+//!
+//! Standalone servers and domain controllers are ready when their management
+//! interface is healthy (see [`wait_for_healthy`]), host controllers when they're
+//! registered at a running domain controller. Additional conditions (server state,
+//! deployments, and log messages) are polled afterward until they hold. The exit
+//! code distinguishes a timeout (`124`) from a container which stopped (`3`).
+
+use super::lifecycle::create_progress;
+use crate::args::{
+    selected_containers_argument, username_password_argument, wait_timeout_argument,
+};
+use crate::container::{container_logs_cmd, container_ps, container_state};
+use crate::error::WadoError;
+use crate::healthcheck::wait_for_healthy;
+use crate::json::WaitResult;
+use crate::progress::{CommandStatus, Progress, summary};
+use crate::wildfly::ServerType::{DomainController, HostController, Standalone};
+use crate::wildfly::{ContainerInstance, DeploymentState, DomainHost, ManagementApi};
+use anyhow::Context;
+use clap::ArgMatches;
+use futures::executor::block_on;
+use futures::future::join_all;
+use indicatif::MultiProgress;
+use regex::Regex;
+use serde_json::json;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::time::{Instant, sleep};
+use wildfly_meta::WildFlyImageRegistry;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+pub fn wait(
+    matches: &ArgMatches,
+    registry: &WildFlyImageRegistry,
+    json: bool,
+) -> anyhow::Result<()> {
+    let (username, password) = username_password_argument(matches);
+    let conditions = Conditions {
+        server_state: matches.get_one::<String>("server-state").cloned(),
+        deployments: matches
+            .get_many::<String>("deployment")
+            .unwrap_or_default()
+            .cloned()
+            .collect(),
+        log_match: matches
+            .get_one::<String>("log-match")
+            .map(|pattern| Regex::new(pattern))
+            .transpose()
+            .context("Invalid regular expression for --log-match")?,
+        username: username.to_string(),
+        password: password.to_string(),
+    };
+    let timeout = wait_timeout_argument(matches);
+
+    let instances = selected_containers_argument(
+        matches,
+        vec![Standalone, DomainController, HostController],
+        registry,
+    )?;
+
+    let instant = Instant::now();
+    let deadline = instant + timeout;
+    let multi_progress = if json {
+        None
+    } else {
+        Some(MultiProgress::new())
+    };
+    let outcomes = block_on(join_all(instances.iter().map(|instance| {
+        let progress = create_progress(
+            &multi_progress,
+            &instance.admin_image.wildfly_image.short_name(),
+            &instance.admin_image.image_name(),
+        );
+        let conditions = &conditions;
+        async move {
+            let outcome =
+                wait_for_container(instance, conditions, deadline, &progress, registry).await;
+            let status = match &outcome {
+                Outcome::Ready => progress.finish_no_output(Some(&instance.name)),
+                Outcome::Timeout(reason) => progress
+                    .finish_error(&instance.name, &format!("Timed out waiting for {}", reason)),
+                Outcome::Failed(reason) => progress.finish_error(&instance.name, reason),
+            };
+            (outcome, status, instant.elapsed())
+        }
+    })));
+
+    let results = outcomes
+        .iter()
+        .map(|(outcome, status, elapsed)| WaitResult {
+            container: status.identifier.clone(),
+            outcome: outcome.name().to_string(),
+            elapsed: elapsed.as_secs(),
+            error: (!status.success).then(|| status.error_message.clone()),
+        })
+        .collect::<Vec<_>>();
+    if !json {
+        let statuses: Vec<CommandStatus> = outcomes.iter().map(|(_, s, _)| s.clone()).collect();
+        summary(
+            "Waited for",
+            "container",
+            instances.len(),
+            instant,
+            statuses,
+        );
+    }
+
+    let names = |f: fn(&Outcome) -> bool| {
+        outcomes
+            .iter()
+            .filter(|(outcome, _, _)| f(outcome))
+            .map(|(_, status, _)| status.identifier.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let failed = names(|outcome| matches!(outcome, Outcome::Failed(_)));
+    let timed_out = names(|outcome| matches!(outcome, Outcome::Timeout(_)));
+    // with --json, the results are part of the error envelope printed by main
+    let error = if !failed.is_empty() {
+        WadoError::container_failed(&format!("Containers failed: {}", failed))
+    } else if !timed_out.is_empty() {
+        WadoError::wait_timeout(&timed_out)
+    } else {
+        if json {
+            println!("{}", serde_json::to_string(&results)?);
+        }
+        return Ok(());
+    };
+    Err(error
+        .with_details(json!({ "containers": serde_json::to_value(&results)? }))
+        .into())
+}
+
+/// The conditions to wait for in addition to the health of a container.
+struct Conditions {
+    server_state: Option<String>,
+    deployments: Vec<String>,
+    log_match: Option<Regex>,
+    username: String,
+    password: String,
+}
+
+enum Outcome {
+    Ready,
+    /// The timeout expired while waiting for the given condition.
+    Timeout(String),
+    /// The container stopped or a condition can't be met anymore.
+    Failed(String),
+}
+
+impl Outcome {
+    fn name(&self) -> &'static str {
+        match self {
+            Outcome::Ready => "ready",
+            Outcome::Timeout(_) => "timeout",
+            Outcome::Failed(_) => "failed",
+        }
+    }
+}
+
+/// The result of checking a condition once.
+#[derive(Debug, PartialEq)]
+enum Check {
+    Satisfied,
+    /// Not yet satisfied. Contains a description of what's missing.
+    Pending(String),
+    Failed(String),
+}
+
+async fn wait_for_container(
+    instance: &ContainerInstance,
+    conditions: &Conditions,
+    deadline: Instant,
+    progress: &Progress,
+    registry: &WildFlyImageRegistry,
+) -> Outcome {
+    let api = if instance.admin_image.server_type == HostController {
+        None
+    } else {
+        let Some(port) = instance.ports.as_ref().map(|ports| ports.management) else {
+            return Outcome::Failed(format!(
+                "No management port published for {}",
+                instance.name
+            ));
+        };
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        tokio::select! {
+            healthy = wait_for_healthy(port, timeout, progress) => {
+                if !healthy {
+                    return Outcome::Timeout("health check".to_string());
+                }
+            }
+            reason = watch_container(&instance.name) => return Outcome::Failed(reason),
+        }
+        match ManagementApi::new(port, &conditions.username, &conditions.password) {
+            Ok(api) => Some(api),
+            Err(e) => return Outcome::Failed(format!("{:#}", e)),
+        }
+    };
+
+    let start = Instant::now();
+    loop {
+        if let Some(reason) = container_failure(&instance.name).await {
+            return Outcome::Failed(reason);
+        }
+        match check_conditions(instance, api.as_ref(), conditions, registry).await {
+            Check::Satisfied => return Outcome::Ready,
+            Check::Failed(reason) => return Outcome::Failed(reason),
+            Check::Pending(reason) => {
+                if Instant::now() >= deadline {
+                    return Outcome::Timeout(reason);
+                }
+                progress.show_progress(&format!(
+                    "Waiting for {}... ({}s)",
+                    reason,
+                    start.elapsed().as_secs()
+                ));
+            }
+        }
+        sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Checks all conditions of a container and returns the first one which isn't satisfied.
+async fn check_conditions(
+    instance: &ContainerInstance,
+    api: Option<&ManagementApi>,
+    conditions: &Conditions,
+    registry: &WildFlyImageRegistry,
+) -> Check {
+    let server_type = instance.admin_image.server_type;
+    if server_type == HostController || conditions.server_state.is_some() {
+        let check = if server_type == Standalone {
+            match (api, &conditions.server_state) {
+                (Some(api), Some(state)) => standalone_server_state(api, state).await,
+                _ => Check::Satisfied,
+            }
+        } else {
+            let host = (server_type == HostController).then_some(instance.name.as_str());
+            let hosts = match api {
+                Some(api) => api.domain_hosts().await.ok(),
+                None => Some(registered_hosts(&instance.name, conditions, registry).await),
+            };
+            match (hosts, &conditions.server_state) {
+                (None, _) => Check::Pending("the domain controller".to_string()),
+                (Some(hosts), Some(state)) => domain_server_state(&hosts, host, state),
+                (Some(hosts), None) => registration(&hosts, host),
+            }
+        };
+        if check != Check::Satisfied {
+            return check;
+        }
+    }
+    if let Some(api) = api
+        && !conditions.deployments.is_empty()
+    {
+        let domain = server_type == DomainController;
+        let check = match api.deployment_states(domain).await {
+            Ok(states) => conditions
+                .deployments
+                .iter()
+                .map(|name| deployment(&states, name, domain))
+                .find(|check| *check != Check::Satisfied)
+                .unwrap_or(Check::Satisfied),
+            Err(_) => Check::Pending("deployments".to_string()),
+        };
+        if check != Check::Satisfied {
+            return check;
+        }
+    }
+    if let Some(regex) = &conditions.log_match {
+        let logs = container_logs_cmd(&instance.name, false, None, None)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .await;
+        let found = logs.is_ok_and(|output| {
+            regex.is_match(&String::from_utf8_lossy(&output.stdout))
+                || regex.is_match(&String::from_utf8_lossy(&output.stderr))
+        });
+        if !found {
+            return Check::Pending(format!("log message '{}'", regex.as_str()));
+        }
+    }
+    Check::Satisfied
+}
+
+/// Reads the hosts of all running domain controllers where the host controller
+/// `name` is registered.
+async fn registered_hosts(
+    name: &str,
+    conditions: &Conditions,
+    registry: &WildFlyImageRegistry,
+) -> Vec<DomainHost> {
+    let Ok(domain_controllers) =
+        container_ps(vec![DomainController], None, None, true, false, registry).await
+    else {
+        return vec![];
+    };
+    for dc in domain_controllers {
+        let Some(ports) = dc.ports else { continue };
+        let Ok(api) =
+            ManagementApi::new(ports.management, &conditions.username, &conditions.password)
+        else {
+            continue;
+        };
+        if let Ok(hosts) = api.domain_hosts().await
+            && hosts.iter().any(|host| host.name == name)
+        {
+            return hosts;
+        }
+    }
+    vec![]
+}
+
+async fn standalone_server_state(api: &ManagementApi, state: &str) -> Check {
+    let current = api
+        .execute(&json!({
+            "operation": "read-attribute",
+            "address": [],
+            "name": "server-state",
+        }))
+        .await;
+    match current.as_ref().ok().and_then(|value| value.as_str()) {
+        Some(current) if current == state => Check::Satisfied,
+        Some(current) => Check::Pending(format!("server-state {} (is {})", state, current)),
+        None => Check::Pending(format!("server-state {}", state)),
+    }
+}
+
+/// Checks whether host controller `host` is registered at the domain controller.
+fn registration(hosts: &[DomainHost], host: Option<&str>) -> Check {
+    match host {
+        Some(host) if !hosts.iter().any(|h| h.name == host) => {
+            Check::Pending("registration at the domain controller".to_string())
+        }
+        _ => Check::Satisfied,
+    }
+}
+
+/// Checks whether the servers with `auto-start` of all hosts (or only of `host`) are in
+/// the given server state.
+fn domain_server_state(hosts: &[DomainHost], host: Option<&str>, state: &str) -> Check {
+    let registered = registration(hosts, host);
+    if registered != Check::Satisfied {
+        return registered;
+    }
+    let mut pending = vec![];
+    for h in hosts
+        .iter()
+        .filter(|h| host.is_none_or(|host| h.name == host))
+    {
+        for server in h.servers.iter().filter(|server| server.auto_start) {
+            if server.server_state.as_deref() == Some(state) {
+                continue;
+            }
+            let status = server.status.as_deref().unwrap_or("unknown");
+            if status.contains("FAILED") {
+                return Check::Failed(format!("Server {}/{} failed", h.name, server.name));
+            }
+            pending.push(format!(
+                "{}/{} ({})",
+                h.name,
+                server.name,
+                server.server_state.as_deref().unwrap_or(status)
+            ));
+        }
+    }
+    if pending.is_empty() {
+        Check::Satisfied
+    } else {
+        Check::Pending(format!("server-state {} of {}", state, pending.join(", ")))
+    }
+}
+
+/// Checks whether a deployment is enabled (standalone servers) or assigned to a
+/// server group (domain controllers).
+fn deployment(states: &[DeploymentState], name: &str, domain: bool) -> Check {
+    match states.iter().find(|state| state.name == name) {
+        None => Check::Pending(format!("deployment {}", name)),
+        Some(state) if state.status.as_deref() == Some("FAILED") => {
+            Check::Failed(format!("Deployment {} failed", name))
+        }
+        Some(state) if domain && state.server_groups.is_empty() => {
+            Check::Pending(format!("deployment {} to a server group", name))
+        }
+        Some(state) if !domain && (!state.enabled || state.status.as_deref() != Some("OK")) => {
+            Check::Pending(format!("deployment {}", name))
+        }
+        Some(_) => Check::Satisfied,
+    }
+}
+
+/// Polls the state of a container until it's no longer running and returns the reason.
+async fn watch_container(name: &str) -> String {
+    loop {
+        if let Some(reason) = container_failure(name).await {
+            return reason;
+        }
+        sleep(POLL_INTERVAL).await;
+    }
+}
+
+async fn container_failure(name: &str) -> Option<String> {
+    match container_state(name).await {
+        Ok(Some(state)) if state == "running" => None,
+        Ok(Some(state)) => Some(format!("Container {} is {}", name, state)),
+        Ok(None) => Some(format!("Container {} has been removed", name)),
+        // don't fail if the container runtime is temporarily unavailable
+        Err(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wildfly::DomainServer;
+
+    fn host(name: &str, servers: &[(&str, bool, &str, Option<&str>)]) -> DomainHost {
+        DomainHost {
+            name: name.to_string(),
+            primary: false,
+            host_state: Some("running".to_string()),
+            release_version: None,
+            servers: servers
+                .iter()
+                .map(|(name, auto_start, status, state)| DomainServer {
+                    name: name.to_string(),
+                    group: None,
+                    offset: 0,
+                    auto_start: *auto_start,
+                    status: Some(status.to_string()),
+                    server_state: state.map(str::to_string),
+                })
+                .collect(),
+        }
+    }
+
+    fn state(name: &str, enabled: bool, status: Option<&str>, groups: &[&str]) -> DeploymentState {
+        DeploymentState {
+            name: name.to_string(),
+            enabled,
+            status: status.map(str::to_string),
+            server_groups: groups.iter().map(|g| g.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn server_state_of_domain() {
+        let hosts = vec![
+            host(
+                "dc",
+                &[
+                    ("server-one", true, "STARTED", Some("running")),
+                    ("server-two", false, "STOPPED", None),
+                ],
+            ),
+            host("hc", &[("server-three", true, "STARTING", None)]),
+        ];
+        assert_eq!(
+            domain_server_state(&hosts, Some("dc"), "running"),
+            Check::Satisfied
+        );
+        assert_eq!(
+            domain_server_state(&hosts, None, "running"),
+            Check::Pending("server-state running of hc/server-three (STARTING)".to_string())
+        );
+        assert!(matches!(
+            domain_server_state(&hosts, Some("hc2"), "running"),
+            Check::Pending(_)
+        ));
+    }
+
+    #[test]
+    fn server_state_failed() {
+        let hosts = vec![host("hc", &[("server-one", true, "FAILED", None)])];
+        assert_eq!(
+            domain_server_state(&hosts, Some("hc"), "running"),
+            Check::Failed("Server hc/server-one failed".to_string())
+        );
+    }
+
+    #[test]
+    fn deployments() {
+        let states = vec![
+            state("app.war", true, Some("OK"), &[]),
+            state("broken.war", true, Some("FAILED"), &[]),
+            state("disabled.war", false, None, &[]),
+        ];
+        assert_eq!(deployment(&states, "app.war", false), Check::Satisfied);
+        assert!(matches!(
+            deployment(&states, "broken.war", false),
+            Check::Failed(_)
+        ));
+        assert!(matches!(
+            deployment(&states, "disabled.war", false),
+            Check::Pending(_)
+        ));
+        assert!(matches!(
+            deployment(&states, "missing.war", false),
+            Check::Pending(_)
+        ));
+
+        let states = vec![
+            state("app.war", true, None, &["main-server-group"]),
+            state("unassigned.war", true, None, &[]),
+        ];
+        assert_eq!(deployment(&states, "app.war", true), Check::Satisfied);
+        assert!(matches!(
+            deployment(&states, "unassigned.war", true),
+            Check::Pending(_)
+        ));
+    }
+}
@@ -6,8 +6,8 @@ use clap_complete::engine::CompletionCandidate;
 use futures::executor::block_on;
 use wildfly_meta::WildFlyImageRegistry;
 
-use crate::container::query::{container_ps, running_topology_names};
-use crate::wildfly::ServerType;
+use crate::container::query::{container_ps, kept_containers, running_topology_names};
+use crate::wildfly::{ServerType, Snapshot};
 
 use super::version::parse_prefix_token;
 
@@ -29,6 +29,7 @@ pub fn complete_running_names(
             None,
             None,
             false,
+            false,
             registry(),
         ));
         match instances {
@@ -53,6 +54,7 @@ pub fn complete_running_versions(
             None,
             None,
             false,
+            false,
             registry(),
         ));
         match instances {
@@ -71,6 +73,20 @@ pub fn complete_running_versions(
     }
 }
 
+/// Completes the names of containers started with `--keep`, running or stopped.
+pub fn complete_kept_names() -> impl Fn(&OsStr) -> Vec<CompletionCandidate> {
+    move |_current: &OsStr| {
+        let instances = block_on(kept_containers(None, None, registry()));
+        match instances {
+            Ok(instances) => instances
+                .iter()
+                .map(|i| CompletionCandidate::new(i.name.clone()))
+                .collect(),
+            Err(_) => vec![],
+        }
+    }
+}
+
 pub fn complete_running_topologies() -> impl Fn(&OsStr) -> Vec<CompletionCandidate> {
     move |_current: &OsStr| {
         let names = block_on(running_topology_names(registry()));
@@ -80,3 +96,14 @@ pub fn complete_running_topologies() -> impl Fn(&OsStr) -> Vec<CompletionCandida
         }
     }
 }
+
+/// Completes the labels of all snapshots.
+pub fn complete_snapshots() -> impl Fn(&OsStr) -> Vec<CompletionCandidate> {
+    move |_current: &OsStr| match Snapshot::list() {
+        Ok(snapshots) => snapshots
+            .into_iter()
+            .map(|s| CompletionCandidate::new(s.label))
+            .collect(),
+        Err(_) => vec![],
+    }
+}
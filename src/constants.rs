@@ -6,11 +6,33 @@ pub static WILDFLY_ADMIN_CONTAINER: &str = "wado";
 pub static WILDFLY_ADMIN_CONTAINER_REPOSITORY: &str = "quay.io/wado";
 /// Name of the entrypoint script copied into every image.
 pub static ENTRYPOINT: &str = "wado-entrypoint.sh";
+/// Revision of the entrypoint scripts. Increase it whenever the entrypoints change in
+/// a way which requires images to be rebuilt. Images without a revision have revision 1.
+pub static ENTRYPOINT_REVISION: u32 = 2;
 /// Display width for fully qualified image names in progress output.
 pub static FQN_LENGTH: usize = "quay.io/wado/wado-xx:00.0.0.Final-jdkxx".len();
 
+/// Deployment scanner directory of standalone servers.
+pub static STANDALONE_DEPLOYMENTS_DIRECTORY: &str = "/opt/jboss/wildfly/standalone/deployments";
+/// Directory for deployments mounted into domain controllers.
+pub static DOMAIN_DEPLOYMENTS_DIRECTORY: &str = "/opt/jboss/wado/deployments";
+
+/// JDWP port of the server or host controller process inside the container.
+pub static DEBUG_PORT: u16 = 8787;
+/// Base of the published debug ports (`5<major><minor>`).
+pub static DEBUG_PORT_BASE: u16 = 5000;
+
+/// Directory for configuration files mounted into containers.
+pub static CONFIGURATION_DIRECTORY: &str = "/opt/jboss/wado/configuration";
+
 /// Environment variable for JBoss CLI bootstrap operations.
 pub static BOOTSTRAP_OPERATIONS_VARIABLE: &str = "WADO_BOOTSTRAP_OPERATIONS";
+/// Environment variable for a local configuration file copied into the server configuration directory.
+pub static CONFIG_VARIABLE: &str = "WADO_CONFIG";
+/// Environment variable for a local host configuration file of domain controllers.
+pub static HOST_CONFIG_VARIABLE: &str = "WADO_HOST_CONFIG";
+/// Environment variable to enable JDWP debugging (`y` or `n` to suspend or not).
+pub static DEBUG_VARIABLE: &str = "WADO_DEBUG";
 /// Environment variable for the domain controller hostname (used by host controllers).
 pub static DOMAIN_CONTROLLER_VARIABLE: &str = "WADO_DOMAIN_CONTROLLER";
 /// Environment variable for the container's logical hostname in the domain.
@@ -19,6 +41,8 @@ pub static HOSTNAME_VARIABLE: &str = "WADO_HOSTNAME";
 pub static PASSWORD_VARIABLE: &str = "WADO_PASSWORD";
 /// Environment variable for server definitions passed to domain hosts.
 pub static SERVERS_VARIABLE: &str = "WADO_SERVERS";
+/// Environment variable for the servers to debug (`<server>:<port>:<suspend>,...`).
+pub static SERVER_DEBUG_VARIABLE: &str = "WADO_SERVER_DEBUG";
 /// Environment variable for the management user name.
 pub static USERNAME_VARIABLE: &str = "WADO_USERNAME";
 
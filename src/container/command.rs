@@ -8,14 +8,17 @@
 //! blocks used by higher-level orchestration in [`super::lifecycle`].
 
 use crate::constants::{
-    BOOTSTRAP_OPERATIONS_VARIABLE, SERVERS_VARIABLE, WILDFLY_ADMIN_CONTAINER,
+    BOOTSTRAP_OPERATIONS_VARIABLE, CONFIG_VARIABLE, DEBUG_PORT, DEBUG_VARIABLE,
+    HOST_CONFIG_VARIABLE, SERVER_DEBUG_VARIABLE, SERVERS_VARIABLE, WILDFLY_ADMIN_CONTAINER,
     WILDFLY_ADMIN_CONTAINER_REPOSITORY,
 };
 use crate::error::WadoError;
 use crate::label::Label;
-use crate::wildfly::Server;
+use crate::wildfly::{Server, ServerConfig, ServerType};
 use anyhow::Error;
-use std::path::PathBuf;
+
+use super::{Mount, environment_label_arg};
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use tokio::io::AsyncWriteExt;
 use tokio::process::Command;
@@ -72,25 +75,164 @@ pub async fn container_network_cmd() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// JDWP mode given by `--debug [suspend]` or `debug` in a topology.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugMode {
+    /// The JVM starts without waiting for a debugger.
+    Run,
+    /// The JVM waits for a debugger to attach before it starts.
+    Suspend,
+}
+
+impl DebugMode {
+    /// Returns the value of the JDWP `suspend` option.
+    fn suspend(&self) -> &'static str {
+        match self {
+            DebugMode::Run => "n",
+            DebugMode::Suspend => "y",
+        }
+    }
+}
+
+/// JDWP settings of a domain server given by `debug` in a topology.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServerDebug {
+    pub server: String,
+    pub mode: DebugMode,
+    /// The published port on the host.
+    pub port: u16,
+}
+
+impl ServerDebug {
+    /// Returns the `<server>:<port>:<suspend>` entries of [`SERVER_DEBUG_VARIABLE`]. The
+    /// servers listen on consecutive ports following the debug port of the host process.
+    fn variable(server_debug: &[ServerDebug]) -> String {
+        server_debug
+            .iter()
+            .enumerate()
+            .map(|(index, debug)| {
+                format!(
+                    "{}:{}:{}",
+                    debug.server,
+                    ServerDebug::container_port(index),
+                    debug.mode.suspend()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    fn container_port(index: usize) -> u16 {
+        DEBUG_PORT + 1 + index as u16
+    }
+
+    /// For `podman run --label <server-debug-key>=<server>:<port>,...`
+    pub fn label_arg(server_debug: &[ServerDebug]) -> String {
+        Label::ServerDebug.run_arg(
+            &server_debug
+                .iter()
+                .map(|debug| format!("{}:{}", debug.server, debug.port))
+                .collect::<Vec<_>>()
+                .join(","),
+        )
+    }
+
+    /// Parses the value of the server debug label into pairs of server and port.
+    pub fn parse_label(raw: &str) -> Vec<(String, u16)> {
+        Label::ServerDebug
+            .parse_value(raw)
+            .map(|value| {
+                value
+                    .split(',')
+                    .filter_map(|entry| {
+                        let (server, port) = entry.rsplit_once(':')?;
+                        Some((server.to_string(), port.parse().ok()?))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Options for `podman run` / `docker run` given on the command line or in a topology.
+#[derive(Clone, Default)]
+pub struct RunOptions {
+    /// Bootstrap operations in normalized CLI syntax.
+    pub operations: Vec<String>,
+    pub mounts: Vec<Mount>,
+    /// The configuration given by `--config` (`--host-config` for host controllers).
+    /// Local files are also part of `mounts`.
+    pub config: Option<ServerConfig>,
+    /// The host configuration of domain controllers, e.g. of a snapshot.
+    /// Local files are also part of `mounts`.
+    pub host_config: Option<ServerConfig>,
+    /// Environment variables incl. `JAVA_OPTS`.
+    pub env: Vec<(String, String)>,
+    pub system_properties: Vec<(String, String)>,
+    pub debug: Option<DebugMode>,
+    /// The domain servers to debug (topology only).
+    pub server_debug: Vec<ServerDebug>,
+    /// Keep the container after it has been stopped (omits `--rm`).
+    pub keep: bool,
+}
+
+impl RunOptions {
+    /// Replaces the configuration, e.g. by the configuration of a snapshot.
+    pub fn set_config(&mut self, config: ServerConfig) {
+        self.mounts.extend(config.mount());
+        self.config = Some(config);
+    }
+
+    /// Replaces the host configuration of domain controllers.
+    pub fn set_host_config(&mut self, host_config: ServerConfig) {
+        self.mounts.extend(host_config.mount());
+        self.host_config = Some(host_config);
+    }
+
+    /// Returns the WildFly parameters derived from these options: `-c <config>`
+    /// (`--host-config <config>` for host controllers) if a configuration was given,
+    /// `--host-config <host-config>` if a host configuration was given and
+    /// `-D<key>=<value>` per system property.
+    pub fn parameters(&self, server_type: ServerType) -> Vec<String> {
+        let mut parameters = self
+            .config
+            .as_ref()
+            .map(|config| config.parameters(server_type))
+            .unwrap_or_default();
+        if let Some(host_config) = &self.host_config {
+            parameters.extend(host_config.parameters(ServerType::HostController));
+        }
+        parameters.extend(
+            self.system_properties
+                .iter()
+                .map(|(key, value)| format!("-D{}={}", key, value)),
+        );
+        parameters
+    }
+}
+
 /// Builds a `podman run` / `docker run` command with the given container configuration.
 ///
 /// The command is constructed but not executed — callers typically add the image name
-/// and any additional arguments before spawning.
+/// and any additional arguments before spawning. The debug port is only published if
+/// debugging was requested in `options`.
 pub fn container_run_cmd(
     name: &str,
     ports: Option<&crate::wildfly::Ports>,
-    operations: Vec<String>,
+    debug_port: Option<u16>,
+    options: &RunOptions,
     dev: bool,
     topology_name: Option<&str>,
     config: Option<&str>,
 ) -> Command {
     let mut command = container_command().expect("Unable to run docker run/podman run.");
-    command
-        .arg("run")
-        .arg("--rm")
-        .arg("--detach")
-        .arg("--name")
-        .arg(name);
+    command.arg("run");
+    if options.keep {
+        command.arg("--label").arg(Label::Keep.run_arg("true"));
+    } else {
+        command.arg("--rm");
+    }
+    command.arg("--detach").arg("--name").arg(name);
     if dev {
         command.arg("--pull=always");
     }
@@ -100,14 +242,73 @@ pub fn container_run_cmd(
             .arg(format!("{}:8080", ports.http))
             .arg("--publish")
             .arg(format!("{}:9990", ports.management));
+        for (port, host_port) in &ports.extra {
+            command
+                .arg("--publish")
+                .arg(format!("{}:{}", host_port, port.container_port()));
+        }
+    }
+    if let (Some(mode), Some(port)) = (options.debug, debug_port) {
+        command
+            .arg("--publish")
+            .arg(format!("{}:{}", port, DEBUG_PORT))
+            .arg("--env")
+            .arg(format!("{}={}", DEBUG_VARIABLE, mode.suspend()))
+            .arg("--label")
+            .arg(Label::Debug.run_arg(&port.to_string()));
+    }
+    if !options.server_debug.is_empty() {
+        for (index, debug) in options.server_debug.iter().enumerate() {
+            command.arg("--publish").arg(format!(
+                "{}:{}",
+                debug.port,
+                ServerDebug::container_port(index)
+            ));
+        }
+        command
+            .arg("--env")
+            .arg(format!(
+                "{}={}",
+                SERVER_DEBUG_VARIABLE,
+                ServerDebug::variable(&options.server_debug)
+            ))
+            .arg("--label")
+            .arg(ServerDebug::label_arg(&options.server_debug));
     }
-    if !operations.is_empty() {
+    if !options.operations.is_empty() {
         command.arg("--env").arg(format!(
             "{}={}",
             BOOTSTRAP_OPERATIONS_VARIABLE,
-            operations.join(",")
+            options.operations.join(",")
+        ));
+    }
+    for (key, value) in &options.env {
+        command.arg("--env").arg(format!("{}={}", key, value));
+    }
+    if !options.env.is_empty() || !options.system_properties.is_empty() {
+        command.arg("--label").arg(environment_label_arg(
+            &options.env,
+            &options.system_properties,
         ));
     }
+    if let Some(mount) = options.config.as_ref().and_then(ServerConfig::mount) {
+        command
+            .arg("--env")
+            .arg(format!("{}={}", CONFIG_VARIABLE, mount.target));
+    }
+    if let Some(mount) = options.host_config.as_ref().and_then(ServerConfig::mount) {
+        command
+            .arg("--env")
+            .arg(format!("{}={}", HOST_CONFIG_VARIABLE, mount.target));
+    }
+    if !options.mounts.is_empty() {
+        for mount in &options.mounts {
+            command.arg("--volume").arg(mount.run_arg());
+        }
+        command
+            .arg("--label")
+            .arg(Mount::label_arg(&options.mounts));
+    }
     if let Some(topology) = topology_name {
         command
             .arg("--label")
@@ -126,6 +327,81 @@ pub fn container_stop_cmd(name: &str) -> Command {
     command
 }
 
+/// Builds a `podman start` / `docker start` command for the given (stopped) container name.
+pub fn container_start_cmd(name: &str) -> Command {
+    let mut command = container_command().expect("Unable to run docker start/podman start.");
+    command.arg("start").arg(name);
+    command
+}
+
+/// Builds a `podman restart` / `docker restart` command for the given container name.
+pub fn container_restart_cmd(name: &str) -> Command {
+    let mut command = container_command().expect("Unable to run docker restart/podman restart.");
+    command.arg("restart").arg(name);
+    command
+}
+
+/// Builds a `podman rm` / `docker rm` command for the given container name.
+///
+/// When `force` is true, running containers are stopped and removed.
+pub fn container_rm_cmd(name: &str, force: bool) -> Command {
+    let mut command = container_command().expect("Unable to run docker rm/podman rm.");
+    command.arg("rm");
+    if force {
+        command.arg("--force");
+    }
+    command.arg(name);
+    command
+}
+
+/// Builds a `podman cp` / `docker cp` command copying `source` out of the given container to `target`.
+pub fn container_cp_cmd(name: &str, source: &str, target: &Path) -> Command {
+    let mut command = container_command().expect("Unable to run docker cp/podman cp.");
+    command
+        .arg("cp")
+        .arg(format!("{}:{}", name, source))
+        .arg(target);
+    command
+}
+
+/// Builds a `podman logs` / `docker logs` command for the given container name.
+///
+/// Timestamps are always requested, so that the lines of several containers
+/// can be merged in chronological order.
+pub fn container_logs_cmd(
+    name: &str,
+    follow: bool,
+    since: Option<&str>,
+    tail: Option<&str>,
+) -> Command {
+    let mut command = container_command().expect("Unable to run docker logs/podman logs.");
+    command.arg("logs").arg("--timestamps");
+    if follow {
+        command.arg("--follow");
+    }
+    if let Some(since) = since {
+        command.arg("--since").arg(since);
+    }
+    if let Some(tail) = tail {
+        command.arg("--tail").arg(tail);
+    }
+    command.arg(name);
+    command
+}
+
+/// Builds a `podman exec` / `docker exec` command running `command` in the given container.
+///
+/// When `interactive` is true, stdin is kept open and a pseudo-TTY is allocated.
+pub fn container_exec_cmd(name: &str, interactive: bool, command: &[String]) -> Command {
+    let mut exec_command = container_command().expect("Unable to run docker exec/podman exec.");
+    exec_command.arg("exec");
+    if interactive {
+        exec_command.arg("--interactive").arg("--tty");
+    }
+    exec_command.arg(name).args(command);
+    exec_command
+}
+
 // ------------------------------------------------------ related functions
 
 /// Appends `--env SERVERS=...` to the command if servers are provided.
@@ -160,3 +436,38 @@ pub async fn create_secret(secret_name: &str, secret_value: &str) -> anyhow::Res
     podman_secret.wait().await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_debug() {
+        let server_debug = vec![
+            ServerDebug {
+                server: "server-one".to_string(),
+                mode: DebugMode::Run,
+                port: 5341,
+            },
+            ServerDebug {
+                server: "server-two".to_string(),
+                mode: DebugMode::Suspend,
+                port: 5342,
+            },
+        ];
+        assert_eq!(
+            ServerDebug::variable(&server_debug),
+            "server-one:8788:n,server-two:8789:y"
+        );
+        let label = ServerDebug::label_arg(&server_debug);
+        let value = label.split_once('=').unwrap().1;
+        assert_eq!(
+            ServerDebug::parse_label(value),
+            vec![
+                ("server-one".to_string(), 5341),
+                ("server-two".to_string(), 5342)
+            ]
+        );
+        assert!(ServerDebug::parse_label("<no value>").is_empty());
+    }
+}
@@ -0,0 +1,133 @@
+//! Environment variables and JVM options of containers.
+//!
+//! Variables are given as `--env KEY=VALUE`, read from `--env-file`, or derived
+//! from `--java-opts` (`JAVA_OPTS`). System properties (`--system-property k=v`)
+//! are passed to WildFly as `-Dk=v`. Both are recorded in the
+//! [`Label::Environment`] label as a JSON array.
+
+use crate::label::Label;
+use anyhow::{Context, bail};
+use std::path::Path;
+
+/// The environment variable holding the JVM options of WildFly.
+const JAVA_OPTS: &str = "JAVA_OPTS";
+
+/// Parses a variable or system property in the format `KEY=VALUE`.
+pub fn parse_key_value(value: &str) -> anyhow::Result<(String, String)> {
+    match value.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => bail!("Invalid value '{}'. Expected <key>=<value>", value),
+    }
+}
+
+/// Reads an env file with one `KEY=VALUE` per line. Empty lines and lines
+/// starting with `#` are ignored.
+pub fn read_env_file(path: &Path) -> anyhow::Result<Vec<(String, String)>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read env file {}", path.display()))?;
+    content
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(index, line)| {
+            parse_key_value(line).with_context(|| format!("{}:{}", path.display(), index + 1))
+        })
+        .collect()
+}
+
+/// Adds `JAVA_OPTS` to the environment. Fails if `JAVA_OPTS` is already defined.
+pub fn add_java_opts(env: &mut Vec<(String, String)>, java_opts: &str) -> anyhow::Result<()> {
+    if env.iter().any(|(key, _)| key == JAVA_OPTS) {
+        bail!(
+            "{} is defined both as environment variable and as JVM options",
+            JAVA_OPTS
+        );
+    }
+    env.push((JAVA_OPTS.to_string(), java_opts.to_string()));
+    Ok(())
+}
+
+/// For `podman run --label <environment-key>=[...]`
+pub fn environment_label_arg(
+    env: &[(String, String)],
+    system_properties: &[(String, String)],
+) -> String {
+    let entries = env
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .chain(
+            system_properties
+                .iter()
+                .map(|(key, value)| format!("-D{}={}", key, value)),
+        )
+        .collect::<Vec<_>>();
+    Label::Environment.run_arg(&serde_json::to_string(&entries).unwrap_or_default())
+}
+
+/// Parses the raw value of the [`Label::Environment`] label.
+pub fn parse_environment_label(raw: &str) -> Vec<String> {
+    Label::Environment
+        .parse_value(raw)
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_value() {
+        assert_eq!(
+            parse_key_value("FOO=bar=baz").unwrap(),
+            ("FOO".to_string(), "bar=baz".to_string())
+        );
+        assert_eq!(
+            parse_key_value("EMPTY=").unwrap(),
+            ("EMPTY".to_string(), String::new())
+        );
+        assert!(parse_key_value("FOO").is_err());
+        assert!(parse_key_value("=bar").is_err());
+    }
+
+    #[test]
+    fn env_file() {
+        let path = std::env::temp_dir().join("wado-env-file-test.env");
+        std::fs::write(&path, "# comment\nFOO=bar\n\n  BAZ=qux  \n").unwrap();
+        assert_eq!(
+            read_env_file(&path).unwrap(),
+            vec![
+                ("FOO".to_string(), "bar".to_string()),
+                ("BAZ".to_string(), "qux".to_string())
+            ]
+        );
+        std::fs::write(&path, "FOO=bar\ninvalid\n").unwrap();
+        let error = read_env_file(&path).unwrap_err();
+        assert!(format!("{:#}", error).contains("wado-env-file-test.env:2"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn java_opts() {
+        let mut env = vec![];
+        add_java_opts(&mut env, "-Xmx1g").unwrap();
+        assert_eq!(env, vec![("JAVA_OPTS".to_string(), "-Xmx1g".to_string())]);
+        assert!(add_java_opts(&mut env, "-Xmx2g").is_err());
+    }
+
+    #[test]
+    fn label_round_trip() {
+        let env = vec![("JAVA_OPTS".to_string(), "-Xmx1g -Da=b|c".to_string())];
+        let system_properties = vec![("foo".to_string(), "bar,baz".to_string())];
+        let label = environment_label_arg(&env, &system_properties);
+        let value = label.split_once('=').unwrap().1;
+        assert_eq!(
+            parse_environment_label(value),
+            vec!["JAVA_OPTS=-Xmx1g -Da=b|c", "-Dfoo=bar,baz"]
+        );
+        assert!(parse_environment_label("<no value>").is_empty());
+    }
+}
@@ -5,9 +5,13 @@
 //! and orchestrating container lifecycle operations.
 
 mod command;
+mod environment;
+mod mount;
 pub mod query;
 mod resolve;
 
 pub use command::*;
+pub use environment::*;
+pub use mount::*;
 pub use query::*;
 pub use resolve::*;
@@ -0,0 +1,313 @@
+//! Bind mounts and volumes of containers.
+//!
+//! Mounts are given as `--volume host:container[:options]` or as deployments
+//! (`--deployment <path>`) which are mounted into the deployment scanner
+//! directory of standalone servers or into a staging directory of domain
+//! controllers. All mounts are recorded in the [`Label::Mounts`] label.
+
+use crate::constants::{DOMAIN_DEPLOYMENTS_DIRECTORY, STANDALONE_DEPLOYMENTS_DIRECTORY};
+use crate::label::Label;
+use crate::wildfly::{Operation, Server, ServerGroup, ServerType, Value};
+use anyhow::{Context, bail};
+use std::path::{Path, PathBuf};
+
+/// A bind mount or named volume.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mount {
+    pub source: String,
+    pub target: String,
+    pub options: Option<String>,
+}
+
+impl Mount {
+    /// Parses a volume in the format `host:container[:options]`. Relative host
+    /// paths are resolved against `base_dir` and must exist. Sources without a
+    /// path separator are named volumes.
+    pub fn parse_volume(volume: &str, base_dir: &Path) -> anyhow::Result<Mount> {
+        let parts = volume.splitn(3, ':').collect::<Vec<_>>();
+        if parts.len() < 2 || parts[0].is_empty() || parts[1].is_empty() {
+            bail!(
+                "Invalid volume '{}'. Expected <host>:<container>[:<options>]",
+                volume
+            );
+        }
+        if !parts[1].starts_with('/') {
+            bail!(
+                "Invalid volume '{}'. The container path must be absolute",
+                volume
+            );
+        }
+        let source = if parts[0].contains('/') || parts[0].starts_with('.') {
+            absolute_path(Path::new(parts[0]), base_dir)?
+                .to_string_lossy()
+                .to_string()
+        } else {
+            parts[0].to_string()
+        };
+        Ok(Mount {
+            source,
+            target: parts[1].to_string(),
+            options: parts.get(2).map(|options| options.to_string()),
+        })
+    }
+
+    /// Creates a mount for an archive or exploded directory. Relative paths are
+    /// resolved against `base_dir`.
+    pub fn deployment(
+        path: &Path,
+        base_dir: &Path,
+        server_type: ServerType,
+    ) -> anyhow::Result<Mount> {
+        let source = absolute_path(path, base_dir)?;
+        let name = deployment_name(&source)?;
+        let target = match server_type {
+            ServerType::Standalone => format!("{}/{}", STANDALONE_DEPLOYMENTS_DIRECTORY, name),
+            ServerType::DomainController => {
+                if source.is_dir() {
+                    bail!(
+                        "Exploded deployment {} is not supported for domain controllers",
+                        path.display()
+                    );
+                }
+                format!("{}/{}", DOMAIN_DEPLOYMENTS_DIRECTORY, name)
+            }
+            ServerType::HostController => {
+                bail!("Deployments are not supported for host controllers")
+            }
+        };
+        Ok(Mount {
+            source: source.to_string_lossy().to_string(),
+            target,
+            options: None,
+        })
+    }
+
+    /// For `podman run --volume <source>:<target>[:<options>]`
+    pub fn run_arg(&self) -> String {
+        match &self.options {
+            Some(options) => format!("{}:{}:{}", self.source, self.target, options),
+            None => format!("{}:{}", self.source, self.target),
+        }
+    }
+
+    /// For `podman run --label <mounts-key>=<source>:<target>,...`
+    ///
+    /// `%` and `,` are percent-encoded, so paths containing commas survive the round trip.
+    pub fn label_arg(mounts: &[Mount]) -> String {
+        Label::Mounts.run_arg(
+            &mounts
+                .iter()
+                .map(|mount| encode_label_entry(&format!("{}:{}", mount.source, mount.target)))
+                .collect::<Vec<_>>()
+                .join(","),
+        )
+    }
+
+    /// Parses the raw value of the [`Label::Mounts`] label.
+    pub fn parse_label(raw: &str) -> Vec<String> {
+        Label::Mounts
+            .parse_value(raw)
+            .map(|value| value.split(',').map(decode_label_entry).collect())
+            .unwrap_or_default()
+    }
+}
+
+fn encode_label_entry(entry: &str) -> String {
+    entry.replace('%', "%25").replace(',', "%2C")
+}
+
+fn decode_label_entry(entry: &str) -> String {
+    entry.replace("%2C", ",").replace("%25", "%")
+}
+
+/// Returns the server groups to assign deployments of a domain controller to: the
+/// groups of the given servers or the `main-server-group` of the default servers.
+pub fn deployment_server_groups(servers: &[Server]) -> Vec<String> {
+    let mut groups: Vec<String> = vec![];
+    for server in servers {
+        let group = server.server_group.name();
+        if !groups.iter().any(|g| g == group) {
+            groups.push(group.to_string());
+        }
+    }
+    if groups.is_empty() {
+        groups.push(ServerGroup::MainServerGroup.name().to_string());
+    }
+    groups
+}
+
+/// Returns the bootstrap operations needed to activate mounted deployments.
+///
+/// Standalone servers deploy archives using the deployment scanner, but exploded
+/// deployments require `auto-deploy-exploded`. Domain controllers add mounted
+/// archives as managed content assigned to the given server groups.
+pub fn deployment_operations(
+    mounts: &[Mount],
+    server_type: ServerType,
+    server_groups: &[String],
+) -> Vec<String> {
+    match server_type {
+        ServerType::Standalone => {
+            if mounts.iter().any(|mount| Path::new(&mount.source).is_dir()) {
+                vec![
+                    "/subsystem=deployment-scanner/scanner=default:write-attribute(name=auto-deploy-exploded,value=true)"
+                        .to_string(),
+                ]
+            } else {
+                vec![]
+            }
+        }
+        ServerType::DomainController => mounts
+            .iter()
+            .flat_map(|mount| {
+                let name = mount.target.rsplit('/').next().unwrap_or_default();
+                let add = Operation {
+                    address: vec![("deployment".to_string(), name.to_string())],
+                    name: "add".to_string(),
+                    parameters: vec![(
+                        "content".to_string(),
+                        Value::List(vec![Value::Object(vec![(
+                            "url".to_string(),
+                            Value::Quoted(format!("file:{}", mount.target)),
+                        )])]),
+                    )],
+                    headers: vec![],
+                };
+                let assign = server_groups.iter().map(|group| Operation {
+                    address: vec![
+                        ("server-group".to_string(), group.clone()),
+                        ("deployment".to_string(), name.to_string()),
+                    ],
+                    name: "add".to_string(),
+                    parameters: vec![("enabled".to_string(), Value::Text("true".to_string()))],
+                    headers: vec![],
+                });
+                std::iter::once(add)
+                    .chain(assign)
+                    .map(|operation| operation.to_string())
+                    .collect::<Vec<_>>()
+            })
+            .collect(),
+        ServerType::HostController => vec![],
+    }
+}
+
+fn absolute_path(path: &Path, base_dir: &Path) -> anyhow::Result<PathBuf> {
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        base_dir.join(path)
+    };
+    path.canonicalize()
+        .with_context(|| format!("Path not found: {}", path.display()))
+}
+
+fn deployment_name(path: &Path) -> anyhow::Result<String> {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .with_context(|| format!("Invalid deployment: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_named_volume() {
+        let mount = Mount::parse_volume("data:/opt/data:ro", Path::new("/")).unwrap();
+        assert_eq!(mount.source, "data");
+        assert_eq!(mount.target, "/opt/data");
+        assert_eq!(mount.options.as_deref(), Some("ro"));
+        assert_eq!(mount.run_arg(), "data:/opt/data:ro");
+    }
+
+    #[test]
+    fn parse_bind_mount() {
+        let base_dir = std::env::temp_dir();
+        let mount = Mount::parse_volume("./:/opt/tmp", &base_dir).unwrap();
+        assert_eq!(
+            mount.source,
+            base_dir.canonicalize().unwrap().to_string_lossy()
+        );
+        assert!(Mount::parse_volume("./does-not-exist:/opt/tmp", &base_dir).is_err());
+    }
+
+    #[test]
+    fn parse_invalid_volume() {
+        assert!(Mount::parse_volume("data", Path::new("/")).is_err());
+        assert!(Mount::parse_volume(":/opt/data", Path::new("/")).is_err());
+        assert!(Mount::parse_volume("data:opt/data", Path::new("/")).is_err());
+    }
+
+    #[test]
+    fn label_round_trip() {
+        let mounts = vec![
+            Mount {
+                source: "/tmp/app.war".to_string(),
+                target: "/opt/app.war".to_string(),
+                options: Some("ro".to_string()),
+            },
+            Mount {
+                source: "data".to_string(),
+                target: "/opt/data".to_string(),
+                options: None,
+            },
+        ];
+        let label = Mount::label_arg(&mounts);
+        let value = label.split_once('=').unwrap().1;
+        assert_eq!(
+            Mount::parse_label(value),
+            vec!["/tmp/app.war:/opt/app.war", "data:/opt/data"]
+        );
+        assert!(Mount::parse_label("<no value>").is_empty());
+    }
+
+    #[test]
+    fn label_with_comma() {
+        let mounts = vec![Mount {
+            source: "/tmp/a,b%c/app.war".to_string(),
+            target: "/opt/app.war".to_string(),
+            options: None,
+        }];
+        let label = Mount::label_arg(&mounts);
+        let value = label.split_once('=').unwrap().1;
+        assert_eq!(
+            Mount::parse_label(value),
+            vec!["/tmp/a,b%c/app.war:/opt/app.war"]
+        );
+    }
+
+    #[test]
+    fn server_groups_of_servers() {
+        let servers = Server::parse_servers("s1:osg,s2:msg,s3:osg").unwrap();
+        assert_eq!(
+            deployment_server_groups(&servers),
+            vec!["other-server-group", "main-server-group"]
+        );
+        assert_eq!(deployment_server_groups(&[]), vec!["main-server-group"]);
+    }
+
+    #[test]
+    fn domain_deployment_operations() {
+        let mounts = vec![Mount {
+            source: "/tmp/app.war".to_string(),
+            target: format!("{}/app.war", DOMAIN_DEPLOYMENTS_DIRECTORY),
+            options: None,
+        }];
+        assert_eq!(
+            deployment_operations(
+                &mounts,
+                ServerType::DomainController,
+                &["app-group".to_string(), "other-server-group".to_string()]
+            ),
+            vec![
+                format!(
+                    "/deployment=app.war:add(content=[{{url=\"file:{}/app.war\"}}])",
+                    DOMAIN_DEPLOYMENTS_DIRECTORY
+                ),
+                "/server-group=app-group/deployment=app.war:add(enabled=true)".to_string(),
+                "/server-group=other-server-group/deployment=app.war:add(enabled=true)".to_string(),
+            ]
+        );
+    }
+}
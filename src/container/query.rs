@@ -4,28 +4,31 @@
 //! container instances managed by wado.
 
 use crate::label::Label;
-use crate::wildfly::ServerType::DomainController;
-use crate::wildfly::{ContainerInstance, Ports, ServerType};
+use crate::wildfly::{ContainerInstance, ExtraPort, Ports, ServerType};
 use futures::future::join_all;
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashMap};
 use std::process::Stdio;
 use wildfly_meta::{WildFlyImage, WildFlyImageRegistry};
 
 use super::command::container_command;
+use super::{Mount, ServerDebug, parse_environment_label};
 
 /// Lists running wado containers, filtered by server type, version, and name.
 ///
 /// When `resolve_ports` is true, each container is inspected to determine its
 /// actual host port mappings — this adds one `podman inspect` call per container.
+/// When `all` is true, stopped containers (started with `--keep`) are included.
 pub async fn container_ps(
     server_types: Vec<ServerType>,
     wildfly_images: Option<&[WildFlyImage]>,
     name: Option<&str>,
     resolve_ports: bool,
+    all: bool,
     registry: &WildFlyImageRegistry,
 ) -> anyhow::Result<Vec<ContainerInstance>> {
     let mut instances = ps_instances(
         &Label::Id.filter(),
+        all,
         |instance| {
             let server_type_match = server_types.contains(&instance.admin_image.server_type);
             let version_match = if let Some(versions) = &wildfly_images {
@@ -59,17 +62,50 @@ pub async fn containers_by_topology(
 ) -> anyhow::Result<Vec<ContainerInstance>> {
     ps_instances(
         &Label::Topology.filter_value(topology_name),
+        false,
         |_| true,
         registry,
     )
     .await
 }
 
+/// Selects running containers by topology, server type, version, and name.
+///
+/// Without a topology this is the same as [`container_ps`]. With a topology, only
+/// the containers of that topology (see [`containers_by_topology`]) are considered.
+/// Port mappings are always resolved.
+pub async fn select_containers(
+    server_types: Vec<ServerType>,
+    wildfly_images: Option<&[WildFlyImage]>,
+    name: Option<&str>,
+    topology: Option<&str>,
+    registry: &WildFlyImageRegistry,
+) -> anyhow::Result<Vec<ContainerInstance>> {
+    match topology {
+        Some(topology) => {
+            let instances = containers_by_topology(topology, registry)
+                .await?
+                .into_iter()
+                .filter(|instance| {
+                    server_types.contains(&instance.admin_image.server_type)
+                        && wildfly_images
+                            .is_none_or(|wcs| wcs.contains(&instance.admin_image.wildfly_image))
+                        && name.is_none_or(|name| name == instance.name)
+                })
+                .collect::<Vec<_>>();
+            let futures = instances.iter().map(container_ports);
+            let results = join_all(futures).await;
+            Ok(results.into_iter().filter_map(|r| r.ok()).collect())
+        }
+        None => container_ps(server_types, wildfly_images, name, true, false, registry).await,
+    }
+}
+
 /// Returns the names of all currently running topologies.
 pub async fn running_topology_names(
     registry: &WildFlyImageRegistry,
 ) -> anyhow::Result<Vec<String>> {
-    let instances = ps_instances(&Label::Topology.filter(), |_| true, registry).await?;
+    let instances = ps_instances(&Label::Topology.filter(), false, |_| true, registry).await?;
     let names: BTreeSet<String> = instances
         .iter()
         .filter_map(|i| i.topology.clone())
@@ -77,23 +113,18 @@ pub async fn running_topology_names(
     Ok(names.into_iter().collect())
 }
 
-/// Looks up exactly one running container matching the given filters.
+/// Looks up exactly one running container of the given server types matching the
+/// given filters.
 ///
 /// Returns an error if zero or more than one container matches — callers
 /// should provide enough filters (version, name) to identify a single instance.
 pub async fn get_instance(
+    server_types: Vec<ServerType>,
     wildfly_images: Option<&[WildFlyImage]>,
     name: Option<&str>,
     registry: &WildFlyImageRegistry,
 ) -> anyhow::Result<ContainerInstance> {
-    let instances = container_ps(
-        vec![ServerType::Standalone, DomainController],
-        wildfly_images,
-        name,
-        true,
-        registry,
-    )
-    .await?;
+    let instances = container_ps(server_types, wildfly_images, name, true, false, registry).await?;
     if instances.is_empty() || instances.len() > 1 {
         let what = if instances.is_empty() {
             "No container"
@@ -124,15 +155,215 @@ pub async fn get_instance(
     container_ports(&instances[0]).await
 }
 
-// ------------------------------------------------------ internal
+/// Returns all containers started with `--keep` (running and stopped), filtered by
+/// version and name.
+pub async fn kept_containers(
+    wildfly_images: Option<&[WildFlyImage]>,
+    name: Option<&str>,
+    registry: &WildFlyImageRegistry,
+) -> anyhow::Result<Vec<ContainerInstance>> {
+    ps_instances(
+        &Label::Keep.filter(),
+        true,
+        |instance| {
+            wildfly_images.is_none_or(|wcs| wcs.contains(&instance.admin_image.wildfly_image))
+                && name.is_none_or(|name| name == instance.name)
+        },
+        registry,
+    )
+    .await
+}
+
+/// Returns the state of a container (e.g. `running` or `exited`), or `None` if
+/// there's no such container (anymore).
+pub async fn container_state(name: &str) -> anyhow::Result<Option<String>> {
+    let mut command = container_command()?;
+    command
+        .arg("inspect")
+        .arg("--format")
+        .arg("{{.State.Status}}")
+        .arg(name);
+    let output = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .output()
+        .await?;
+    if output.status.success() {
+        Ok(Some(String::from_utf8(output.stdout)?.trim().to_string()))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Returns the value of the label of a container, or `None` if the label is not set.
+pub async fn container_label(name: &str, label: Label) -> anyhow::Result<Option<String>> {
+    let mut command = container_command()?;
+    command
+        .arg("inspect")
+        .arg("--format")
+        .arg(format!("{{{{index .Config.Labels \"{}\"}}}}", label.key()))
+        .arg(name);
+    let output = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .await?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Failed to inspect container {}: {}",
+            name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(label.parse_value(&String::from_utf8(output.stdout)?))
+}
 
-pub(super) async fn container_ports(
+/// Returns the value of the label of a local image, or `None` if the label is not set.
+/// Fails if the image doesn't exist locally.
+pub async fn image_label(image: &str, label: Label) -> anyhow::Result<Option<String>> {
+    let mut command = container_command()?;
+    command
+        .arg("image")
+        .arg("inspect")
+        .arg("--format")
+        .arg(format!("{{{{index .Config.Labels \"{}\"}}}}", label.key()))
+        .arg(image);
+    let output = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .await?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Failed to inspect image {}: {}",
+            image,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(label.parse_value(&String::from_utf8(output.stdout)?))
+}
+
+/// The health of a container as reported by the `HEALTHCHECK` of the container runtime.
+pub struct RuntimeHealth {
+    /// `starting`, `healthy` or `unhealthy`.
+    pub status: String,
+    /// The number of consecutive failed health checks.
+    pub failing_streak: u64,
+    /// The output of the last health check (if any).
+    pub last_output: Option<String>,
+}
+
+/// Returns the runtime health of a container, or `None` if the container has no
+/// health check.
+pub async fn container_health(name: &str) -> anyhow::Result<Option<RuntimeHealth>> {
+    let mut command = container_command()?;
+    command
+        .arg("inspect")
+        .arg("--format")
+        .arg("{{json .State.Health}}")
+        .arg(name);
+    let output = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .await?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Failed to inspect container {}: {}",
+            name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(parse_runtime_health(&String::from_utf8(output.stdout)?))
+}
+
+fn parse_runtime_health(output: &str) -> Option<RuntimeHealth> {
+    let health: serde_json::Value = serde_json::from_str(output.trim()).ok()?;
+    let status = health.get("Status")?.as_str()?;
+    if status.is_empty() {
+        return None;
+    }
+    Some(RuntimeHealth {
+        status: status.to_string(),
+        failing_streak: health
+            .get("FailingStreak")
+            .and_then(serde_json::Value::as_u64)
+            .unwrap_or_default(),
+        last_output: health
+            .get("Log")
+            .and_then(serde_json::Value::as_array)
+            .and_then(|log| log.last())
+            .and_then(|entry| entry.get("Output"))
+            .and_then(serde_json::Value::as_str)
+            .map(|output| output.trim().to_string())
+            .filter(|output| !output.is_empty()),
+    })
+}
+
+/// The resource usage of a running container as reported by `podman stats`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContainerStats {
+    /// The CPU usage (e.g. `1.23%`).
+    pub cpu: String,
+    /// The memory usage and limit (e.g. `512MB / 8GB`).
+    pub memory: String,
+    /// The memory usage relative to the limit (e.g. `6.40%`).
+    pub memory_percent: String,
+}
+
+/// Returns the resource usage of the given running containers, keyed by container name.
+pub async fn container_stats(names: &[String]) -> anyhow::Result<HashMap<String, ContainerStats>> {
+    if names.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let mut command = container_command()?;
+    command
+        .arg("stats")
+        .arg("--no-stream")
+        .arg("--format")
+        .arg("{{.Name}}\t{{.CPUPerc}}\t{{.MemUsage}}\t{{.MemPerc}}")
+        .args(names);
+    let output = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .await?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Failed to read container stats: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(parse_container_stats(&String::from_utf8(output.stdout)?))
+}
+
+fn parse_container_stats(output: &str) -> HashMap<String, ContainerStats> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t').map(str::trim);
+            let name = fields.next().filter(|name| !name.is_empty())?;
+            Some((
+                name.to_string(),
+                ContainerStats {
+                    cpu: fields.next()?.to_string(),
+                    memory: fields.next()?.to_string(),
+                    memory_percent: fields.next().unwrap_or_default().to_string(),
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Inspects a running container to determine its actual host port mappings.
+pub async fn container_ports(
     container_instance: &ContainerInstance,
 ) -> anyhow::Result<ContainerInstance> {
     let mut command = container_command()?;
-    command.arg("inspect")
+    command
+        .arg("inspect")
         .arg("--format")
-        .arg("{{ (index (index .NetworkSettings.Ports \"8080/tcp\") 0).HostPort }}|{{ (index (index .NetworkSettings.Ports \"9990/tcp\") 0).HostPort }}")
+        .arg("{{ range $port, $bindings := .NetworkSettings.Ports }}{{ range $bindings }}{{ $port }}={{ .HostPort }} {{ end }}{{ end }}")
         .arg(container_instance.container_id.as_str());
     let child = command
         .stdout(Stdio::piped())
@@ -140,15 +371,7 @@ pub(super) async fn container_ports(
         .spawn()?;
     let output = child.wait_with_output().await?;
     let ports = if output.status.success() {
-        let output = String::from_utf8(output.stdout)?;
-        let parts = output.trim().split("|").collect::<Vec<&str>>();
-        if parts.len() == 2 {
-            let http = parts[0].parse::<u16>()?;
-            let management = parts[1].parse::<u16>()?;
-            Some(Ports { http, management })
-        } else {
-            container_instance.ports.clone()
-        }
+        parse_port_mappings(&String::from_utf8(output.stdout)?)
     } else {
         None
     };
@@ -158,22 +381,54 @@ pub(super) async fn container_ports(
     })
 }
 
+// ------------------------------------------------------ internal
+
+/// Parses the published ports in the format `<container-port>/tcp=<host-port> ...`.
+/// Returns `None` if the HTTP or management port isn't published (e.g. for host controllers).
+fn parse_port_mappings(output: &str) -> Option<Ports> {
+    let mappings: HashMap<u16, u16> = output
+        .split_whitespace()
+        .filter_map(|mapping| {
+            let (container_port, host_port) = mapping.split_once('=')?;
+            let container_port = container_port.strip_suffix("/tcp")?.parse().ok()?;
+            Some((container_port, host_port.parse().ok()?))
+        })
+        .collect();
+    Some(Ports {
+        http: *mappings.get(&8080)?,
+        management: *mappings.get(&9990)?,
+        extra: ExtraPort::all()
+            .into_iter()
+            .filter_map(|port| Some((port, *mappings.get(&port.container_port())?)))
+            .collect(),
+    })
+}
+
 pub(super) async fn ps_instances(
     filter: &str,
+    all: bool,
     predicate: impl Fn(&ContainerInstance) -> bool,
     registry: &WildFlyImageRegistry,
 ) -> anyhow::Result<Vec<ContainerInstance>> {
     let mut command = container_command()?;
+    command.arg("ps");
+    if all {
+        command.arg("--all");
+    }
     command
-        .arg("ps")
         .arg("--filter")
         .arg(filter)
         .arg("--format")
         .arg(format!(
-            "{{{{.ID}}}}|{}|{{{{.Names}}}}|{{{{.Status}}}}|{}|{}",
+            "{{{{.ID}}}}|{}|{{{{.Names}}}}|{{{{.Status}}}}|{}|{}|{{{{.State}}}}|{}|{}|{}|{}|{}",
             Label::Id.format_expr(),
             Label::Topology.format_expr(),
             Label::Config.format_expr(),
+            Label::Keep.format_expr(),
+            Label::Mounts.format_expr(),
+            Label::Debug.format_expr(),
+            Label::ServerDebug.format_expr(),
+            Label::Environment.format_expr(),
         ));
     let child = command
         .stdout(Stdio::piped())
@@ -183,15 +438,67 @@ pub(super) async fn ps_instances(
     let output = String::from_utf8(output.stdout)?;
     let mut instances = Vec::new();
     for line in output.lines() {
-        let parts: Vec<&str> = line.split('|').collect();
-        if parts.len() == 6
-            && let Ok(instance) = ContainerInstance::new(
+        // the environment comes last since its values may contain '|'
+        let parts: Vec<&str> = line.splitn(12, '|').collect();
+        if parts.len() == 12
+            && let Ok(mut instance) = ContainerInstance::new(
                 parts[1], parts[0], parts[2], parts[3], parts[4], parts[5], registry,
             )
             && predicate(&instance)
         {
+            instance.running = parts[6].trim() == "running";
+            instance.keep = Label::Keep.parse_value(parts[7]).as_deref() == Some("true");
+            instance.mounts = Mount::parse_label(parts[8]);
+            instance.debug_port = Label::Debug
+                .parse_value(parts[9])
+                .and_then(|port| port.parse().ok());
+            instance.server_debug_ports = ServerDebug::parse_label(parts[10]);
+            instance.environment = parse_environment_label(parts[11]);
             instances.push(instance);
         }
     }
     Ok(instances)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runtime_health() {
+        let health = parse_runtime_health(
+            r#"{"Status":"unhealthy","FailingStreak":3,"Log":[{"ExitCode":1,"Output":""},{"ExitCode":1,"Output":"connection refused\n"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(health.status, "unhealthy");
+        assert_eq!(health.failing_streak, 3);
+        assert_eq!(health.last_output.as_deref(), Some("connection refused"));
+        assert!(parse_runtime_health("null").is_none());
+        assert!(parse_runtime_health(r#"{"Status":"","FailingStreak":0,"Log":null}"#).is_none());
+    }
+
+    #[test]
+    fn container_stats() {
+        let stats = parse_container_stats(
+            "wado-sa-390\t1.52%\t412.3MB / 8.2GB\t5.03%\nwado-dc-390\t0.10%\t301MiB / 7.6GiB\t3.87%\n\n",
+        );
+        assert_eq!(stats.len(), 2);
+        let sa = &stats["wado-sa-390"];
+        assert_eq!(sa.cpu, "1.52%");
+        assert_eq!(sa.memory, "412.3MB / 8.2GB");
+        assert_eq!(sa.memory_percent, "5.03%");
+        assert_eq!(stats["wado-dc-390"].memory, "301MiB / 7.6GiB");
+    }
+
+    #[test]
+    fn port_mappings() {
+        let ports =
+            parse_port_mappings("8080/tcp=8390 8443/tcp=18390 8787/tcp=5390 9990/tcp=9390 \n")
+                .unwrap();
+        assert_eq!(ports.http, 8390);
+        assert_eq!(ports.management, 9390);
+        assert_eq!(ports.extra, vec![(ExtraPort::Https, 18390)]);
+        assert!(parse_port_mappings("").is_none());
+        assert!(parse_port_mappings("8787/tcp=5390").is_none());
+    }
+}
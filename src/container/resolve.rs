@@ -3,21 +3,26 @@
 //! Auto-generates unique container names and non-colliding port mappings
 //! based on already-running instances of the same WildFly version.
 
+use crate::label::Label;
 use crate::wildfly::{Ports, ResolvedStart, ServerType, StartSpec};
 use std::collections::{HashMap, HashSet};
 use wildfly_meta::{WildFlyImage, WildFlyImageRegistry};
 
-use super::query::container_ps;
+use super::ServerDebug;
+use super::query::{container_ps, ps_instances};
 
 /// Resolves a list of [`StartSpec`]s into [`ResolvedStart`]s with unique names and ports.
 ///
 /// For each spec without a custom name, a unique name is generated based on the
 /// WildFly version and the number of already-running instances of the same type.
-/// Ports are offset to avoid collisions with all running instances of the same version
-/// (regardless of server type).
+/// Ports (including the ones given by `--publish`) are offset to avoid collisions with all
+/// running instances of the same version (regardless of server type). Debug ports are allocated for specs with `debug`,
+/// skipping the debug ports of running containers and `reserved_debug_ports` (ports
+/// allocated for containers which are about to be started).
 pub async fn resolve_start_specs(
     server_type: ServerType,
     specs: Vec<StartSpec>,
+    reserved_debug_ports: &[u16],
     registry: &WildFlyImageRegistry,
 ) -> anyhow::Result<Vec<ResolvedStart>> {
     let has_ports = server_type != ServerType::HostController;
@@ -47,13 +52,26 @@ pub async fn resolve_start_specs(
         counts.insert(wc.identifier, (same_type, all_types));
     }
 
-    Ok(resolve_specs_with_counts(has_ports, &specs, &counts))
+    let mut used_debug_ports = if specs.iter().any(|s| s.debug) {
+        running_debug_ports(registry).await?
+    } else {
+        HashSet::new()
+    };
+    used_debug_ports.extend(reserved_debug_ports);
+
+    Ok(resolve_specs_with_counts(
+        has_ports,
+        &specs,
+        &counts,
+        used_debug_ports,
+    ))
 }
 
 fn resolve_specs_with_counts(
     has_ports: bool,
     specs: &[StartSpec],
     counts: &HashMap<u16, (u16, u16)>,
+    mut used_debug_ports: HashSet<u16>,
 ) -> Vec<ResolvedStart> {
     let mut result = Vec::new();
     let chunks = specs.chunk_by(|a, b| {
@@ -87,7 +105,30 @@ fn resolve_specs_with_counts(
                 let management = spec
                     .custom_management
                     .unwrap_or_else(|| wc.management_port() + port_offset);
-                Some(Ports { http, management })
+                let extra_offset = spec.custom_offset.unwrap_or(port_offset);
+                let mut publish = spec.publish.clone();
+                publish.sort();
+                publish.dedup();
+                let extra = publish
+                    .into_iter()
+                    .map(|port| (port, port.default_port(wc) + extra_offset))
+                    .collect();
+                Some(Ports {
+                    http,
+                    management,
+                    extra,
+                })
+            } else {
+                None
+            };
+
+            let debug_port = if spec.debug {
+                let mut port = Ports::default_debug_port(wc) + all_types + position as u16;
+                while used_debug_ports.contains(&port) {
+                    port += 1;
+                }
+                used_debug_ports.insert(port);
+                Some(port)
             } else {
                 None
             };
@@ -96,13 +137,15 @@ fn resolve_specs_with_counts(
                 admin_image: spec.admin_image.clone(),
                 name,
                 ports,
+                debug_port,
             });
         }
     }
     result
 }
 
-/// Counts how many instances of a given WildFly version are currently running.
+/// Counts how many instances of a given WildFly version are currently running or
+/// stopped but kept (`--keep`).
 ///
 /// Returns `(same_type, all_types)` where `same_type` is the count of instances
 /// matching the given server type, and `all_types` is the total count across
@@ -117,6 +160,7 @@ pub async fn running_instance_counts(
         Some(std::slice::from_ref(wildfly_image)),
         None,
         false,
+        true,
         registry,
     )
     .await?;
@@ -128,12 +172,44 @@ pub async fn running_instance_counts(
     Ok((same_type, all_types))
 }
 
+/// Returns the debug ports (incl. the ones of domain servers) of all running or kept
+/// wado containers.
+pub async fn running_debug_ports(registry: &WildFlyImageRegistry) -> anyhow::Result<HashSet<u16>> {
+    let instances = ps_instances(&Label::Id.filter(), true, |_| true, registry).await?;
+    Ok(instances
+        .iter()
+        .flat_map(|i| {
+            i.debug_port
+                .into_iter()
+                .chain(i.server_debug_ports.iter().map(|(_, port)| *port))
+        })
+        .collect())
+}
+
+/// Allocates the published ports of the debugged servers of a domain host, starting after the
+/// default debug port of `wildfly_image` and skipping `used` ports.
+pub fn allocate_server_debug_ports(
+    server_debug: &mut [ServerDebug],
+    wildfly_image: &WildFlyImage,
+    used: &mut HashSet<u16>,
+) {
+    let mut port = Ports::default_debug_port(wildfly_image);
+    for debug in server_debug {
+        port += 1;
+        while used.contains(&port) {
+            port += 1;
+        }
+        used.insert(port);
+        debug.port = port;
+    }
+}
+
 // ------------------------------------------------------ tests
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::wildfly::{AdminImage, Ports, StartSpec};
+    use crate::wildfly::{AdminImage, ExtraPort, Ports, StartSpec};
     use wildfly_meta::parse_wildfly_image;
 
     fn test_registry() -> WildFlyImageRegistry {
@@ -149,6 +225,9 @@ mod tests {
             custom_name: None,
             custom_http: None,
             custom_management: None,
+            custom_offset: None,
+            publish: vec![],
+            debug: false,
         }
     }
 
@@ -160,7 +239,7 @@ mod tests {
     }
 
     fn resolve(specs: &[StartSpec], count_entries: &[(u16, u16, u16)]) -> Vec<ResolvedStart> {
-        resolve_specs_with_counts(true, specs, &counts(count_entries))
+        resolve_specs_with_counts(true, specs, &counts(count_entries), HashSet::new())
     }
 
     // ------------------------------------------------------ resolve_specs_with_counts
@@ -294,9 +373,77 @@ mod tests {
             custom_name: None,
             custom_http: None,
             custom_management: None,
+            custom_offset: None,
+            publish: vec![],
+            debug: false,
         };
-        let result = resolve_specs_with_counts(false, &[spec], &HashMap::new());
+        let result = resolve_specs_with_counts(false, &[spec], &HashMap::new(), HashSet::new());
         assert_eq!(result[0].name, "wado-hc-390");
         assert_eq!(result[0].ports, None);
+        assert_eq!(result[0].debug_port, None);
+    }
+
+    #[test]
+    fn published_ports() {
+        let mut spec = sa_spec("39");
+        spec.publish = vec![ExtraPort::Ajp, ExtraPort::Https, ExtraPort::Https];
+        let id = spec.admin_image.wildfly_image.identifier;
+        let result = resolve(&[spec.clone(), spec.clone()], &[(id, 0, 1)]);
+        assert_eq!(
+            result[0].ports.as_ref().unwrap().extra,
+            vec![(ExtraPort::Https, 18391), (ExtraPort::Ajp, 7391)]
+        );
+        assert_eq!(
+            result[1]
+                .ports
+                .as_ref()
+                .unwrap()
+                .extra_port(ExtraPort::Https),
+            Some(18392)
+        );
+
+        spec.custom_offset = Some(100);
+        let result = resolve(&[spec], &[(id, 0, 1)]);
+        assert_eq!(
+            result[0]
+                .ports
+                .as_ref()
+                .unwrap()
+                .extra_port(ExtraPort::Https),
+            Some(18490)
+        );
+    }
+
+    #[test]
+    fn debug_ports() {
+        let mut spec = sa_spec("39");
+        spec.debug = true;
+        let specs = vec![spec.clone(), sa_spec("39"), spec];
+        let id = specs[0].admin_image.wildfly_image.identifier;
+        let result =
+            resolve_specs_with_counts(true, &specs, &counts(&[(id, 0, 1)]), HashSet::from([5392]));
+        assert_eq!(result[0].debug_port, Some(5391));
+        assert_eq!(result[1].debug_port, None);
+        assert_eq!(result[2].debug_port, Some(5393));
+    }
+
+    #[test]
+    fn server_debug_ports() {
+        let registry = test_registry();
+        let wc = parse_wildfly_image("34", &registry).unwrap();
+        let mut server_debug = ["server-one", "server-two"]
+            .map(|server| ServerDebug {
+                server: server.to_string(),
+                mode: crate::container::DebugMode::Run,
+                port: 0,
+            })
+            .to_vec();
+        let mut used = HashSet::from([5340, 5341]);
+        allocate_server_debug_ports(&mut server_debug, &wc, &mut used);
+        assert_eq!(
+            server_debug.iter().map(|d| d.port).collect::<Vec<_>>(),
+            vec![5342, 5343]
+        );
+        assert!(used.contains(&5343));
     }
 }
@@ -13,6 +13,10 @@ pub enum WadoErrorCode {
     RegistryInitFailed,
     UnknownVersion,
     TopologyError,
+    ManagementOperationFailed,
+    InvalidOperation,
+    WaitTimeout,
+    ContainerFailed,
     ClapParseError,
     Internal,
 }
@@ -23,6 +27,7 @@ pub enum WadoErrorCode {
 pub struct WadoError {
     pub code: WadoErrorCode,
     pub message: String,
+    pub details: Option<serde_json::Value>,
 }
 
 #[allow(dead_code)]
@@ -31,6 +36,7 @@ impl WadoError {
         Self {
             code: WadoErrorCode::ContainerRuntimeNotFound,
             message: "Neither podman nor docker found. Install one of them to continue".into(),
+            details: None,
         }
     }
 
@@ -38,6 +44,7 @@ impl WadoError {
         Self {
             code: WadoErrorCode::ContainerCommandFailed,
             message: format!("{context}: {stderr}"),
+            details: None,
         }
     }
 
@@ -45,6 +52,7 @@ impl WadoError {
         Self {
             code: WadoErrorCode::ContainerStartFailed,
             message: format!("Failed to start container {name}: {stderr}"),
+            details: None,
         }
     }
 
@@ -52,6 +60,7 @@ impl WadoError {
         Self {
             code: WadoErrorCode::ContainerStopFailed,
             message: format!("Failed to stop container {name}: {stderr}"),
+            details: None,
         }
     }
 
@@ -59,6 +68,7 @@ impl WadoError {
         Self {
             code: WadoErrorCode::ContainerListFailed,
             message: format!("Failed to list containers: {stderr}"),
+            details: None,
         }
     }
 
@@ -66,6 +76,7 @@ impl WadoError {
         Self {
             code: WadoErrorCode::ImageListFailed,
             message: format!("Failed to list images: {stderr}"),
+            details: None,
         }
     }
 
@@ -73,6 +84,7 @@ impl WadoError {
         Self {
             code: WadoErrorCode::RegistryInitFailed,
             message: format!("Failed to initialize registry: {details}"),
+            details: None,
         }
     }
 
@@ -83,6 +95,7 @@ impl WadoError {
                 "\"{input}\" is not a known WildFly version. \
                  Use 'wado versions' to list available versions."
             ),
+            details: None,
         }
     }
 
@@ -90,13 +103,54 @@ impl WadoError {
         Self {
             code: WadoErrorCode::TopologyError,
             message: details.to_string(),
+            details: None,
         }
     }
 
+    pub fn management_operation_failed(details: &str) -> Self {
+        Self {
+            code: WadoErrorCode::ManagementOperationFailed,
+            message: format!("Management operation failed: {details}"),
+            details: None,
+        }
+    }
+
+    pub fn invalid_operation(details: &str) -> Self {
+        Self {
+            code: WadoErrorCode::InvalidOperation,
+            message: format!("Invalid operation: {details}"),
+            details: None,
+        }
+    }
+
+    pub fn wait_timeout(details: &str) -> Self {
+        Self {
+            code: WadoErrorCode::WaitTimeout,
+            message: format!("Timed out waiting for {details}"),
+            details: None,
+        }
+    }
+
+    pub fn container_failed(details: &str) -> Self {
+        Self {
+            code: WadoErrorCode::ContainerFailed,
+            message: details.to_string(),
+            details: None,
+        }
+    }
+
+    /// Adds structured details to the error, e.g. the results of the containers
+    /// which were waited for. The details are part of the JSON error envelope.
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
     pub fn clap_parse_error(details: &str) -> Self {
         Self {
             code: WadoErrorCode::ClapParseError,
             message: details.to_string(),
+            details: None,
         }
     }
 
@@ -105,6 +159,17 @@ impl WadoError {
             .map(|e| e.code)
             .unwrap_or(WadoErrorCode::Internal)
     }
+
+    /// Returns the process exit code for an error: `124` if waiting timed out
+    /// (like `timeout(1)`), `3` if a container failed while waiting, and `1`
+    /// otherwise. `2` is left to usage errors reported by clap.
+    pub fn exit_code(err: &anyhow::Error) -> i32 {
+        match Self::error_code(err) {
+            WadoErrorCode::WaitTimeout => 124,
+            WadoErrorCode::ContainerFailed => 3,
+            _ => 1,
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -116,6 +181,8 @@ pub struct JsonErrorEnvelope {
 pub struct JsonErrorBody {
     pub code: WadoErrorCode,
     pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
 }
 
 impl JsonErrorEnvelope {
@@ -125,12 +192,14 @@ impl JsonErrorEnvelope {
                 error: JsonErrorBody {
                     code: wado.code,
                     message: wado.message.clone(),
+                    details: wado.details.clone(),
                 },
             },
             None => Self {
                 error: JsonErrorBody {
                     code: WadoErrorCode::Internal,
                     message: err.to_string(),
+                    details: None,
                 },
             },
         }
@@ -153,6 +222,16 @@ mod tests {
         assert_eq!(json, "\"UNKNOWN_VERSION\"");
     }
 
+    #[test]
+    fn exit_codes() {
+        let timeout: anyhow::Error = WadoError::wait_timeout("wado-sa-390").into();
+        let failed: anyhow::Error = WadoError::container_failed("exited").into();
+        let other = anyhow::anyhow!("other");
+        assert_eq!(WadoError::exit_code(&timeout), 124);
+        assert_eq!(WadoError::exit_code(&failed), 3);
+        assert_eq!(WadoError::exit_code(&other), 1);
+    }
+
     #[test]
     fn wado_error_display_uses_message() {
         let err = WadoError::container_runtime_not_found();
@@ -186,6 +265,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn json_error_envelope_with_details() {
+        let err: anyhow::Error = WadoError::wait_timeout("wado-sa-390")
+            .with_details(serde_json::json!({"containers": [{"container": "wado-sa-390"}]}))
+            .into();
+        let json = serde_json::to_value(JsonErrorEnvelope::from_anyhow(&err)).unwrap();
+        assert_eq!(json["error"]["code"], "WAIT_TIMEOUT");
+        assert_eq!(
+            json["error"]["details"]["containers"][0]["container"],
+            "wado-sa-390"
+        );
+
+        let err: anyhow::Error = WadoError::wait_timeout("wado-sa-390").into();
+        let json = serde_json::to_value(JsonErrorEnvelope::from_anyhow(&err)).unwrap();
+        assert!(json["error"].get("details").is_none());
+    }
+
     #[test]
     fn json_error_envelope_from_anyhow_without_wado_error() {
         let err = anyhow::anyhow!("something unexpected");
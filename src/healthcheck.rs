@@ -1,17 +1,27 @@
-use crate::progress::Progress;
-use reqwest::Client;
+use crate::progress::{Progress, ServerStatus};
+use crate::wildfly::{DomainHost, ManagementApi};
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
 use std::time::Duration;
 use tokio::time::{Instant, sleep};
 
 const POLL_INTERVAL: Duration = Duration::from_secs(2);
-const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(3);
 
+/// Default of `--wait-timeout` in seconds.
+pub const DEFAULT_WAIT_TIMEOUT: u64 = 120;
+
 /// Polls the WildFly management interface until it responds or the timeout expires.
 ///
 /// Tries `/health/ready` first (WildFly 17+). If that returns 404, falls back to
 /// `/management` and accepts any HTTP response as proof the management interface is up.
-pub async fn wait_for_healthy(management_port: u16, progress: &Progress) -> bool {
+pub async fn wait_for_healthy(
+    management_port: u16,
+    timeout: Duration,
+    progress: &Progress,
+) -> bool {
     let client = match Client::builder().timeout(REQUEST_TIMEOUT).build() {
         Ok(c) => c,
         Err(_) => return false,
@@ -19,7 +29,7 @@ pub async fn wait_for_healthy(management_port: u16, progress: &Progress) -> bool
 
     let health_url = format!("http://localhost:{}/health/ready", management_port);
     let management_url = format!("http://localhost:{}/management", management_port);
-    let deadline = Instant::now() + DEFAULT_TIMEOUT;
+    let deadline = Instant::now() + timeout;
     let mut use_fallback = false;
     let start = Instant::now();
 
@@ -75,3 +85,281 @@ async fn check_health_ready(client: &Client, url: &str) -> HealthResult {
 async fn check_management(client: &Client, url: &str) -> bool {
     client.get(url).send().await.is_ok()
 }
+
+// ------------------------------------------------------ domain readiness
+
+/// A host which is expected to register at the domain controller together with
+/// the servers which are expected to run (the servers with `auto-start`).
+pub struct ExpectedDomainHost {
+    pub name: String,
+    pub servers: Vec<String>,
+}
+
+/// The readiness of an [`ExpectedDomainHost`].
+pub struct HostReadiness {
+    pub name: String,
+    pub registered: bool,
+    pub servers: Vec<ServerStatus>,
+    /// Whether the state is final, i.e. all servers are running or have failed.
+    pub done: bool,
+}
+
+/// Polls the domain controller until all expected hosts are registered and their
+/// servers are running or have failed, or until the timeout expires. Shows the
+/// progress on the progress bar of each host (if any).
+pub async fn wait_for_domain(
+    api: &ManagementApi,
+    expected: &[ExpectedDomainHost],
+    timeout: Duration,
+    progress: &BTreeMap<String, Progress>,
+) -> Vec<HostReadiness> {
+    let deadline = Instant::now() + timeout;
+    let start = Instant::now();
+    loop {
+        let domain_hosts = api.domain_hosts().await.unwrap_or_default();
+        let readiness = domain_readiness(expected, &domain_hosts);
+        for host in &readiness {
+            if let Some(progress) = progress.get(&host.name)
+                && !host.done
+            {
+                let running = host.servers.iter().filter(|s| s.ready).count();
+                progress.show_progress(&if host.registered {
+                    format!(
+                        "Waiting for servers... {}/{} running ({}s)",
+                        running,
+                        host.servers.len(),
+                        start.elapsed().as_secs()
+                    )
+                } else {
+                    format!(
+                        "Waiting for registration... ({}s)",
+                        start.elapsed().as_secs()
+                    )
+                });
+            }
+        }
+        if readiness.iter().all(|host| host.done) || Instant::now() >= deadline {
+            return readiness;
+        }
+        sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Returns the readiness of the expected hosts based on the hosts and servers
+/// reported by the domain controller.
+pub fn domain_readiness(
+    expected: &[ExpectedDomainHost],
+    domain_hosts: &[DomainHost],
+) -> Vec<HostReadiness> {
+    expected
+        .iter()
+        .map(|host| {
+            let domain_host = domain_hosts.iter().find(|dh| dh.name == host.name);
+            let mut done = domain_host.is_some();
+            let servers = host
+                .servers
+                .iter()
+                .map(|name| {
+                    let server =
+                        domain_host.and_then(|dh| dh.servers.iter().find(|s| s.name == *name));
+                    let (ready, failed, state) = match server {
+                        Some(server) => {
+                            let status = server.status.as_deref().unwrap_or("unknown");
+                            match server.server_state.as_deref() {
+                                Some(
+                                    state @ ("running" | "reload-required" | "restart-required"),
+                                ) => (true, false, state.to_string()),
+                                Some(state) => (false, false, state.to_string()),
+                                None => (false, status.contains("FAILED"), status.to_string()),
+                            }
+                        }
+                        None if domain_host.is_some() => (false, false, "unknown".to_string()),
+                        None => (false, false, "host not registered".to_string()),
+                    };
+                    done &= ready || failed;
+                    ServerStatus {
+                        name: name.clone(),
+                        ready,
+                        state,
+                    }
+                })
+                .collect();
+            HostReadiness {
+                name: host.name.clone(),
+                registered: domain_host.is_some(),
+                servers,
+                done,
+            }
+        })
+        .collect()
+}
+
+// ------------------------------------------------------ microprofile health
+
+/// The MicroProfile Health endpoints of the management interface.
+pub const HEALTH_ENDPOINTS: [&str; 4] = [
+    "/health",
+    "/health/live",
+    "/health/ready",
+    "/health/started",
+];
+
+/// The JSON response of a MicroProfile Health endpoint.
+#[derive(Deserialize)]
+pub struct HealthResponse {
+    /// `UP` or `DOWN`.
+    pub status: String,
+    #[serde(default)]
+    pub checks: Vec<HealthCheck>,
+}
+
+/// A single check of a [`HealthResponse`].
+#[derive(Deserialize)]
+pub struct HealthCheck {
+    pub name: String,
+    pub status: String,
+    #[serde(default)]
+    pub data: Option<Map<String, Value>>,
+}
+
+/// The result of reading a MicroProfile Health endpoint.
+pub enum HealthEndpoint {
+    Available(HealthResponse),
+    /// The endpoint is not supported by this WildFly version.
+    NotFound,
+    Unavailable(String),
+}
+
+/// Reads a MicroProfile Health endpoint (see [`HEALTH_ENDPOINTS`]) of the management
+/// interface. Both `200 UP` and `503 DOWN` contain the checks.
+pub async fn read_health(management_port: u16, endpoint: &str) -> HealthEndpoint {
+    let client = match Client::builder().timeout(REQUEST_TIMEOUT).build() {
+        Ok(c) => c,
+        Err(e) => return HealthEndpoint::Unavailable(e.to_string()),
+    };
+    let url = format!("http://localhost:{}{}", management_port, endpoint);
+    match client.get(&url).send().await {
+        Ok(response) if response.status() == StatusCode::NOT_FOUND => HealthEndpoint::NotFound,
+        Ok(response)
+            if response.status().is_success()
+                || response.status() == StatusCode::SERVICE_UNAVAILABLE =>
+        {
+            let body = response.bytes().await.unwrap_or_default();
+            match serde_json::from_slice::<HealthResponse>(&body) {
+                Ok(health) => HealthEndpoint::Available(health),
+                Err(e) => HealthEndpoint::Unavailable(format!("Invalid response: {}", e)),
+            }
+        }
+        Ok(response) => HealthEndpoint::Unavailable(format!("HTTP {}", response.status())),
+        Err(e) => HealthEndpoint::Unavailable(e.without_url().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wildfly::DomainServer;
+
+    fn expected(name: &str, servers: &[&str]) -> ExpectedDomainHost {
+        ExpectedDomainHost {
+            name: name.to_string(),
+            servers: servers.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn server(name: &str, status: &str, server_state: Option<&str>) -> DomainServer {
+        DomainServer {
+            name: name.to_string(),
+            group: Some("main-server-group".to_string()),
+            offset: 0,
+            auto_start: true,
+            status: Some(status.to_string()),
+            server_state: server_state.map(str::to_string),
+        }
+    }
+
+    fn host(name: &str, servers: Vec<DomainServer>) -> DomainHost {
+        DomainHost {
+            name: name.to_string(),
+            primary: false,
+            host_state: Some("running".to_string()),
+            release_version: None,
+            servers,
+        }
+    }
+
+    #[test]
+    fn health_response() {
+        let health: HealthResponse = serde_json::from_str(
+            r#"{"status":"DOWN","checks":[
+                {"name":"ready-deployments","status":"UP"},
+                {"name":"data-sources","status":"DOWN","data":{"ExampleDS":"DOWN"}}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(health.status, "DOWN");
+        assert_eq!(health.checks.len(), 2);
+        assert!(health.checks[0].data.is_none());
+        assert_eq!(
+            health.checks[1].data.as_ref().unwrap()["ExampleDS"],
+            Value::from("DOWN")
+        );
+
+        let health: HealthResponse = serde_json::from_str(r#"{"status":"UP"}"#).unwrap();
+        assert!(health.checks.is_empty());
+    }
+
+    #[test]
+    fn all_servers_running() {
+        let readiness = domain_readiness(
+            &[expected("hc1", &["server-one", "server-two"])],
+            &[host(
+                "hc1",
+                vec![
+                    server("server-one", "STARTED", Some("running")),
+                    server("server-two", "STARTED", Some("reload-required")),
+                ],
+            )],
+        );
+        assert!(readiness[0].registered);
+        assert!(readiness[0].done);
+        assert!(readiness[0].servers.iter().all(|s| s.ready));
+    }
+
+    #[test]
+    fn pending_and_failed_servers() {
+        let readiness = domain_readiness(
+            &[expected("hc1", &["server-one", "server-two"])],
+            &[host(
+                "hc1",
+                vec![
+                    server("server-one", "STARTING", Some("starting")),
+                    server("server-two", "FAILED", None),
+                ],
+            )],
+        );
+        assert!(!readiness[0].done);
+        assert_eq!(readiness[0].servers[0].state, "starting");
+        assert!(!readiness[0].servers[1].ready);
+        assert_eq!(readiness[0].servers[1].state, "FAILED");
+
+        let readiness = domain_readiness(
+            &[expected("hc1", &["server-two"])],
+            &[host("hc1", vec![server("server-two", "FAILED", None)])],
+        );
+        assert!(readiness[0].done);
+    }
+
+    #[test]
+    fn host_not_registered() {
+        let readiness = domain_readiness(
+            &[expected("hc1", &["server-one"]), expected("hc2", &[])],
+            &[host("hc2", vec![])],
+        );
+        assert!(!readiness[0].registered);
+        assert!(!readiness[0].done);
+        assert_eq!(readiness[0].servers[0].state, "host not registered");
+        assert!(readiness[1].registered);
+        assert!(readiness[1].done);
+    }
+}
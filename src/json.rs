@@ -1,5 +1,6 @@
 use crate::error::WadoErrorCode;
 use serde::Serialize;
+use std::collections::BTreeMap;
 
 #[derive(Serialize)]
 pub struct CommandResult {
@@ -10,20 +11,38 @@ pub struct CommandResult {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub management: Option<u16>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub debug: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub error_code: Option<WadoErrorCode>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub servers: Vec<ServerResult>,
+}
+
+#[derive(Serialize)]
+pub struct ServerResult {
+    pub name: String,
+    pub ready: bool,
+    pub state: String,
 }
 
 impl CommandResult {
-    pub fn success(identifier: &str, http: Option<u16>, management: Option<u16>) -> Self {
+    pub fn success(
+        identifier: &str,
+        http: Option<u16>,
+        management: Option<u16>,
+        debug: Option<u16>,
+    ) -> Self {
         Self {
             identifier: identifier.to_string(),
             success: true,
             http,
             management,
+            debug,
             error_code: None,
             error: None,
+            servers: vec![],
         }
     }
 
@@ -33,8 +52,10 @@ impl CommandResult {
             success: false,
             http: None,
             management: None,
+            debug: None,
             error_code: Some(WadoErrorCode::ContainerCommandFailed),
             error: Some(error.to_string()),
+            servers: vec![],
         }
     }
 }
@@ -50,10 +71,30 @@ pub struct ContainerInfo {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub management: Option<u16>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub https: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub management_https: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ajp: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remoting: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub debug: Option<u16>,
+    /// The debug ports of domain servers by server name.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub server_debug: BTreeMap<String, u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub config: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub topology: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub mounts: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub environment: Vec<String>,
     pub status: String,
+    pub running: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub keep: bool,
     pub container_id: String,
 }
 
@@ -64,3 +105,192 @@ pub struct VersionInfo {
     pub core_version: String,
     pub repository: String,
 }
+
+#[derive(Serialize)]
+pub struct LogLine {
+    pub container: String,
+    pub timestamp: String,
+    pub message: String,
+}
+
+#[derive(Serialize)]
+pub struct ExecResult {
+    pub container: String,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+#[derive(Serialize)]
+pub struct DeploymentInfo {
+    pub container: String,
+    pub name: String,
+    pub enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub server_groups: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct ModelDumpResult {
+    pub version: String,
+    pub server_type: String,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct TopologyStatusInfo {
+    pub name: String,
+    pub hosts: Vec<HostStatusInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct HostStatusInfo {
+    pub name: String,
+    pub server_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release_version: Option<String>,
+    /// `registered`, `unregistered` (running, but not registered at the domain
+    /// controller), `missing` (defined, but not running) or `unknown`.
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host_state: Option<String>,
+    pub servers: Vec<ServerStatusInfo>,
+}
+
+#[derive(Serialize)]
+pub struct ServerStatusInfo {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// The status of the server configuration or `missing` if it's defined in the
+    /// topology, but not known to the domain controller.
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_state: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct PlanChangeInfo {
+    /// `add`, `update`, `start` or `remove`.
+    pub action: String,
+    /// `server-group`, `server` or `host`.
+    pub resource: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Whether the change has been applied successfully (`None` for dry runs).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub success: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct TopologyValidationInfo {
+    pub name: String,
+    pub hosts: usize,
+    pub servers: usize,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct WaitResult {
+    pub container: String,
+    /// `ready`, `timeout` or `failed`.
+    pub outcome: String,
+    /// The number of seconds waited.
+    pub elapsed: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct HealthInfo {
+    pub container: String,
+    pub version: String,
+    pub server_type: String,
+    /// The health reported by the `HEALTHCHECK` of the container runtime.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<RuntimeHealthInfo>,
+    pub endpoints: Vec<HealthEndpointInfo>,
+}
+
+#[derive(Serialize)]
+pub struct RuntimeHealthInfo {
+    pub status: String,
+    pub failing_streak: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_output: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct HealthEndpointInfo {
+    pub endpoint: String,
+    /// `UP` or `DOWN` (`None` if the endpoint is not available).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub checks: Vec<HealthCheckInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct HealthCheckInfo {
+    pub name: String,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+#[derive(Serialize)]
+pub struct MetricsInfo {
+    pub container: String,
+    pub version: String,
+    /// `metrics` (the `/metrics` endpoint) or `management` (the management API).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heap_used: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heap_max: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threads: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub datasources: Vec<DatasourceMetricsInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requests: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub samples: Vec<MetricSampleInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct DatasourceMetricsInfo {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub in_use: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct MetricSampleInfo {
+    pub name: String,
+    #[serde(skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub labels: std::collections::BTreeMap<String, String>,
+    pub value: f64,
+}
@@ -13,6 +13,20 @@ pub enum Label {
     Topology,
     /// Server configuration file name (e.g. `"standalone.xml"`).
     Config,
+    /// Comma-separated bind mounts and volumes (e.g. `"/tmp/app.war:/opt/app.war"`).
+    Mounts,
+    /// JSON array of environment variables and system properties (e.g. `["JAVA_OPTS=-Xmx1g"]`).
+    Environment,
+    /// Published JDWP port (e.g. `"5390"`), set when the container was started with `--debug`.
+    Debug,
+    /// Published JDWP ports of domain servers (e.g. `"server-one:5391,server-two:5392"`).
+    ServerDebug,
+    /// Set to `"true"` when the container was started with `--keep` (without `--rm`).
+    Keep,
+    /// JSON array of the domain-wide operations of a topology, set on its domain controller.
+    Operations,
+    /// Revision of the entrypoint script (e.g. `"2"`), set on images by `wado build`.
+    Entrypoint,
 }
 
 impl Label {
@@ -22,6 +36,13 @@ impl Label {
             Label::Id => "org.wildfly.wado.id",
             Label::Topology => "org.wildfly.wado.topology",
             Label::Config => "org.wildfly.wado.config",
+            Label::Mounts => "org.wildfly.wado.mounts",
+            Label::Environment => "org.wildfly.wado.environment",
+            Label::Debug => "org.wildfly.wado.debug",
+            Label::ServerDebug => "org.wildfly.wado.server-debug",
+            Label::Keep => "org.wildfly.wado.keep",
+            Label::Operations => "org.wildfly.wado.operations",
+            Label::Entrypoint => "org.wildfly.wado.entrypoint",
         }
     }
 
@@ -66,6 +87,13 @@ mod tests {
         assert_eq!(Label::Id.key(), "org.wildfly.wado.id");
         assert_eq!(Label::Topology.key(), "org.wildfly.wado.topology");
         assert_eq!(Label::Config.key(), "org.wildfly.wado.config");
+        assert_eq!(Label::Mounts.key(), "org.wildfly.wado.mounts");
+        assert_eq!(Label::Environment.key(), "org.wildfly.wado.environment");
+        assert_eq!(Label::Debug.key(), "org.wildfly.wado.debug");
+        assert_eq!(Label::ServerDebug.key(), "org.wildfly.wado.server-debug");
+        assert_eq!(Label::Keep.key(), "org.wildfly.wado.keep");
+        assert_eq!(Label::Operations.key(), "org.wildfly.wado.operations");
+        assert_eq!(Label::Entrypoint.key(), "org.wildfly.wado.entrypoint");
     }
 
     #[test]
@@ -8,6 +8,7 @@ mod error;
 mod healthcheck;
 mod json;
 mod label;
+mod paths;
 mod progress;
 mod resources;
 mod wildfly;
@@ -17,17 +18,33 @@ use crate::command::cli::cli;
 use crate::command::completions::completions;
 use crate::command::console::console;
 use crate::command::dc::{dc_start, dc_stop};
+use crate::command::deploy::{deploy, deployments, undeploy};
+use crate::command::diff::diff;
+use crate::command::exec::exec;
 use crate::command::hc::{hc_start, hc_stop};
+use crate::command::health::health;
 use crate::command::images::images;
+use crate::command::logs::logs;
+use crate::command::metrics::metrics;
+use crate::command::model::model_dump;
+use crate::command::op::op;
 use crate::command::ps::ps;
 use crate::command::push::push;
+use crate::command::restart::restart;
+use crate::command::rm::rm;
+use crate::command::snapshot::{snapshot_list, snapshot_restore, snapshot_save};
 use crate::command::standalone::{standalone_start, standalone_stop};
-use crate::command::topology::{topology_start, topology_stop};
+use crate::command::top::top;
+use crate::command::topology::{
+    topology_apply, topology_render, topology_schema, topology_start, topology_status,
+    topology_stop, topology_validate,
+};
 use crate::command::update::update;
 use crate::command::versions::versions;
+use crate::command::wait::wait;
 use crate::completion::{
-    complete_running_names, complete_running_topologies, complete_running_versions,
-    complete_versions,
+    complete_kept_names, complete_running_names, complete_running_topologies,
+    complete_running_versions, complete_snapshots, complete_versions,
 };
 use crate::wildfly::Server;
 use crate::wildfly::ServerType::{DomainController, HostController, Standalone};
@@ -55,10 +72,19 @@ fn build_app_full() -> clap::Command {
             })
         })
         .mut_subcommand("start", |sub_cmd| {
-            sub_cmd.mut_arg("wildfly-version", |arg| {
-                arg.value_parser(parse_version_enumeration)
-                    .add(ArgValueCompleter::new(complete_versions))
-            })
+            sub_cmd
+                .mut_arg("wildfly-version", |arg| {
+                    arg.value_parser(parse_version_enumeration)
+                        .add(ArgValueCompleter::new(complete_versions))
+                })
+                .mut_arg("deployment", |arg| arg.value_parser(value_parser!(PathBuf)))
+                .mut_arg("env-file", |arg| arg.value_parser(value_parser!(PathBuf)))
+                .mut_arg("resume", |arg| {
+                    arg.add(ArgValueCompleter::new(complete_kept_names()))
+                })
+                .mut_arg("from-snapshot", |arg| {
+                    arg.add(ArgValueCompleter::new(complete_snapshots()))
+                })
         })
         .mut_subcommand("stop", |sub_cmd| {
             sub_cmd
@@ -82,6 +108,14 @@ fn build_app_full() -> clap::Command {
                             .add(ArgValueCompleter::new(complete_versions))
                     })
                     .mut_arg("server", |arg| arg.value_parser(parse_servers))
+                    .mut_arg("deployment", |arg| arg.value_parser(value_parser!(PathBuf)))
+                    .mut_arg("env-file", |arg| arg.value_parser(value_parser!(PathBuf)))
+                    .mut_arg("resume", |arg| {
+                        arg.add(ArgValueCompleter::new(complete_kept_names()))
+                    })
+                    .mut_arg("from-snapshot", |arg| {
+                        arg.add(ArgValueCompleter::new(complete_snapshots()))
+                    })
             })
         })
         .mut_subcommand("dc", |sub_cmd| {
@@ -108,11 +142,16 @@ fn build_app_full() -> clap::Command {
                             .add(ArgValueCompleter::new(complete_versions))
                     })
                     .mut_arg("server", |arg| arg.value_parser(parse_servers))
+                    .mut_arg("deployment", |arg| arg.value_parser(value_parser!(PathBuf)))
                     .mut_arg("domain-controller", |arg| {
                         arg.add(ArgValueCompleter::new(complete_running_names(vec![
                             DomainController,
                         ])))
                     })
+                    .mut_arg("env-file", |arg| arg.value_parser(value_parser!(PathBuf)))
+                    .mut_arg("resume", |arg| {
+                        arg.add(ArgValueCompleter::new(complete_kept_names()))
+                    })
             })
         })
         .mut_subcommand("hc", |sub_cmd| {
@@ -136,11 +175,166 @@ fn build_app_full() -> clap::Command {
                 .mut_subcommand("start", |sub_sub_cmd| {
                     sub_sub_cmd.mut_arg("setup", |arg| arg.value_parser(value_parser!(PathBuf)))
                 })
+                .mut_subcommand("apply", |sub_sub_cmd| {
+                    sub_sub_cmd.mut_arg("setup", |arg| arg.value_parser(value_parser!(PathBuf)))
+                })
+                .mut_subcommand("render", |sub_sub_cmd| {
+                    sub_sub_cmd.mut_arg("setup", |arg| arg.value_parser(value_parser!(PathBuf)))
+                })
+                .mut_subcommand("validate", |sub_sub_cmd| {
+                    sub_sub_cmd.mut_arg("setup", |arg| arg.value_parser(value_parser!(PathBuf)))
+                })
                 .mut_subcommand("stop", |sub_sub_cmd| {
                     sub_sub_cmd.mut_arg("setup", |arg| {
                         arg.add(ArgValueCompleter::new(complete_running_topologies()))
                     })
                 })
+                .mut_subcommand("status", |sub_sub_cmd| {
+                    sub_sub_cmd.mut_arg("setup", |arg| {
+                        arg.add(ArgValueCompleter::new(complete_running_topologies()))
+                    })
+                })
+        })
+        .mut_subcommand("restart", |sub_cmd| {
+            sub_cmd
+                .mut_arg("wildfly-version", |arg| {
+                    arg.value_parser(parse_version_enumeration)
+                        .add(ArgValueCompleter::new(complete_versions))
+                })
+                .mut_arg("name", |arg| {
+                    arg.add(ArgValueCompleter::new(complete_kept_names()))
+                })
+        })
+        .mut_subcommand("rm", |sub_cmd| {
+            sub_cmd
+                .mut_arg("wildfly-version", |arg| {
+                    arg.value_parser(parse_version_enumeration)
+                        .add(ArgValueCompleter::new(complete_versions))
+                })
+                .mut_arg("name", |arg| {
+                    arg.add(ArgValueCompleter::new(complete_kept_names()))
+                })
+        })
+        .mut_subcommand("snapshot", |sub_cmd| {
+            sub_cmd
+                .mut_subcommand("save", |sub_sub_cmd| {
+                    sub_sub_cmd.mut_arg("name", |arg| {
+                        arg.add(ArgValueCompleter::new(complete_running_names(vec![
+                            Standalone,
+                            DomainController,
+                            HostController,
+                        ])))
+                    })
+                })
+                .mut_subcommand("restore", |sub_sub_cmd| {
+                    sub_sub_cmd
+                        .mut_arg("label", |arg| {
+                            arg.add(ArgValueCompleter::new(complete_snapshots()))
+                        })
+                        .mut_arg("domain-controller", |arg| {
+                            arg.add(ArgValueCompleter::new(complete_running_names(vec![
+                                DomainController,
+                            ])))
+                        })
+                })
+        })
+        .mut_subcommand("wait", |sub_cmd| {
+            sub_cmd
+                .mut_arg("wildfly-version", |arg| {
+                    arg.value_parser(parse_version_enumeration)
+                        .add(ArgValueCompleter::new(complete_running_versions(vec![
+                            Standalone,
+                            DomainController,
+                            HostController,
+                        ])))
+                })
+                .mut_arg("name", |arg| {
+                    arg.add(ArgValueCompleter::new(complete_running_names(vec![
+                        Standalone,
+                        DomainController,
+                        HostController,
+                    ])))
+                })
+                .mut_arg("topology", |arg| {
+                    arg.add(ArgValueCompleter::new(complete_running_topologies()))
+                })
+        })
+        .mut_subcommand("health", |sub_cmd| {
+            sub_cmd
+                .mut_arg("wildfly-version", |arg| {
+                    arg.value_parser(parse_version_enumeration)
+                        .add(ArgValueCompleter::new(complete_running_versions(vec![
+                            Standalone,
+                            DomainController,
+                            HostController,
+                        ])))
+                })
+                .mut_arg("name", |arg| {
+                    arg.add(ArgValueCompleter::new(complete_running_names(vec![
+                        Standalone,
+                        DomainController,
+                        HostController,
+                    ])))
+                })
+                .mut_arg("topology", |arg| {
+                    arg.add(ArgValueCompleter::new(complete_running_topologies()))
+                })
+        })
+        .mut_subcommand("metrics", |sub_cmd| {
+            sub_cmd
+                .mut_arg("wildfly-version", |arg| {
+                    arg.value_parser(parse_version_enumeration)
+                        .add(ArgValueCompleter::new(complete_running_versions(vec![
+                            Standalone,
+                        ])))
+                })
+                .mut_arg("name", |arg| {
+                    arg.add(ArgValueCompleter::new(complete_running_names(vec![
+                        Standalone,
+                    ])))
+                })
+        })
+        .mut_subcommand("logs", |sub_cmd| {
+            sub_cmd
+                .mut_arg("wildfly-version", |arg| {
+                    arg.value_parser(parse_version_enumeration)
+                        .add(ArgValueCompleter::new(complete_running_versions(vec![
+                            Standalone,
+                            DomainController,
+                            HostController,
+                        ])))
+                })
+                .mut_arg("name", |arg| {
+                    arg.add(ArgValueCompleter::new(complete_running_names(vec![
+                        Standalone,
+                        DomainController,
+                        HostController,
+                    ])))
+                })
+                .mut_arg("topology", |arg| {
+                    arg.add(ArgValueCompleter::new(complete_running_topologies()))
+                })
+        })
+        .mut_subcommand("exec", |sub_cmd| {
+            sub_cmd
+                .mut_arg("wildfly-version", |arg| {
+                    arg.value_parser(parse_version_enumeration)
+                        .add(ArgValueCompleter::new(complete_running_versions(vec![
+                            Standalone,
+                            DomainController,
+                            HostController,
+                        ])))
+                })
+                .mut_arg("name", |arg| {
+                    arg.add(ArgValueCompleter::new(complete_running_names(vec![
+                        Standalone,
+                        DomainController,
+                        HostController,
+                    ])))
+                })
+                .mut_arg("topology", |arg| {
+                    arg.add(ArgValueCompleter::new(complete_running_topologies()))
+                })
         })
         .mut_subcommand("console", |sub_cmd| {
             sub_cmd
@@ -172,6 +366,94 @@ fn build_app_full() -> clap::Command {
                     ])))
                 })
         })
+        .mut_subcommand("op", |sub_cmd| {
+            sub_cmd
+                .mut_arg("wildfly-version", |arg| {
+                    arg.value_parser(parse_version).add(ArgValueCompleter::new(
+                        complete_running_versions(vec![Standalone, DomainController]),
+                    ))
+                })
+                .mut_arg("name", |arg| {
+                    arg.add(ArgValueCompleter::new(complete_running_names(vec![
+                        Standalone,
+                        DomainController,
+                    ])))
+                })
+        })
+        .mut_subcommand("diff", |sub_cmd| {
+            sub_cmd
+                .mut_arg("left", |arg| {
+                    arg.add(ArgValueCompleter::new(complete_running_names(vec![
+                        Standalone,
+                        DomainController,
+                    ])))
+                })
+                .mut_arg("right", |arg| {
+                    arg.add(ArgValueCompleter::new(complete_running_names(vec![
+                        Standalone,
+                        DomainController,
+                    ])))
+                })
+        })
+        .mut_subcommand("model", |sub_cmd| {
+            sub_cmd.mut_subcommand("dump", |sub_sub_cmd| {
+                sub_sub_cmd
+                    .mut_arg("wildfly-version", |arg| {
+                        arg.value_parser(parse_version_enumeration)
+                            .add(ArgValueCompleter::new(complete_versions))
+                    })
+                    .mut_arg("output", |arg| arg.value_parser(value_parser!(PathBuf)))
+            })
+        })
+        .mut_subcommand("deploy", |sub_cmd| {
+            sub_cmd
+                .mut_arg("wildfly-version", |arg| {
+                    arg.value_parser(parse_version_enumeration)
+                        .add(ArgValueCompleter::new(complete_running_versions(vec![
+                            Standalone,
+                            DomainController,
+                        ])))
+                })
+                .mut_arg("deployment", |arg| arg.value_parser(value_parser!(PathBuf)))
+                .mut_arg("name", |arg| {
+                    arg.add(ArgValueCompleter::new(complete_running_names(vec![
+                        Standalone,
+                        DomainController,
+                    ])))
+                })
+        })
+        .mut_subcommand("undeploy", |sub_cmd| {
+            sub_cmd
+                .mut_arg("wildfly-version", |arg| {
+                    arg.value_parser(parse_version_enumeration)
+                        .add(ArgValueCompleter::new(complete_running_versions(vec![
+                            Standalone,
+                            DomainController,
+                        ])))
+                })
+                .mut_arg("name", |arg| {
+                    arg.add(ArgValueCompleter::new(complete_running_names(vec![
+                        Standalone,
+                        DomainController,
+                    ])))
+                })
+        })
+        .mut_subcommand("deployments", |sub_cmd| {
+            sub_cmd
+                .mut_arg("wildfly-version", |arg| {
+                    arg.value_parser(parse_version_enumeration)
+                        .add(ArgValueCompleter::new(complete_running_versions(vec![
+                            Standalone,
+                            DomainController,
+                        ])))
+                })
+                .mut_arg("name", |arg| {
+                    arg.add(ArgValueCompleter::new(complete_running_names(vec![
+                        Standalone,
+                        DomainController,
+                    ])))
+                })
+        })
 }
 
 //noinspection DuplicatedCode
@@ -187,7 +469,7 @@ async fn main() -> Result<()> {
         } else {
             eprintln!("Error: {e:#}");
         }
-        std::process::exit(1);
+        std::process::exit(error::WadoError::exit_code(&e));
     }
     Ok(())
 }
@@ -220,14 +502,44 @@ async fn run(json: bool) -> Result<()> {
 
                 Some(("topology", sub_matches)) => match sub_matches.subcommand() {
                     Some(("start", m)) => topology_start(m, &registry, json)?,
+                    Some(("apply", m)) => topology_apply(m, &registry, json)?,
                     Some(("stop", m)) => topology_stop(m, &registry, json)?,
+                    Some(("status", m)) => topology_status(m, &registry, json)?,
+                    Some(("render", m)) => topology_render(m, json)?,
+                    Some(("validate", m)) => topology_validate(m, &registry, json)?,
+                    Some(("schema", _)) => topology_schema()?,
+                    _ => unreachable!("Unknown subcommand"),
+                },
+
+                Some(("restart", m)) => restart(m, &registry, json)?,
+                Some(("rm", m)) => rm(m, &registry, json)?,
+
+                Some(("snapshot", sub_matches)) => match sub_matches.subcommand() {
+                    Some(("save", m)) => snapshot_save(m, &registry, json)?,
+                    Some(("list", _)) => snapshot_list(json)?,
+                    Some(("restore", m)) => snapshot_restore(m, &registry, json)?,
                     _ => unreachable!("Unknown subcommand"),
                 },
 
                 Some(("images", _)) => images(&registry)?,
                 Some(("ps", m)) => ps(m, &registry, json)?,
+                Some(("logs", m)) => logs(m, &registry, json)?,
+                Some(("wait", m)) => wait(m, &registry, json)?,
+                Some(("health", m)) => health(m, &registry, json)?,
+                Some(("metrics", m)) => metrics(m, &registry, json)?,
+                Some(("top", m)) => top(m, &registry)?,
+                Some(("exec", m)) => exec(m, &registry, json)?,
                 Some(("console", m)) => console(m, &registry)?,
                 Some(("cli", m)) => cli(m, &registry)?,
+                Some(("op", m)) => op(m, &registry, json)?,
+                Some(("diff", m)) => diff(m, &registry, json)?,
+                Some(("model", sub_matches)) => match sub_matches.subcommand() {
+                    Some(("dump", m)) => model_dump(m, &registry, json)?,
+                    _ => unreachable!("Unknown subcommand"),
+                },
+                Some(("deploy", m)) => deploy(m, &registry, json)?,
+                Some(("undeploy", m)) => undeploy(m, &registry, json)?,
+                Some(("deployments", m)) => deployments(m, &registry, json)?,
                 Some(("versions", _)) => versions(&registry, json)?,
 
                 _ => unreachable!("Unknown subcommand"),
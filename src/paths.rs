@@ -0,0 +1,11 @@
+//! Locations in the file system of the current user.
+
+use std::env;
+use std::path::PathBuf;
+
+/// Returns the home directory of the current user.
+pub fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
@@ -22,6 +22,18 @@ pub struct CommandStatus {
     pub error_message: String,
     pub http: Option<u16>,
     pub management: Option<u16>,
+    pub debug: Option<u16>,
+    /// The readiness of the domain servers of a domain or host controller.
+    pub servers: Vec<ServerStatus>,
+}
+
+/// Readiness of a domain server after a topology start.
+#[derive(Clone)]
+pub struct ServerStatus {
+    pub name: String,
+    pub ready: bool,
+    /// The last known `server-state` or status of the server.
+    pub state: String,
 }
 
 impl CommandStatus {
@@ -33,6 +45,8 @@ impl CommandStatus {
             error_message: "".to_string(),
             http: None,
             management: None,
+            debug: None,
+            servers: vec![],
         }
     }
 
@@ -44,6 +58,8 @@ impl CommandStatus {
             error_message: error_message.to_string(),
             http: None,
             management: None,
+            debug: None,
+            servers: vec![],
         }
     }
 
@@ -56,6 +72,52 @@ impl CommandStatus {
         }
     }
 
+    /// Returns a new status with the debug port (if any).
+    pub fn with_debug_port(self, debug: Option<u16>) -> Self {
+        CommandStatus { debug, ..self }
+    }
+
+    /// Returns the identifier followed by the debug port (if any), e.g. `wado-sa-390 (debug 5390)`.
+    pub fn display_name(&self) -> String {
+        match self.debug {
+            Some(port) => format!("{} (debug {})", self.identifier, port),
+            None => self.identifier.clone(),
+        }
+    }
+
+    /// Returns a new status with the readiness of the domain servers. Marks the status
+    /// as failed if one of the servers is not ready.
+    pub fn with_servers(self, servers: Vec<ServerStatus>) -> Self {
+        let failed: Vec<String> = servers
+            .iter()
+            .filter(|server| !server.ready)
+            .map(|server| format!("{} ({})", server.name, server.state))
+            .collect();
+        if failed.is_empty() || !self.success {
+            CommandStatus { servers, ..self }
+        } else {
+            CommandStatus {
+                success: false,
+                error_message: format!("Servers not running: {}", failed.join(", ")),
+                servers,
+                ..self
+            }
+        }
+    }
+
+    /// Returns a new status marking a failure with the given message.
+    pub fn with_failure(self, error_message: &str) -> Self {
+        if self.success {
+            CommandStatus {
+                success: false,
+                error_message: error_message.to_string(),
+                ..self
+            }
+        } else {
+            self
+        }
+    }
+
     /// Returns a new status marking a health check timeout failure.
     pub fn with_health_failure(self) -> Self {
         CommandStatus {
@@ -271,6 +333,12 @@ impl Progress {
         CommandStatus::success(id)
     }
 
+    /// Completes the spinner as failed with the given error message.
+    pub fn finish_error(&self, identifier: &str, error_message: &str) -> CommandStatus {
+        self.error(error_message);
+        CommandStatus::error(identifier, error_message)
+    }
+
     pub fn finish_healthy(&self, container_name: &str) {
         self.success(Some(container_name));
     }
@@ -279,6 +347,11 @@ impl Progress {
         self.error("health check timed out");
     }
 
+    /// Completes the spinner as failed without returning a [`CommandStatus`].
+    pub fn finish_failure(&self, error_message: &str) {
+        self.error(error_message);
+    }
+
     fn success(&self, status: Option<&str>) {
         self.bar.set_prefix(format!(
             "{:<4}   ",
@@ -3,6 +3,10 @@
 //! Contains the Handlebars Dockerfile template and the entrypoint scripts for
 //! standalone servers, domain controllers, and host controllers. These are
 //! rendered at image build time with version-specific data.
+//!
+//! The entrypoints bootstrap a container only once (guarded by
+//! `$JBOSS_HOME/.wado-bootstrapped`), so that containers started with `--keep`
+//! can be restarted without repeating the bootstrap.
 
 /// Handlebars Dockerfile template for all server types and build modes (dev/stable).
 ///
@@ -32,6 +36,7 @@ FROM {{base-image}}
 
 LABEL maintainer="hpehl@redhat.com"
 LABEL {{label-name}}="{{label-value}}"
+LABEL {{entrypoint-label-name}}="{{entrypoint-revision}}"
 
 USER root
 COPY {{entrypoint}} $JBOSS_HOME/bin/{{entrypoint}}
@@ -71,21 +76,33 @@ CMD ["-c", "standalone.xml"]
 
 /// Entrypoint script for standalone servers.
 ///
-/// Optionally bootstraps the server with CLI operations (via `WADO_BOOTSTRAP_OPERATIONS`)
-/// before starting the server in normal mode.
+/// Optionally copies a local configuration (via `WADO_CONFIG`) and bootstraps the server
+/// with CLI operations (via `WADO_BOOTSTRAP_OPERATIONS`) before starting the server in
+/// normal mode. JDWP (via `WADO_DEBUG`) is only enabled for the final start and passed as
+/// `JAVA_TOOL_OPTIONS`, so that the default `JAVA_OPTS` of `standalone.conf` still apply.
 // language=shell script
 pub static STANDALONE_ENTRYPOINT_SH: &str = r#"#!/bin/bash
 
-if [[ ! -z $WADO_BOOTSTRAP_OPERATIONS ]]; then
-    $JBOSS_HOME/bin/standalone.sh $@ --admin-only &
-    until `$JBOSS_HOME/bin/jboss-cli.sh -c ":read-attribute(name=server-state)" 2> /dev/null | grep -q running`; do
-        sleep 1
-    done
-    echo "[== Bootstrap WildFly Standalone $WILDFLY_VERSION ==]"
-    echo "[-- Execute bootstrap operation: $WADO_BOOTSTRAP_OPERATIONS --]"
-    $JBOSS_HOME/bin/jboss-cli.sh -c --commands="$WADO_BOOTSTRAP_OPERATIONS"
-    $JBOSS_HOME/bin/jboss-cli.sh -c ":shutdown()"
-    echo "[== Bootstrap finished ==]"
+if [[ ! -f $JBOSS_HOME/.wado-bootstrapped ]]; then
+    if [[ ! -z $WADO_CONFIG ]]; then
+        cp "$WADO_CONFIG" $JBOSS_HOME/standalone/configuration/
+    fi
+    if [[ ! -z $WADO_BOOTSTRAP_OPERATIONS ]]; then
+        $JBOSS_HOME/bin/standalone.sh $@ --admin-only &
+        until `$JBOSS_HOME/bin/jboss-cli.sh -c ":read-attribute(name=server-state)" 2> /dev/null | grep -q running`; do
+            sleep 1
+        done
+        echo "[== Bootstrap WildFly Standalone $WILDFLY_VERSION ==]"
+        echo "[-- Execute bootstrap operation: $WADO_BOOTSTRAP_OPERATIONS --]"
+        $JBOSS_HOME/bin/jboss-cli.sh -c --commands="$WADO_BOOTSTRAP_OPERATIONS"
+        $JBOSS_HOME/bin/jboss-cli.sh -c ":shutdown()"
+        echo "[== Bootstrap finished ==]"
+    fi
+    touch $JBOSS_HOME/.wado-bootstrapped
+fi
+if [[ ! -z $WADO_DEBUG ]]; then
+    if java -version 2>&1 | grep -q 'version "1\.'; then DEBUG_ADDRESS=8787; else DEBUG_ADDRESS="*:8787"; fi
+    export JAVA_TOOL_OPTIONS="$JAVA_TOOL_OPTIONS -agentlib:jdwp=transport=dt_socket,address=$DEBUG_ADDRESS,server=y,suspend=$WADO_DEBUG"
 fi
 $JBOSS_HOME/bin/standalone.sh $@
 "#;
@@ -94,31 +111,55 @@ $JBOSS_HOME/bin/standalone.sh $@
 
 /// Entrypoint script for domain controllers.
 ///
-/// Bootstraps the domain by renaming the primary host, optionally adding servers
-/// and running CLI operations, then restarts in normal mode.
+/// Bootstraps the domain by optionally copying a local domain and host configuration, renaming the
+/// primary host, optionally adding servers and running CLI operations, then restarts
+/// in normal mode. JDWP (via `WADO_DEBUG`) is only enabled for the host controller
+/// process of the final start. Servers listed in `WADO_SERVER_DEBUG` get JDWP JVM options.
 // language=shell script
 pub static DOMAIN_CONTROLLER_ENTRYPOINT_SH: &str = r#"#!/bin/bash
 
-$JBOSS_HOME/bin/domain.sh $@ --admin-only &
-until `$JBOSS_HOME/bin/jboss-cli.sh -c "/host=primary:read-attribute(name=host-state)" 2> /dev/null | grep -q running`; do
-    sleep 1
-done
-echo "[== Bootstrap WildFly Domain Controller $WILDFLY_VERSION ==]"
-echo "[-- Rename primary to $WADO_HOSTNAME --]"
-$JBOSS_HOME/bin/jboss-cli.sh -c --commands="/host=primary:write-attribute(name=name,value=$WADO_HOSTNAME),/host=primary:reload(admin-only)"
-until `$JBOSS_HOME/bin/jboss-cli.sh -c "/host=$WADO_HOSTNAME:read-attribute(name=host-state)" 2> /dev/null | grep -q running`; do
-    sleep 1
-done
-if [[ ! -z $WADO_SERVERS ]]; then
-    echo "[-- Add servers: $WADO_SERVERS --]"
-    $JBOSS_HOME/bin/jboss-cli.sh -c --commands="$WADO_SERVERS"
+if [[ ! -f $JBOSS_HOME/.wado-bootstrapped ]]; then
+    if [[ ! -z $WADO_CONFIG ]]; then
+        cp "$WADO_CONFIG" $JBOSS_HOME/domain/configuration/
+    fi
+    if [[ ! -z $WADO_HOST_CONFIG ]]; then
+        cp "$WADO_HOST_CONFIG" $JBOSS_HOME/domain/configuration/
+    fi
+    $JBOSS_HOME/bin/domain.sh $@ --admin-only &
+    until `$JBOSS_HOME/bin/jboss-cli.sh -c "/host=primary:read-attribute(name=host-state)" 2> /dev/null | grep -q running`; do
+        sleep 1
+    done
+    echo "[== Bootstrap WildFly Domain Controller $WILDFLY_VERSION ==]"
+    echo "[-- Rename primary to $WADO_HOSTNAME --]"
+    $JBOSS_HOME/bin/jboss-cli.sh -c --commands="/host=primary:write-attribute(name=name,value=$WADO_HOSTNAME),/host=primary:reload(admin-only)"
+    until `$JBOSS_HOME/bin/jboss-cli.sh -c "/host=$WADO_HOSTNAME:read-attribute(name=host-state)" 2> /dev/null | grep -q running`; do
+        sleep 1
+    done
+    if [[ ! -z $WADO_SERVERS ]]; then
+        echo "[-- Add servers: $WADO_SERVERS --]"
+        $JBOSS_HOME/bin/jboss-cli.sh -c --commands="$WADO_SERVERS"
+    fi
+    if [[ ! -z $WADO_BOOTSTRAP_OPERATIONS ]]; then
+        echo "[-- Execute bootstrap operation: $WADO_BOOTSTRAP_OPERATIONS --]"
+        $JBOSS_HOME/bin/jboss-cli.sh -c --commands="$WADO_BOOTSTRAP_OPERATIONS"
+    fi
+    if [[ ! -z $WADO_SERVER_DEBUG ]]; then
+        if java -version 2>&1 | grep -q 'version "1\.'; then DEBUG_HOST=""; else DEBUG_HOST="*:"; fi
+        for server_debug in ${WADO_SERVER_DEBUG//,/ }; do
+            IFS=: read -r server port suspend <<< "$server_debug"
+            echo "[-- Enable debugging of $server on port $port --]"
+            $JBOSS_HOME/bin/jboss-cli.sh -c --command="/host=$WADO_HOSTNAME/server-config=$server/jvm=default:add(jvm-options=[\"-agentlib:jdwp=transport=dt_socket,address=$DEBUG_HOST$port,server=y,suspend=$suspend\"])"
+        done
+    fi
+    $JBOSS_HOME/bin/jboss-cli.sh -c "/host=$WADO_HOSTNAME:shutdown()"
+    echo "[== Bootstrap finished ==]"
+    touch $JBOSS_HOME/.wado-bootstrapped
 fi
-if [[ ! -z $WADO_BOOTSTRAP_OPERATIONS ]]; then
-    echo "[-- Execute bootstrap operation: $WADO_BOOTSTRAP_OPERATIONS --]"
-    $JBOSS_HOME/bin/jboss-cli.sh -c --commands="$WADO_BOOTSTRAP_OPERATIONS"
+if [[ ! -z $WADO_DEBUG ]]; then
+    if java -version 2>&1 | grep -q 'version "1\.'; then DEBUG_ADDRESS=8787; else DEBUG_ADDRESS="*:8787"; fi
+    # domain.conf only applies its defaults if HOST_CONTROLLER_JAVA_OPTS is unset, so append to them
+    export HOST_CONTROLLER_JAVA_OPTS="$(. $JBOSS_HOME/bin/domain.conf > /dev/null 2>&1; echo "$HOST_CONTROLLER_JAVA_OPTS") -agentlib:jdwp=transport=dt_socket,address=$DEBUG_ADDRESS,server=y,suspend=$WADO_DEBUG"
 fi
-$JBOSS_HOME/bin/jboss-cli.sh -c "/host=$WADO_HOSTNAME:shutdown()"
-echo "[== Bootstrap finished ==]"
 $JBOSS_HOME/bin/domain.sh $@
 "#;
 
@@ -126,33 +167,60 @@ $JBOSS_HOME/bin/domain.sh $@
 
 /// Entrypoint script for host controllers.
 ///
-/// Bootstraps the host by renaming it, configuring authentication against
-/// the domain controller, optionally adding servers and running CLI operations,
-/// then restarts in normal mode.
+/// Bootstraps the host by optionally copying a local configuration, renaming it,
+/// configuring authentication against the domain controller, optionally adding
+/// servers and running CLI operations, then restarts in normal mode. JDWP (via
+/// `WADO_DEBUG`) is only enabled for the host controller process of the final start.
+/// Servers listed in `WADO_SERVER_DEBUG` get JDWP JVM options.
 // language=shell script
 pub static HOST_CONTROLLER_ENTRYPOINT_SH: &str = r#"#!/bin/bash
 
-$JBOSS_HOME/bin/domain.sh $@ --admin-only &
-until `$JBOSS_HOME/bin/jboss-cli.sh -c "/host=$HOSTNAME:read-attribute(name=host-state)" 2> /dev/null | grep -q running`; do
-    sleep 1
-done
-echo "[== Bootstrap WildFly Host Controller $WILDFLY_VERSION ==]"
-echo "[-- Rename $HOSTNAME to $WADO_HOSTNAME --]"
-$JBOSS_HOME/bin/jboss-cli.sh -c --commands="/host=$HOSTNAME:write-attribute(name=name,value=$WADO_HOSTNAME),/host=$HOSTNAME:reload(admin-only)"
-until `$JBOSS_HOME/bin/jboss-cli.sh -c "/host=$WADO_HOSTNAME:read-attribute(name=host-state)" 2> /dev/null | grep -q running`; do
-    sleep 1
-done
-echo "[-- Add authentication context --]"
-$JBOSS_HOME/bin/jboss-cli.sh -c --commands="/host=$WADO_HOSTNAME/subsystem=elytron/authentication-configuration=wac-auth-config:add(sasl-mechanism-selector=DIGEST-MD5,authentication-name=$WADO_USERNAME,realm=ManagementRealm,credential-reference={clear-text=$WADO_PASSWORD}),/host=$WADO_HOSTNAME/subsystem=elytron/authentication-context=wac-auth-context:add(match-rules=[{match-host=$WADO_DOMAIN_CONTROLLER,authentication-configuration=wac-auth-config}]),/host=$WADO_HOSTNAME:write-attribute(name=domain-controller.remote.authentication-context,value=wac-auth-context)"
-if [[ ! -z $WADO_SERVERS ]]; then
-    echo "[-- Add servers: $WADO_SERVERS --]"
-    $JBOSS_HOME/bin/jboss-cli.sh -c --commands="$WADO_SERVERS"
+if [[ ! -f $JBOSS_HOME/.wado-bootstrapped ]]; then
+    if [[ ! -z $WADO_CONFIG ]]; then
+        cp "$WADO_CONFIG" $JBOSS_HOME/domain/configuration/
+    fi
+    $JBOSS_HOME/bin/domain.sh $@ --admin-only &
+    until `$JBOSS_HOME/bin/jboss-cli.sh -c "/host=$HOSTNAME:read-attribute(name=host-state)" 2> /dev/null | grep -q running`; do
+        sleep 1
+    done
+    echo "[== Bootstrap WildFly Host Controller $WILDFLY_VERSION ==]"
+    echo "[-- Rename $HOSTNAME to $WADO_HOSTNAME --]"
+    $JBOSS_HOME/bin/jboss-cli.sh -c --commands="/host=$HOSTNAME:write-attribute(name=name,value=$WADO_HOSTNAME),/host=$HOSTNAME:reload(admin-only)"
+    until `$JBOSS_HOME/bin/jboss-cli.sh -c "/host=$WADO_HOSTNAME:read-attribute(name=host-state)" 2> /dev/null | grep -q running`; do
+        sleep 1
+    done
+    if $JBOSS_HOME/bin/jboss-cli.sh -c --command="/host=$WADO_HOSTNAME/subsystem=elytron/authentication-context=wac-auth-context:read-resource" > /dev/null 2>&1; then
+        # restored host configurations already contain the authentication context
+        echo "[-- Update authentication context --]"
+        $JBOSS_HOME/bin/jboss-cli.sh -c --commands="/host=$WADO_HOSTNAME/subsystem=elytron/authentication-configuration=wac-auth-config:write-attribute(name=authentication-name,value=$WADO_USERNAME),/host=$WADO_HOSTNAME/subsystem=elytron/authentication-configuration=wac-auth-config:write-attribute(name=credential-reference,value={clear-text=$WADO_PASSWORD}),/host=$WADO_HOSTNAME/subsystem=elytron/authentication-context=wac-auth-context:write-attribute(name=match-rules,value=[{match-host=$WADO_DOMAIN_CONTROLLER,authentication-configuration=wac-auth-config}])"
+    else
+        echo "[-- Add authentication context --]"
+        $JBOSS_HOME/bin/jboss-cli.sh -c --commands="/host=$WADO_HOSTNAME/subsystem=elytron/authentication-configuration=wac-auth-config:add(sasl-mechanism-selector=DIGEST-MD5,authentication-name=$WADO_USERNAME,realm=ManagementRealm,credential-reference={clear-text=$WADO_PASSWORD}),/host=$WADO_HOSTNAME/subsystem=elytron/authentication-context=wac-auth-context:add(match-rules=[{match-host=$WADO_DOMAIN_CONTROLLER,authentication-configuration=wac-auth-config}]),/host=$WADO_HOSTNAME:write-attribute(name=domain-controller.remote.authentication-context,value=wac-auth-context)"
+    fi
+    if [[ ! -z $WADO_SERVERS ]]; then
+        echo "[-- Add servers: $WADO_SERVERS --]"
+        $JBOSS_HOME/bin/jboss-cli.sh -c --commands="$WADO_SERVERS"
+    fi
+    if [[ ! -z $WADO_BOOTSTRAP_OPERATIONS ]]; then
+        echo "[-- Execute bootstrap operation: $WADO_BOOTSTRAP_OPERATIONS --]"
+        $JBOSS_HOME/bin/jboss-cli.sh -c --commands="$WADO_BOOTSTRAP_OPERATIONS"
+    fi
+    if [[ ! -z $WADO_SERVER_DEBUG ]]; then
+        if java -version 2>&1 | grep -q 'version "1\.'; then DEBUG_HOST=""; else DEBUG_HOST="*:"; fi
+        for server_debug in ${WADO_SERVER_DEBUG//,/ }; do
+            IFS=: read -r server port suspend <<< "$server_debug"
+            echo "[-- Enable debugging of $server on port $port --]"
+            $JBOSS_HOME/bin/jboss-cli.sh -c --command="/host=$WADO_HOSTNAME/server-config=$server/jvm=default:add(jvm-options=[\"-agentlib:jdwp=transport=dt_socket,address=$DEBUG_HOST$port,server=y,suspend=$suspend\"])"
+        done
+    fi
+    $JBOSS_HOME/bin/jboss-cli.sh -c "/host=$WADO_HOSTNAME:shutdown()"
+    echo "[== Bootstrap finished ==]"
+    touch $JBOSS_HOME/.wado-bootstrapped
 fi
-if [[ ! -z $WADO_BOOTSTRAP_OPERATIONS ]]; then
-    echo "[-- Execute bootstrap operation: $WADO_BOOTSTRAP_OPERATIONS --]"
-    $JBOSS_HOME/bin/jboss-cli.sh -c --commands="$WADO_BOOTSTRAP_OPERATIONS"
+if [[ ! -z $WADO_DEBUG ]]; then
+    if java -version 2>&1 | grep -q 'version "1\.'; then DEBUG_ADDRESS=8787; else DEBUG_ADDRESS="*:8787"; fi
+    # domain.conf only applies its defaults if HOST_CONTROLLER_JAVA_OPTS is unset, so append to them
+    export HOST_CONTROLLER_JAVA_OPTS="$(. $JBOSS_HOME/bin/domain.conf > /dev/null 2>&1; echo "$HOST_CONTROLLER_JAVA_OPTS") -agentlib:jdwp=transport=dt_socket,address=$DEBUG_ADDRESS,server=y,suspend=$WADO_DEBUG"
 fi
-$JBOSS_HOME/bin/jboss-cli.sh -c "/host=$WADO_HOSTNAME:shutdown()"
-echo "[== Bootstrap finished ==]"
 $JBOSS_HOME/bin/domain.sh $@
 "#;
@@ -0,0 +1,261 @@
+//! Server configuration files.
+//!
+//! A configuration is either one of the built-in `standalone*.xml` / `domain.xml`
+//! files of a WildFly version (`host*.xml` for host controllers) or a local XML file. Local files are mounted into
+//! [`CONFIGURATION_DIRECTORY`] and copied into the configuration directory of the
+//! server by the entrypoint before WildFly boots.
+
+use crate::constants::CONFIGURATION_DIRECTORY;
+use crate::container::Mount;
+use crate::wildfly::ServerType;
+use anyhow::{Context, bail};
+use std::path::{Path, PathBuf};
+use wildfly_meta::WildFlyImage;
+
+/// A built-in or local server configuration file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ServerConfig {
+    BuiltIn(String),
+    Local(PathBuf),
+}
+
+impl ServerConfig {
+    /// Parses the value of `--config`. Values naming an existing file (relative to
+    /// `base_dir`) or containing a path separator are local files, all other
+    /// values are names of built-in configurations.
+    pub fn parse(value: &str, base_dir: &Path) -> anyhow::Result<ServerConfig> {
+        let path = base_dir.join(value);
+        if path.is_file() || value.contains('/') {
+            let path = path
+                .canonicalize()
+                .with_context(|| format!("Configuration not found: {}", value))?;
+            if !path.is_file() {
+                bail!("Configuration {} is not a file", value);
+            }
+            if path.extension().is_none_or(|extension| extension != "xml") {
+                bail!("Configuration {} is not an XML file", value);
+            }
+            Ok(ServerConfig::Local(path))
+        } else {
+            Ok(ServerConfig::BuiltIn(value.to_string()))
+        }
+    }
+
+    /// Returns the file name used for `-c <name>`.
+    pub fn name(&self) -> String {
+        match self {
+            ServerConfig::BuiltIn(name) => name.clone(),
+            ServerConfig::Local(path) => path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Verifies that a built-in configuration is part of the given WildFly version.
+    pub fn validate(
+        &self,
+        wildfly_image: &WildFlyImage,
+        server_type: ServerType,
+    ) -> anyhow::Result<()> {
+        if let ServerConfig::BuiltIn(name) = self {
+            let configs = builtin_configs(wildfly_image, server_type);
+            if !configs.contains(name) {
+                let kind = if server_type == ServerType::HostController {
+                    "host configuration"
+                } else {
+                    "configuration"
+                };
+                bail!(
+                    "Unknown {} '{}' for {}. Valid configurations are: {}",
+                    kind,
+                    name,
+                    wildfly_image.short_name(),
+                    configs.join(", ")
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the read-only mount of a local configuration file.
+    pub fn mount(&self) -> Option<Mount> {
+        match self {
+            ServerConfig::BuiltIn(_) => None,
+            ServerConfig::Local(path) => Some(Mount {
+                source: path.to_string_lossy().to_string(),
+                target: format!("{}/{}", CONFIGURATION_DIRECTORY, self.name()),
+                options: Some("ro".to_string()),
+            }),
+        }
+    }
+
+    /// Returns the WildFly parameters to boot with this configuration: `-c <name>` for
+    /// standalone servers and domain controllers, `--host-config <name>` for host controllers.
+    pub fn parameters(&self, server_type: ServerType) -> Vec<String> {
+        let option = match server_type {
+            ServerType::HostController => "--host-config",
+            ServerType::Standalone | ServerType::DomainController => "-c",
+        };
+        vec![option.to_string(), self.name()]
+    }
+}
+
+/// Returns the configuration files shipped with the given WildFly version,
+/// including the `-no-auth` variants added by wado. Host controllers are started with
+/// a host configuration, since they get the domain configuration from the domain controller.
+pub fn builtin_configs(wildfly_image: &WildFlyImage, server_type: ServerType) -> Vec<String> {
+    let configs = match server_type {
+        ServerType::Standalone => {
+            let major = wildfly_image.version.major;
+            let dev = wildfly_image.is_dev();
+            let mut profiles = vec!["", "-ha", "-full", "-full-ha"];
+            if dev || major >= 11 {
+                profiles.push("-load-balancer");
+            }
+            if dev || major >= 19 {
+                profiles.extend(["-microprofile", "-microprofile-ha"]);
+            }
+            profiles
+                .iter()
+                .map(|profile| format!("standalone{}.xml", profile))
+                .collect::<Vec<_>>()
+        }
+        ServerType::DomainController => return vec!["domain.xml".to_string()],
+        ServerType::HostController => vec![
+            "host.xml".to_string(),
+            default_host_config(wildfly_image, ServerType::DomainController).unwrap_or_default(),
+            default_host_config(wildfly_image, ServerType::HostController).unwrap_or_default(),
+        ],
+    };
+    configs
+        .into_iter()
+        .flat_map(|config| {
+            let no_auth = config.replace(".xml", "-no-auth.xml");
+            [config, no_auth]
+        })
+        .collect()
+}
+
+/// Returns the host configuration domain and host controllers are started with:
+/// `host-primary.xml` / `host-secondary.xml` or `host-master.xml` / `host-slave.xml`
+/// before WildFly 27.
+pub fn default_host_config(
+    wildfly_image: &WildFlyImage,
+    server_type: ServerType,
+) -> Option<String> {
+    let legacy = !wildfly_image.is_dev() && wildfly_image.version.major < 27;
+    let name = match (server_type, legacy) {
+        (ServerType::Standalone, _) => return None,
+        (ServerType::DomainController, true) => "master",
+        (ServerType::DomainController, false) => "primary",
+        (ServerType::HostController, true) => "slave",
+        (ServerType::HostController, false) => "secondary",
+    };
+    Some(format!("host-{}.xml", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wildfly_meta::WildFlyImageRegistry;
+
+    fn test_registry() -> WildFlyImageRegistry {
+        WildFlyImageRegistry::from_toml(include_str!("../../testdata/wildfly-images.toml"))
+            .expect("failed to parse test registry")
+    }
+
+    #[test]
+    fn parse_builtin() {
+        let config = ServerConfig::parse("standalone-full-ha.xml", Path::new("/")).unwrap();
+        assert_eq!(
+            config,
+            ServerConfig::BuiltIn("standalone-full-ha.xml".to_string())
+        );
+        assert_eq!(config.name(), "standalone-full-ha.xml");
+        assert_eq!(config.mount(), None);
+        assert_eq!(
+            config.parameters(ServerType::Standalone),
+            vec!["-c", "standalone-full-ha.xml"]
+        );
+    }
+
+    #[test]
+    fn parse_local() {
+        let dir = std::env::temp_dir().join("wado-config-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("custom.xml"), "<server/>").unwrap();
+        std::fs::write(dir.join("custom.txt"), "").unwrap();
+
+        let config = ServerConfig::parse("custom.xml", &dir).unwrap();
+        assert_eq!(config.name(), "custom.xml");
+        let mount = config.mount().unwrap();
+        assert_eq!(
+            mount.target,
+            format!("{}/custom.xml", CONFIGURATION_DIRECTORY)
+        );
+        assert_eq!(mount.options.as_deref(), Some("ro"));
+
+        assert!(ServerConfig::parse("./custom.txt", &dir).is_err());
+        assert!(ServerConfig::parse("./missing.xml", &dir).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn validate_builtin() {
+        let registry = test_registry();
+        let wf10 = registry.get(100).unwrap();
+        let wf26 = registry.get(261).unwrap();
+        let microprofile = ServerConfig::BuiltIn("standalone-microprofile.xml".to_string());
+        assert!(microprofile.validate(wf10, ServerType::Standalone).is_err());
+        assert!(microprofile.validate(wf26, ServerType::Standalone).is_ok());
+        assert!(
+            ServerConfig::BuiltIn("standalone-full-no-auth.xml".to_string())
+                .validate(wf10, ServerType::Standalone)
+                .is_ok()
+        );
+        assert!(
+            ServerConfig::BuiltIn("domain.xml".to_string())
+                .validate(wf26, ServerType::Standalone)
+                .is_err()
+        );
+        assert!(
+            ServerConfig::BuiltIn("domain.xml".to_string())
+                .validate(wf26, ServerType::DomainController)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn validate_host_config() {
+        let registry = test_registry();
+        let wf26 = registry.get(261).unwrap();
+        let wf34 = registry.get(340).unwrap();
+        let secondary = ServerConfig::BuiltIn("host-secondary.xml".to_string());
+        assert!(
+            secondary
+                .validate(wf26, ServerType::HostController)
+                .is_err()
+        );
+        assert!(secondary.validate(wf34, ServerType::HostController).is_ok());
+        assert!(
+            ServerConfig::BuiltIn("host-slave-no-auth.xml".to_string())
+                .validate(wf26, ServerType::HostController)
+                .is_ok()
+        );
+        assert!(
+            ServerConfig::BuiltIn("domain.xml".to_string())
+                .validate(wf34, ServerType::HostController)
+                .is_err()
+        );
+        assert_eq!(
+            secondary.parameters(ServerType::HostController),
+            vec!["--host-config", "host-secondary.xml"]
+        );
+        assert_eq!(
+            default_host_config(wf26, ServerType::HostController).as_deref(),
+            Some("host-slave.xml")
+        );
+        assert_eq!(default_host_config(wf34, ServerType::Standalone), None);
+    }
+}
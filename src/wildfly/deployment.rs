@@ -0,0 +1,471 @@
+//! Deployments using the HTTP management API.
+//!
+//! Archives are uploaded as managed content. Exploded directories are added as
+//! empty managed deployments which are filled using `add-content`. On domain
+//! controllers, deployments are assigned to server groups.
+
+use anyhow::{Context, bail};
+use serde_json::{Value, json};
+use std::fs;
+use std::path::Path;
+
+use super::ManagementApi;
+
+// ------------------------------------------------------ deployment
+
+/// An archive or exploded directory to deploy.
+pub struct Deployment {
+    pub name: String,
+    pub content: DeploymentContent,
+}
+
+pub enum DeploymentContent {
+    Archive(Vec<u8>),
+    /// Files of an exploded deployment as pairs of relative path and content.
+    Exploded(Vec<(String, Vec<u8>)>),
+}
+
+/// A deployment of a running server or domain controller.
+pub struct DeploymentState {
+    pub name: String,
+    pub enabled: bool,
+    /// The runtime status (standalone servers only).
+    pub status: Option<String>,
+    /// The server groups the deployment is assigned to (domain controllers only).
+    pub server_groups: Vec<String>,
+}
+
+impl Deployment {
+    /// Reads an archive or exploded directory. The deployment name is the file name.
+    pub fn load(path: &Path) -> anyhow::Result<Deployment> {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .with_context(|| format!("Invalid deployment: {}", path.display()))?;
+        let content = if path.is_dir() {
+            let mut files = vec![];
+            collect_files(path, path, &mut files)?;
+            if files.is_empty() {
+                bail!("Deployment directory {} is empty", path.display());
+            }
+            DeploymentContent::Exploded(files)
+        } else {
+            DeploymentContent::Archive(
+                fs::read(path)
+                    .with_context(|| format!("Failed to read deployment {}", path.display()))?,
+            )
+        };
+        Ok(Deployment { name, content })
+    }
+}
+
+fn collect_files(
+    root: &Path,
+    directory: &Path,
+    files: &mut Vec<(String, Vec<u8>)>,
+) -> anyhow::Result<()> {
+    let mut entries = fs::read_dir(directory)
+        .with_context(|| format!("Failed to read directory {}", directory.display()))?
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(root, &path, files)?;
+        } else {
+            let relative = path
+                .strip_prefix(root)?
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join("/");
+            let content =
+                fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+            files.push((relative, content));
+        }
+    }
+    Ok(())
+}
+
+// ------------------------------------------------------ management api
+
+impl ManagementApi {
+    /// Deploys to a standalone server (`server_groups` is `None`) or to the given
+    /// server groups of a domain controller. Existing deployments are only
+    /// replaced if `force` is set.
+    pub async fn deploy(
+        &self,
+        deployment: &Deployment,
+        server_groups: Option<&[String]>,
+        force: bool,
+    ) -> anyhow::Result<()> {
+        if self.deployment_names().await?.contains(&deployment.name) {
+            if force {
+                self.undeploy(&deployment.name, server_groups.is_some())
+                    .await?;
+            } else {
+                bail!(
+                    "Deployment {} already exists. Use --force to redeploy.",
+                    deployment.name
+                );
+            }
+        }
+        match &deployment.content {
+            DeploymentContent::Archive(content) => {
+                let operation = deploy_archive_operation(&deployment.name, server_groups);
+                self.upload(&operation, &[(deployment.name.as_str(), content)])
+                    .await?;
+            }
+            DeploymentContent::Exploded(files) => {
+                self.execute(&json!({
+                    "operation": "add",
+                    "address": deployment_address(&deployment.name),
+                    "content": [{"empty": true}],
+                }))
+                .await?;
+                let attachments = files
+                    .iter()
+                    .map(|(path, content)| (path.as_str(), content.as_slice()))
+                    .collect::<Vec<_>>();
+                let added = async {
+                    self.upload(
+                        &add_content_operation(&deployment.name, files),
+                        &attachments,
+                    )
+                    .await?;
+                    self.execute(&enable_operation(&deployment.name, server_groups))
+                        .await
+                }
+                .await;
+                if let Err(error) = added {
+                    // don't leave a half-baked deployment behind
+                    let _ = self
+                        .undeploy(&deployment.name, server_groups.is_some())
+                        .await;
+                    return Err(error);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Assigns an existing deployment of a domain controller to the server groups which
+    /// don't have it yet.
+    pub async fn assign(&self, name: &str, server_groups: &[String]) -> anyhow::Result<()> {
+        let assigned = self
+            .deployment_states(true)
+            .await?
+            .into_iter()
+            .find(|state| state.name == name)
+            .map(|state| state.server_groups)
+            .unwrap_or_default();
+        let missing = server_groups
+            .iter()
+            .filter(|group| !assigned.contains(group))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            self.execute(&enable_operation(name, Some(&missing)))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Undeploys and removes a deployment. On domain controllers, the deployment
+    /// is removed from all server groups first.
+    pub async fn undeploy(&self, name: &str, domain: bool) -> anyhow::Result<()> {
+        let mut steps = vec![];
+        if domain {
+            for group in self.server_group_names().await? {
+                let deployments = self
+                    .execute(&read_children_names(
+                        json!([{"server-group": group}]),
+                        "deployment",
+                    ))
+                    .await?;
+                if string_list(&deployments).iter().any(|n| n == name) {
+                    steps.push(json!({
+                        "operation": "remove",
+                        "address": server_group_deployment_address(&group, name),
+                    }));
+                }
+            }
+        } else {
+            steps.push(json!({"operation": "undeploy", "address": deployment_address(name)}));
+        }
+        steps.push(json!({"operation": "remove", "address": deployment_address(name)}));
+        self.execute(&composite(steps)).await?;
+        Ok(())
+    }
+
+    /// Returns the names of all deployments.
+    pub async fn deployment_names(&self) -> anyhow::Result<Vec<String>> {
+        let names = self
+            .execute(&read_children_names(json!([]), "deployment"))
+            .await?;
+        Ok(string_list(&names))
+    }
+
+    /// Returns the names of all server groups of a domain controller.
+    pub async fn server_group_names(&self) -> anyhow::Result<Vec<String>> {
+        let names = self
+            .execute(&read_children_names(json!([]), "server-group"))
+            .await?;
+        Ok(string_list(&names))
+    }
+
+    /// Reads the deployments of a standalone server or domain controller.
+    pub async fn deployment_states(&self, domain: bool) -> anyhow::Result<Vec<DeploymentState>> {
+        let deployments = self
+            .execute(&json!({
+                "operation": "read-children-resources",
+                "address": [],
+                "child-type": "deployment",
+                "include-runtime": true,
+            }))
+            .await?;
+        let server_groups = if domain {
+            self.execute(&json!({
+                "operation": "read-children-resources",
+                "address": [],
+                "child-type": "server-group",
+                "recursive": true,
+                "recursive-depth": 1,
+            }))
+            .await?
+        } else {
+            Value::Null
+        };
+        Ok(parse_deployment_states(
+            &deployments,
+            &server_groups,
+            domain,
+        ))
+    }
+}
+
+// ------------------------------------------------------ operations
+
+fn deploy_archive_operation(name: &str, server_groups: Option<&[String]>) -> Value {
+    let content = json!([{"input-stream-index": 0}]);
+    match server_groups {
+        None => json!({
+            "operation": "add",
+            "address": deployment_address(name),
+            "content": content,
+            "enabled": true,
+        }),
+        Some(server_groups) => {
+            let mut steps = vec![json!({
+                "operation": "add",
+                "address": deployment_address(name),
+                "content": content,
+            })];
+            steps.extend(server_groups.iter().map(|group| {
+                json!({
+                    "operation": "add",
+                    "address": server_group_deployment_address(group, name),
+                    "enabled": true,
+                })
+            }));
+            composite(steps)
+        }
+    }
+}
+
+fn add_content_operation(name: &str, files: &[(String, Vec<u8>)]) -> Value {
+    let content = files
+        .iter()
+        .enumerate()
+        .map(|(index, (path, _))| json!({"input-stream-index": index, "target-path": path}))
+        .collect::<Vec<_>>();
+    json!({
+        "operation": "add-content",
+        "address": deployment_address(name),
+        "content": content,
+    })
+}
+
+fn enable_operation(name: &str, server_groups: Option<&[String]>) -> Value {
+    match server_groups {
+        None => json!({"operation": "deploy", "address": deployment_address(name)}),
+        Some(server_groups) => composite(
+            server_groups
+                .iter()
+                .map(|group| {
+                    json!({
+                        "operation": "add",
+                        "address": server_group_deployment_address(group, name),
+                        "enabled": true,
+                    })
+                })
+                .collect(),
+        ),
+    }
+}
+
+fn parse_deployment_states(
+    deployments: &Value,
+    server_groups: &Value,
+    domain: bool,
+) -> Vec<DeploymentState> {
+    let Some(deployments) = deployments.as_object() else {
+        return vec![];
+    };
+    deployments
+        .iter()
+        .map(|(name, deployment)| {
+            if domain {
+                let groups = server_groups
+                    .as_object()
+                    .map(|groups| {
+                        groups
+                            .iter()
+                            .filter_map(|(group, resource)| {
+                                resource
+                                    .get("deployment")
+                                    .and_then(|d| d.get(name))
+                                    .map(|d| (group.clone(), enabled(d)))
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                DeploymentState {
+                    name: name.clone(),
+                    enabled: groups.iter().any(|(_, enabled)| *enabled),
+                    status: None,
+                    server_groups: groups.into_iter().map(|(group, _)| group).collect(),
+                }
+            } else {
+                DeploymentState {
+                    name: name.clone(),
+                    enabled: enabled(deployment),
+                    status: deployment
+                        .get("status")
+                        .and_then(Value::as_str)
+                        .map(str::to_string),
+                    server_groups: vec![],
+                }
+            }
+        })
+        .collect()
+}
+
+// ------------------------------------------------------ helpers
+
+fn deployment_address(name: &str) -> Value {
+    json!([{"deployment": name}])
+}
+
+fn server_group_deployment_address(group: &str, name: &str) -> Value {
+    json!([{"server-group": group}, {"deployment": name}])
+}
+
+fn composite(steps: Vec<Value>) -> Value {
+    json!({"operation": "composite", "address": [], "steps": steps})
+}
+
+fn read_children_names(address: Value, child_type: &str) -> Value {
+    json!({
+        "operation": "read-children-names",
+        "address": address,
+        "child-type": child_type,
+    })
+}
+
+fn enabled(resource: &Value) -> bool {
+    resource.get("enabled").and_then(Value::as_bool) == Some(true)
+}
+
+fn string_list(value: &Value) -> Vec<String> {
+    value
+        .as_array()
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// ------------------------------------------------------ tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standalone_archive_operation() {
+        assert_eq!(
+            deploy_archive_operation("app.war", None),
+            json!({
+                "operation": "add",
+                "address": [{"deployment": "app.war"}],
+                "content": [{"input-stream-index": 0}],
+                "enabled": true,
+            })
+        );
+    }
+
+    #[test]
+    fn domain_archive_operation() {
+        let groups = vec!["main-server-group".to_string()];
+        let operation = deploy_archive_operation("app.war", Some(&groups));
+        assert_eq!(operation["operation"], "composite");
+        assert_eq!(
+            operation["steps"][1],
+            json!({
+                "operation": "add",
+                "address": [{"server-group": "main-server-group"}, {"deployment": "app.war"}],
+                "enabled": true,
+            })
+        );
+    }
+
+    #[test]
+    fn add_content() {
+        let files = vec![
+            ("index.html".to_string(), vec![]),
+            ("WEB-INF/web.xml".to_string(), vec![]),
+        ];
+        assert_eq!(
+            add_content_operation("app.war", &files)["content"],
+            json!([
+                {"input-stream-index": 0, "target-path": "index.html"},
+                {"input-stream-index": 1, "target-path": "WEB-INF/web.xml"},
+            ])
+        );
+    }
+
+    #[test]
+    fn standalone_states() {
+        let deployments = json!({
+            "app.war": {"enabled": true, "status": "OK"},
+            "other.war": {"enabled": false, "status": "STOPPED"},
+        });
+        let states = parse_deployment_states(&deployments, &Value::Null, false);
+        assert_eq!(states.len(), 2);
+        assert!(states[0].enabled);
+        assert_eq!(states[0].status.as_deref(), Some("OK"));
+        assert!(!states[1].enabled);
+    }
+
+    #[test]
+    fn domain_states() {
+        let deployments = json!({"app.war": {"name": "app.war"}, "other.war": {}});
+        let server_groups = json!({
+            "main-server-group": {"deployment": {"app.war": {"enabled": true}}},
+            "other-server-group": {"deployment": {"app.war": {"enabled": false}}},
+        });
+        let states = parse_deployment_states(&deployments, &server_groups, true);
+        assert!(states[0].enabled);
+        assert_eq!(
+            states[0].server_groups,
+            vec!["main-server-group", "other-server-group"]
+        );
+        assert!(!states[1].enabled);
+        assert!(states[1].server_groups.is_empty());
+    }
+}
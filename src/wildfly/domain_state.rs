@@ -0,0 +1,289 @@
+//! Runtime state of a managed domain using the HTTP management API.
+//!
+//! Reads the hosts registered at a domain controller together with their
+//! server configurations and the runtime state of the running servers as well
+//! as the server groups and system properties of the domain.
+
+use serde_json::{Map, Value, json};
+use std::collections::BTreeMap;
+
+use super::ManagementApi;
+
+/// A host registered at the domain controller.
+pub struct DomainHost {
+    pub name: String,
+    /// Whether this host is the domain controller.
+    pub primary: bool,
+    pub host_state: Option<String>,
+    pub release_version: Option<String>,
+    pub servers: Vec<DomainServer>,
+}
+
+/// A server configuration of a [`DomainHost`].
+pub struct DomainServer {
+    pub name: String,
+    pub group: Option<String>,
+    pub offset: u16,
+    pub auto_start: bool,
+    /// The status of the server configuration (e.g. `STARTED` or `STOPPED`).
+    pub status: Option<String>,
+    /// The `server-state` of running servers (e.g. `running` or `reload-required`).
+    pub server_state: Option<String>,
+}
+
+/// A server group of the domain.
+pub struct DomainServerGroup {
+    pub name: String,
+    pub profile: Option<String>,
+    pub socket_binding_group: Option<String>,
+    /// The JVM resources of the server group by name.
+    pub jvms: BTreeMap<String, Value>,
+}
+
+impl ManagementApi {
+    /// Reads the server groups of the domain incl. their JVMs.
+    pub async fn domain_server_groups(&self) -> anyhow::Result<Vec<DomainServerGroup>> {
+        let groups = self
+            .execute(&json!({
+                "operation": "read-children-resources",
+                "address": [],
+                "child-type": "server-group",
+                "recursive-depth": 1,
+            }))
+            .await?;
+        Ok(parse_server_groups(&groups))
+    }
+
+    /// Reads the domain-wide system properties as pairs of name and value.
+    pub async fn domain_system_properties(&self) -> anyhow::Result<BTreeMap<String, String>> {
+        let properties = self
+            .execute(&json!({
+                "operation": "read-children-resources",
+                "address": [],
+                "child-type": "system-property",
+            }))
+            .await?;
+        Ok(properties
+            .as_object()
+            .map(|properties| {
+                properties
+                    .iter()
+                    .map(|(name, property)| {
+                        (name.clone(), string(property, "value").unwrap_or_default())
+                    })
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// Reads the hosts and servers of the domain controller.
+    pub async fn domain_hosts(&self) -> anyhow::Result<Vec<DomainHost>> {
+        let hosts = self
+            .execute(&json!({
+                "operation": "read-children-resources",
+                "address": [],
+                "child-type": "host",
+                "include-runtime": true,
+            }))
+            .await?;
+        let server_configs = self
+            .execute(&json!({
+                "operation": "read-resource",
+                "address": [{"host": "*"}, {"server-config": "*"}],
+                "include-runtime": true,
+            }))
+            .await?;
+        // Only running servers have a server resource, so failures are not an error.
+        let server_states = self
+            .execute(&json!({
+                "operation": "read-attribute",
+                "address": [{"host": "*"}, {"server": "*"}],
+                "name": "server-state",
+            }))
+            .await
+            .unwrap_or(Value::Null);
+        Ok(parse_domain_hosts(&hosts, &server_configs, &server_states))
+    }
+}
+
+fn parse_domain_hosts(
+    hosts: &Value,
+    server_configs: &Value,
+    server_states: &Value,
+) -> Vec<DomainHost> {
+    let Some(hosts) = hosts.as_object() else {
+        return vec![];
+    };
+    let server_configs = wildcard_results(server_configs);
+    let server_states = wildcard_results(server_states);
+    hosts
+        .iter()
+        .map(|(name, host)| DomainHost {
+            name: name.clone(),
+            primary: host
+                .get("primary")
+                .or_else(|| host.get("master"))
+                .and_then(Value::as_bool)
+                .unwrap_or_default(),
+            host_state: string(host, "host-state"),
+            release_version: string(host, "release-version"),
+            servers: server_configs
+                .iter()
+                .filter(|(host, _, _)| host == name)
+                .map(|(_, server, config)| DomainServer {
+                    name: server.clone(),
+                    group: string(config, "group"),
+                    offset: config
+                        .get("socket-binding-port-offset")
+                        .and_then(Value::as_u64)
+                        .and_then(|offset| u16::try_from(offset).ok())
+                        .unwrap_or_default(),
+                    auto_start: config
+                        .get("auto-start")
+                        .and_then(Value::as_bool)
+                        .unwrap_or_default(),
+                    status: string(config, "status"),
+                    server_state: server_states
+                        .iter()
+                        .find(|(h, s, _)| h == name && s == server)
+                        .and_then(|(_, _, state)| state.as_str())
+                        .map(str::to_string),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+fn parse_server_groups(groups: &Value) -> Vec<DomainServerGroup> {
+    let empty = Map::new();
+    groups
+        .as_object()
+        .unwrap_or(&empty)
+        .iter()
+        .map(|(name, group)| DomainServerGroup {
+            name: name.clone(),
+            profile: string(group, "profile"),
+            socket_binding_group: string(group, "socket-binding-group"),
+            jvms: group
+                .get("jvm")
+                .and_then(Value::as_object)
+                .map(|jvms| {
+                    jvms.iter()
+                        .map(|(name, jvm)| (name.clone(), jvm.clone()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// Returns the host name, the server name and the result of the successful steps
+/// of an operation with a `/host=*/<server-type>=*` address.
+fn wildcard_results(response: &Value) -> Vec<(String, String, &Value)> {
+    response
+        .as_array()
+        .map(|steps| {
+            steps
+                .iter()
+                .filter(|step| step.get("outcome").and_then(Value::as_str) == Some("success"))
+                .filter_map(|step| {
+                    let address = step.get("address")?.as_array()?;
+                    let host = address.first()?.get("host")?.as_str()?;
+                    let server = address.get(1)?.as_object()?.values().next()?.as_str()?;
+                    Some((host.to_string(), server.to_string(), step.get("result")?))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn string(value: &Value, name: &str) -> Option<String> {
+    value.get(name).and_then(Value::as_str).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hosts() {
+        let hosts = json!({
+            "dc": {"primary": true, "host-state": "running", "release-version": "34.0.1.Final"},
+            "host1": {"master": false, "host-state": "running", "release-version": "33.0.2.Final"},
+        });
+        let server_configs = json!([
+            {
+                "address": [{"host": "host1"}, {"server-config": "server-one"}],
+                "outcome": "success",
+                "result": {"group": "main-server-group", "status": "STARTED"}
+            },
+            {
+                "address": [{"host": "host1"}, {"server-config": "server-two"}],
+                "outcome": "success",
+                "result": {
+                    "group": "other-server-group",
+                    "status": "STOPPED",
+                    "socket-binding-port-offset": 100,
+                    "auto-start": true
+                }
+            }
+        ]);
+        let server_states = json!([
+            {
+                "address": [{"host": "host1"}, {"server": "server-one"}],
+                "outcome": "success",
+                "result": "running"
+            }
+        ]);
+        let hosts = parse_domain_hosts(&hosts, &server_configs, &server_states);
+        assert_eq!(hosts.len(), 2);
+        assert_eq!(hosts[0].name, "dc");
+        assert!(hosts[0].primary);
+        assert!(hosts[0].servers.is_empty());
+        assert_eq!(hosts[1].name, "host1");
+        assert!(!hosts[1].primary);
+        assert_eq!(hosts[1].release_version.as_deref(), Some("33.0.2.Final"));
+        assert_eq!(hosts[1].servers.len(), 2);
+        assert_eq!(hosts[1].servers[0].name, "server-one");
+        assert_eq!(
+            hosts[1].servers[0].group.as_deref(),
+            Some("main-server-group")
+        );
+        assert_eq!(hosts[1].servers[0].status.as_deref(), Some("STARTED"));
+        assert_eq!(hosts[1].servers[0].server_state.as_deref(), Some("running"));
+        assert_eq!(hosts[1].servers[1].server_state, None);
+        assert_eq!(hosts[1].servers[1].offset, 100);
+        assert!(hosts[1].servers[1].auto_start);
+    }
+
+    #[test]
+    fn parse_groups() {
+        let groups = json!({
+            "app-group": {
+                "profile": "full",
+                "socket-binding-group": "full-sockets",
+                "jvm": {"default": {"heap-size": "64m", "max-heap-size": null}}
+            },
+            "main-server-group": {
+                "profile": "default",
+                "socket-binding-group": "standard-sockets",
+                "jvm": null
+            }
+        });
+        let groups = parse_server_groups(&groups);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].name, "app-group");
+        assert_eq!(groups[0].profile.as_deref(), Some("full"));
+        assert_eq!(
+            groups[0].socket_binding_group.as_deref(),
+            Some("full-sockets")
+        );
+        assert_eq!(groups[0].jvms["default"]["heap-size"], "64m");
+        assert!(groups[1].jvms.is_empty());
+    }
+
+    #[test]
+    fn parse_no_hosts() {
+        assert!(parse_domain_hosts(&Value::Null, &Value::Null, &Value::Null).is_empty());
+    }
+}
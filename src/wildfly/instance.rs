@@ -4,6 +4,7 @@
 //! containers about to be started, and [`ContainerInstance`] for running containers
 //! parsed from `podman ps` output.
 
+use crate::constants::DEBUG_PORT_BASE;
 use crate::label::Label;
 use anyhow::bail;
 use std::cmp::Ordering;
@@ -13,11 +14,71 @@ use super::AdminImage;
 
 // ------------------------------------------------------ ports
 
-/// HTTP and management port pair for a container instance.
+/// Optional ports published with `--publish`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum ExtraPort {
+    Https,
+    ManagementHttps,
+    Ajp,
+    Remoting,
+}
+
+impl ExtraPort {
+    pub fn all() -> [ExtraPort; 4] {
+        [
+            ExtraPort::Https,
+            ExtraPort::ManagementHttps,
+            ExtraPort::Ajp,
+            ExtraPort::Remoting,
+        ]
+    }
+
+    /// Returns the name used by `--publish` and `wado ps`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExtraPort::Https => "https",
+            ExtraPort::ManagementHttps => "mgmt-https",
+            ExtraPort::Ajp => "ajp",
+            ExtraPort::Remoting => "remoting",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ExtraPort> {
+        ExtraPort::all()
+            .into_iter()
+            .find(|port| port.name() == name)
+    }
+
+    /// Returns the port inside the container.
+    pub fn container_port(&self) -> u16 {
+        match self {
+            ExtraPort::Https => 8443,
+            ExtraPort::ManagementHttps => 9993,
+            ExtraPort::Ajp => 8009,
+            ExtraPort::Remoting => 4447,
+        }
+    }
+
+    /// Computes the default host port from a WildFly image (HTTPS: `18<major><minor>`,
+    /// management HTTPS: `19<major><minor>`, AJP: `7<major><minor>`, remoting: `4<major><minor>`).
+    pub fn default_port(&self, wildfly_image: &WildFlyImage) -> u16 {
+        let base = match self {
+            ExtraPort::Https => 18000,
+            ExtraPort::ManagementHttps => 19000,
+            ExtraPort::Ajp => 7000,
+            ExtraPort::Remoting => 4000,
+        };
+        base + wildfly_image.identifier
+    }
+}
+
+/// HTTP and management port pair for a container instance plus the ports given by `--publish`.
 #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub struct Ports {
     pub http: u16,
     pub management: u16,
+    /// Additional host ports, ordered by [`ExtraPort`].
+    pub extra: Vec<(ExtraPort, u16)>,
 }
 
 impl Ports {
@@ -26,14 +87,33 @@ impl Ports {
         Ports {
             http: wildfly_image.http_port(),
             management: wildfly_image.management_port(),
+            extra: vec![],
         }
     }
 
+    /// Returns the host port of an additional port if it is published.
+    pub fn extra_port(&self, extra_port: ExtraPort) -> Option<u16> {
+        self.extra
+            .iter()
+            .find(|(port, _)| *port == extra_port)
+            .map(|(_, host_port)| *host_port)
+    }
+
+    /// Computes the default debug port from a WildFly image (`5<major><minor>`).
+    pub fn default_debug_port(wildfly_image: &WildFlyImage) -> u16 {
+        DEBUG_PORT_BASE + wildfly_image.identifier
+    }
+
     #[cfg(test)]
     pub fn with_offset(&self, offset: u16) -> Ports {
         Ports {
             http: self.http + offset,
             management: self.management + offset,
+            extra: self
+                .extra
+                .iter()
+                .map(|(port, host_port)| (*port, host_port + offset))
+                .collect(),
         }
     }
 }
@@ -46,14 +126,21 @@ pub struct StandaloneInstance {
     pub admin_image: AdminImage,
     pub name: String,
     pub ports: Ports,
+    pub debug_port: Option<u16>,
 }
 
 impl StandaloneInstance {
-    pub fn new(admin_image: AdminImage, name: String, ports: Ports) -> StandaloneInstance {
+    pub fn new(
+        admin_image: AdminImage,
+        name: String,
+        ports: Ports,
+        debug_port: Option<u16>,
+    ) -> StandaloneInstance {
         StandaloneInstance {
             admin_image,
             name,
             ports,
+            debug_port,
         }
     }
 }
@@ -68,14 +155,21 @@ pub struct DomainController {
     pub admin_image: AdminImage,
     pub name: String,
     pub ports: Ports,
+    pub debug_port: Option<u16>,
 }
 
 impl DomainController {
-    pub fn new(admin_image: AdminImage, name: String, ports: Ports) -> DomainController {
+    pub fn new(
+        admin_image: AdminImage,
+        name: String,
+        ports: Ports,
+        debug_port: Option<u16>,
+    ) -> DomainController {
         DomainController {
             admin_image,
             name,
             ports,
+            debug_port,
         }
     }
 }
@@ -90,14 +184,21 @@ pub struct HostController {
     pub admin_image: AdminImage,
     pub name: String,
     pub domain_controller: String,
+    pub debug_port: Option<u16>,
 }
 
 impl HostController {
-    pub fn new(admin_image: AdminImage, name: String, domain_controller: String) -> HostController {
+    pub fn new(
+        admin_image: AdminImage,
+        name: String,
+        domain_controller: String,
+        debug_port: Option<u16>,
+    ) -> HostController {
         HostController {
             admin_image,
             name,
             domain_controller,
+            debug_port,
         }
     }
 }
@@ -117,6 +218,13 @@ pub struct ContainerInstance {
     pub status: String,
     pub topology: Option<String>,
     pub config: Option<String>,
+    pub mounts: Vec<String>,
+    pub environment: Vec<String>,
+    pub debug_port: Option<u16>,
+    /// The published debug ports of domain servers as pairs of server and port.
+    pub server_debug_ports: Vec<(String, u16)>,
+    /// Whether the container was started with `--keep`.
+    pub keep: bool,
 }
 
 impl ContainerInstance {
@@ -142,6 +250,11 @@ impl ContainerInstance {
                 status: status.to_string(),
                 topology,
                 config,
+                mounts: vec![],
+                environment: vec![],
+                debug_port: None,
+                server_debug_ports: vec![],
+                keep: false,
             })
         } else {
             bail!("Invalid identifier: '{}'", identifier);
@@ -195,6 +308,8 @@ mod tests {
         let ports = Ports::default_ports(&img);
         assert_eq!(ports.http, img.http_port());
         assert_eq!(ports.management, img.management_port());
+        assert_eq!(Ports::default_debug_port(&img), 5390);
+        assert_eq!(Ports::default_debug_port(&wimg("dev")), 5000);
     }
 
     #[test]
@@ -202,10 +317,27 @@ mod tests {
         let ports = Ports {
             http: 8390,
             management: 9390,
+            extra: vec![(ExtraPort::Https, 18390)],
         };
         let shifted = ports.with_offset(2);
         assert_eq!(shifted.http, 8392);
         assert_eq!(shifted.management, 9392);
+        assert_eq!(shifted.extra_port(ExtraPort::Https), Some(18392));
+        assert_eq!(shifted.extra_port(ExtraPort::Ajp), None);
+    }
+
+    #[test]
+    fn extra_ports() {
+        let img = wimg("39");
+        assert_eq!(ExtraPort::Https.default_port(&img), 18390);
+        assert_eq!(ExtraPort::ManagementHttps.default_port(&img), 19390);
+        assert_eq!(ExtraPort::Ajp.default_port(&img), 7390);
+        assert_eq!(ExtraPort::Remoting.default_port(&img), 4390);
+        assert_eq!(
+            ExtraPort::from_name("mgmt-https"),
+            Some(ExtraPort::ManagementHttps)
+        );
+        assert_eq!(ExtraPort::from_name("ftp"), None);
     }
 
     #[test]
@@ -216,6 +348,7 @@ mod tests {
             ai.clone(),
             "my-server".to_string(),
             Ports::default_ports(&ai.wildfly_image),
+            None,
         );
         assert_eq!(si.name(), "my-server");
         assert_eq!(si.admin_image().server_type, ServerType::Standalone);
@@ -229,8 +362,10 @@ mod tests {
             ai.clone(),
             "dc-1".to_string(),
             Ports::default_ports(&ai.wildfly_image),
+            Some(5390),
         );
         assert_eq!(dc.name(), "dc-1");
+        assert_eq!(dc.debug_port(), Some(5390));
         assert_eq!(dc.admin_image().server_type, ServerType::DomainController);
     }
 
@@ -238,7 +373,7 @@ mod tests {
     fn host_controller_container_config() {
         use crate::wildfly::ContainerConfig;
         let ai = AdminImage::new(wimg("39"), ServerType::HostController);
-        let hc = HostController::new(ai.clone(), "hc-1".to_string(), "dc-1".to_string());
+        let hc = HostController::new(ai.clone(), "hc-1".to_string(), "dc-1".to_string(), None);
         assert_eq!(hc.name(), "hc-1");
         assert_eq!(hc.domain_controller, "dc-1");
     }
@@ -177,6 +177,11 @@ mod tests {
             status: "Up".to_string(),
             topology: None,
             config: None,
+            mounts: vec![],
+            environment: vec![],
+            debug_port: None,
+            server_debug_ports: vec![],
+            keep: false,
         };
         let client = ManagementClient::from_container_instance(&ci, &registry);
         assert_eq!(client.management_port, ai.wildfly_image.management_port());
@@ -0,0 +1,325 @@
+//! Native client for the HTTP management API of WildFly.
+//!
+//! Sends DMR operations as JSON to the `/management` endpoint of a running
+//! container. The management interface of the wado images is secured with
+//! HTTP digest authentication, which is handled transparently.
+
+use crate::error::WadoError;
+use anyhow::{Context, bail};
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, WWW_AUTHENTICATE};
+use reqwest::{Client, Response, StatusCode};
+use serde_json::Value;
+use std::time::Duration;
+
+use super::ManagementClient;
+
+const MANAGEMENT_PATH: &str = "/management";
+const UPLOAD_PATH: &str = "/management-upload";
+const MULTIPART_BOUNDARY: &str = "----wado-management-upload";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// The timeout of management operations. Uploads have no timeout, since the transfer
+/// of large archives can take arbitrarily long.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Client for the HTTP management API of a WildFly server or domain controller.
+pub struct ManagementApi {
+    client: Client,
+    base_url: String,
+    username: String,
+    password: String,
+}
+
+impl ManagementClient {
+    /// Creates a native HTTP management API client for this management interface.
+    pub fn http_api(&self, username: &str, password: &str) -> anyhow::Result<ManagementApi> {
+        ManagementApi::new(self.management_port, username, password)
+    }
+}
+
+impl ManagementApi {
+    /// Creates a client for the management interface published at the given port on localhost.
+    pub fn new(management_port: u16, username: &str, password: &str) -> anyhow::Result<Self> {
+        let client = Client::builder().connect_timeout(CONNECT_TIMEOUT).build()?;
+        Ok(ManagementApi {
+            client,
+            base_url: format!("http://localhost:{}", management_port),
+            username: username.to_string(),
+            password: password.to_string(),
+        })
+    }
+
+    /// Executes an operation and returns its result. Fails with the failure
+    /// description if the outcome is not `success`.
+    pub async fn execute(&self, operation: &Value) -> anyhow::Result<Value> {
+        result(self.execute_raw(operation).await?)
+    }
+
+    /// Executes an operation and returns the complete response
+    /// (including `outcome`, `result` and `failure-description`).
+    pub async fn execute_raw(&self, operation: &Value) -> anyhow::Result<Value> {
+        let body = serde_json::to_vec(operation)?;
+        self.send(
+            MANAGEMENT_PATH,
+            "application/json",
+            body,
+            Some(REQUEST_TIMEOUT),
+        )
+        .await
+    }
+
+    /// Executes an operation with attached content (e.g. deployment archives) and returns
+    /// its result. The attachments are referenced by their index using `input-stream-index`.
+    pub async fn upload(
+        &self,
+        operation: &Value,
+        attachments: &[(&str, &[u8])],
+    ) -> anyhow::Result<Value> {
+        let mut body = vec![];
+        for (index, (file_name, content)) in attachments.iter().enumerate() {
+            body.extend_from_slice(
+                format!(
+                    "--{}\r\nContent-Disposition: form-data; name=\"file-{}\"; filename=\"{}\"\r\n\
+                     Content-Type: application/octet-stream\r\n\r\n",
+                    MULTIPART_BOUNDARY,
+                    index,
+                    file_name.replace('"', "")
+                )
+                .as_bytes(),
+            );
+            body.extend_from_slice(content);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"operation\"\r\n\
+                 Content-Type: application/json\r\n\r\n{}\r\n--{}--\r\n",
+                MULTIPART_BOUNDARY,
+                serde_json::to_string(operation)?,
+                MULTIPART_BOUNDARY
+            )
+            .as_bytes(),
+        );
+        let content_type = format!("multipart/form-data; boundary={}", MULTIPART_BOUNDARY);
+        result(self.send(UPLOAD_PATH, &content_type, body, None).await?)
+    }
+
+    async fn send(
+        &self,
+        path: &str,
+        content_type: &str,
+        body: Vec<u8>,
+        timeout: Option<Duration>,
+    ) -> anyhow::Result<Value> {
+        let url = format!("{}{}", self.base_url, path);
+        let mut response = self.post(&url, content_type, &body, None, timeout).await?;
+        if response.status() == StatusCode::UNAUTHORIZED {
+            let authorization = self.authorization(&response, path, &body)?;
+            response = self
+                .post(&url, content_type, &body, Some(authorization), timeout)
+                .await?;
+            if response.status() == StatusCode::UNAUTHORIZED {
+                bail!(WadoError::management_operation_failed(&format!(
+                    "Authentication failed for user '{}'",
+                    self.username
+                )));
+            }
+        }
+        let status = response.status();
+        let text = response.text().await?;
+        serde_json::from_str(&text).with_context(|| {
+            format!(
+                "Unexpected response from {} ({}): {}",
+                url,
+                status,
+                text.trim()
+            )
+        })
+    }
+
+    async fn post(
+        &self,
+        url: &str,
+        content_type: &str,
+        body: &[u8],
+        authorization: Option<Authorization>,
+        timeout: Option<Duration>,
+    ) -> anyhow::Result<Response> {
+        let mut request = self
+            .client
+            .post(url)
+            .header(CONTENT_TYPE, content_type)
+            .body(body.to_vec());
+        if let Some(timeout) = timeout {
+            request = request.timeout(timeout);
+        }
+        match authorization {
+            Some(Authorization::Digest(header)) => {
+                request = request.header(AUTHORIZATION, header);
+            }
+            Some(Authorization::Basic) => {
+                request = request.basic_auth(&self.username, Some(&self.password));
+            }
+            None => {}
+        }
+        request
+            .send()
+            .await
+            .with_context(|| format!("Unable to connect to {}", url))
+    }
+
+    /// Answers the digest (or basic) authentication challenge of a 401 response.
+    fn authorization(
+        &self,
+        response: &Response,
+        path: &str,
+        body: &[u8],
+    ) -> anyhow::Result<Authorization> {
+        let challenges = response
+            .headers()
+            .get_all(WWW_AUTHENTICATE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .collect::<Vec<_>>();
+        if let Some(challenge) = challenges.iter().find(|c| c.starts_with("Digest")) {
+            let context = digest_auth::AuthContext::new_post(
+                self.username.as_str(),
+                self.password.as_str(),
+                path,
+                Some(body),
+            );
+            let mut prompt = digest_auth::parse(challenge)
+                .map_err(|e| anyhow::anyhow!("Invalid digest challenge: {}", e))?;
+            let answer = prompt
+                .respond(&context)
+                .map_err(|e| anyhow::anyhow!("Unable to answer digest challenge: {}", e))?;
+            Ok(Authorization::Digest(answer.to_header_string()))
+        } else if challenges.iter().any(|c| c.starts_with("Basic")) {
+            Ok(Authorization::Basic)
+        } else {
+            bail!(
+                "Unsupported authentication mechanism: {}",
+                challenges.join(", ")
+            )
+        }
+    }
+}
+
+enum Authorization {
+    Digest(String),
+    Basic,
+}
+
+/// Returns the result of a successful management response or fails with its failure description.
+fn result(response: Value) -> anyhow::Result<Value> {
+    if is_success(&response) {
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    } else {
+        bail!(WadoError::management_operation_failed(
+            &failure_description(&response)
+        ))
+    }
+}
+
+/// Returns whether the outcome of a management response is `success`.
+pub fn is_success(response: &Value) -> bool {
+    response.get("outcome").and_then(Value::as_str) == Some("success")
+}
+
+/// Returns the failure description of a management response.
+pub fn failure_description(response: &Value) -> String {
+    match response.get("failure-description") {
+        Some(Value::String(description)) => description.clone(),
+        Some(description) => description.to_string(),
+        None => "Unknown failure".to_string(),
+    }
+}
+
+/// Formats a JSON value in the DMR text format used by the JBoss CLI.
+pub fn dmr_string(value: &Value) -> String {
+    let mut output = String::new();
+    write_dmr(value, 0, &mut output);
+    output
+}
+
+fn write_dmr(value: &Value, indent: usize, output: &mut String) {
+    let padding = "    ".repeat(indent + 1);
+    let closing = "    ".repeat(indent);
+    match value {
+        Value::Null => output.push_str("undefined"),
+        Value::Bool(b) => output.push_str(&b.to_string()),
+        Value::Number(n) => output.push_str(&n.to_string()),
+        Value::String(s) => output.push_str(&format!("{:?}", s)),
+        Value::Array(values) if values.is_empty() => output.push_str("[]"),
+        Value::Array(values) => {
+            output.push_str("[\n");
+            for (index, value) in values.iter().enumerate() {
+                output.push_str(&padding);
+                write_dmr(value, indent + 1, output);
+                if index < values.len() - 1 {
+                    output.push(',');
+                }
+                output.push('\n');
+            }
+            output.push_str(&closing);
+            output.push(']');
+        }
+        Value::Object(map) if map.is_empty() => output.push_str("{}"),
+        Value::Object(map) => {
+            output.push_str("{\n");
+            for (index, (key, value)) in map.iter().enumerate() {
+                output.push_str(&format!("{}{:?} => ", padding, key));
+                write_dmr(value, indent + 1, output);
+                if index < map.len() - 1 {
+                    output.push(',');
+                }
+                output.push('\n');
+            }
+            output.push_str(&closing);
+            output.push('}');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn success_outcome() {
+        assert!(is_success(&json!({"outcome": "success", "result": 1})));
+        assert!(!is_success(&json!({"outcome": "failed"})));
+        assert!(!is_success(&json!({})));
+    }
+
+    #[test]
+    fn failure_description_string() {
+        let response =
+            json!({"outcome": "failed", "failure-description": "WFLYCTL0216: not found"});
+        assert_eq!(failure_description(&response), "WFLYCTL0216: not found");
+    }
+
+    #[test]
+    fn failure_description_missing() {
+        assert_eq!(failure_description(&json!({})), "Unknown failure");
+    }
+
+    #[test]
+    fn dmr_string_nested() {
+        let value = json!({"outcome": "success", "result": {"a": [1, true], "b": null, "c": {}}});
+        assert_eq!(
+            dmr_string(&value),
+            r#"{
+    "outcome" => "success",
+    "result" => {
+        "a" => [
+            1,
+            true
+        ],
+        "b" => undefined,
+        "c" => {}
+    }
+}"#
+        );
+    }
+}
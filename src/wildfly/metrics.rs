@@ -0,0 +1,370 @@
+//! Metrics of standalone servers.
+//!
+//! Scrapes the `/metrics` endpoint of the management interface (Prometheus text
+//! format) and summarizes heap, threads, datasources, and Undertow requests. Versions
+//! without the metrics subsystem read the same numbers using the management API.
+
+use reqwest::{Client, StatusCode};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use wildfly_meta::WildFlyImage;
+
+use super::ManagementApi;
+
+/// The first version with the metrics subsystem (WildFly 15).
+const METRICS_MIN_IDENTIFIER: u16 = 150;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+const HEAP_USED: [&str; 2] = ["base_memory_usedheap_bytes", "base_memory_used_heap_bytes"];
+const HEAP_MAX: [&str; 2] = ["base_memory_maxheap_bytes", "base_memory_max_heap_bytes"];
+const THREADS: [&str; 1] = ["base_thread_count"];
+const DATASOURCE_ACTIVE: [&str; 1] = ["wildfly_datasources_pool_active_count"];
+const DATASOURCE_IN_USE: [&str; 1] = ["wildfly_datasources_pool_in_use_count"];
+const REQUESTS: [&str; 2] = [
+    "wildfly_undertow_request_count_total",
+    "wildfly_undertow_request_count",
+];
+
+/// A single sample of the Prometheus text format.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sample {
+    pub name: String,
+    pub labels: BTreeMap<String, String>,
+    pub value: f64,
+}
+
+/// Heap, threads, datasources, and Undertow requests of a server. Values which aren't
+/// available (e.g. because statistics are disabled) are `None`.
+#[derive(Debug, Default, PartialEq)]
+pub struct MetricsSummary {
+    pub heap_used: Option<u64>,
+    pub heap_max: Option<u64>,
+    pub threads: Option<u64>,
+    pub datasources: Vec<DatasourceMetrics>,
+    /// The number of requests of all HTTP listeners.
+    pub requests: Option<u64>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DatasourceMetrics {
+    pub name: String,
+    pub active: Option<u64>,
+    pub in_use: Option<u64>,
+}
+
+/// Returns whether a version has the `/metrics` endpoint.
+pub fn has_metrics_endpoint(wildfly_image: &WildFlyImage) -> bool {
+    wildfly_image.is_dev() || wildfly_image.identifier >= METRICS_MIN_IDENTIFIER
+}
+
+/// Reads the `/metrics` endpoint of the management interface. Returns `None` if the
+/// endpoint doesn't exist.
+pub async fn scrape_metrics(management_port: u16) -> anyhow::Result<Option<String>> {
+    let client = Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+    let url = format!("http://localhost:{}/metrics", management_port);
+    let response = client.get(&url).send().await?;
+    match response.status() {
+        StatusCode::NOT_FOUND => Ok(None),
+        status if status.is_success() => Ok(Some(response.text().await?)),
+        status => anyhow::bail!("Unable to read {}: HTTP {}", url, status),
+    }
+}
+
+/// Parses the Prometheus text format. Comments and malformed lines are skipped.
+pub fn parse_prometheus(text: &str) -> Vec<Sample> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(parse_sample)
+        .collect()
+}
+
+fn parse_sample(line: &str) -> Option<Sample> {
+    let (name, labels, rest) = match line.find('{') {
+        Some(start) => {
+            let end = start + line[start..].find('}')?;
+            (
+                &line[..start],
+                parse_labels(&line[start + 1..end])?,
+                &line[end + 1..],
+            )
+        }
+        None => {
+            let (name, rest) = line.split_once(char::is_whitespace)?;
+            (name, BTreeMap::new(), rest)
+        }
+    };
+    // the value might be followed by a timestamp
+    let value = rest.split_whitespace().next()?;
+    let value = match value {
+        "+Inf" => f64::INFINITY,
+        "-Inf" => f64::NEG_INFINITY,
+        value => value.parse().ok()?,
+    };
+    Some(Sample {
+        name: name.trim().to_string(),
+        labels,
+        value,
+    })
+}
+
+fn parse_labels(labels: &str) -> Option<BTreeMap<String, String>> {
+    let mut result = BTreeMap::new();
+    let mut rest = labels.trim();
+    while !rest.is_empty() {
+        let (key, value) = rest.split_once('=')?;
+        let value = value.trim_start().strip_prefix('"')?;
+        let mut unescaped = String::new();
+        let mut chars = value.char_indices();
+        let end = loop {
+            match chars.next()? {
+                (_, '\\') => match chars.next()?.1 {
+                    'n' => unescaped.push('\n'),
+                    c => unescaped.push(c),
+                },
+                (index, '"') => break index,
+                (_, c) => unescaped.push(c),
+            }
+        };
+        result.insert(key.trim().to_string(), unescaped);
+        rest = value[end + 1..].trim_start();
+        rest = rest.strip_prefix(',').unwrap_or(rest).trim_start();
+    }
+    Some(result)
+}
+
+/// Normalizes the metric names of the different MicroProfile Metrics versions,
+/// e.g. `base:memory_used_heap_bytes` and `base_memory_usedHeap_bytes`.
+fn normalize(name: &str) -> String {
+    name.replace(':', "_").to_lowercase()
+}
+
+impl MetricsSummary {
+    /// Summarizes the samples of the `/metrics` endpoint.
+    pub fn from_samples(samples: &[Sample]) -> MetricsSummary {
+        let find = |names: &[&str]| {
+            samples
+                .iter()
+                .filter(|sample| names.contains(&normalize(&sample.name).as_str()))
+                .collect::<Vec<_>>()
+        };
+        let first = |names: &[&str]| find(names).first().map(|sample| sample.value as u64);
+        let requests = find(&REQUESTS);
+
+        let mut datasources: BTreeMap<String, DatasourceMetrics> = BTreeMap::new();
+        for (names, active) in [(&DATASOURCE_ACTIVE, true), (&DATASOURCE_IN_USE, false)] {
+            for sample in find(names) {
+                let Some(name) = sample
+                    .labels
+                    .get("data_source")
+                    .or_else(|| sample.labels.get("datasource"))
+                else {
+                    continue;
+                };
+                let datasource =
+                    datasources
+                        .entry(name.clone())
+                        .or_insert_with(|| DatasourceMetrics {
+                            name: name.clone(),
+                            active: None,
+                            in_use: None,
+                        });
+                if active {
+                    datasource.active = Some(sample.value as u64);
+                } else {
+                    datasource.in_use = Some(sample.value as u64);
+                }
+            }
+        }
+
+        MetricsSummary {
+            heap_used: first(&HEAP_USED),
+            heap_max: first(&HEAP_MAX),
+            threads: first(&THREADS),
+            datasources: datasources.into_values().collect(),
+            requests: (!requests.is_empty())
+                .then(|| requests.iter().map(|sample| sample.value as u64).sum()),
+        }
+    }
+}
+
+impl ManagementApi {
+    /// Reads the metrics summary of a standalone server using the management API.
+    /// Used for versions without the `/metrics` endpoint.
+    pub async fn metrics_summary(&self) -> anyhow::Result<MetricsSummary> {
+        let heap = self
+            .execute(&json!({
+                "operation": "read-attribute",
+                "address": [{"core-service": "platform-mbean"}, {"type": "memory"}],
+                "name": "heap-memory-usage",
+            }))
+            .await?;
+        let threads = self
+            .execute(&json!({
+                "operation": "read-attribute",
+                "address": [{"core-service": "platform-mbean"}, {"type": "threading"}],
+                "name": "thread-count",
+            }))
+            .await
+            .unwrap_or(Value::Null);
+        let datasources = self
+            .execute(&json!({
+                "operation": "read-resource",
+                "address": [{"subsystem": "datasources"}, {"data-source": "*"}, {"statistics": "pool"}],
+                "include-runtime": true,
+            }))
+            .await
+            .unwrap_or(Value::Null);
+        let requests = self
+            .execute(&json!({
+                "operation": "read-attribute",
+                "address": [{"subsystem": "undertow"}, {"server": "*"}, {"http-listener": "*"}],
+                "name": "request-count",
+            }))
+            .await
+            .unwrap_or(Value::Null);
+        Ok(parse_management_metrics(
+            &heap,
+            &threads,
+            &datasources,
+            &requests,
+        ))
+    }
+}
+
+fn parse_management_metrics(
+    heap: &Value,
+    threads: &Value,
+    datasources: &Value,
+    requests: &Value,
+) -> MetricsSummary {
+    let steps = |response: &Value| {
+        response
+            .as_array()
+            .map(|steps| {
+                steps
+                    .iter()
+                    .filter(|step| step.get("outcome").and_then(Value::as_str) == Some("success"))
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+    };
+    let datasources = steps(datasources)
+        .iter()
+        .filter_map(|step| {
+            let name = step
+                .get("address")?
+                .as_array()?
+                .iter()
+                .find_map(|segment| segment.get("data-source"))?
+                .as_str()?;
+            let result = step.get("result")?;
+            Some(DatasourceMetrics {
+                name: name.to_string(),
+                active: result.get("ActiveCount").and_then(Value::as_u64),
+                in_use: result.get("InUseCount").and_then(Value::as_u64),
+            })
+        })
+        .collect();
+    let requests = steps(requests)
+        .iter()
+        .filter_map(|step| step.get("result").and_then(Value::as_u64))
+        .collect::<Vec<_>>();
+    MetricsSummary {
+        heap_used: heap.get("used").and_then(Value::as_u64),
+        heap_max: heap
+            .get("max")
+            .and_then(Value::as_u64)
+            .filter(|max| *max > 0),
+        threads: threads.as_u64(),
+        datasources,
+        requests: (!requests.is_empty()).then(|| requests.iter().sum()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METRICS: &str = r#"# HELP base_memory_usedHeap_bytes Displays the amount of used memory.
+# TYPE base_memory_usedHeap_bytes gauge
+base_memory_usedHeap_bytes 1.23456789E8
+# TYPE base_memory_maxHeap_bytes gauge
+base_memory_maxHeap_bytes 5.36870912E8
+base_thread_count 42.0
+wildfly_datasources_pool_active_count{data_source="ExampleDS"} 2.0
+wildfly_datasources_pool_in_use_count{data_source="ExampleDS"} 1.0
+wildfly_undertow_request_count_total{server="default-server",http_listener="default"} 10.0 1719312345000
+wildfly_undertow_request_count_total{server="default-server",http_listener="other"} 5.0
+"#;
+
+    #[test]
+    fn parse() {
+        let samples = parse_prometheus(METRICS);
+        assert_eq!(samples.len(), 7);
+        assert_eq!(samples[0].name, "base_memory_usedHeap_bytes");
+        assert_eq!(samples[0].value, 123456789.0);
+        assert_eq!(samples[5].labels["http_listener"], "default");
+        assert_eq!(samples[5].value, 10.0);
+    }
+
+    #[test]
+    fn parse_escaped_labels() {
+        let samples = parse_prometheus(r#"metric{a="x\"y", b = "1,2"} +Inf"#);
+        assert_eq!(samples[0].labels["a"], "x\"y");
+        assert_eq!(samples[0].labels["b"], "1,2");
+        assert_eq!(samples[0].value, f64::INFINITY);
+        assert!(parse_prometheus("metric{a=\"unterminated} 1").is_empty());
+    }
+
+    #[test]
+    fn summary() {
+        let summary = MetricsSummary::from_samples(&parse_prometheus(METRICS));
+        assert_eq!(summary.heap_used, Some(123456789));
+        assert_eq!(summary.heap_max, Some(536870912));
+        assert_eq!(summary.threads, Some(42));
+        assert_eq!(
+            summary.datasources,
+            vec![DatasourceMetrics {
+                name: "ExampleDS".to_string(),
+                active: Some(2),
+                in_use: Some(1),
+            }]
+        );
+        assert_eq!(summary.requests, Some(15));
+    }
+
+    #[test]
+    fn summary_legacy_names() {
+        let summary = MetricsSummary::from_samples(&parse_prometheus(
+            "base:memory_used_heap_bytes 1024\nbase:thread_count 7",
+        ));
+        assert_eq!(summary.heap_used, Some(1024));
+        assert_eq!(summary.threads, Some(7));
+        assert_eq!(summary.requests, None);
+    }
+
+    #[test]
+    fn management_metrics() {
+        let summary = parse_management_metrics(
+            &json!({"init": 0, "used": 2048, "committed": 4096, "max": -1}),
+            &json!(12),
+            &json!([{
+                "address": [{"subsystem": "datasources"}, {"data-source": "ExampleDS"}, {"statistics": "pool"}],
+                "outcome": "success",
+                "result": {"ActiveCount": 3, "InUseCount": 0},
+            }]),
+            &json!([
+                {"address": [], "outcome": "success", "result": 4},
+                {"address": [], "outcome": "failed"},
+            ]),
+        );
+        assert_eq!(summary.heap_used, Some(2048));
+        assert_eq!(summary.heap_max, None);
+        assert_eq!(summary.threads, Some(12));
+        assert_eq!(summary.datasources[0].active, Some(3));
+        assert_eq!(summary.requests, Some(4));
+    }
+}
@@ -0,0 +1,152 @@
+//! Version compatibility of host controllers and their domain controller.
+//!
+//! A domain controller only manages host controllers of the same or an older
+//! version. Older host controllers are supported by model transformers which
+//! WildFly keeps for a limited number of WildFly Core major versions only.
+
+use serde_json::{Value, json};
+use wildfly_meta::WildFlyImage;
+
+/// The number of WildFly Core major versions a host controller may be behind its
+/// domain controller before the combination is reported as untested.
+pub const MIXED_DOMAIN_CORE_MAJORS: u64 = 3;
+
+/// The compatibility of a host controller version with its domain controller version.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MixedDomain {
+    Same,
+    /// The host controller is older, but within [`MIXED_DOMAIN_CORE_MAJORS`].
+    Supported,
+    /// The host controller is older than [`MIXED_DOMAIN_CORE_MAJORS`] and might
+    /// fail to register.
+    Untested,
+    /// The host controller is newer than the domain controller.
+    Unsupported,
+}
+
+impl MixedDomain {
+    /// Compares the version of a host controller with the version of its domain
+    /// controller. The development version is considered newer than any release.
+    pub fn check(dc: &WildFlyImage, hc: &WildFlyImage) -> MixedDomain {
+        let rank = |image: &WildFlyImage| {
+            if image.is_dev() {
+                u16::MAX
+            } else {
+                image.identifier
+            }
+        };
+        let (dc_rank, hc_rank) = (rank(dc), rank(hc));
+        if hc_rank == dc_rank {
+            MixedDomain::Same
+        } else if hc_rank > dc_rank {
+            MixedDomain::Unsupported
+        } else if !dc.is_dev()
+            && dc.core_version.major > hc.core_version.major + MIXED_DOMAIN_CORE_MAJORS
+        {
+            MixedDomain::Untested
+        } else {
+            MixedDomain::Supported
+        }
+    }
+
+    /// Returns the name of the compatibility used in machine-readable output.
+    pub fn name(&self) -> &'static str {
+        match self {
+            MixedDomain::Same => "same",
+            MixedDomain::Supported => "supported",
+            MixedDomain::Untested => "untested",
+            MixedDomain::Unsupported => "unsupported",
+        }
+    }
+
+    /// Returns the host, both versions and the compatibility as details of a
+    /// [`WadoError`](crate::error::WadoError).
+    pub fn details(&self, host: &str, dc: &WildFlyImage, hc: &WildFlyImage) -> Value {
+        json!({
+            "host": host,
+            "dc_version": dc.short_name(),
+            "hc_version": hc.short_name(),
+            "compatibility": self.name(),
+        })
+    }
+
+    /// Returns an error message for [`MixedDomain::Unsupported`] and a warning for
+    /// [`MixedDomain::Untested`].
+    pub fn message(&self, host: &str, dc: &WildFlyImage, hc: &WildFlyImage) -> Option<String> {
+        match self {
+            MixedDomain::Same | MixedDomain::Supported => None,
+            MixedDomain::Untested => Some(format!(
+                "Host controller '{}' runs WildFly {} (Core {}) which is more than {} WildFly Core major versions older than the domain controller (WildFly {}, Core {}). The host controller might fail to register.",
+                host,
+                hc.short_name(),
+                hc.core_version,
+                MIXED_DOMAIN_CORE_MAJORS,
+                dc.short_name(),
+                dc.core_version
+            )),
+            MixedDomain::Unsupported => Some(format!(
+                "Host controller '{}' runs WildFly {} which is newer than the domain controller (WildFly {}). The domain controller must run the highest version of a domain.",
+                host,
+                hc.short_name(),
+                dc.short_name()
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wildfly_meta::{WildFlyImageRegistry, parse_wildfly_image};
+
+    fn image(version: &str) -> WildFlyImage {
+        let registry =
+            WildFlyImageRegistry::from_toml(include_str!("../../testdata/wildfly-images.toml"))
+                .expect("failed to parse test registry");
+        parse_wildfly_image(version, &registry).unwrap()
+    }
+
+    #[test]
+    fn same() {
+        assert_eq!(
+            MixedDomain::check(&image("34"), &image("34")),
+            MixedDomain::Same
+        );
+        assert_eq!(
+            MixedDomain::check(&image("dev"), &image("dev")),
+            MixedDomain::Same
+        );
+    }
+
+    #[test]
+    fn older_host_controller() {
+        assert_eq!(
+            MixedDomain::check(&image("34"), &image("33")),
+            MixedDomain::Supported
+        );
+        assert_eq!(
+            MixedDomain::check(&image("dev"), &image("10")),
+            MixedDomain::Supported
+        );
+        assert_eq!(
+            MixedDomain::check(&image("34"), &image("26.1")),
+            MixedDomain::Untested
+        );
+    }
+
+    #[test]
+    fn newer_host_controller() {
+        let check = MixedDomain::check(&image("33"), &image("34"));
+        assert_eq!(check, MixedDomain::Unsupported);
+        assert!(
+            check
+                .message("host1", &image("33"), &image("34"))
+                .unwrap()
+                .contains("newer than the domain controller")
+        );
+        assert_eq!(
+            MixedDomain::check(&image("34"), &image("dev")),
+            MixedDomain::Unsupported
+        );
+    }
+}
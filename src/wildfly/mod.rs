@@ -5,15 +5,26 @@
 //! definitions for managed domains, and the management client configuration.
 
 mod admin_image;
+mod config;
+mod deployment;
+mod domain_state;
 mod management;
+mod management_api;
+mod metrics;
+mod mixed_domain;
+mod model_diff;
+mod operation;
 mod server;
 mod server_type;
+mod snapshot;
 mod start_spec;
 
 /// Configuration for a named container instance with its admin container metadata.
 pub trait ContainerConfig: Clone {
     fn admin_image(&self) -> &AdminImage;
     fn name(&self) -> &str;
+    /// The published JDWP port if the container is started with `--debug`.
+    fn debug_port(&self) -> Option<u16>;
 }
 
 macro_rules! impl_container_instance {
@@ -25,6 +36,9 @@ macro_rules! impl_container_instance {
             fn name(&self) -> &str {
                 &self.name
             }
+            fn debug_port(&self) -> Option<u16> {
+                self.debug_port
+            }
         }
     };
 }
@@ -32,8 +46,17 @@ macro_rules! impl_container_instance {
 mod instance;
 
 pub use admin_image::*;
+pub use config::*;
+pub use deployment::*;
+pub use domain_state::*;
 pub use instance::*;
 pub use management::*;
+pub use management_api::*;
+pub use metrics::*;
+pub use mixed_domain::*;
+pub use model_diff::*;
+pub use operation::*;
 pub use server::*;
 pub use server_type::*;
+pub use snapshot::*;
 pub use start_spec::*;
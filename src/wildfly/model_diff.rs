@@ -0,0 +1,278 @@
+//! Structural diff of two management models.
+//!
+//! Compares the results of two `read-resource(recursive=true)` operations. The
+//! JSON representation of a resource doesn't distinguish between attributes and
+//! child types, so the results of `read-resource-description(recursive=true)` are
+//! used to tell them apart: an entry is a child type if it's one of the `children`
+//! of the resource description of either side.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+
+/// The kind of a [`ModelChange`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A resource or attribute that differs between two management models.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ModelChange {
+    pub kind: ChangeKind,
+    /// The address of the resource in CLI syntax (e.g. `/subsystem=undertow`).
+    pub address: String,
+    /// The attribute name or `None` if the whole resource was added or removed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attribute: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub left: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub right: Option<Value>,
+}
+
+/// The result of `read-resource(recursive=true)` together with the result of
+/// `read-resource-description(recursive=true)` of the same resource.
+pub struct Model<'a> {
+    pub resource: &'a Value,
+    pub description: &'a Value,
+}
+
+/// Returns the added and removed resources and the added, removed, and changed
+/// attributes of `right` compared to `left`. `address` is the address of the
+/// compared resource, e.g. `/` for the root resource.
+pub fn diff_models(address: &str, left: Model, right: Model) -> Vec<ModelChange> {
+    let mut changes = vec![];
+    let empty = Map::new();
+    diff_resource(
+        address.trim_end_matches('/'),
+        left.resource.as_object().unwrap_or(&empty),
+        right.resource.as_object().unwrap_or(&empty),
+        (left.description, right.description),
+        &mut changes,
+    );
+    changes
+}
+
+fn diff_resource(
+    address: &str,
+    left: &Map<String, Value>,
+    right: &Map<String, Value>,
+    descriptions: (&Value, &Value),
+    changes: &mut Vec<ModelChange>,
+) {
+    let empty = Map::new();
+    let keys: BTreeSet<&String> = left.keys().chain(right.keys()).collect();
+    for key in keys {
+        let l = left.get(key);
+        let r = right.get(key);
+        if is_child_type(descriptions.0, key) || is_child_type(descriptions.1, key) {
+            let l = l.and_then(Value::as_object).unwrap_or(&empty);
+            let r = r.and_then(Value::as_object).unwrap_or(&empty);
+            let names: BTreeSet<&String> = l.keys().chain(r.keys()).collect();
+            for name in names {
+                let child_address = format!("{}/{}={}", address, key, name);
+                match (l.get(name), r.get(name)) {
+                    (Some(_), None) => changes.push(resource(ChangeKind::Removed, child_address)),
+                    (None, Some(_)) => changes.push(resource(ChangeKind::Added, child_address)),
+                    (Some(lc), Some(rc)) => diff_resource(
+                        &child_address,
+                        lc.as_object().unwrap_or(&empty),
+                        rc.as_object().unwrap_or(&empty),
+                        (
+                            child_description(descriptions.0, key, name),
+                            child_description(descriptions.1, key, name),
+                        ),
+                        changes,
+                    ),
+                    (None, None) => {}
+                }
+            }
+        } else if l != r {
+            let kind = match (l, r) {
+                (Some(_), None) => ChangeKind::Removed,
+                (None, Some(_)) => ChangeKind::Added,
+                _ => ChangeKind::Changed,
+            };
+            changes.push(ModelChange {
+                kind,
+                address: display_address(address),
+                attribute: Some(key.clone()),
+                left: l.cloned(),
+                right: r.cloned(),
+            });
+        }
+    }
+}
+
+fn is_child_type(description: &Value, key: &str) -> bool {
+    description["children"].get(key).is_some()
+}
+
+/// Returns the description of the child `key=name`: the description registered
+/// for `name` or the wildcard description `*`.
+fn child_description<'a>(description: &'a Value, key: &str, name: &str) -> &'a Value {
+    let model = &description["children"][key]["model-description"];
+    model.get(name).unwrap_or(&model["*"])
+}
+
+fn resource(kind: ChangeKind, address: String) -> ModelChange {
+    ModelChange {
+        kind,
+        address,
+        attribute: None,
+        left: None,
+        right: None,
+    }
+}
+
+fn display_address(address: &str) -> String {
+    if address.is_empty() {
+        "/".to_string()
+    } else {
+        address.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn model<'a>(resource: &'a Value, description: &'a Value) -> Model<'a> {
+        Model {
+            resource,
+            description,
+        }
+    }
+
+    fn description() -> Value {
+        json!({
+            "attributes": {"name": {}},
+            "children": {
+                "deployment": {"model-description": {"*": {"attributes": {"enabled": {}}}}},
+                "subsystem": {
+                    "model-description": {
+                        "logging": {
+                            "children": {
+                                "console-handler": {
+                                    "model-description": {"*": {"attributes": {"level": {}}}}
+                                }
+                            }
+                        },
+                        "ee": {"attributes": {"props": {}}},
+                        "*": {}
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn equal() {
+        let model_value = json!({"name": "foo", "subsystem": {"logging": {"level": "INFO"}}});
+        let description = description();
+        assert!(
+            diff_models(
+                "/",
+                model(&model_value, &description),
+                model(&model_value, &description)
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn attributes() {
+        let left = json!({"name": "foo", "removed": 1, "undefined": null});
+        let right = json!({"name": "bar", "added": true, "undefined": null});
+        let description = description();
+        let changes = diff_models("/", model(&left, &description), model(&right, &description));
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[0].kind, ChangeKind::Added);
+        assert_eq!(changes[0].address, "/");
+        assert_eq!(changes[0].attribute.as_deref(), Some("added"));
+        assert_eq!(changes[1].kind, ChangeKind::Changed);
+        assert_eq!(changes[1].attribute.as_deref(), Some("name"));
+        assert_eq!(changes[1].left, Some(json!("foo")));
+        assert_eq!(changes[1].right, Some(json!("bar")));
+        assert_eq!(changes[2].kind, ChangeKind::Removed);
+        assert_eq!(changes[2].attribute.as_deref(), Some("removed"));
+    }
+
+    #[test]
+    fn resources() {
+        let left = json!({
+            "subsystem": {
+                "logging": {"console-handler": {"CONSOLE": {"level": "INFO"}}},
+                "jsr77": null
+            }
+        });
+        let right = json!({
+            "subsystem": {
+                "logging": {"console-handler": {"CONSOLE": {"level": "DEBUG"}}},
+                "micrometer": {"endpoint": "http://localhost"}
+            }
+        });
+        let description = description();
+        let changes = diff_models("/", model(&left, &description), model(&right, &description));
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[0].kind, ChangeKind::Removed);
+        assert_eq!(changes[0].address, "/subsystem=jsr77");
+        assert_eq!(changes[0].attribute, None);
+        assert_eq!(changes[1].kind, ChangeKind::Changed);
+        assert_eq!(
+            changes[1].address,
+            "/subsystem=logging/console-handler=CONSOLE"
+        );
+        assert_eq!(changes[1].attribute.as_deref(), Some("level"));
+        assert_eq!(changes[2].kind, ChangeKind::Added);
+        assert_eq!(changes[2].address, "/subsystem=micrometer");
+    }
+
+    #[test]
+    fn child_type_without_children() {
+        let left = json!({"deployment": null});
+        let right = json!({"deployment": {"app.war": {"enabled": true}}});
+        let description = description();
+        let changes = diff_models("/", model(&left, &description), model(&right, &description));
+        assert_eq!(
+            changes,
+            vec![resource(
+                ChangeKind::Added,
+                "/deployment=app.war".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn object_attribute() {
+        let left = json!({"subsystem": {"ee": {"props": {"a": {"b": "1"}}}}});
+        let right = json!({"subsystem": {"ee": {"props": {"a": {"b": "2"}}}}});
+        let description = description();
+        let changes = diff_models("/", model(&left, &description), model(&right, &description));
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].address, "/subsystem=ee");
+        assert_eq!(changes[0].attribute.as_deref(), Some("props"));
+    }
+
+    #[test]
+    fn child_type_of_one_side() {
+        // the child type is only known to the description of the right side
+        let left = json!({});
+        let right = json!({"subsystem": {"micrometer": {}}});
+        let empty = json!({});
+        let description = description();
+        let changes = diff_models("/", model(&left, &empty), model(&right, &description));
+        assert_eq!(
+            changes,
+            vec![resource(
+                ChangeKind::Added,
+                "/subsystem=micrometer".to_string()
+            )]
+        );
+    }
+}
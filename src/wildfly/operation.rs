@@ -0,0 +1,870 @@
+//! Management operations in JBoss CLI syntax.
+//!
+//! Parses operation strings like `/subsystem=logging:read-resource(recursive=true)`
+//! into an [`Operation`] and converts them into the JSON representation of DMR
+//! understood by the HTTP management API. Lists of bootstrap commands (operations
+//! plus `batch` / `run-batch`) are parsed into [`CliCommand`]s and normalized
+//! before they are passed to `jboss-cli.sh --commands`.
+
+use serde_json::{Map, Value as JsonValue};
+use std::fmt;
+
+// ------------------------------------------------------ operation
+
+/// A management operation consisting of an address, a name, parameters and headers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Operation {
+    pub address: Vec<(String, String)>,
+    pub name: String,
+    pub parameters: Vec<(String, Value)>,
+    pub headers: Vec<(String, Value)>,
+}
+
+/// A parameter or header value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// An unquoted value. Booleans and integers are converted to typed JSON values.
+    Text(String),
+    /// A quoted string which is always converted to a JSON string.
+    Quoted(String),
+    List(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+/// A command as understood by `jboss-cli.sh --commands`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CliCommand {
+    Operation(Operation),
+    Batch,
+    RunBatch,
+}
+
+/// A syntax error at a character position of the parsed input.
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+#[error("{message} at position {position}")]
+pub struct SyntaxError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl SyntaxError {
+    /// Formats the error together with the input and a caret pointing to the position.
+    pub fn display_with(&self, input: &str) -> String {
+        format!("{}\n  {}\n  {}^", self, input, " ".repeat(self.position))
+    }
+}
+
+impl Operation {
+    /// Parses an operation in JBoss CLI syntax.
+    pub fn parse(input: &str) -> Result<Operation, SyntaxError> {
+        let mut parser = Parser::new(input);
+        let operation = parser.operation()?;
+        parser.skip_whitespace();
+        if !parser.at_end() {
+            return Err(parser.error("Unexpected input after operation"));
+        }
+        Ok(operation)
+    }
+
+    /// Converts the operation into the JSON representation of DMR.
+    pub fn to_dmr(&self) -> JsonValue {
+        let mut map = Map::new();
+        map.insert(
+            "operation".to_string(),
+            JsonValue::String(self.name.clone()),
+        );
+        map.insert(
+            "address".to_string(),
+            JsonValue::Array(
+                self.address
+                    .iter()
+                    .map(|(key, value)| {
+                        let mut segment = Map::new();
+                        segment.insert(key.clone(), JsonValue::String(value.clone()));
+                        JsonValue::Object(segment)
+                    })
+                    .collect(),
+            ),
+        );
+        for (name, value) in &self.parameters {
+            map.insert(name.clone(), value.to_json());
+        }
+        if !self.headers.is_empty() {
+            let headers = self
+                .headers
+                .iter()
+                .map(|(name, value)| (name.clone(), value.to_json()))
+                .collect::<Map<_, _>>();
+            map.insert("operation-headers".to_string(), JsonValue::Object(headers));
+        }
+        JsonValue::Object(map)
+    }
+}
+
+impl Value {
+    /// Converts the value into JSON.
+    pub fn to_json(&self) -> JsonValue {
+        match self {
+            Value::Text(text) => {
+                if text == "true" || text == "false" {
+                    JsonValue::Bool(text == "true")
+                } else if let Ok(number) = text.parse::<i64>()
+                    && number.to_string() == *text
+                {
+                    JsonValue::from(number)
+                } else {
+                    JsonValue::String(text.clone())
+                }
+            }
+            Value::Quoted(text) => JsonValue::String(text.clone()),
+            Value::List(values) => JsonValue::Array(values.iter().map(Value::to_json).collect()),
+            Value::Object(entries) => JsonValue::Object(
+                entries
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.to_json()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+// ------------------------------------------------------ commands
+
+/// Parses a comma-separated list of operations, `batch` and `run-batch` commands.
+///
+/// Commas inside parameters, lists, objects, quotes and expressions don't
+/// separate commands.
+pub fn parse_commands(input: &str) -> Result<Vec<CliCommand>, SyntaxError> {
+    let mut parser = Parser::new(input);
+    let mut commands = vec![];
+    loop {
+        parser.skip_whitespace();
+        commands.push(parser.command()?);
+        parser.skip_whitespace();
+        match parser.next() {
+            Some(',') => continue,
+            None => break,
+            Some(_) => {
+                parser.position -= 1;
+                return Err(parser.error("Expected ',' or end of input"));
+            }
+        }
+    }
+    Ok(commands)
+}
+
+/// Checks that every `batch` is closed by a `run-batch` and that batches are not nested.
+pub fn validate_batches(commands: &[CliCommand]) -> Result<(), String> {
+    let mut in_batch = false;
+    for command in commands {
+        match command {
+            CliCommand::Batch if in_batch => return Err("Nested 'batch'".to_string()),
+            CliCommand::Batch => in_batch = true,
+            CliCommand::RunBatch if !in_batch => {
+                return Err("'run-batch' without preceding 'batch'".to_string());
+            }
+            CliCommand::RunBatch => in_batch = false,
+            CliCommand::Operation(_) => {}
+        }
+    }
+    if in_batch {
+        Err("'batch' without closing 'run-batch'".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+// ------------------------------------------------------ display
+
+/// Formats the operation in normalized CLI syntax.
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (key, value) in &self.address {
+            write!(f, "/{}=", key)?;
+            if value.contains(['/', ':', '=', ',', '"', '\\']) || value.trim() != value {
+                write_quoted(f, value)?;
+            } else {
+                f.write_str(value)?;
+            }
+        }
+        write!(f, ":{}", self.name)?;
+        if !self.parameters.is_empty() {
+            f.write_str("(")?;
+            write_entries(f, &self.parameters, ",")?;
+            f.write_str(")")?;
+        }
+        if !self.headers.is_empty() {
+            f.write_str("{")?;
+            write_entries(f, &self.headers, ";")?;
+            f.write_str("}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Text(text) if needs_quotes(text) => write_quoted(f, text),
+            Value::Text(text) => f.write_str(text),
+            Value::Quoted(text) => write_quoted(f, text),
+            Value::List(values) => {
+                f.write_str("[")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                f.write_str("]")
+            }
+            Value::Object(entries) => {
+                f.write_str("{")?;
+                write_entries(f, entries, ",")?;
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+impl fmt::Display for CliCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliCommand::Operation(operation) => write!(f, "{}", operation),
+            CliCommand::Batch => f.write_str("batch"),
+            CliCommand::RunBatch => f.write_str("run-batch"),
+        }
+    }
+}
+
+fn write_entries(
+    f: &mut fmt::Formatter<'_>,
+    entries: &[(String, Value)],
+    separator: &str,
+) -> fmt::Result {
+    for (index, (name, value)) in entries.iter().enumerate() {
+        if index > 0 {
+            f.write_str(separator)?;
+        }
+        write!(f, "{}={}", name, value)?;
+    }
+    Ok(())
+}
+
+fn write_quoted(f: &mut fmt::Formatter<'_>, text: &str) -> fmt::Result {
+    write!(f, "\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Whether an unquoted value contains characters which would end the value
+/// when parsed again. Characters inside expressions (`${...}`) are fine.
+fn needs_quotes(text: &str) -> bool {
+    let mut depth = 0;
+    let mut previous = None;
+    for c in text.chars() {
+        match c {
+            '"' | '\\' => return true,
+            '{' if previous == Some('$') => depth += 1,
+            '}' if depth > 0 => depth -= 1,
+            ',' | ';' | '(' | ')' | '[' | ']' | '{' | '}' | '=' if depth == 0 => return true,
+            _ => {}
+        }
+        previous = Some(c);
+    }
+    false
+}
+
+// ------------------------------------------------------ parser
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn new(input: &str) -> Parser {
+        Parser {
+            chars: input.chars().collect(),
+            position: 0,
+        }
+    }
+
+    fn command(&mut self) -> Result<CliCommand, SyntaxError> {
+        if self.keyword("run-batch") {
+            Ok(CliCommand::RunBatch)
+        } else if self.keyword("batch") {
+            Ok(CliCommand::Batch)
+        } else if matches!(self.peek(), Some('/') | Some(':')) {
+            self.operation().map(CliCommand::Operation)
+        } else {
+            Err(self.error("Expected operation, 'batch' or 'run-batch'"))
+        }
+    }
+
+    /// Consumes the keyword if it is followed by whitespace, a comma or the end of input.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let end = self.position + keyword.chars().count();
+        let matches = self.chars.len() >= end
+            && self.chars[self.position..end]
+                .iter()
+                .copied()
+                .eq(keyword.chars())
+            && self
+                .chars
+                .get(end)
+                .is_none_or(|c| c.is_whitespace() || *c == ',');
+        if matches {
+            self.position = end;
+        }
+        matches
+    }
+
+    fn operation(&mut self) -> Result<Operation, SyntaxError> {
+        self.skip_whitespace();
+        let address = self.address()?;
+        self.skip_whitespace();
+        self.expect(':')?;
+        let name = self.name("operation name")?;
+        self.skip_whitespace();
+        let parameters = if self.peek() == Some('(') {
+            self.parameters()?
+        } else {
+            vec![]
+        };
+        self.skip_whitespace();
+        let headers = if self.peek() == Some('{') {
+            self.headers()?
+        } else {
+            vec![]
+        };
+        Ok(Operation {
+            address,
+            name,
+            parameters,
+            headers,
+        })
+    }
+
+    fn address(&mut self) -> Result<Vec<(String, String)>, SyntaxError> {
+        let mut address = vec![];
+        while self.peek() == Some('/') {
+            self.position += 1;
+            self.skip_whitespace();
+            if matches!(self.peek(), Some(':') | None) {
+                // root address "/:operation"
+                break;
+            }
+            let key = self.name("resource type")?;
+            self.skip_whitespace();
+            self.expect('=')?;
+            self.skip_whitespace();
+            let value = self.address_value()?;
+            address.push((key, value));
+            self.skip_whitespace();
+        }
+        Ok(address)
+    }
+
+    fn address_value(&mut self) -> Result<String, SyntaxError> {
+        if self.peek() == Some('"') {
+            return self.quoted();
+        }
+        let start = self.position;
+        let mut value = String::new();
+        while let Some(c) = self.peek() {
+            match c {
+                '/' | ':' => break,
+                '\\' => {
+                    self.position += 1;
+                    match self.next() {
+                        Some(escaped) => value.push(escaped),
+                        None => return Err(self.error("Unterminated escape sequence")),
+                    }
+                }
+                _ => {
+                    value.push(c);
+                    self.position += 1;
+                }
+            }
+        }
+        let value = value.trim().to_string();
+        if value.is_empty() {
+            Err(SyntaxError {
+                position: start,
+                message: "Expected resource name".to_string(),
+            })
+        } else {
+            Ok(value)
+        }
+    }
+
+    fn parameters(&mut self) -> Result<Vec<(String, Value)>, SyntaxError> {
+        self.expect('(')?;
+        let mut parameters = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some(')') {
+            self.position += 1;
+            return Ok(parameters);
+        }
+        loop {
+            self.skip_whitespace();
+            let name = self.name("parameter name")?;
+            self.skip_whitespace();
+            let value = if self.peek() == Some('=') {
+                self.position += 1;
+                self.skip_whitespace();
+                self.value(&[',', ')'])?
+            } else {
+                // a parameter without value is a boolean flag
+                Value::Text("true".to_string())
+            };
+            parameters.push((name, value));
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some(')') => break,
+                Some(_) => {
+                    self.position -= 1;
+                    return Err(self.error("Expected ',' or ')'"));
+                }
+                None => return Err(self.error("Expected ')'")),
+            }
+        }
+        Ok(parameters)
+    }
+
+    fn headers(&mut self) -> Result<Vec<(String, Value)>, SyntaxError> {
+        self.expect('{')?;
+        let mut headers = vec![];
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some('}') {
+                self.position += 1;
+                break;
+            }
+            let name = self.name("header name")?;
+            self.skip_whitespace();
+            self.expect('=')?;
+            self.skip_whitespace();
+            let value = self.value(&[';', ',', '}'])?;
+            headers.push((name, value));
+            self.skip_whitespace();
+            match self.next() {
+                Some(';') | Some(',') => continue,
+                Some('}') => break,
+                Some(_) => {
+                    self.position -= 1;
+                    return Err(self.error("Expected ';' or '}'"));
+                }
+                None => return Err(self.error("Expected '}'")),
+            }
+        }
+        Ok(headers)
+    }
+
+    fn value(&mut self, terminators: &[char]) -> Result<Value, SyntaxError> {
+        match self.peek() {
+            Some('"') => self.quoted().map(Value::Quoted),
+            Some('[') => self.list(),
+            Some('{') => self.object(),
+            Some(_) => self.text(terminators),
+            None => Err(self.error("Expected value")),
+        }
+    }
+
+    fn list(&mut self) -> Result<Value, SyntaxError> {
+        self.expect('[')?;
+        let mut values = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.position += 1;
+            return Ok(Value::List(values));
+        }
+        loop {
+            self.skip_whitespace();
+            values.push(self.value(&[',', ']'])?);
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some(']') => break,
+                Some(_) => {
+                    self.position -= 1;
+                    return Err(self.error("Expected ',' or ']'"));
+                }
+                None => return Err(self.error("Expected ']'")),
+            }
+        }
+        Ok(Value::List(values))
+    }
+
+    fn object(&mut self) -> Result<Value, SyntaxError> {
+        self.expect('{')?;
+        let mut entries = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.position += 1;
+            return Ok(Value::Object(entries));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = if self.peek() == Some('"') {
+                self.quoted()?
+            } else {
+                self.name("key")?
+            };
+            self.skip_whitespace();
+            self.expect('=')?;
+            if self.peek() == Some('>') {
+                self.position += 1;
+            }
+            self.skip_whitespace();
+            let value = self.value(&[',', '}'])?;
+            entries.push((key, value));
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some('}') => break,
+                Some(_) => {
+                    self.position -= 1;
+                    return Err(self.error("Expected ',' or '}'"));
+                }
+                None => return Err(self.error("Expected '}'")),
+            }
+        }
+        Ok(Value::Object(entries))
+    }
+
+    /// Reads an unquoted value up to one of the terminators. Expressions like
+    /// `${jboss.bind.address:127.0.0.1}` may contain terminators inside braces.
+    fn text(&mut self, terminators: &[char]) -> Result<Value, SyntaxError> {
+        let start = self.position;
+        let mut text = String::new();
+        let mut depth = 0;
+        while let Some(c) = self.peek() {
+            if depth == 0 && terminators.contains(&c) {
+                break;
+            }
+            match c {
+                '\\' => {
+                    self.position += 1;
+                    match self.next() {
+                        Some(escaped) => text.push(escaped),
+                        None => return Err(self.error("Unterminated escape sequence")),
+                    }
+                    continue;
+                }
+                '{' if text.ends_with('$') => depth += 1,
+                '}' if depth > 0 => depth -= 1,
+                _ => {}
+            }
+            text.push(c);
+            self.position += 1;
+        }
+        if depth > 0 {
+            return Err(self.error("Unterminated expression"));
+        }
+        let text = text.trim().to_string();
+        if text.is_empty() {
+            Err(SyntaxError {
+                position: start,
+                message: "Expected value".to_string(),
+            })
+        } else {
+            Ok(Value::Text(text))
+        }
+    }
+
+    fn quoted(&mut self) -> Result<String, SyntaxError> {
+        let start = self.position;
+        self.expect('"')?;
+        let mut text = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(text),
+                Some('\\') => match self.next() {
+                    Some(escaped) => text.push(escaped),
+                    None => break,
+                },
+                Some(c) => text.push(c),
+                None => break,
+            }
+        }
+        Err(SyntaxError {
+            position: start,
+            message: "Unterminated quoted string".to_string(),
+        })
+    }
+
+    fn name(&mut self, what: &str) -> Result<String, SyntaxError> {
+        let start = self.position;
+        while let Some(c) = self.peek() {
+            if c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | '*') {
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+        if start == self.position {
+            Err(self.error(&format!("Expected {}", what)))
+        } else {
+            Ok(self.chars[start..self.position].iter().collect())
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), SyntaxError> {
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{}'", expected)))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        if c.is_some() {
+            self.position += 1;
+        }
+        c
+    }
+
+    fn at_end(&self) -> bool {
+        self.position >= self.chars.len()
+    }
+
+    fn error(&self, message: &str) -> SyntaxError {
+        SyntaxError {
+            position: self.position,
+            message: message.to_string(),
+        }
+    }
+}
+
+// ------------------------------------------------------ tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parse_root_operation() {
+        let operation = Operation::parse(":read-resource").unwrap();
+        assert!(operation.address.is_empty());
+        assert_eq!(operation.name, "read-resource");
+        assert!(operation.parameters.is_empty());
+
+        let operation = Operation::parse("/:read-resource").unwrap();
+        assert!(operation.address.is_empty());
+    }
+
+    #[test]
+    fn parse_address_and_parameters() {
+        let operation = Operation::parse(
+            "/subsystem=logging/console-handler=CONSOLE:write-attribute(name=level,value=DEBUG)",
+        )
+        .unwrap();
+        assert_eq!(
+            operation.address,
+            vec![
+                ("subsystem".to_string(), "logging".to_string()),
+                ("console-handler".to_string(), "CONSOLE".to_string()),
+            ]
+        );
+        assert_eq!(operation.name, "write-attribute");
+        assert_eq!(
+            operation.parameters,
+            vec![
+                ("name".to_string(), Value::Text("level".to_string())),
+                ("value".to_string(), Value::Text("DEBUG".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_nested_values() {
+        let operation = Operation::parse(
+            "/system-property=foo:add(value=\"a, b\", list=[1, 2], map={a=1, b=>[x]})",
+        )
+        .unwrap();
+        assert_eq!(operation.parameters[0].1, Value::Quoted("a, b".to_string()));
+        assert_eq!(
+            operation.parameters[1].1,
+            Value::List(vec![
+                Value::Text("1".to_string()),
+                Value::Text("2".to_string())
+            ])
+        );
+        assert_eq!(
+            operation.parameters[2].1,
+            Value::Object(vec![
+                ("a".to_string(), Value::Text("1".to_string())),
+                (
+                    "b".to_string(),
+                    Value::List(vec![Value::Text("x".to_string())])
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_expression_value() {
+        let operation =
+            Operation::parse("/system-property=foo:add(value=${jboss.home.dir:/opt})").unwrap();
+        assert_eq!(
+            operation.parameters[0].1,
+            Value::Text("${jboss.home.dir:/opt}".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_headers() {
+        let operation =
+            Operation::parse(":reload(){allow-resource-service-restart=true; blocking-timeout=5}")
+                .unwrap();
+        assert_eq!(operation.headers.len(), 2);
+        assert_eq!(operation.headers[0].0, "allow-resource-service-restart");
+    }
+
+    #[test]
+    fn parse_flag_parameter() {
+        let operation = Operation::parse(":read-resource(recursive)").unwrap();
+        assert_eq!(
+            operation.parameters,
+            vec![("recursive".to_string(), Value::Text("true".to_string()))]
+        );
+    }
+
+    #[test]
+    fn parse_errors_report_position() {
+        let error = Operation::parse("/subsystem=logging").unwrap_err();
+        assert_eq!(error.position, 18);
+        let error = Operation::parse(":read-resource(recursive=true").unwrap_err();
+        assert_eq!(error.position, 29);
+        let error = Operation::parse(":read-resource(recursive=true) foo").unwrap_err();
+        assert_eq!(error.position, 31);
+        let error = Operation::parse(":add(value=\"foo)").unwrap_err();
+        assert_eq!(error.position, 11);
+    }
+
+    #[test]
+    fn to_dmr() {
+        let operation = Operation::parse(
+            "/subsystem=logging:read-resource(recursive=true,depth=2,name=\"true\")",
+        )
+        .unwrap();
+        assert_eq!(
+            operation.to_dmr(),
+            json!({
+                "operation": "read-resource",
+                "address": [{"subsystem": "logging"}],
+                "recursive": true,
+                "depth": 2,
+                "name": "true"
+            })
+        );
+    }
+
+    #[test]
+    fn to_dmr_keeps_leading_zeros() {
+        let operation = Operation::parse(":foo(value=007)").unwrap();
+        assert_eq!(operation.to_dmr()["value"], json!("007"));
+    }
+
+    #[test]
+    fn parse_command_list() {
+        let commands = parse_commands(
+            "batch, /system-property=a:add(value=[1,2]),:write-attribute(name=x,value={a=1,b=2}), run-batch",
+        )
+        .unwrap();
+        assert_eq!(commands.len(), 4);
+        assert_eq!(commands[0], CliCommand::Batch);
+        assert!(matches!(&commands[1], CliCommand::Operation(op) if op.name == "add"));
+        assert!(matches!(&commands[2], CliCommand::Operation(op) if op.address.is_empty()));
+        assert_eq!(commands[3], CliCommand::RunBatch);
+    }
+
+    #[test]
+    fn parse_command_list_errors() {
+        let error = parse_commands(":read-resource,").unwrap_err();
+        assert_eq!(error.position, 15);
+        let error = parse_commands("batches").unwrap_err();
+        assert_eq!(error.position, 0);
+        let error = parse_commands(":read-resource :whoami").unwrap_err();
+        assert_eq!(error.position, 15);
+        assert_eq!(error.message, "Expected ',' or end of input");
+    }
+
+    #[test]
+    fn batches() {
+        let commands = |input| parse_commands(input).unwrap();
+        assert!(validate_batches(&commands("batch,:a,:b,run-batch,:c")).is_ok());
+        assert!(validate_batches(&commands("batch,:a")).is_err());
+        assert!(validate_batches(&commands(":a,run-batch")).is_err());
+        assert!(validate_batches(&commands("batch,batch,:a,run-batch")).is_err());
+    }
+
+    #[test]
+    fn normalize() {
+        let normalize = |input| Operation::parse(input).unwrap().to_string();
+        assert_eq!(normalize("/:read-resource"), ":read-resource");
+        assert_eq!(normalize(":read-resource()"), ":read-resource");
+        assert_eq!(
+            normalize("/ subsystem = logging :read-resource( recursive , depth = 2 )"),
+            "/subsystem=logging:read-resource(recursive=true,depth=2)"
+        );
+        assert_eq!(
+            normalize("/system-property=foo:add(value=\"a, b\")"),
+            "/system-property=foo:add(value=\"a, b\")"
+        );
+        assert_eq!(
+            normalize("/system-property=foo:add(value=a\\,b)"),
+            "/system-property=foo:add(value=\"a,b\")"
+        );
+        assert_eq!(
+            normalize("/system-property=foo:add(value=${env.FOO:a,b})"),
+            "/system-property=foo:add(value=${env.FOO:a,b})"
+        );
+        assert_eq!(
+            normalize("/deployment=\"a:b\":read-resource{rollback-on-runtime-failure=false}"),
+            "/deployment=\"a:b\":read-resource{rollback-on-runtime-failure=false}"
+        );
+        assert_eq!(
+            normalize(":add(map={a=>[1, 2], \"b\"=x})"),
+            ":add(map={a=[1,2],b=x})"
+        );
+    }
+
+    #[test]
+    fn normalized_operation_keeps_dmr() {
+        let operation =
+            Operation::parse("/a=\"x/y\":add(v=\"q\\\"t\", w=a\\)b, l=[{k=\"v\"}])").unwrap();
+        assert_eq!(
+            Operation::parse(&operation.to_string()).unwrap().to_dmr(),
+            operation.to_dmr()
+        );
+    }
+
+    #[test]
+    fn display_with_caret() {
+        let input = ":read-resource(recursive=true";
+        let error = Operation::parse(input).unwrap_err();
+        assert_eq!(
+            error.display_with(input),
+            format!(
+                "Expected ')' at position 29\n  {}\n  {}^",
+                input,
+                " ".repeat(29)
+            )
+        );
+    }
+}
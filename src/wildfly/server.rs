@@ -10,41 +10,66 @@ use std::fmt::Display;
 // ------------------------------------------------------ server group
 
 /// WildFly domain server group assignment.
+///
+/// The built-in groups are part of the default `domain.xml`. Other groups
+/// have to be declared (e.g. in the `server-groups` of a topology file).
+// the names of the built-in groups mirror their WildFly names
+#[allow(clippy::enum_variant_names)]
 #[derive(Clone, Debug, PartialEq)]
 pub enum ServerGroup {
     MainServerGroup,
     OtherServerGroup,
+    Custom(String),
 }
 
 impl ServerGroup {
     const ALL: [ServerGroup; 2] = [ServerGroup::MainServerGroup, ServerGroup::OtherServerGroup];
 
     /// Returns the full server group name (e.g. `"main-server-group"`).
-    pub fn name(&self) -> &'static str {
+    pub fn name(&self) -> &str {
         match self {
             ServerGroup::MainServerGroup => "main-server-group",
             ServerGroup::OtherServerGroup => "other-server-group",
+            ServerGroup::Custom(name) => name,
         }
     }
 
-    /// Returns the short abbreviation (e.g. `"msg"`).
-    pub fn abbreviation(&self) -> &'static str {
+    /// Returns the short abbreviation (e.g. `"msg"`) of a built-in server group.
+    pub fn abbreviation(&self) -> Option<&'static str> {
         match self {
-            ServerGroup::MainServerGroup => "msg",
-            ServerGroup::OtherServerGroup => "osg",
+            ServerGroup::MainServerGroup => Some("msg"),
+            ServerGroup::OtherServerGroup => Some("osg"),
+            ServerGroup::Custom(_) => None,
         }
     }
 
-    /// Parses a server group from its name or abbreviation (`"msg"`, `"osg"`).
+    /// Returns whether `name` is the name or abbreviation of a built-in server group.
+    pub fn is_builtin(name: &str) -> bool {
+        ServerGroup::parse_group(name).is_some()
+    }
+
+    /// Parses a built-in server group from its name or abbreviation (`"msg"`, `"osg"`).
     pub fn parse_group(input: &str) -> Option<ServerGroup> {
         ServerGroup::ALL
             .iter()
             .find(|sg| {
                 input.eq_ignore_ascii_case(sg.name())
-                    || input.eq_ignore_ascii_case(sg.abbreviation())
+                    || sg
+                        .abbreviation()
+                        .is_some_and(|abbreviation| input.eq_ignore_ascii_case(abbreviation))
             })
             .cloned()
     }
+
+    /// Parses a built-in server group or one of the `declared` server groups.
+    pub fn parse_declared_group(input: &str, declared: &[&str]) -> Option<ServerGroup> {
+        ServerGroup::parse_group(input).or_else(|| {
+            declared
+                .iter()
+                .find(|name| **name == input)
+                .map(|name| ServerGroup::Custom(name.to_string()))
+        })
+    }
 }
 
 impl Display for ServerGroup {
@@ -71,6 +96,7 @@ impl Server {
     }
 
     /// Parses a single server spec: `<name>[:<server-group>][:<offset>][:start]`.
+    /// Only the built-in server groups are accepted.
     pub fn parse_server(input: &str) -> anyhow::Result<Server> {
         let parts: Vec<&str> = input.split(':').collect();
         if parts.is_empty() {
@@ -326,6 +352,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn add_server_op_custom_group() {
+        let server = Server {
+            name: "server-three".to_string(),
+            server_group: ServerGroup::Custom("app-group".to_string()),
+            offset: 0,
+            autostart: false,
+        };
+        assert_eq!(
+            server.add_server_op("primary"),
+            "/host=primary/server-config=server-three:add(group=app-group,auto-start=false)"
+        );
+    }
+
     #[test]
     fn add_server_op_with_offset() {
         let server = Server {
@@ -0,0 +1,381 @@
+//! Configuration snapshots of running containers.
+//!
+//! A snapshot is a copy of the configuration file of a standalone server, the domain and
+//! host configuration of a domain controller, or the host configuration of a host
+//! controller, stored together with the admin image it was taken from in the local
+//! snapshot store (see [`snapshot_directory`]). Snapshots are restored by starting
+//! a new container of the same admin image with the saved files as local
+//! [`ServerConfig`]s.
+
+use crate::paths::home_dir;
+use crate::wildfly::{AdminImage, ServerConfig, ServerType};
+use anyhow::{Context, bail};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use wildfly_meta::WildFlyImageRegistry;
+
+/// File name of the snapshot metadata.
+const METADATA: &str = "snapshot.json";
+
+/// A saved server configuration.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub label: String,
+    /// The identifier of the admin image (e.g. `"sa-390"`).
+    pub identifier: String,
+    pub version: String,
+    pub server_type: String,
+    /// The file name of the configuration (e.g. `"standalone-full.xml"`). For host
+    /// controllers, this is the host configuration.
+    pub config: String,
+    /// The file name of the host configuration of domain controllers (e.g. `"host-primary.xml"`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host_config: Option<String>,
+    /// The name of the container the snapshot was taken from.
+    pub container: String,
+    /// Seconds since the Unix epoch.
+    pub created: u64,
+}
+
+impl Snapshot {
+    pub fn new(
+        label: &str,
+        admin_image: &AdminImage,
+        config: &str,
+        host_config: Option<&str>,
+        container: &str,
+    ) -> Snapshot {
+        Snapshot {
+            label: label.to_string(),
+            identifier: admin_image.identifier(),
+            version: admin_image.wildfly_image.short_name(),
+            server_type: admin_image.server_type.short_name().to_string(),
+            config: config.to_string(),
+            host_config: host_config.map(str::to_string),
+            container: container.to_string(),
+            created: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Loads the snapshot `label` from the local snapshot store.
+    pub fn load(label: &str) -> anyhow::Result<Snapshot> {
+        Snapshot::load_from(&snapshot_directory()?, label)
+    }
+
+    fn load_from(store: &Path, label: &str) -> anyhow::Result<Snapshot> {
+        validate_label(label)?;
+        let path = store.join(label).join(METADATA);
+        if !path.is_file() {
+            bail!(
+                "Snapshot '{}' not found. Use 'wado snapshot list' to show all snapshots.",
+                label
+            );
+        }
+        let json = fs::read_to_string(&path)
+            .with_context(|| format!("Unable to read snapshot {}", path.display()))?;
+        serde_json::from_str(&json).with_context(|| format!("Invalid snapshot {}", path.display()))
+    }
+
+    /// Returns all snapshots of the local snapshot store sorted by label.
+    pub fn list() -> anyhow::Result<Vec<Snapshot>> {
+        Snapshot::list_in(&snapshot_directory()?)
+    }
+
+    fn list_in(store: &Path) -> anyhow::Result<Vec<Snapshot>> {
+        let mut snapshots = vec![];
+        if store.is_dir() {
+            for entry in fs::read_dir(store)? {
+                let entry = entry?;
+                if entry.path().join(METADATA).is_file() {
+                    let label = entry.file_name().to_string_lossy().to_string();
+                    snapshots.push(Snapshot::load_from(store, &label)?);
+                }
+            }
+        }
+        snapshots.sort_by(|a, b| a.label.cmp(&b.label));
+        Ok(snapshots)
+    }
+
+    /// Writes the snapshot, its configuration and the host configuration `host_xml` of
+    /// domain controllers to the local snapshot store. Existing snapshots with the same
+    /// label are only replaced if `force` is true.
+    pub fn save(&self, xml: &str, host_xml: Option<&str>, force: bool) -> anyhow::Result<()> {
+        self.save_to(&snapshot_directory()?, xml, host_xml, force)
+    }
+
+    fn save_to(
+        &self,
+        store: &Path,
+        xml: &str,
+        host_xml: Option<&str>,
+        force: bool,
+    ) -> anyhow::Result<()> {
+        validate_label(&self.label)?;
+        let directory = store.join(&self.label);
+        if directory.exists() {
+            if !force {
+                bail!(
+                    "Snapshot '{}' already exists. Use --force to replace it.",
+                    self.label
+                );
+            }
+            fs::remove_dir_all(&directory)?;
+        }
+        fs::create_dir_all(&directory)
+            .with_context(|| format!("Unable to create {}", directory.display()))?;
+        fs::write(directory.join(&self.config), xml)?;
+        if let (Some(host_config), Some(host_xml)) = (&self.host_config, host_xml) {
+            fs::write(directory.join(host_config), host_xml)?;
+        }
+        fs::write(
+            directory.join(METADATA),
+            serde_json::to_string_pretty(self)?,
+        )?;
+        Ok(())
+    }
+
+    /// Resolves the admin image the snapshot was taken from.
+    pub fn admin_image(&self, registry: &WildFlyImageRegistry) -> anyhow::Result<AdminImage> {
+        AdminImage::from_identifier(self.identifier.clone(), registry).with_context(|| {
+            format!(
+                "Unknown WildFly version '{}' of snapshot '{}'",
+                self.version, self.label
+            )
+        })
+    }
+
+    /// Returns the saved configuration as local [`ServerConfig`].
+    pub fn server_config(&self) -> anyhow::Result<ServerConfig> {
+        Ok(ServerConfig::Local(
+            snapshot_directory()?.join(&self.label).join(&self.config),
+        ))
+    }
+
+    /// Returns the saved host configuration of domain controllers as local [`ServerConfig`].
+    pub fn host_server_config(&self) -> anyhow::Result<Option<ServerConfig>> {
+        match &self.host_config {
+            Some(host_config) => Ok(Some(ServerConfig::Local(
+                snapshot_directory()?.join(&self.label).join(host_config),
+            ))),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Returns the local snapshot store: `$XDG_DATA_HOME/wado/snapshots` or
+/// `~/.local/share/wado/snapshots` if `XDG_DATA_HOME` is not set.
+pub fn snapshot_directory() -> anyhow::Result<PathBuf> {
+    let data = match std::env::var_os("XDG_DATA_HOME") {
+        Some(data) => PathBuf::from(data),
+        None => home_dir()
+            .with_context(|| "Could not determine home directory. Please set XDG_DATA_HOME.")?
+            .join(".local")
+            .join("share"),
+    };
+    Ok(data.join("wado").join("snapshots"))
+}
+
+/// Returns the path of the configuration file `config` inside the container.
+pub fn configuration_path(server_type: ServerType, config: &str) -> String {
+    match server_type {
+        ServerType::Standalone => {
+            format!("/opt/jboss/wildfly/standalone/configuration/{}", config)
+        }
+        ServerType::DomainController | ServerType::HostController => {
+            format!("/opt/jboss/wildfly/domain/configuration/{}", config)
+        }
+    }
+}
+
+/// Labels are used as directory names and must only contain letters, digits,
+/// `.`, `_` and `-`.
+pub fn validate_label(label: &str) -> anyhow::Result<()> {
+    if label.is_empty()
+        || label.starts_with('.')
+        || !label
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+    {
+        bail!(
+            "Invalid snapshot label '{}'. Use only letters, digits, '.', '_' and '-'.",
+            label
+        );
+    }
+    Ok(())
+}
+
+/// Removes all `<deployments>` elements and returns the stripped configuration together
+/// with the names of the removed deployments. The content of managed deployments lives
+/// in the content repository of the container and is not part of a snapshot, so
+/// references to it would prevent a restored server from booting.
+pub fn strip_deployments(xml: &str) -> (String, Vec<String>) {
+    let deployments = Regex::new(r"(?s)\n?[ \t]*<deployments>.*?</deployments>").unwrap();
+    let deployment = Regex::new(r#"<deployment\s[^>]*?name="([^"]*)""#).unwrap();
+    let mut names: Vec<String> = deployments
+        .find_iter(xml)
+        .flat_map(|m| deployment.captures_iter(m.as_str()))
+        .map(|c| c[1].to_string())
+        .collect();
+    names.sort();
+    names.dedup();
+    (deployments.replace_all(xml, "").to_string(), names)
+}
+
+/// Resets the name of the host in the host configuration `xml` to the name the image
+/// boots with: `primary` for domain controllers and no name (the host name of the
+/// container) for host controllers. The entrypoint renames the host to the name of
+/// the new container, which fails if the host still has the name of the old one.
+pub fn reset_host_name(xml: &str, server_type: ServerType) -> String {
+    let host = Regex::new(r"<host(\s[^>]*)?>").unwrap();
+    let name = Regex::new(r#"\s+name="[^"]*""#).unwrap();
+    host.replacen(xml, 1, |c: &regex::Captures| {
+        let attributes = c.get(1).map_or("", |m| m.as_str());
+        let attributes = name.replace(attributes, "");
+        if server_type == ServerType::DomainController {
+            format!("<host name=\"primary\"{}>", attributes)
+        } else {
+            format!("<host{}>", attributes)
+        }
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_registry() -> WildFlyImageRegistry {
+        WildFlyImageRegistry::from_toml(include_str!("../../testdata/wildfly-images.toml"))
+            .expect("failed to parse test registry")
+    }
+
+    #[test]
+    fn label() {
+        assert!(validate_label("before-upgrade").is_ok());
+        assert!(validate_label("v1.2_final").is_ok());
+        assert!(validate_label("").is_err());
+        assert!(validate_label(".hidden").is_err());
+        assert!(validate_label("foo/bar").is_err());
+        assert!(validate_label("foo bar").is_err());
+    }
+
+    #[test]
+    fn strip() {
+        let xml = "<server>\n    <profile/>\n    <deployments>\n        <deployment name=\"app.war\">\n            <content sha1=\"abc\"/>\n        </deployment>\n    </deployments>\n</server>";
+        assert_eq!(
+            strip_deployments(xml),
+            (
+                "<server>\n    <profile/>\n</server>".to_string(),
+                vec!["app.war".to_string()]
+            )
+        );
+        let xml = "<deployments><deployment name=\"a\" runtime-name=\"a\"/></deployments><server-group name=\"main\"><deployments><deployment name=\"a\"/></deployments></server-group>";
+        assert_eq!(
+            strip_deployments(xml),
+            (
+                "<server-group name=\"main\"></server-group>".to_string(),
+                vec!["a".to_string()]
+            )
+        );
+        assert_eq!(
+            strip_deployments("<server/>"),
+            ("<server/>".to_string(), vec![])
+        );
+    }
+
+    #[test]
+    fn host_name() {
+        let xml = "<?xml version=\"1.0\"?>\n<host xmlns=\"urn:jboss:domain:20.0\" name=\"wado-dc-340\">\n<host-excludes/>";
+        assert_eq!(
+            reset_host_name(xml, ServerType::DomainController),
+            "<?xml version=\"1.0\"?>\n<host name=\"primary\" xmlns=\"urn:jboss:domain:20.0\">\n<host-excludes/>"
+        );
+        assert_eq!(
+            reset_host_name(xml, ServerType::HostController),
+            "<?xml version=\"1.0\"?>\n<host xmlns=\"urn:jboss:domain:20.0\">\n<host-excludes/>"
+        );
+        assert_eq!(
+            reset_host_name("<host name=\"hc\">", ServerType::HostController),
+            "<host>"
+        );
+    }
+
+    #[test]
+    fn save_load_list() {
+        let registry = test_registry();
+        let store = tempfile::tempdir().unwrap();
+        let admin_image =
+            AdminImage::new(registry.get(390).unwrap().clone(), ServerType::Standalone);
+        let snapshot = Snapshot::new(
+            "foo",
+            &admin_image,
+            "standalone-full.xml",
+            None,
+            "wado-sa-390",
+        );
+        snapshot
+            .save_to(store.path(), "<server/>", None, false)
+            .unwrap();
+        assert!(
+            snapshot
+                .save_to(store.path(), "<server/>", None, false)
+                .is_err()
+        );
+        snapshot
+            .save_to(store.path(), "<server/>", None, true)
+            .unwrap();
+
+        let loaded = Snapshot::load_from(store.path(), "foo").unwrap();
+        assert_eq!(loaded, snapshot);
+        assert_eq!(loaded.identifier, "sa-390");
+        assert!(loaded.admin_image(&registry).unwrap() == admin_image);
+        assert_eq!(
+            fs::read_to_string(store.path().join("foo").join("standalone-full.xml")).unwrap(),
+            "<server/>"
+        );
+        assert!(Snapshot::load_from(store.path(), "bar").is_err());
+
+        Snapshot::new("bar", &admin_image, "standalone.xml", None, "wado-sa-390")
+            .save_to(store.path(), "<server/>", None, false)
+            .unwrap();
+        let labels: Vec<String> = Snapshot::list_in(store.path())
+            .unwrap()
+            .into_iter()
+            .map(|s| s.label)
+            .collect();
+        assert_eq!(labels, vec!["bar", "foo"]);
+    }
+
+    #[test]
+    fn save_host_config() {
+        let registry = test_registry();
+        let store = tempfile::tempdir().unwrap();
+        let admin_image = AdminImage::new(
+            registry.get(390).unwrap().clone(),
+            ServerType::DomainController,
+        );
+        let snapshot = Snapshot::new(
+            "domain",
+            &admin_image,
+            "domain.xml",
+            Some("host-primary.xml"),
+            "wado-dc-390",
+        );
+        snapshot
+            .save_to(store.path(), "<domain/>", Some("<host/>"), false)
+            .unwrap();
+        let directory = store.path().join("domain");
+        assert_eq!(
+            fs::read_to_string(directory.join("host-primary.xml")).unwrap(),
+            "<host/>"
+        );
+        let loaded = Snapshot::load_from(store.path(), "domain").unwrap();
+        assert_eq!(loaded.host_config, Some("host-primary.xml".to_string()));
+    }
+}
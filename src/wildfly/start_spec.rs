@@ -1,6 +1,6 @@
 //! Start specifications and resolved container configurations.
 
-use super::{AdminImage, Ports};
+use super::{AdminImage, ExtraPort, Ports};
 
 /// Captures what the user (or topology.yml) explicitly provided for a container start.
 /// `None` fields will be auto-resolved based on running instance counts.
@@ -10,6 +10,12 @@ pub struct StartSpec {
     pub custom_name: Option<String>,
     pub custom_http: Option<u16>,
     pub custom_management: Option<u16>,
+    /// The offset given by `--offset`, used for the ports given by `--publish`.
+    pub custom_offset: Option<u16>,
+    /// Additional ports to publish.
+    pub publish: Vec<ExtraPort>,
+    /// Whether a debug port should be allocated.
+    pub debug: bool,
 }
 
 /// Resolved container name and ports, unique among running *wado* containers.
@@ -18,4 +24,5 @@ pub struct ResolvedStart {
     pub admin_image: AdminImage,
     pub name: String,
     pub ports: Option<Ports>,
+    pub debug_port: Option<u16>,
 }